@@ -70,6 +70,10 @@ pub struct AudioState {
 #[derive(Component)]
 pub struct RemoteSpatialSound;
 
+/// Marker for near-miss crack / whiz audio
+#[derive(Component)]
+pub struct NearMissSound;
+
 // =============================================================================
 // AUDIO MANAGER (limits and prioritization)
 // =============================================================================
@@ -87,8 +91,10 @@ pub enum AudioPriority {
     Dialogue = 3,
     /// Local vehicle sounds
     VehicleLocal = 4,
+    /// Rounds cracking / whizzing past the local player
+    NearMiss = 5,
     /// Local weapon sounds - highest priority, never dropped
-    CombatLocal = 5,
+    CombatLocal = 6,
 }
 
 /// Marker component for audio entities managed by AudioManager
@@ -98,12 +104,14 @@ pub struct ManagedAudioTag {
     pub spawn_time: f32,
 }
 
-/// A queued dialogue request (NPC wants to speak)
+/// A queued dialogue request (NPC says a voice line)
 #[derive(Debug, Clone)]
 pub struct DialogueRequest {
     pub npc_entity: Entity,
     pub distance_sq: f32,
     pub archetype: NpcArchetype,
+    /// Index into the archetype's voice clips
+    pub clip: u8,
 }
 
 /// Central audio manager - tracks limits and queues
@@ -119,6 +127,8 @@ pub struct AudioManager {
     pub max_remote_footsteps: usize,
     /// Max remote vehicle audio pairs (each vehicle = 2 emitters)
     pub max_remote_vehicles: usize,
+    /// Max concurrent near-miss cracks / whizzes
+    pub max_near_miss: usize,
     /// Queued dialogue requests for this frame
     pub dialogue_queue: Vec<DialogueRequest>,
 }
@@ -131,6 +141,7 @@ impl Default for AudioManager {
             max_remote_combat: 8,
             max_remote_footsteps: 12,
             max_remote_vehicles: 6,
+            max_near_miss: 4,
             dialogue_queue: Vec::with_capacity(8),
         }
     }
@@ -311,8 +322,9 @@ pub fn play_gunshot_sfx(
 /// Handle audio events from remote players (spatial audio)
 ///
 /// When other players shoot, we receive an AudioEvent from the server
-/// and play a spatial sound at their position.
-/// Respects max_remote_combat limit by despawning oldest sounds when at capacity.
+/// and play a spatial sound at their position. Rounds passing close by
+/// crack or whiz at their closest point to us.
+/// Respects max_remote_combat / max_near_miss by despawning oldest sounds when at capacity.
 pub fn handle_remote_audio_events(
     mut commands: Commands,
     time: Res<Time>,
@@ -326,6 +338,7 @@ pub fn handle_remote_audio_events(
     mut receiver: Query<&mut MessageReceiver<AudioEvent>, (With<crate::GameClient>, With<Connected>)>,
     // Query existing remote combat sounds to enforce limit
     remote_sounds: Query<(Entity, &ManagedAudioTag, &Transform), With<RemoteSpatialSound>>,
+    near_miss_sounds: Query<(Entity, &ManagedAudioTag), With<NearMissSound>>,
 ) {
     // Don't process until audio assets are ready
     if !audio_state.assets_ready {
@@ -343,6 +356,7 @@ pub fn handle_remote_audio_events(
     let mut current_remote_count = remote_sounds.iter()
         .filter(|(_, tag, _)| tag.priority == AudioPriority::CombatRemote)
         .count();
+    let mut current_near_miss_count = near_miss_sounds.iter().count();
 
     // Process incoming audio events
     for mut recv in receiver.iter_mut() {
@@ -390,6 +404,40 @@ pub fn handle_remote_audio_events(
                     ));
                     current_remote_count += 1;
                 }
+                AudioEventKind::NearMiss { supersonic, miss_distance } => {
+                    if current_near_miss_count >= audio_manager.max_near_miss {
+                        if let Some((oldest_entity, _)) = near_miss_sounds.iter()
+                            .min_by(|(_, a), (_, b)| a.spawn_time.partial_cmp(&b.spawn_time).unwrap_or(std::cmp::Ordering::Equal))
+                        {
+                            commands.entity(oldest_entity).despawn();
+                            current_near_miss_count = current_near_miss_count.saturating_sub(1);
+                        }
+                    }
+
+                    // No dedicated crack/whiz assets yet: a sped-up gunshot reads as a sharp
+                    // supersonic snap, a quieter, less sped-up one as a subsonic whiz
+                    let closeness = (1.0 - miss_distance / shared::weapons::NEAR_MISS_RADIUS).clamp(0.0, 1.0);
+                    let (speed, volume) = if supersonic {
+                        (2.6 + rand::random::<f32>() * 0.3, 0.5 + 0.6 * closeness)
+                    } else {
+                        (1.7 + rand::random::<f32>() * 0.2, 0.25 + 0.35 * closeness)
+                    };
+
+                    commands.spawn((
+                        NearMissSound,
+                        ManagedAudioTag {
+                            priority: AudioPriority::NearMiss,
+                            spawn_time: now,
+                        },
+                        AudioPlayer::new(audio.gun_shot.clone()),
+                        PlaybackSettings::DESPAWN
+                            .with_volume(Volume::Linear(volume))
+                            .with_speed(speed)
+                            .with_spatial(true),
+                        Transform::from_translation(audio_event.position),
+                    ));
+                    current_near_miss_count += 1;
+                }
             }
         }
     }
@@ -1,10 +1,14 @@
 //! Chest/storage system - detect nearby chests, show prompt, handle visuals
 //!
 //! Press E near a chest to open it. The chest UI is integrated into the inventory.
+//! Dead NPCs carrying loot (`LootableCorpse`) open the same way but have no chest model.
 
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
-use shared::{ChestStorage, ChestPosition, LocalPlayer, PlayerPosition, OpenChestRequest, CloseChestRequest, ReliableChannel, CHEST_RANGE};
+use shared::{
+    ChestStorage, ChestPosition, LocalPlayer, PlayerPosition, OpenChestRequest, CloseChestRequest, ReliableChannel,
+    LootableCorpse, CHEST_RANGE,
+};
 use lightyear::prelude::*;
 use lightyear::prelude::client::Connected;
 
@@ -45,6 +49,8 @@ impl Plugin for ChestPlugin {
 pub struct NearbyChest {
     pub entity: Option<Entity>,
     pub position: Option<Vec3>,
+    /// It's a dead NPC's loot rather than a chest
+    pub corpse: bool,
 }
 
 /// Resource tracking which chest is currently open (client-side view)
@@ -67,7 +73,7 @@ pub struct ChestVisual {
 fn detect_nearby_chests(
     mut nearby: ResMut<NearbyChest>,
     local_player: Query<&PlayerPosition, With<LocalPlayer>>,
-    chests: Query<(Entity, &ChestPosition)>,
+    chests: Query<(Entity, &ChestPosition, Has<LootableCorpse>)>,
     input_state: Res<InputState>,
 ) {
     // Don't detect while in vehicle or dead
@@ -82,20 +88,21 @@ fn detect_nearby_chests(
     };
     
     // Find the nearest chest within range
-    let mut closest: Option<(Entity, Vec3, f32)> = None;
+    let mut closest: Option<(Entity, Vec3, f32, bool)> = None;
     
-    for (entity, pos) in chests.iter() {
+    for (entity, pos, corpse) in chests.iter() {
         let distance = player_pos.0.distance(pos.0);
         if distance <= CHEST_RANGE {
             if closest.is_none() || distance < closest.unwrap().2 {
-                closest = Some((entity, pos.0, distance));
+                closest = Some((entity, pos.0, distance, corpse));
             }
         }
     }
     
-    if let Some((entity, pos, _)) = closest {
+    if let Some((entity, pos, _, corpse)) = closest {
         nearby.entity = Some(entity);
         nearby.position = Some(pos);
+        nearby.corpse = corpse;
     } else {
         *nearby = NearbyChest::default();
     }
@@ -130,7 +137,7 @@ fn show_chest_prompt(
                 margin: UiRect::left(Val::Px(-100.0)),
                 ..default()
             },
-            Text::new("Press [E] to open chest"),
+            Text::new(if nearby.corpse { "Press [E] to loot" } else { "Press [E] to open chest" }),
             TextFont {
                 font_size: 20.0,
                 ..default()
@@ -233,12 +240,12 @@ fn auto_close_chest_on_distance(
     }
 }
 
-/// Spawn 3D visuals for chests
+/// Spawn 3D visuals for chests (corpses are drawn as the NPC's body)
 fn spawn_chest_visuals(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    chests: Query<(Entity, &ChestPosition), Without<ChestVisual>>,
+    chests: Query<(Entity, &ChestPosition), (Without<ChestVisual>, Without<LootableCorpse>)>,
     existing_visuals: Query<&ChestVisual>,
 ) {
     // Track which server entities already have visuals
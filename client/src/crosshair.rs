@@ -1,11 +1,27 @@
 //! Crosshair UI for first-person shooting
 //!
 //! Simple centered dot crosshair that shows in first-person mode.
-//! Shrinks when aiming down sights (ADS).
+//! Shrinks when aiming down sights (ADS). Zeroable sights show the zero range and a
+//! mil-dot holdover ladder; the scoped sniper adds a wind hold mark. Both come from
+//! the shared ballistic model. Near misses darken the screen edges (suppression).
 
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use shared::{weapons::ballistics, EquippedWeapon, LocalPlayer, Suppression, WeaponType, Wind};
 use crate::input::CameraMode;
 
+/// Range the scope's wind hold mark is computed for (m)
+pub const SCOPE_WIND_REFERENCE_RANGE: f32 = 400.0;
+
+/// Number of holdover dots below the center (one per mil)
+pub const MIL_DOT_COUNT: u32 = 5;
+
+/// Longest range labelled on the holdover ladder (m)
+const HOLDOVER_MAX_RANGE: f32 = 1200.0;
+
+/// Range sampling step for the holdover ladder (m)
+const HOLDOVER_RANGE_STEP: f32 = 25.0;
+
 /// Marker component for the crosshair UI
 #[derive(Component)]
 pub struct Crosshair;
@@ -29,6 +45,30 @@ pub enum CrosshairLineDir {
     Right,
 }
 
+/// Marker for the scoped wind hold mark (offset from center by the expected drift)
+#[derive(Component)]
+pub struct WindHoldMark;
+
+/// Marker for the scoped wind readout text
+#[derive(Component)]
+pub struct ScopeWindText;
+
+/// Holdover dot `mils` below the center of a zeroed sight
+#[derive(Component)]
+pub struct MilDot {
+    pub mils: u32,
+}
+
+/// Range label next to a holdover dot
+#[derive(Component)]
+pub struct MilDotLabel {
+    pub mils: u32,
+}
+
+/// Marker for the zero range readout ("ZERO 400m")
+#[derive(Component)]
+pub struct ScopeZeroText;
+
 /// Marker for the hit marker overlay
 #[derive(Component)]
 pub struct HitMarker {
@@ -117,6 +157,105 @@ pub fn spawn_crosshair(mut commands: Commands) {
                 },
                 BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.7)),
             ));
+            
+            // Holdover ladder (zeroable sights only; placed by `update_scope_zero_reticle`)
+            for mils in 1..=MIL_DOT_COUNT {
+                parent.spawn((
+                    MilDot { mils },
+                    Node {
+                        width: Val::Px(3.0),
+                        height: Val::Px(3.0),
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(50.0),
+                        top: Val::Percent(50.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(1.0, 0.3, 0.3, 0.9)),
+                    BorderRadius::all(Val::Px(1.5)),
+                    Visibility::Hidden,
+                ));
+                parent.spawn((
+                    MilDotLabel { mils },
+                    Text::new(""),
+                    TextFont {
+                        font_size: 10.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgba(1.0, 0.6, 0.6, 0.8)),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(50.0),
+                        top: Val::Percent(50.0),
+                        ..default()
+                    },
+                    Visibility::Hidden,
+                ));
+            }
+            
+            // Zero range readout (zeroable sights only)
+            parent.spawn((
+                ScopeZeroText,
+                Text::new(""),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(1.0, 0.6, 0.6, 0.9)),
+                TextLayout::new_with_justify(Justify::Center),
+                Node {
+                    width: Val::Px(260.0),
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(50.0),
+                    top: Val::Percent(50.0),
+                    margin: UiRect {
+                        left: Val::Px(-130.0),
+                        top: Val::Px(-60.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Visibility::Hidden,
+            ));
+            
+            // Wind hold mark (scoped sniper only)
+            parent.spawn((
+                WindHoldMark,
+                Node {
+                    width: Val::Px(2.0),
+                    height: Val::Px(10.0),
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(50.0),
+                    top: Val::Percent(50.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.3, 0.9, 1.0, 0.9)),
+                Visibility::Hidden,
+            ));
+            
+            // Wind readout (scoped sniper only)
+            parent.spawn((
+                ScopeWindText,
+                Text::new(""),
+                TextFont {
+                    font_size: 13.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.3, 0.9, 1.0, 0.9)),
+                TextLayout::new_with_justify(Justify::Center),
+                Node {
+                    width: Val::Px(260.0),
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(50.0),
+                    top: Val::Percent(50.0),
+                    margin: UiRect {
+                        left: Val::Px(-130.0),
+                        top: Val::Px(60.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Visibility::Hidden,
+            ));
         });
 }
 
@@ -187,14 +326,204 @@ pub fn update_crosshair_ads(
     }
 }
 
+/// Convert an angle in mils to screen pixels for the camera's current (vertical) FOV
+fn mils_to_pixels(mils: f32, projection: &Projection, window: &Window) -> f32 {
+    let fov = match projection {
+        Projection::Perspective(persp) => persp.fov,
+        _ => std::f32::consts::FRAC_PI_4,
+    };
+    let pixels_per_radian = window.height() / (2.0 * (fov * 0.5).tan());
+    (mils / 1000.0).tan() * pixels_per_radian
+}
+
+/// Range (m) at which a shot needs `mils` of holdover with the given zero, if within the ladder.
+///
+/// Holdover is the bullet's angular drop below the line of sight: its drop angle at
+/// the target range minus the zero angle already built into the sight.
+fn holdover_range(table: &[ballistics::TrajectoryPoint], zero_angle: f32, mils: f32) -> Option<f32> {
+    let holdover = |p: &ballistics::TrajectoryPoint| p.drop_mils() - zero_angle * 1000.0;
+    table.windows(2).find_map(|pair| {
+        let (near, far) = (holdover(&pair[0]), holdover(&pair[1]));
+        if near <= mils && far >= mils && far > near {
+            let t = (mils - near) / (far - near);
+            Some(pair[0].distance + (pair[1].distance - pair[0].distance) * t)
+        } else {
+            None
+        }
+    })
+}
+
+/// Show the zero range and place the mil-dot holdover ladder for zeroable sights.
+pub fn update_scope_zero_reticle(
+    mut dots: Query<(&MilDot, &mut Node, &mut Visibility), (Without<MilDotLabel>, Without<ScopeZeroText>)>,
+    mut labels: Query<(&MilDotLabel, &mut Text, &mut Node, &mut Visibility), (Without<MilDot>, Without<ScopeZeroText>)>,
+    mut zero_text: Query<(&mut Text, &mut Visibility), (With<ScopeZeroText>, Without<MilDot>, Without<MilDotLabel>)>,
+    input_state: Res<crate::input::InputState>,
+    local_player: Query<&EquippedWeapon, With<LocalPlayer>>,
+    camera: Query<&Projection, With<Camera3d>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cached_table: Local<Option<(WeaponType, Vec<ballistics::TrajectoryPoint>)>>,
+) {
+    let weapon = local_player
+        .iter()
+        .next()
+        .filter(|w| !w.weapon_type.zero_ranges().is_empty() && w.zero_range > 0.0);
+    let view = camera.single().ok().zip(windows.single().ok());
+    let active = input_state.aiming && input_state.camera_mode == CameraMode::FirstPerson;
+
+    let (Some(weapon), Some((projection, window)), true) = (weapon, view, active) else {
+        for (_, _, mut vis) in dots.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+        for (.., mut vis) in labels.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+        for (_, mut vis) in zero_text.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+        return;
+    };
+
+    // The still-air drop table only depends on the weapon, so build it once per weapon
+    let weapon_type = weapon.weapon_type;
+    if cached_table.as_ref().is_none_or(|(cached, _)| *cached != weapon_type) {
+        let ranges: Vec<f32> = (1..=(HOLDOVER_MAX_RANGE / HOLDOVER_RANGE_STEP) as u32)
+            .map(|i| i as f32 * HOLDOVER_RANGE_STEP)
+            .collect();
+        let table = ballistics::trajectory_table(
+            weapon_type.stats().bullet_speed,
+            weapon_type.ballistic_coefficient(),
+            0.0,
+            &ranges,
+        );
+        *cached_table = Some((weapon_type, table));
+    }
+    let Some((_, table)) = cached_table.as_ref() else {
+        return;
+    };
+    let zero_angle = weapon_type.zero_angle(weapon.zero_range);
+
+    for (dot, mut node, mut vis) in dots.iter_mut() {
+        let y = mils_to_pixels(dot.mils as f32, projection, window);
+        node.margin = UiRect {
+            left: Val::Px(-1.5),
+            top: Val::Px(y - 1.5),
+            ..default()
+        };
+        *vis = Visibility::Visible;
+    }
+
+    for (label, mut text, mut node, mut vis) in labels.iter_mut() {
+        let y = mils_to_pixels(label.mils as f32, projection, window);
+        node.margin = UiRect {
+            left: Val::Px(6.0),
+            top: Val::Px(y - 7.0),
+            ..default()
+        };
+        match holdover_range(table, zero_angle, label.mils as f32) {
+            Some(range) => {
+                **text = format!("{:.0}", (range / 10.0).round() * 10.0);
+                *vis = Visibility::Visible;
+            }
+            None => *vis = Visibility::Hidden,
+        }
+    }
+
+    for (mut text, mut vis) in zero_text.iter_mut() {
+        **text = format!("ZERO {:.0}m  [PgUp/PgDn]", weapon.zero_range);
+        *vis = Visibility::Visible;
+    }
+}
+
+/// Place the scoped wind hold mark and readout from the replicated wind.
+///
+/// Uses the same drift table as the server's bullets, so holding the mark on target
+/// cancels the crosswind at `SCOPE_WIND_REFERENCE_RANGE`.
+pub fn update_scope_wind_reticle(
+    mut hold_mark: Query<(&mut Node, &mut Visibility), (With<WindHoldMark>, Without<ScopeWindText>)>,
+    mut wind_text: Query<(&mut Text, &mut Visibility), (With<ScopeWindText>, Without<WindHoldMark>)>,
+    input_state: Res<crate::input::InputState>,
+    local_player: Query<&EquippedWeapon, With<LocalPlayer>>,
+    wind: Query<&Wind>,
+    camera: Query<(&Transform, &Projection), With<Camera3d>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let scoped = input_state.aiming
+        && input_state.camera_mode == CameraMode::FirstPerson
+        && local_player.iter().next().is_some_and(|w| w.weapon_type == WeaponType::Sniper);
+    let wind = wind.iter().next().map(|w| w.velocity).unwrap_or(Vec3::ZERO);
+    let view = camera.single().ok().zip(windows.single().ok());
+
+    let (Some(((camera_transform, projection), window)), true) = (view, scoped) else {
+        for (_, mut vis) in hold_mark.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+        for (_, mut vis) in wind_text.iter_mut() {
+            *vis = Visibility::Hidden;
+        }
+        return;
+    };
+
+    // Crosswind component (positive = blowing toward the right of the view)
+    let right = camera_transform.right().as_vec3();
+    let right = Vec3::new(right.x, 0.0, right.z).normalize_or_zero();
+    let crosswind = wind.dot(right);
+
+    let weapon = WeaponType::Sniper;
+    let drift_mils = ballistics::trajectory_table(
+        weapon.stats().bullet_speed,
+        weapon.ballistic_coefficient(),
+        crosswind.abs(),
+        &[SCOPE_WIND_REFERENCE_RANGE],
+    )
+    .first()
+    .map(|p| p.drift_mils())
+    .unwrap_or(0.0);
+
+    let offset = mils_to_pixels(drift_mils, projection, window);
+    // Bullet drifts downwind, so hold upwind
+    let hold_x = -offset * crosswind.signum();
+
+    for (mut node, mut vis) in hold_mark.iter_mut() {
+        node.margin = UiRect {
+            left: Val::Px(hold_x - 1.0),
+            top: Val::Px(-5.0),
+            ..default()
+        };
+        *vis = Visibility::Visible;
+    }
+
+    let arrow = if crosswind > 0.1 {
+        "->"
+    } else if crosswind < -0.1 {
+        "<-"
+    } else {
+        "--"
+    };
+    for (mut text, mut vis) in wind_text.iter_mut() {
+        **text = format!(
+            "WIND {:.1} m/s {}  HOLD {:.1} mil @ {:.0}m",
+            wind.length(),
+            arrow,
+            drift_mils,
+            SCOPE_WIND_REFERENCE_RANGE
+        );
+        *vis = Visibility::Visible;
+    }
+}
+
 /// Spawn a hit marker when we hit someone
+/// `armor_hit` tints the marker blue when the target's armor absorbed part of the hit.
 pub fn spawn_hit_marker(
     commands: &mut Commands,
     time: &Time,
     is_kill: bool,
+    armor_hit: bool,
 ) {
     let color = if is_kill {
         Color::srgba(1.0, 0.2, 0.2, 1.0) // Red for kill
+    } else if armor_hit {
+        Color::srgba(0.4, 0.7, 1.0, 1.0) // Blue for armor
     } else {
         Color::srgba(1.0, 1.0, 1.0, 1.0) // White for hit
     };
@@ -425,3 +754,55 @@ pub fn despawn_death_screen(
         commands.entity(entity).despawn();
     }
 }
+
+// =============================================================================
+// SUPPRESSION VIGNETTE
+// =============================================================================
+
+/// Darkened screen edges while suppressed
+#[derive(Component)]
+pub struct SuppressionVignette;
+
+/// Spawn the suppression vignette (transparent until a near miss)
+pub fn spawn_suppression_vignette(mut commands: Commands) {
+    commands.spawn((
+        SuppressionVignette,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            border: UiRect::all(Val::Vw(9.0)),
+            ..default()
+        },
+        BorderColor::from(Color::srgba(0.0, 0.0, 0.0, 0.0)),
+        BorderRadius::all(Val::Vw(18.0)),
+        Pickable::IGNORE,
+    ));
+}
+
+/// Fade the vignette with the local player's replicated suppression level
+pub fn update_suppression_vignette(
+    local_player: Query<Option<&Suppression>, With<LocalPlayer>>,
+    mut vignettes: Query<&mut BorderColor, With<SuppressionVignette>>,
+) {
+    let level = local_player
+        .single()
+        .ok()
+        .flatten()
+        .map(|s| s.level)
+        .unwrap_or(0.0);
+
+    for mut border in vignettes.iter_mut() {
+        *border = BorderColor::from(Color::srgba(0.02, 0.02, 0.02, 0.6 * level));
+    }
+}
+
+/// Despawn the suppression vignette when leaving gameplay
+pub fn despawn_suppression_vignette(
+    mut commands: Commands,
+    vignettes: Query<Entity, With<SuppressionVignette>>,
+) {
+    for entity in vignettes.iter() {
+        commands.entity(entity).despawn();
+    }
+}
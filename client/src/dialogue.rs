@@ -1,29 +1,38 @@
 //! NPC Dialogue System
 //!
-//! Handles proximity-based dialogue for special NPCs (Doctor, GarbageMan).
-//! When the player gets close, the NPC will play a random voice line
-//! and look toward the player.
+//! Press E near a knight, a townsperson or a trader to talk. Conversations are run by the
+//! server: it answers a `StartDialogueRequest` with a `DialogueUpdate` holding what the NPC
+//! says and the responses on offer, and clicking a response sends a `DialogueChoiceRequest`.
+//! E, ESC or walking away ends the conversation. Voice lines arrive as `NpcVoiceLine`s
+//! (sent to every player in earshot, so everyone nearby hears the same line) and play at
+//! the NPC through the audio manager's dialogue slots.
 
 use bevy::prelude::*;
 use bevy::audio::{SpatialAudioSink, Volume};
+use bevy::window::{CursorOptions, PrimaryWindow};
+use lightyear::prelude::*;
+use lightyear::prelude::client::Connected;
 
-use shared::{Health, LocalPlayer, Npc, NpcArchetype, NpcPosition, PlayerPosition};
+use shared::{
+    dialogue_kind, DialogueChoiceRequest, DialogueUpdate, DialogueView, EndDialogueRequest, Health, LocalPlayer, Npc,
+    NpcArchetype, NpcIndoors, NpcPosition, NpcVoiceLine, PlayerPosition, ReliableChannel, StartDialogueRequest,
+    TraderStock, DIALOGUE_RANGE,
+};
 
 use crate::audio::{AudioManager, AudioPriority, DialogueRequest, ManagedAudioTag};
+use crate::chest::{NearbyChest, OpenChest};
+use crate::input::InputState;
 use crate::states::GameState;
+use crate::trade::{set_cursor_free, OpenTrade, TradeFeedback};
+use crate::ui::inventory::InventoryOpen;
+use crate::ui::styles::*;
 
 // =============================================================================
 // CONSTANTS
 // =============================================================================
 
-/// Distance at which NPC will start talking
-const DIALOGUE_TRIGGER_DISTANCE: f32 = 8.0;
-
-/// Minimum time between dialogue lines (seconds)
-const DIALOGUE_COOLDOWN: f32 = 12.0;
-
-/// How long a dialogue line typically lasts (we'll stop "talking" state after this)
-const DIALOGUE_DURATION: f32 = 4.0;
+/// Seconds a dialogue notice ("Received 60x Coins", ...) stays on screen
+const DIALOGUE_NOTICE_TIME: f32 = 3.0;
 
 // =============================================================================
 // RESOURCES
@@ -36,49 +45,57 @@ pub struct DialogueAssets {
     pub rogue_lines: Vec<Handle<AudioSource>>,
 }
 
-// =============================================================================
-// COMPONENTS
-// =============================================================================
+/// Nearest living NPC within talking range that has something to say
+#[derive(Resource, Default)]
+pub struct NearbyTalker {
+    /// `Npc::id`
+    pub npc: Option<u64>,
+}
 
-/// Marks an NPC as capable of dialogue
-#[derive(Component)]
-pub struct DialogueNpc {
-    /// Which archetype (to select correct voice lines)
-    pub archetype: NpcArchetype,
-    /// Time since last dialogue (for cooldown)
-    pub cooldown_timer: f32,
-    /// Currently playing dialogue?
-    pub is_talking: bool,
-    /// Timer for how long they've been talking
-    pub talk_timer: f32,
-    /// Index for cycling through lines
-    pub line_index: usize,
+/// The conversation in progress (client-side view of the server's)
+#[derive(Resource, Default)]
+pub struct ActiveDialogue {
+    /// `Npc::id` of the NPC we're talking to
+    pub npc: Option<u64>,
+    pub view: Option<DialogueView>,
 }
 
-impl DialogueNpc {
-    pub fn new(archetype: NpcArchetype) -> Self {
-        Self {
-            archetype,
-            cooldown_timer: 0.0, // Start ready to talk
-            is_talking: false,
-            talk_timer: 0.0,
-            line_index: 0,
-        }
-    }
+/// What the last response did (or why it couldn't), shown for a few seconds
+#[derive(Resource, Default)]
+pub struct DialogueNotice {
+    pub text: String,
+    /// Seconds until it's cleared
+    pub timer: f32,
 }
 
+// =============================================================================
+// COMPONENTS
+// =============================================================================
+
 /// Marks the audio entity playing a dialogue line
 #[derive(Component)]
 pub struct DialogueAudio {
     pub npc_entity: Entity,
 }
 
-/// Component to track NPC looking at player
+/// Marker for the talk prompt UI
+#[derive(Component)]
+pub struct TalkPrompt;
+
+/// Marker for the conversation window
 #[derive(Component)]
-pub struct LookingAtPlayer {
-    pub target_yaw: f32,
+pub struct DialoguePanel;
+
+/// A response the player can click (index into `DialogueView::responses`)
+#[derive(Component)]
+pub struct DialogueResponseButton {
+    pub option: u8,
 }
 
+/// Marker for the dialogue notice text
+#[derive(Component)]
+pub struct DialogueNoticeText;
+
 // =============================================================================
 // SYSTEMS
 // =============================================================================
@@ -109,124 +126,384 @@ pub fn setup_dialogue_assets(mut commands: Commands, asset_server: Res<AssetServ
     info!("Dialogue assets queued for loading");
 }
 
-/// Add DialogueNpc component to Knight and RogueHooded NPCs when they spawn
-pub fn setup_dialogue_npcs(
-    mut commands: Commands,
-    new_npcs: Query<(Entity, &Npc), Added<Npc>>,
+/// Find the nearest living NPC within talking range that has a dialogue tree
+fn detect_nearby_talkers(
+    mut nearby: ResMut<NearbyTalker>,
+    local_player: Query<&PlayerPosition, With<LocalPlayer>>,
+    npcs: Query<(&Npc, &NpcPosition, &Health, Has<TraderStock>), Without<NpcIndoors>>,
+    input_state: Res<InputState>,
 ) {
-    for (entity, npc) in new_npcs.iter() {
-        match npc.archetype {
-            NpcArchetype::Knight | NpcArchetype::RogueHooded => {
-                commands.entity(entity).insert(DialogueNpc::new(npc.archetype));
-                info!("Added DialogueNpc component to {:?} NPC", npc.archetype);
-            }
-            _ => {}
-        }
+    if input_state.in_vehicle || input_state.is_dead {
+        nearby.npc = None;
+        return;
     }
+    let Ok(player_pos) = local_player.single() else {
+        nearby.npc = None;
+        return;
+    };
+
+    nearby.npc = npcs
+        .iter()
+        .filter(|(npc, pos, health, trader)| {
+            !health.is_dead()
+                && dialogue_kind(npc.archetype, *trader).is_some()
+                && pos.0.distance(player_pos.0) <= DIALOGUE_RANGE
+        })
+        .min_by(|(_, a, ..), (_, b, ..)| {
+            a.0.distance_squared(player_pos.0).total_cmp(&b.0.distance_squared(player_pos.0))
+        })
+        .map(|(npc, ..)| npc.id);
 }
 
-/// Main dialogue system - queues dialogue requests when player is close
-/// Actual spawning is handled by process_dialogue_queue to respect limits
-pub fn update_dialogue(
+/// Show "Press E to talk" when near someone to talk to (chests take priority)
+fn show_talk_prompt(
     mut commands: Commands,
-    time: Res<Time>,
-    player_query: Query<&PlayerPosition, With<LocalPlayer>>,
-    mut dialogue_npcs: Query<(Entity, &NpcPosition, &mut DialogueNpc, Option<&Health>)>,
-    existing_audio: Query<&DialogueAudio>,
-    mut audio_manager: ResMut<AudioManager>,
+    nearby: Res<NearbyTalker>,
+    nearby_chest: Res<NearbyChest>,
+    inventory_open: Res<InventoryOpen>,
+    active: Res<ActiveDialogue>,
+    existing_prompt: Query<Entity, With<TalkPrompt>>,
 ) {
-    let Ok(player_pos) = player_query.single() else { return };
+    for entity in existing_prompt.iter() {
+        commands.entity(entity).despawn();
+    }
 
-    let dt = time.delta_secs();
+    if nearby.npc.is_none() || nearby_chest.entity.is_some() || inventory_open.0 || active.npc.is_some() {
+        return;
+    }
 
-    // Clear the dialogue queue from previous frame
-    audio_manager.dialogue_queue.clear();
+    commands.spawn((
+        TalkPrompt,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(150.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-100.0)),
+            ..default()
+        },
+        Text::new("Press [E] to talk"),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::srgba(1.0, 1.0, 1.0, 0.9)),
+    ));
+}
 
-    for (npc_entity, npc_pos, mut dialogue, health_opt) in dialogue_npcs.iter_mut() {
-        // Dead NPCs don't talk
-        if health_opt.is_some_and(|h| h.is_dead()) {
-            // Clean up dialogue state if they were talking
-            if dialogue.is_talking {
-                dialogue.is_talking = false;
-                dialogue.talk_timer = 0.0;
-                commands.entity(npc_entity).remove::<LookingAtPlayer>();
-            }
-            continue;
-        }
+/// Handle E key to start talking (the window opens once the server answers)
+fn handle_talk_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    nearby: Res<NearbyTalker>,
+    nearby_chest: Res<NearbyChest>,
+    open_chest: Res<OpenChest>,
+    inventory_open: Res<InventoryOpen>,
+    active: Res<ActiveDialogue>,
+    mut sender: Query<&mut MessageSender<StartDialogueRequest>, (With<crate::GameClient>, With<Connected>)>,
+) {
+    // E during a conversation ends it (see `handle_dialogue_close_input`)
+    if !keyboard.just_pressed(KeyCode::KeyE) || active.npc.is_some() {
+        return;
+    }
+    // Chests handle E themselves
+    if nearby_chest.entity.is_some() || open_chest.entity.is_some() || inventory_open.0 {
+        return;
+    }
+    let Some(npc) = nearby.npc else {
+        return;
+    };
+    if let Ok(mut sender) = sender.single_mut() {
+        let _ = sender.send::<ReliableChannel>(StartDialogueRequest { npc });
+    }
+}
 
-        // Update cooldown
-        if dialogue.cooldown_timer > 0.0 {
-            dialogue.cooldown_timer -= dt;
+/// Open, move on or close the conversation as the server says (and open the trade window
+/// when a response asks for it)
+fn receive_dialogue_updates(
+    mut receiver: Query<&mut MessageReceiver<DialogueUpdate>, (With<crate::GameClient>, With<Connected>)>,
+    mut active: ResMut<ActiveDialogue>,
+    mut notice: ResMut<DialogueNotice>,
+    traders: Query<(Entity, &Npc), With<TraderStock>>,
+    mut open_trade: ResMut<OpenTrade>,
+    mut trade_feedback: ResMut<TradeFeedback>,
+    mut inventory_open: ResMut<InventoryOpen>,
+    mut input_state: ResMut<InputState>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut cursor_opts: Query<&mut CursorOptions>,
+) {
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    for update in receiver.receive() {
+        if !update.notices.is_empty() {
+            *notice = DialogueNotice { text: update.notices.join("\n"), timer: DIALOGUE_NOTICE_TIME };
         }
 
-        // Update talk timer
-        if dialogue.is_talking {
-            dialogue.talk_timer += dt;
-            if dialogue.talk_timer > DIALOGUE_DURATION {
-                dialogue.is_talking = false;
-                dialogue.talk_timer = 0.0;
+        match update.view {
+            Some(view) => {
+                if active.view.is_none() {
+                    // Free the mouse for the responses and stop moving while talking
+                    input_state.inventory_open = true;
+                    set_cursor_free(true, &windows, &mut cursor_opts);
+                }
+                active.npc = Some(update.npc);
+                active.view = Some(view);
+            }
+            None => {
+                // A refusal for another NPC doesn't end this conversation
+                if active.npc != Some(update.npc) {
+                    continue;
+                }
+                *active = ActiveDialogue::default();
+
+                let trader = traders.iter().find(|(_, npc)| npc.id == update.npc).map(|(entity, _)| entity);
+                match trader.filter(|_| update.open_trade) {
+                    Some(entity) => {
+                        // The trade window lives in the inventory UI; it keeps the mouse
+                        open_trade.entity = Some(entity);
+                        *trade_feedback = TradeFeedback::default();
+                        inventory_open.0 = true;
+                    }
+                    None => {
+                        input_state.inventory_open = false;
+                        set_cursor_free(false, &windows, &mut cursor_opts);
+                    }
+                }
             }
         }
+    }
+}
+
+/// E or ESC ends the conversation; so does opening the inventory or dying
+fn handle_dialogue_close_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut active: ResMut<ActiveDialogue>,
+    inventory_open: Res<InventoryOpen>,
+    mut input_state: ResMut<InputState>,
+    mut sender: Query<&mut MessageSender<EndDialogueRequest>, (With<crate::GameClient>, With<Connected>)>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut cursor_opts: Query<&mut CursorOptions>,
+) {
+    if active.npc.is_none() {
+        return;
+    }
+    let leave = keyboard.just_pressed(KeyCode::KeyE) || keyboard.just_pressed(KeyCode::Escape);
+    if !leave && !inventory_open.0 && !input_state.is_dead {
+        return;
+    }
 
-        // Check if already has audio playing
-        let has_active_audio = existing_audio.iter().any(|d| d.npc_entity == npc_entity);
-
-        // Check distance to player
-        let distance_sq = npc_pos.0.distance_squared(player_pos.0);
-        let distance = distance_sq.sqrt();
-
-        if distance < DIALOGUE_TRIGGER_DISTANCE
-            && dialogue.cooldown_timer <= 0.0
-            && !dialogue.is_talking
-            && !has_active_audio
-        {
-            // Queue this NPC's dialogue request (will be processed by process_dialogue_queue)
-            audio_manager.dialogue_queue.push(DialogueRequest {
-                npc_entity,
-                distance_sq,
-                archetype: dialogue.archetype,
+    if let Ok(mut sender) = sender.single_mut() {
+        let _ = sender.send::<ReliableChannel>(EndDialogueRequest);
+    }
+    *active = ActiveDialogue::default();
+    // An inventory opened over the conversation keeps the mouse
+    if !inventory_open.0 {
+        input_state.inventory_open = false;
+        set_cursor_free(false, &windows, &mut cursor_opts);
+    }
+}
+
+/// Rebuild the conversation window whenever the conversation changes
+fn update_dialogue_panel(
+    mut commands: Commands,
+    active: Res<ActiveDialogue>,
+    panels: Query<Entity, With<DialoguePanel>>,
+) {
+    if !active.is_changed() {
+        return;
+    }
+    for entity in panels.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Some(view) = &active.view else {
+        return;
+    };
+
+    commands.spawn((
+        DialoguePanel,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(60.0),
+            left: Val::Percent(50.0),
+            width: Val::Px(640.0),
+            margin: UiRect::left(Val::Px(-320.0)),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(6.0),
+            padding: UiRect::all(Val::Px(16.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(MENU_BACKGROUND.with_alpha(0.92)),
+        BorderColor::from(BUTTON_BORDER),
+    )).with_children(|panel| {
+        panel.spawn((
+            Text::new(view.speaker.clone()),
+            TextFont {
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(ACCENT_COLOR),
+        ));
+        panel.spawn((
+            Text::new(view.text.clone()),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Node {
+                margin: UiRect::bottom(Val::Px(8.0)),
+                ..default()
+            },
+        ));
+
+        for (option, response) in view.responses.iter().enumerate() {
+            panel.spawn((
+                Button,
+                DialogueResponseButton { option: option as u8 },
+                Node {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BackgroundColor(BUTTON_NORMAL),
+                BorderColor::from(BUTTON_BORDER),
+            )).with_children(|button| {
+                button.spawn((
+                    Text::new(format!("{}. {}", option + 1, response)),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                ));
             });
         }
 
-        // Remove look-at when done talking
-        if !dialogue.is_talking {
-            commands.entity(npc_entity).remove::<LookingAtPlayer>();
+        panel.spawn((
+            Text::new("[E] / [ESC] Leave"),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(TEXT_MUTED),
+        ));
+    });
+}
+
+/// Send the clicked response to the server
+fn handle_response_buttons(
+    mut buttons: Query<(&Interaction, &DialogueResponseButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut sender: Query<&mut MessageSender<DialogueChoiceRequest>, (With<crate::GameClient>, With<Connected>)>,
+) {
+    for (interaction, button, mut bg_color) in buttons.iter_mut() {
+        *bg_color = match interaction {
+            Interaction::Pressed => BackgroundColor(BUTTON_PRESSED),
+            Interaction::Hovered => BackgroundColor(BUTTON_HOVERED),
+            Interaction::None => BackgroundColor(BUTTON_NORMAL),
+        };
+        if *interaction == Interaction::Pressed {
+            if let Ok(mut sender) = sender.single_mut() {
+                let _ = sender.send::<ReliableChannel>(DialogueChoiceRequest { option: button.option });
+            }
         }
     }
 }
 
+/// Show the latest dialogue notice for a few seconds
+fn show_dialogue_notice(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notice: ResMut<DialogueNotice>,
+    existing: Query<Entity, With<DialogueNoticeText>>,
+) {
+    for entity in existing.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if notice.text.is_empty() {
+        return;
+    }
+    notice.timer -= time.delta_secs();
+    if notice.timer <= 0.0 {
+        *notice = DialogueNotice::default();
+        return;
+    }
+
+    commands.spawn((
+        DialogueNoticeText,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(120.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(notice.text.clone()),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(ACCENT_COLOR),
+        TextLayout::new_with_justify(Justify::Center),
+    ));
+}
+
+/// Queue the voice lines the server says NPCs near us are speaking
+fn receive_voice_lines(
+    mut receiver: Query<&mut MessageReceiver<NpcVoiceLine>, (With<crate::GameClient>, With<Connected>)>,
+    npcs: Query<(Entity, &Npc, &NpcPosition)>,
+    local_player: Query<&PlayerPosition, With<LocalPlayer>>,
+    mut audio_manager: ResMut<AudioManager>,
+) {
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    let listener = local_player.single().ok().map(|pos| pos.0);
+
+    for line in receiver.receive() {
+        let Some((npc_entity, npc, npc_pos)) = npcs.iter().find(|(_, npc, _)| npc.id == line.npc) else {
+            continue;
+        };
+        audio_manager.dialogue_queue.push(DialogueRequest {
+            npc_entity,
+            distance_sq: listener.map_or(0.0, |listener| listener.distance_squared(npc_pos.0)),
+            archetype: npc.archetype,
+            clip: line.clip,
+        });
+    }
+}
+
 /// Process queued dialogue requests, respecting the max_dialogue limit
-/// Only the closest NPCs get to speak if there are more requests than slots
+/// Only the closest NPCs get to speak if there are more requests than slots; the rest are
+/// dropped (played late they'd be out of step with everyone else's)
 pub fn process_dialogue_queue(
     mut commands: Commands,
     time: Res<Time>,
     assets: Option<Res<DialogueAssets>>,
     mut audio_manager: ResMut<AudioManager>,
-    mut dialogue_npcs: Query<(&NpcPosition, &mut DialogueNpc)>,
-    existing_dialogue: Query<&DialogueAudio>,
-    player_query: Query<&PlayerPosition, With<LocalPlayer>>,
+    npc_positions: Query<&NpcPosition>,
+    existing_dialogue: Query<(Entity, &DialogueAudio)>,
 ) {
-    let Some(assets) = assets else { return };
-    let Ok(player_pos) = player_query.single() else { return };
+    if audio_manager.dialogue_queue.is_empty() {
+        return;
+    }
+    let Some(assets) = assets else {
+        audio_manager.dialogue_queue.clear();
+        return;
+    };
     let now = time.elapsed_secs();
 
     // Count current dialogue audio
     let current_dialogue_count = existing_dialogue.iter().count();
     let available_slots = audio_manager.max_dialogue.saturating_sub(current_dialogue_count);
 
-    if available_slots == 0 || audio_manager.dialogue_queue.is_empty() {
-        return;
-    }
-
     // Sort by distance (closest first)
     audio_manager.dialogue_queue.sort_by(|a, b| {
         a.distance_sq.partial_cmp(&b.distance_sq).unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    // Process up to available_slots requests
-    let requests_to_process = available_slots.min(audio_manager.dialogue_queue.len());
-    for request in audio_manager.dialogue_queue.drain(..requests_to_process) {
-        let Ok((npc_pos, mut dialogue)) = dialogue_npcs.get_mut(request.npc_entity) else {
+    for request in audio_manager.dialogue_queue.drain(..).take(available_slots) {
+        let Ok(npc_pos) = npc_positions.get(request.npc_entity) else {
             continue;
         };
 
@@ -239,10 +516,14 @@ pub fn process_dialogue_queue(
         if lines.is_empty() {
             continue;
         }
+        let line = lines[request.clip as usize % lines.len()].clone();
 
-        // Cycle through lines
-        let line = lines[dialogue.line_index % lines.len()].clone();
-        dialogue.line_index = (dialogue.line_index + 1) % lines.len();
+        // A new line cuts off whatever the NPC was still saying
+        for (entity, audio) in existing_dialogue.iter() {
+            if audio.npc_entity == request.npc_entity {
+                commands.entity(entity).despawn();
+            }
+        }
 
         // Spawn spatial audio at NPC position with ManagedAudioTag for limit tracking
         commands.spawn((
@@ -259,18 +540,10 @@ pub fn process_dialogue_queue(
             GlobalTransform::from_translation(npc_pos.0),
         ));
 
-        dialogue.is_talking = true;
-        dialogue.talk_timer = 0.0;
-        dialogue.cooldown_timer = DIALOGUE_COOLDOWN;
-
-        // Add look-at component
-        let dir_to_player = player_pos.0 - npc_pos.0;
-        let target_yaw = (-dir_to_player.x).atan2(-dir_to_player.z);
-        commands.entity(request.npc_entity).insert(LookingAtPlayer { target_yaw });
-
-        info!(
-            "{:?} NPC speaking (distance: {:.1}m)",
+        debug!(
+            "{:?} NPC speaking line {} (distance: {:.1}m)",
             request.archetype,
+            request.clip,
             request.distance_sq.sqrt()
         );
     }
@@ -301,42 +574,29 @@ pub fn update_dialogue_audio_positions(
     }
 }
 
-/// Override NPC rotation when they should look at player
-pub fn apply_look_at_player(
-    time: Res<Time>,
-    mut npcs: Query<(&mut Transform, &LookingAtPlayer, Option<&Health>), With<DialogueNpc>>,
+/// Leave any conversation and clear the dialogue UI
+fn cleanup_dialogue_ui(
+    mut commands: Commands,
+    ui: Query<Entity, Or<(With<TalkPrompt>, With<DialoguePanel>, With<DialogueNoticeText>)>>,
+    mut active: ResMut<ActiveDialogue>,
+    mut notice: ResMut<DialogueNotice>,
+    inventory_open: Res<InventoryOpen>,
+    mut input_state: ResMut<InputState>,
+    mut sender: Query<&mut MessageSender<EndDialogueRequest>, (With<crate::GameClient>, With<Connected>)>,
 ) {
-    let dt = time.delta_secs();
-    let turn_speed = 5.0; // Radians per second
-
-    for (mut transform, look_at, health_opt) in npcs.iter_mut() {
-        // Dead NPCs don't look at player
-        if health_opt.is_some_and(|h| h.is_dead()) {
-            continue;
-        }
-        // Smoothly rotate toward player
-        let current_yaw = transform.rotation.to_euler(EulerRot::YXZ).0;
-        let target_yaw = look_at.target_yaw;
-
-        // Normalize angle difference
-        let mut diff = target_yaw - current_yaw;
-        while diff > std::f32::consts::PI {
-            diff -= std::f32::consts::TAU;
+    for entity in ui.iter() {
+        commands.entity(entity).despawn();
+    }
+    if active.npc.is_some() {
+        if let Ok(mut sender) = sender.single_mut() {
+            let _ = sender.send::<ReliableChannel>(EndDialogueRequest);
         }
-        while diff < -std::f32::consts::PI {
-            diff += std::f32::consts::TAU;
+        if !inventory_open.0 {
+            input_state.inventory_open = false;
         }
-
-        // Apply rotation
-        let turn_amount = turn_speed * dt;
-        let new_yaw = if diff.abs() < turn_amount {
-            target_yaw
-        } else {
-            current_yaw + diff.signum() * turn_amount
-        };
-
-        transform.rotation = Quat::from_rotation_y(new_yaw);
     }
+    *active = ActiveDialogue::default();
+    *notice = DialogueNotice::default();
 }
 
 // =============================================================================
@@ -347,18 +607,29 @@ pub struct DialoguePlugin;
 
 impl Plugin for DialoguePlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<NearbyTalker>();
+        app.init_resource::<ActiveDialogue>();
+        app.init_resource::<DialogueNotice>();
+
         app.add_systems(Startup, setup_dialogue_assets);
         app.add_systems(
             Update,
             (
-                setup_dialogue_npcs,
-                update_dialogue,
+                detect_nearby_talkers,
+                show_talk_prompt,
+                handle_talk_input,
+                handle_dialogue_close_input,
+                receive_dialogue_updates,
+                update_dialogue_panel,
+                handle_response_buttons,
+                show_dialogue_notice,
+                receive_voice_lines,
                 process_dialogue_queue, // Process queued requests (respects max_dialogue limit)
                 update_dialogue_audio_positions,
-                apply_look_at_player,
             )
                 .chain()
                 .run_if(in_state(GameState::Playing)),
         );
+        app.add_systems(OnExit(GameState::Playing), cleanup_dialogue_ui);
     }
 }
@@ -0,0 +1,243 @@
+//! Client-side explosives
+//!
+//! Throw input (G = grenade, H = place charge), visuals for live explosives,
+//! and detonation effects (flash, smoke, boom) from server `Explosion` messages.
+//! Craters arrive separately through the replicated terrain delta chunks.
+
+use bevy::prelude::*;
+use bevy::audio::Volume;
+use lightyear::prelude::*;
+use shared::{
+    weapons::{Explosion, Explosive, ExplosiveType, ThrowRequest},
+    ItemType, LocalPlayer, PlayerPosition, ReliableChannel,
+};
+
+use crate::input::InputState;
+use crate::weapons::WeaponAudioAssets;
+
+/// Cached meshes/materials for explosives and their effects
+#[derive(Resource)]
+pub struct ExplosiveVisualAssets {
+    pub grenade_mesh: Handle<Mesh>,
+    pub grenade_material: Handle<StandardMaterial>,
+    pub charge_mesh: Handle<Mesh>,
+    pub charge_material: Handle<StandardMaterial>,
+    /// Unit sphere, scaled per effect
+    pub effect_sphere_mesh: Handle<Mesh>,
+}
+
+/// Expanding fireball / smoke sphere spawned on detonation
+#[derive(Component)]
+pub struct ExplosionEffect {
+    pub spawn_time: f32,
+    pub lifetime: f32,
+    pub max_scale: f32,
+    /// Rises slowly (smoke) instead of staying put (fireball)
+    pub rise_speed: f32,
+}
+
+/// Create shared meshes/materials for explosives
+pub fn setup_explosive_visual_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ExplosiveVisualAssets {
+        grenade_mesh: meshes.add(Sphere::new(0.08).mesh().ico(2).unwrap()),
+        grenade_material: materials.add(StandardMaterial {
+            base_color: ItemType::Explosive(ExplosiveType::Grenade).color(),
+            perceptual_roughness: 0.7,
+            ..default()
+        }),
+        charge_mesh: meshes.add(Cuboid::new(0.25, 0.1, 0.18)),
+        charge_material: materials.add(StandardMaterial {
+            base_color: ItemType::Explosive(ExplosiveType::Charge).color(),
+            emissive: LinearRgba::new(0.6, 0.1, 0.0, 1.0), // Visible warning glow
+            perceptual_roughness: 0.6,
+            ..default()
+        }),
+        effect_sphere_mesh: meshes.add(Sphere::new(1.0).mesh().ico(2).unwrap()),
+    });
+}
+
+/// Handle throw input - G throws a grenade, H places a charge
+pub fn handle_throw_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut client_query: Query<&mut MessageSender<ThrowRequest>, (With<crate::GameClient>, With<Connected>)>,
+    local_player: Query<&shared::Inventory, With<LocalPlayer>>,
+    camera: Query<&Transform, With<Camera3d>>,
+    input_state: Res<InputState>,
+) {
+    if input_state.is_dead || input_state.in_vehicle || input_state.inventory_open || input_state.build_mode_active {
+        return;
+    }
+
+    let explosive_type = if keyboard.just_pressed(KeyCode::KeyG) {
+        ExplosiveType::Grenade
+    } else if keyboard.just_pressed(KeyCode::KeyH) {
+        ExplosiveType::Charge
+    } else {
+        return;
+    };
+
+    let Ok(inventory) = local_player.single() else {
+        return;
+    };
+    if inventory.count_item(ItemType::Explosive(explosive_type)) == 0 {
+        return;
+    }
+    let Ok(camera_transform) = camera.single() else {
+        return;
+    };
+
+    if let Ok(mut sender) = client_query.single_mut() {
+        let _ = sender.send::<ReliableChannel>(ThrowRequest {
+            explosive_type,
+            direction: camera_transform.forward().as_vec3(),
+        });
+    }
+}
+
+/// Attach a mesh to newly replicated explosives
+pub fn handle_explosive_spawned(
+    mut commands: Commands,
+    assets: Option<Res<ExplosiveVisualAssets>>,
+    explosives: Query<(Entity, &Explosive, Option<&PlayerPosition>), Added<Explosive>>,
+) {
+    let Some(assets) = assets else {
+        return;
+    };
+
+    for (entity, explosive, position) in explosives.iter() {
+        let (mesh, material) = match explosive.explosive_type {
+            ExplosiveType::Grenade => (assets.grenade_mesh.clone(), assets.grenade_material.clone()),
+            ExplosiveType::Charge => (assets.charge_mesh.clone(), assets.charge_material.clone()),
+        };
+        commands.entity(entity).insert((
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::from_translation(position.map(|p| p.0).unwrap_or_default()),
+        ));
+    }
+}
+
+/// Move explosive visuals to their replicated positions
+pub fn update_explosive_visuals(
+    mut explosives: Query<(&PlayerPosition, &mut Transform), With<Explosive>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    for (position, mut transform) in explosives.iter_mut() {
+        // Smooth toward the server position (replication runs slower than rendering)
+        let t = (dt * 25.0).min(1.0);
+        transform.translation = transform.translation.lerp(position.0, t);
+    }
+}
+
+/// Spawn fireball, smoke and sound for server-reported detonations
+pub fn handle_explosions(
+    mut commands: Commands,
+    assets: Option<Res<ExplosiveVisualAssets>>,
+    audio: Option<Res<WeaponAudioAssets>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut client_query: Query<&mut MessageReceiver<Explosion>, (With<crate::GameClient>, With<Connected>)>,
+    time: Res<Time>,
+) {
+    let Some(assets) = assets else {
+        return;
+    };
+    let Ok(mut receiver) = client_query.single_mut() else {
+        return;
+    };
+    let now = time.elapsed_secs();
+
+    for explosion in receiver.receive() {
+        // Fireball: bright, fast, roughly half the blast radius
+        commands.spawn((
+            ExplosionEffect {
+                spawn_time: now,
+                lifetime: 0.35,
+                max_scale: explosion.radius * 0.5,
+                rise_speed: 0.0,
+            },
+            Mesh3d(assets.effect_sphere_mesh.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(1.0, 0.6, 0.2, 0.9),
+                emissive: LinearRgba::new(30.0, 12.0, 3.0, 1.0),
+                unlit: true,
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })),
+            Transform::from_translation(explosion.position).with_scale(Vec3::splat(0.2)),
+        ));
+
+        // Smoke: slower, darker, drifts upward
+        commands.spawn((
+            ExplosionEffect {
+                spawn_time: now,
+                lifetime: 2.5,
+                max_scale: explosion.radius * 0.6,
+                rise_speed: 1.5,
+            },
+            Mesh3d(assets.effect_sphere_mesh.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.25, 0.23, 0.2, 0.6),
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 1.0,
+                ..default()
+            })),
+            Transform::from_translation(explosion.position).with_scale(Vec3::splat(0.2)),
+        ));
+
+        // Boom: no dedicated asset yet, so use a slowed-down gunshot
+        if let Some(ref audio) = audio {
+            commands.spawn((
+                AudioPlayer::new(audio.gun_shot.clone()),
+                PlaybackSettings::DESPAWN
+                    .with_volume(Volume::Linear(1.2))
+                    .with_speed(0.45)
+                    .with_spatial(true),
+                Transform::from_translation(explosion.position),
+            ));
+        }
+    }
+}
+
+/// Expand and fade explosion effects
+pub fn update_explosion_effects(
+    mut commands: Commands,
+    mut effects: Query<(Entity, &ExplosionEffect, &mut Transform, &MeshMaterial3d<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+
+    for (entity, effect, mut transform, material_handle) in effects.iter_mut() {
+        let age = now - effect.spawn_time;
+        if age > effect.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let t = (age / effect.lifetime).clamp(0.0, 1.0);
+        let ease_t = 1.0 - (1.0 - t).powi(3); // Cubic ease-out
+        transform.scale = Vec3::splat(0.2 + (effect.max_scale - 0.2) * ease_t);
+        transform.translation.y += effect.rise_speed * time.delta_secs();
+
+        if let Some(mat) = materials.get_mut(&material_handle.0) {
+            let base = mat.base_color.to_srgba();
+            let alpha = (1.0 - t) * if effect.rise_speed > 0.0 { 0.6 } else { 0.9 };
+            mat.base_color = Color::srgba(base.red, base.green, base.blue, alpha);
+        }
+    }
+}
+
+/// Despawn leftover explosion effects when leaving gameplay
+pub fn despawn_explosion_effects(
+    mut commands: Commands,
+    effects: Query<Entity, With<ExplosionEffect>>,
+) {
+    for entity in effects.iter() {
+        commands.entity(entity).despawn();
+    }
+}
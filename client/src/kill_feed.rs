@@ -0,0 +1,273 @@
+//! Kill feed and damage log HUD
+//!
+//! Server-broadcast `KillFeedEntry`s scroll in at the top-right. When we die the
+//! server sends a `DamageLogReport` for the life we just lost; Tab on the death
+//! screen opens it to show every hit taken and dealt.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use shared::{damage_totals, Combatant, DamageLogEntry, DamageLogReport, KillFeedEntry};
+
+use crate::camera::peer_id_to_u64;
+use crate::crosshair::Pickable;
+use crate::input::InputState;
+
+/// Lines shown at once (oldest dropped first)
+const KILL_FEED_MAX_LINES: usize = 5;
+
+/// Seconds a kill feed line stays up
+const KILL_FEED_LINE_LIFETIME: f32 = 6.0;
+
+/// Seconds a line takes to fade out at the end of its lifetime
+const KILL_FEED_FADE_TIME: f32 = 1.0;
+
+/// Last received damage log and whether the panel is open
+#[derive(Resource, Default)]
+pub struct DamageLogState {
+    pub entries: Vec<DamageLogEntry>,
+    pub open: bool,
+}
+
+/// Kill feed container (top-right)
+#[derive(Component)]
+pub struct KillFeed;
+
+/// One kill feed line
+#[derive(Component)]
+pub struct KillFeedLine {
+    pub spawn_time: f32,
+    pub color: Color,
+}
+
+/// Damage log panel (death screen only)
+#[derive(Component)]
+pub struct DamageLogPanel;
+
+/// Damage log text
+#[derive(Component)]
+pub struct DamageLogText;
+
+/// Spawn the (empty) kill feed and the hidden damage log panel
+pub fn spawn_kill_feed(mut commands: Commands) {
+    commands.spawn((
+        KillFeed,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.0),
+            top: Val::Px(20.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::End,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        Pickable::IGNORE,
+    ));
+
+    commands
+        .spawn((
+            DamageLogPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(40.0),
+                bottom: Val::Px(40.0),
+                max_width: Val::Percent(60.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                DamageLogText,
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.9, 0.9, 0.9, 0.95)),
+            ));
+        });
+}
+
+/// Kill feed text for one entry
+fn kill_feed_text(entry: &KillFeedEntry) -> String {
+    let mut details = format!("{:.0}m", entry.distance);
+    if entry.headshot {
+        details.push_str(", HS");
+    }
+    let victim = entry.victim.display_name();
+    match &entry.killer {
+        Combatant::Environment => format!("{} died [{}]", victim, entry.cause.label()),
+        killer if killer == &entry.victim => format!("{} [{}] themselves", victim, entry.cause.label()),
+        killer => format!("{} [{}] {}  ({})", killer.display_name(), entry.cause.label(), victim, details),
+    }
+}
+
+/// Add server-broadcast kills to the feed
+pub fn handle_kill_feed(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut receiver: Query<&mut MessageReceiver<KillFeedEntry>, (With<crate::GameClient>, With<Connected>)>,
+    local_id: Query<&LocalId, (With<crate::GameClient>, With<Connected>)>,
+    feed: Query<Entity, With<KillFeed>>,
+    lines: Query<(Entity, &KillFeedLine)>,
+) {
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    let Ok(feed) = feed.single() else {
+        return;
+    };
+    let our_id = local_id.iter().next().map(|id| peer_id_to_u64(id.0));
+    let now = time.elapsed_secs();
+
+    let mut line_count = lines.iter().count();
+    let mut oldest: Vec<(Entity, f32)> = lines.iter().map(|(e, l)| (e, l.spawn_time)).collect();
+    oldest.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    let mut oldest = oldest.into_iter();
+
+    for entry in receiver.receive() {
+        // Our kills in yellow, our deaths in red
+        let color = if our_id.is_some() && entry.victim.id() == our_id {
+            Color::srgb(1.0, 0.35, 0.3)
+        } else if our_id.is_some() && entry.killer.id() == our_id {
+            Color::srgb(1.0, 0.85, 0.3)
+        } else {
+            Color::srgb(0.95, 0.95, 0.95)
+        };
+
+        if line_count >= KILL_FEED_MAX_LINES {
+            if let Some((entity, _)) = oldest.next() {
+                commands.entity(entity).despawn();
+                line_count -= 1;
+            }
+        }
+
+        let line = commands
+            .spawn((
+                KillFeedLine { spawn_time: now, color },
+                Text::new(kill_feed_text(&entry)),
+                TextFont {
+                    font_size: 15.0,
+                    ..default()
+                },
+                TextColor(color),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+                Node {
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                    ..default()
+                },
+            ))
+            .id();
+        commands.entity(feed).add_child(line);
+        line_count += 1;
+    }
+}
+
+/// Fade out and remove old kill feed lines
+pub fn update_kill_feed(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lines: Query<(Entity, &KillFeedLine, &mut TextColor)>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, line, mut color) in lines.iter_mut() {
+        let age = now - line.spawn_time;
+        if age > KILL_FEED_LINE_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let fade = ((KILL_FEED_LINE_LIFETIME - age) / KILL_FEED_FADE_TIME).clamp(0.0, 1.0);
+        color.0 = line.color.with_alpha(fade);
+    }
+}
+
+/// Store the damage log the server sends when we die
+pub fn handle_damage_log_reports(
+    mut receiver: Query<&mut MessageReceiver<DamageLogReport>, (With<crate::GameClient>, With<Connected>)>,
+    mut state: ResMut<DamageLogState>,
+) {
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    for report in receiver.receive() {
+        state.entries = report.entries;
+        state.open = false;
+    }
+}
+
+/// Damage log panel text
+fn damage_log_text(entries: &[DamageLogEntry]) -> String {
+    let (taken, dealt) = damage_totals(entries);
+    let mut text = format!("DAMAGE LOG  -  taken {:.0}, dealt {:.0}   [Tab] close\n", taken, dealt);
+    let end_time = entries.last().map(|e| e.time).unwrap_or(0.0);
+    for entry in entries {
+        let (verb, preposition) = if entry.dealt { ("DEALT", "to") } else { ("TOOK", "from") };
+        text.push_str(&format!(
+            "\n{:>6.1}s  {} {:.0} {} {} ({}, {:?}, {:.0}m)",
+            entry.time - end_time,
+            verb,
+            entry.damage,
+            preposition,
+            entry.other.display_name(),
+            entry.cause.label(),
+            entry.hit_zone,
+            entry.distance,
+        ));
+        if entry.armor_absorbed > 0.0 {
+            text.push_str(&format!(" +{:.0} armor", entry.armor_absorbed));
+        }
+        if entry.fatal {
+            text.push_str("  FATAL");
+        }
+    }
+    text
+}
+
+/// Tab on the death screen toggles the damage log; respawning closes it
+pub fn toggle_damage_log(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    input_state: Res<InputState>,
+    mut state: ResMut<DamageLogState>,
+    mut panel: Query<&mut Visibility, With<DamageLogPanel>>,
+    mut text: Query<&mut Text, With<DamageLogText>>,
+) {
+    if !input_state.is_dead {
+        state.open = false;
+    } else if keyboard.just_pressed(KeyCode::Tab) {
+        state.open = !state.open;
+    }
+
+    let content = if !input_state.is_dead {
+        None
+    } else if state.open {
+        Some(damage_log_text(&state.entries))
+    } else if !state.entries.is_empty() {
+        Some("[Tab] Damage log".to_string())
+    } else {
+        None
+    };
+
+    for mut visibility in panel.iter_mut() {
+        *visibility = if content.is_some() { Visibility::Visible } else { Visibility::Hidden };
+    }
+    if let Some(content) = content {
+        for mut text in text.iter_mut() {
+            if text.0 != content {
+                text.0 = content.clone();
+            }
+        }
+    }
+}
+
+/// Despawn the kill feed and damage log when leaving gameplay
+pub fn despawn_kill_feed(
+    mut commands: Commands,
+    roots: Query<Entity, Or<(With<KillFeed>, With<DamageLogPanel>)>>,
+) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -8,16 +8,24 @@ mod camera;
 mod chest;
 mod crosshair;
 mod dialogue;
+mod explosives;
 mod input;
+mod kill_feed;
+mod match_hud;
+mod name_tags;
+mod npc_debug;
 mod pickup;
 mod props;
 mod states;
 mod structures;
 mod systems;
 mod terrain;
+mod territory;
+mod trade;
 mod ui;
 mod weapons;
 mod weapon_view;
+mod zone;
 
 use bevy::prelude::*;
 use bevy::audio::{AudioPlugin, SpatialScale};
@@ -123,6 +131,9 @@ fn main() {
     
     // Chest plugin (storage containers)
     app.add_plugins(chest::ChestPlugin);
+
+    // Trade plugin (vendor NPCs)
+    app.add_plugins(trade::TradePlugin);
     
     // Build mode plugin
     app.add_plugins(build_mode::BuildModePlugin);
@@ -139,6 +150,7 @@ fn main() {
         systems::setup_particle_assets,
         weapons::setup_weapon_visual_assets,
         weapons::setup_weapon_audio_assets,
+        explosives::setup_explosive_visual_assets,
         systems::setup_player_character_assets,
         systems::setup_npc_assets,
     ));
@@ -149,6 +161,9 @@ fn main() {
     app.init_resource::<weapons::ShootingState>();
     app.init_resource::<weapons::ReloadState>();
     app.init_resource::<weapons::DebugBulletTrails>();
+    app.init_resource::<kill_feed::DamageLogState>();
+    app.init_resource::<match_hud::ScoreboardState>();
+    app.init_resource::<territory::CaptureAnnouncements>();
     app.init_resource::<weapon_view::CurrentWeaponView>();
     app.init_resource::<weapon_view::CurrentThirdPersonWeapon>();
     
@@ -173,6 +188,11 @@ fn main() {
         systems::spawn_world,
         crosshair::spawn_crosshair,
         crosshair::spawn_death_screen,
+        crosshair::spawn_suppression_vignette,
+        kill_feed::spawn_kill_feed,
+        match_hud::spawn_match_hud,
+        zone::spawn_zone_hud,
+        territory::spawn_territory_hud,
         weapon_view::spawn_weapon_hud,
         weapons::spawn_debug_overlay,
     ));
@@ -181,10 +201,18 @@ fn main() {
     app.add_systems(OnExit(GameState::Playing), (
         crosshair::despawn_crosshair,
         crosshair::despawn_death_screen,
+        crosshair::despawn_suppression_vignette,
+        kill_feed::despawn_kill_feed,
+        match_hud::despawn_match_hud,
+        name_tags::despawn_name_tags,
+        npc_debug::despawn_npc_behaviour_labels,
+        zone::despawn_zone_hud,
+        territory::despawn_territory_hud,
         weapon_view::despawn_weapon_hud,
         weapon_view::despawn_third_person_weapon,
         weapon_view::despawn_remote_third_person_weapons,
         weapons::despawn_debug_overlay,
+        explosives::despawn_explosion_effects,
     ));
 
     // Send input to server at fixed tick rate (60 Hz)
@@ -243,6 +271,7 @@ fn main() {
             systems::setup_ranger_rig,
             systems::update_ranger_animation,
             systems::update_local_player_visibility,
+            systems::update_player_armor_visuals,
         )
             .run_if(in_state(GameState::Playing)),
     );
@@ -253,6 +282,7 @@ fn main() {
         (
             systems::setup_npc_rig,
             systems::update_npc_animation,
+            systems::update_npc_indoors_visibility,
             systems::debug_draw_npc_hitboxes,
         )
             .run_if(in_state(GameState::Playing)),
@@ -264,8 +294,11 @@ fn main() {
         (
             crosshair::update_crosshair_visibility,
             crosshair::update_crosshair_ads,
+            crosshair::update_scope_wind_reticle,
+            crosshair::update_scope_zero_reticle,
             crosshair::update_hit_markers,
             crosshair::update_death_screen,
+            crosshair::update_suppression_vignette,
         )
             .run_if(in_state(GameState::Playing)),
     );
@@ -275,6 +308,9 @@ fn main() {
         (
             weapons::handle_shoot_input,
             weapons::handle_reload_input,
+            weapons::sync_reload_state,
+            weapons::handle_fire_mode_input,
+            weapons::handle_zero_input,
             weapons::play_weapon_sounds,
             weapons::handle_bullet_spawned,
             weapons::recover_recoil,
@@ -319,10 +355,79 @@ fn main() {
             weapon_view::update_third_person_weapon,
             weapon_view::update_remote_third_person_weapons,
             weapon_view::animate_weapon,
+            weapon_view::start_remote_melee_swings,
+            weapon_view::animate_third_person_swings,
+        )
+            .run_if(in_state(GameState::Playing)),
+    );
+
+    // Explosives (throw input, live charges/grenades, detonation effects)
+    app.add_systems(
+        Update,
+        (
+            explosives::handle_throw_input,
+            explosives::handle_explosive_spawned,
+            explosives::update_explosive_visuals,
+            explosives::handle_explosions,
+            explosives::update_explosion_effects,
         )
             .run_if(in_state(GameState::Playing)),
     );
 
+    // Kill feed and post-death damage log
+    app.add_systems(
+        Update,
+        (
+            kill_feed::handle_kill_feed,
+            kill_feed::update_kill_feed,
+            kill_feed::handle_damage_log_reports,
+            kill_feed::toggle_damage_log,
+        )
+            .run_if(in_state(GameState::Playing)),
+    );
+
+    // Match status line + end-of-match scoreboard
+    app.add_systems(
+        Update,
+        (match_hud::handle_match_scoreboard, match_hud::update_match_hud)
+            .chain()
+            .run_if(in_state(GameState::Playing)),
+    );
+
+    // Battle-royale zone wall + zone timer
+    app.add_systems(
+        Update,
+        (zone::sync_zone_wall, zone::update_zone_hud).run_if(in_state(GameState::Playing)),
+    );
+
+    // Territory capture status, announcements + map
+    app.add_systems(
+        Update,
+        (
+            territory::handle_capture_announcements,
+            territory::update_territory_hud,
+            territory::update_territory_map,
+        )
+            .chain()
+            .run_if(in_state(GameState::Playing)),
+    );
+
+    // Team-coloured name tags over other players
+    app.add_systems(
+        Update,
+        (name_tags::spawn_name_tags, name_tags::update_name_tags)
+            .chain()
+            .run_if(in_state(GameState::Playing)),
+    );
+
+    // Active behaviour leaf over NPCs (F4 debug mode)
+    app.add_systems(
+        Update,
+        (npc_debug::spawn_npc_behaviour_labels, npc_debug::update_npc_behaviour_labels)
+            .chain()
+            .run_if(in_state(GameState::Playing)),
+    );
+
     // Input resource
     app.init_resource::<input::InputState>();
     app.init_resource::<systems::LastCameraMode>();
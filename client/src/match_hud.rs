@@ -0,0 +1,193 @@
+//! Match HUD and end-of-match scoreboard
+//!
+//! Shows the replicated `MatchState` (mode, phase, clock and team scores) at the
+//! top-center and, after a match ends, the `MatchScoreboard` the server sends until
+//! the next warmup starts.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use shared::{MatchPhase, MatchScoreboard, MatchState, Team, TEAM_COUNT};
+
+use crate::crosshair::Pickable;
+
+/// Last scoreboard received from the server
+#[derive(Resource, Default)]
+pub struct ScoreboardState {
+    pub scoreboard: Option<MatchScoreboard>,
+}
+
+/// Top-center match status line
+#[derive(Component)]
+pub struct MatchHudText;
+
+/// End-of-match scoreboard panel
+#[derive(Component)]
+pub struct ScoreboardPanel;
+
+/// Scoreboard text
+#[derive(Component)]
+pub struct ScoreboardText;
+
+/// Spawn the (hidden) match HUD and scoreboard
+pub fn spawn_match_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            MatchHudText,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.95)),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                Node {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..default()
+                },
+            ));
+        });
+
+    commands
+        .spawn((
+            ScoreboardPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ScoreboardText,
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.95, 0.95, 0.95, 1.0)),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+                Node {
+                    padding: UiRect::all(Val::Px(16.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Store the scoreboard the server sends when a match ends
+pub fn handle_match_scoreboard(
+    mut receiver: Query<&mut MessageReceiver<MatchScoreboard>, (With<crate::GameClient>, With<Connected>)>,
+    mut state: ResMut<ScoreboardState>,
+) {
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    for scoreboard in receiver.receive() {
+        state.scoreboard = Some(scoreboard);
+    }
+}
+
+/// "Red 12 - 9 Blue" style score line
+fn score_line(scores: &[u32; TEAM_COUNT]) -> String {
+    format!("{} {}  -  {} {}", Team::RED.name(), scores[0], scores[1], Team::BLUE.name())
+}
+
+/// Scoreboard panel text
+fn scoreboard_text(scoreboard: &MatchScoreboard) -> String {
+    let result = match scoreboard.winner {
+        Some(team) => format!("{} WINS", team.name().to_uppercase()),
+        None => "DRAW".to_string(),
+    };
+    let mut text = format!("MATCH {}  -  {}\n{}\n", scoreboard.round, result, score_line(&scoreboard.scores));
+    text.push_str(&format!("\n{:<20} {:<6} {:>5} {:>6}", "PLAYER", "TEAM", "KILLS", "DEATHS"));
+    for row in &scoreboard.rows {
+        text.push_str(&format!(
+            "\n{:<20} {:<6} {:>5} {:>6}",
+            row.name,
+            row.team.map(|t| t.name()).unwrap_or("-"),
+            row.kills,
+            row.deaths,
+        ));
+    }
+    text
+}
+
+/// Update the status line and show the scoreboard after a match
+pub fn update_match_hud(
+    matches: Query<&MatchState>,
+    mut state: ResMut<ScoreboardState>,
+    mut hud: Query<(&mut Visibility, &Children), With<MatchHudText>>,
+    mut panel: Query<&mut Visibility, (With<ScoreboardPanel>, Without<MatchHudText>)>,
+    mut texts: Query<&mut Text, Without<ScoreboardText>>,
+    mut scoreboard_text_query: Query<&mut Text, With<ScoreboardText>>,
+) {
+    let match_state = matches.iter().next();
+
+    if let Ok((mut visibility, children)) = hud.single_mut() {
+        *visibility = if match_state.is_some() { Visibility::Visible } else { Visibility::Hidden };
+        if let Some(match_state) = match_state {
+            let seconds = match_state.time_remaining.ceil() as u32;
+            let line = format!(
+                "{}  |  {}  |  {:02}:{:02}  |  {}",
+                match_state.mode.label(),
+                match_state.phase.label(),
+                seconds / 60,
+                seconds % 60,
+                score_line(&match_state.scores)
+            );
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(child) {
+                    if text.0 != line {
+                        text.0 = line.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    // The scoreboard stays up through post-match and is dropped once the next match warms up
+    if let Some(match_state) = match_state {
+        let next_match = state.scoreboard.as_ref().is_some_and(|s| s.round < match_state.round);
+        if next_match && match_state.phase == MatchPhase::Warmup {
+            state.scoreboard = None;
+        }
+    }
+    let content = state.scoreboard.as_ref().map(scoreboard_text);
+
+    for mut visibility in panel.iter_mut() {
+        *visibility = if content.is_some() { Visibility::Visible } else { Visibility::Hidden };
+    }
+    if let Some(content) = content {
+        for mut text in scoreboard_text_query.iter_mut() {
+            if text.0 != content {
+                text.0 = content.clone();
+            }
+        }
+    }
+}
+
+/// Despawn the match HUD when leaving gameplay
+pub fn despawn_match_hud(
+    mut commands: Commands,
+    roots: Query<Entity, Or<(With<MatchHudText>, With<ScoreboardPanel>)>>,
+) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
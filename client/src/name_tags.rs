@@ -0,0 +1,94 @@
+//! Team-coloured name tags over other players
+//!
+//! Each remote player with a replicated `PlayerName` gets a UI text label that is
+//! projected to the screen above their head every frame. Teammates are labelled from
+//! much further away than enemies.
+
+use bevy::prelude::*;
+use shared::{are_allies, Health, LocalPlayer, Player, PlayerName, Team, PLAYER_HEIGHT};
+
+use crate::crosshair::Pickable;
+
+/// Teammates' tags are shown out to this distance (m)
+const NAME_TAG_RANGE_TEAM: f32 = 150.0;
+
+/// Enemies' tags are only shown this close (m)
+const NAME_TAG_RANGE_ENEMY: f32 = 30.0;
+
+/// Height of the tag above the player's center (m)
+const NAME_TAG_HEIGHT: f32 = PLAYER_HEIGHT * 0.75;
+
+/// Name tag label following a player entity
+#[derive(Component)]
+pub struct NameTag {
+    pub target: Entity,
+}
+
+/// Spawn a tag for every newly replicated player name
+pub fn spawn_name_tags(
+    mut commands: Commands,
+    named: Query<(Entity, &PlayerName), (Added<PlayerName>, With<Player>)>,
+) {
+    for (entity, name) in named.iter() {
+        commands.spawn((
+            NameTag { target: entity },
+            Text::new(name.0.clone()),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ));
+    }
+}
+
+/// Project tags above their players, colour them by team and hide out-of-range ones
+pub fn update_name_tags(
+    mut commands: Commands,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    local_team: Query<Option<&Team>, With<LocalPlayer>>,
+    players: Query<(&Transform, &Health, Option<&Team>, Has<LocalPlayer>), With<Player>>,
+    mut tags: Query<(Entity, &NameTag, &mut Node, &mut TextColor, &mut Visibility, &ComputedNode)>,
+) {
+    let Ok((camera, camera_transform)) = camera.single() else {
+        return;
+    };
+    let our_team = local_team.single().ok().flatten().copied();
+
+    for (tag_entity, tag, mut node, mut color, mut visibility, computed) in tags.iter_mut() {
+        let Ok((transform, health, team, is_local)) = players.get(tag.target) else {
+            commands.entity(tag_entity).despawn();
+            continue;
+        };
+
+        let head = transform.translation + Vec3::Y * NAME_TAG_HEIGHT;
+        let distance = camera_transform.translation().distance(head);
+        let range = if are_allies(our_team, team.copied()) { NAME_TAG_RANGE_TEAM } else { NAME_TAG_RANGE_ENEMY };
+        let screen = camera.world_to_viewport(camera_transform, head).ok();
+
+        let Some(screen) = screen.filter(|_| !is_local && !health.is_dead() && distance <= range) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // Center the label horizontally over the head
+        let size = computed.size() * computed.inverse_scale_factor();
+        node.left = Val::Px(screen.x - size.x * 0.5);
+        node.top = Val::Px(screen.y - size.y);
+        color.0 = team.map(|t| t.color()).unwrap_or(Color::WHITE);
+        *visibility = Visibility::Visible;
+    }
+}
+
+/// Despawn every name tag when leaving gameplay
+pub fn despawn_name_tags(mut commands: Commands, tags: Query<Entity, With<NameTag>>) {
+    for entity in tags.iter() {
+        commands.entity(entity).despawn();
+    }
+}
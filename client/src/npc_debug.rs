@@ -0,0 +1,88 @@
+//! Behaviour debug labels over NPCs
+//!
+//! While weapon debug mode (F4) is on, every NPC within range shows the behaviour tree
+//! leaf it is running (replicated `NpcActiveBehaviour`), projected above its head.
+
+use bevy::prelude::*;
+use shared::{Health, Npc, NpcActiveBehaviour, WeaponDebugMode, NPC_HEIGHT};
+
+use crate::crosshair::Pickable;
+
+/// Labels are shown out to this distance (m)
+const NPC_DEBUG_LABEL_RANGE: f32 = 60.0;
+
+/// Height of the label above the NPC's center (m)
+const NPC_DEBUG_LABEL_HEIGHT: f32 = NPC_HEIGHT * 0.8;
+
+/// Debug label following an NPC entity
+#[derive(Component)]
+pub struct NpcBehaviourLabel {
+    pub target: Entity,
+}
+
+/// Spawn a label for every NPC that starts reporting its behaviour
+pub fn spawn_npc_behaviour_labels(
+    mut commands: Commands,
+    npcs: Query<(Entity, &NpcActiveBehaviour), (Added<NpcActiveBehaviour>, With<Npc>)>,
+) {
+    for (entity, active) in npcs.iter() {
+        commands.spawn((
+            NpcBehaviourLabel { target: entity },
+            Text::new(active.0.clone()),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.85, 0.3)),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ));
+    }
+}
+
+/// Keep labels' text current and project them above their NPCs while debug mode is on
+pub fn update_npc_behaviour_labels(
+    mut commands: Commands,
+    debug_mode: Res<WeaponDebugMode>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    npcs: Query<(&Transform, &Health, &NpcActiveBehaviour), With<Npc>>,
+    mut labels: Query<(Entity, &NpcBehaviourLabel, &mut Text, &mut Node, &mut Visibility, &ComputedNode)>,
+) {
+    let camera = camera.single().ok();
+
+    for (label_entity, label, mut text, mut node, mut visibility, computed) in labels.iter_mut() {
+        let Ok((transform, health, active)) = npcs.get(label.target) else {
+            commands.entity(label_entity).despawn();
+            continue;
+        };
+
+        let head = transform.translation + Vec3::Y * NPC_DEBUG_LABEL_HEIGHT;
+        let screen = camera
+            .filter(|_| debug_mode.0 && !health.is_dead())
+            .filter(|(_, camera_transform)| camera_transform.translation().distance(head) <= NPC_DEBUG_LABEL_RANGE)
+            .and_then(|(camera, camera_transform)| camera.world_to_viewport(camera_transform, head).ok());
+        let Some(screen) = screen else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        if text.0 != active.0 {
+            text.0.clone_from(&active.0);
+        }
+        let size = computed.size() * computed.inverse_scale_factor();
+        node.left = Val::Px(screen.x - size.x * 0.5);
+        node.top = Val::Px(screen.y - size.y);
+        *visibility = Visibility::Visible;
+    }
+}
+
+/// Despawn every label when leaving gameplay
+pub fn despawn_npc_behaviour_labels(mut commands: Commands, labels: Query<Entity, With<NpcBehaviourLabel>>) {
+    for entity in labels.iter() {
+        commands.entity(entity).despawn();
+    }
+}
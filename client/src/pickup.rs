@@ -362,6 +362,24 @@ fn create_item_mesh(meshes: &mut ResMut<Assets<Mesh>>, item_type: ItemType) -> H
         ItemType::Weapon(_) => {
             meshes.add(Cuboid::new(0.55, 0.12, 0.18))
         }
+        // Armor - vest plate / helmet dome
+        ItemType::Armor(shared::ArmorType::Vest) => {
+            meshes.add(Cuboid::new(0.4, 0.45, 0.15))
+        }
+        ItemType::Armor(shared::ArmorType::Helmet) => {
+            meshes.add(Sphere::new(0.15))
+        }
+        // Explosives - round grenade / flat charge block
+        ItemType::Explosive(shared::ExplosiveType::Grenade) => {
+            meshes.add(Sphere::new(0.1))
+        }
+        ItemType::Explosive(shared::ExplosiveType::Charge) => {
+            meshes.add(Cuboid::new(0.3, 0.12, 0.2))
+        }
+        // Coins - small pouch
+        ItemType::Coins => {
+            meshes.add(Sphere::new(0.12))
+        }
     }
 }
 
@@ -374,12 +392,18 @@ fn create_item_material(materials: &mut ResMut<Assets<StandardMaterial>>, item_t
             ItemType::Stone => 0.1,
             ItemType::Wood => 0.0,
             ItemType::Weapon(_) => 0.35,
+            ItemType::Armor(_) => 0.2,
+            ItemType::Explosive(_) => 0.3,
+            ItemType::Coins => 0.9,
         },
         perceptual_roughness: match item_type {
             ItemType::RifleAmmo | ItemType::PistolAmmo | ItemType::SniperRounds | ItemType::ShotgunShells => 0.3,
             ItemType::Stone => 0.7,
             ItemType::Wood => 0.8,
             ItemType::Weapon(_) => 0.45,
+            ItemType::Armor(_) => 0.6,
+            ItemType::Explosive(_) => 0.6,
+            ItemType::Coins => 0.25,
         },
         emissive: item_type.color().to_linear() * 0.3, // Slight glow so items are visible
         ..default()
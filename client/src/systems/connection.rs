@@ -72,6 +72,10 @@ pub fn start_connection(
         MessageSender::<shared::ShootRequest>::default(),
         MessageSender::<shared::SwitchWeapon>::default(),
         MessageSender::<shared::ReloadRequest>::default(),
+        MessageSender::<shared::InterruptReloadRequest>::default(),
+        MessageSender::<shared::ThrowRequest>::default(),
+        MessageSender::<shared::CycleFireModeRequest>::default(),
+        MessageSender::<shared::AdjustZeroRequest>::default(),
         MessageSender::<shared::PickupRequest>::default(),
         MessageSender::<shared::DropRequest>::default(),
         MessageSender::<shared::SelectHotbarSlot>::default(),
@@ -80,21 +84,40 @@ pub fn start_connection(
         MessageSender::<shared::SubmitPlayerName>::default(),
     ));
     
-    // Chest messages (split to avoid tuple size limit)
+    // Chest, trade and dialogue messages (split to avoid tuple size limit)
     commands.entity(client_entity).insert((
         MessageSender::<shared::OpenChestRequest>::default(),
         MessageSender::<shared::CloseChestRequest>::default(),
         MessageSender::<shared::ChestTransferRequest>::default(),
+        MessageSender::<shared::TradeRequest>::default(),
+        MessageSender::<shared::StartDialogueRequest>::default(),
+        MessageSender::<shared::DialogueChoiceRequest>::default(),
+        MessageSender::<shared::EndDialogueRequest>::default(),
+    ));
+    
+    // Armor messages
+    commands.entity(client_entity).insert((
+        MessageSender::<shared::EquipArmorRequest>::default(),
+        MessageSender::<shared::UnequipArmorRequest>::default(),
     ));
     
     // Add server -> client message receivers (split to avoid tuple size limit)
     commands.entity(client_entity).insert((
         MessageReceiver::<shared::HitConfirm>::default(),
         MessageReceiver::<shared::BulletImpact>::default(),
+        MessageReceiver::<shared::Explosion>::default(),
         MessageReceiver::<shared::DamageReceived>::default(),
         MessageReceiver::<shared::PlayerKilled>::default(),
+        MessageReceiver::<shared::AudioEvent>::default(),
+        MessageReceiver::<shared::KillFeedEntry>::default(),
+        MessageReceiver::<shared::DamageLogReport>::default(),
+        MessageReceiver::<shared::MatchScoreboard>::default(),
+        MessageReceiver::<shared::CaptureAnnouncement>::default(),
         // Name submission response
         MessageReceiver::<shared::NameSubmissionResult>::default(),
+        MessageReceiver::<shared::TradeResponse>::default(),
+        MessageReceiver::<shared::DialogueUpdate>::default(),
+        MessageReceiver::<shared::NpcVoiceLine>::default(),
     ));
     
     // Trigger the Connect event to actually initiate the connection
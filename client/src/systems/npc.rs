@@ -5,8 +5,8 @@ use bevy::animation::graph::{AnimationGraph, AnimationGraphHandle, AnimationNode
 use bevy::prelude::*;
 
 use shared::{
-    npc_capsule_endpoints, npc_head_center, Npc, NpcArchetype, NpcPosition, NpcRotation, Health,
-    NPC_HEAD_RADIUS, NPC_HEIGHT, NPC_RADIUS,
+    weapons::damage::HitZone, HitboxPose, HitboxRig, Npc, NpcArchetype, NpcHitboxStance, NpcIndoors, NpcPosition,
+    NpcRotation, Health, HITBOX_PADDING, NPC_HEIGHT,
 };
 
 use shared::WeaponDebugMode;
@@ -331,6 +331,14 @@ pub fn sync_npc_transforms(
     }
 }
 
+/// Hide NPCs that have gone indoors for the night (server replicates `NpcIndoors`)
+pub fn update_npc_indoors_visibility(mut npcs: Query<(Has<NpcIndoors>, &mut Visibility), With<Npc>>) {
+    for (indoors, mut visibility) in npcs.iter_mut() {
+        let wanted = if indoors { Visibility::Hidden } else { Visibility::Inherited };
+        visibility.set_if_neq(wanted);
+    }
+}
+
 // =============================================================================
 // ANIMATION
 // =============================================================================
@@ -534,40 +542,53 @@ pub fn update_npc_animation(
 // DEBUG (F4)
 // =============================================================================
 
+/// Draw each NPC's per-limb hitbox rig, posed from the same replicated stance as the
+/// server's hit detection.
 pub fn debug_draw_npc_hitboxes(
     mut gizmos: Gizmos,
     debug_mode: Res<WeaponDebugMode>,
-    npcs: Query<(&Transform, &Health), With<Npc>>,
+    npcs: Query<(&NpcPosition, &NpcRotation, &Health, Option<&NpcHitboxStance>), With<Npc>>,
 ) {
     if !debug_mode.0 {
         return;
     }
 
-    for (transform, health) in npcs.iter() {
-        let center = transform.translation;
-
-        let head_center = npc_head_center(center);
-        let (a, b) = npc_capsule_endpoints(center);
+    for (position, rotation, health, stance) in npcs.iter() {
+        let stance = stance.copied().unwrap_or_default();
+        let rig = HitboxRig::posed(&HitboxPose {
+            center: position.0,
+            yaw: rotation.0,
+            stance: stance.stance,
+            armed: stance.armed,
+        });
 
         let alive = !health.is_dead();
 
-        let body_color = if alive {
-            Color::srgba(1.0, 0.85, 0.2, 0.9)
-        } else {
-            Color::srgba(0.6, 0.6, 0.6, 0.7)
-        };
-        let head_color = if alive {
-            Color::srgba(1.0, 0.2, 0.2, 0.95)
-        } else {
-            Color::srgba(0.5, 0.2, 0.2, 0.7)
-        };
-
-        // Body capsule (approx): spheres at endpoints + line between.
-        gizmos.sphere(Isometry3d::from_translation(a), NPC_RADIUS, body_color);
-        gizmos.sphere(Isometry3d::from_translation(b), NPC_RADIUS, body_color);
-        gizmos.line(a, b, body_color);
+        for capsule in &rig.capsules {
+            let color = if !alive {
+                Color::srgba(0.6, 0.6, 0.6, 0.7)
+            } else {
+                match capsule.bone.hit_zone() {
+                    HitZone::Head => Color::srgba(1.0, 0.2, 0.2, 0.95),
+                    HitZone::Chest => Color::srgba(1.0, 0.85, 0.2, 0.9),
+                    HitZone::Stomach => Color::srgba(1.0, 0.6, 0.2, 0.9),
+                    HitZone::Arms => Color::srgba(0.3, 0.8, 1.0, 0.9),
+                    HitZone::Legs => Color::srgba(0.4, 1.0, 0.4, 0.9),
+                }
+            };
 
-        // Head sphere.
-        gizmos.sphere(Isometry3d::from_translation(head_center), NPC_HEAD_RADIUS, head_color);
+            let axis = capsule.b - capsule.a;
+            let length = axis.length();
+            let orientation = if length > 1e-4 {
+                Quat::from_rotation_arc(Vec3::Y, axis / length)
+            } else {
+                Quat::IDENTITY
+            };
+            gizmos.primitive_3d(
+                &Capsule3d::new(capsule.radius + HITBOX_PADDING, length),
+                Isometry3d::new((capsule.a + capsule.b) * 0.5, orientation),
+                color,
+            );
+        }
     }
 }
@@ -7,7 +7,10 @@ use bevy::animation::{AnimationClip, AnimationPlayer, AnimationTarget, Animation
 use bevy::animation::graph::{AnimationGraph, AnimationGraphHandle, AnimationNodeIndex};
 use lightyear::prelude::*;
 use lightyear::prelude::client::Connected;
-use shared::{Health, LocalPlayer, Player, PlayerPosition, PlayerRotation, Vehicle, VehicleDriver, PLAYER_HEIGHT};
+use shared::{
+    ArmorSlot, ArmorType, EquippedArmor, Health, ItemType, LocalPlayer, Player, PlayerPosition,
+    PlayerRotation, Vehicle, VehicleDriver, PLAYER_HEIGHT,
+};
 use std::collections::HashMap;
 
 use crate::input::{CameraMode, InputState};
@@ -822,6 +825,93 @@ pub fn update_local_player_visibility(
     commands.entity(model_root).insert(visibility);
 }
 
+// =============================================================================
+// ARMOR VISUALS
+// =============================================================================
+
+/// Simple mesh for a worn armor piece (child of `RangerModelRoot`, so it inherits
+/// the first-person hiding of the local model).
+#[derive(Component)]
+pub struct ArmorVisual {
+    pub slot: ArmorSlot,
+}
+
+/// The armor state currently shown on a model root (changed slots are rebuilt).
+#[derive(Component, Clone, Copy, PartialEq)]
+pub struct ShownArmor(pub EquippedArmor);
+
+/// Attach/remove helmet and vest meshes on character models to match `EquippedArmor`.
+pub fn update_player_armor_visuals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    players: Query<&EquippedArmor, With<Player>>,
+    model_roots: Query<(Entity, &ChildOf, Option<&ShownArmor>, Option<&Children>), With<RangerModelRoot>>,
+    visuals: Query<&ArmorVisual>,
+) {
+    for (model_root, child_of, shown, children) in model_roots.iter() {
+        let Ok(armor) = players.get(child_of.parent()) else {
+            continue;
+        };
+        // Only compare presence/type - durability changes don't affect the mesh
+        let wanted = [armor.helmet.map(|p| p.armor_type), armor.vest.map(|p| p.armor_type)];
+        let current = shown.map(|shown| [shown.0.helmet.map(|p| p.armor_type), shown.0.vest.map(|p| p.armor_type)]);
+        if current == Some(wanted) {
+            continue;
+        }
+        commands.entity(model_root).insert(ShownArmor(*armor));
+
+        // Only the slots whose piece changed are rebuilt
+        let changed = |slot: ArmorSlot| {
+            let index = match slot {
+                ArmorSlot::Head => 0,
+                ArmorSlot::Body => 1,
+            };
+            current.is_none_or(|current| current[index] != wanted[index])
+        };
+
+        // Despawn old armor meshes
+        if let Some(children) = children {
+            for child in children.iter() {
+                if visuals.get(child).is_ok_and(|visual| changed(visual.slot)) {
+                    commands.entity(child).despawn();
+                }
+            }
+        }
+
+        // Model root origin is at the feet
+        for piece in [armor.helmet, armor.vest].into_iter().flatten() {
+            if !changed(piece.armor_type.slot()) {
+                continue;
+            }
+            let (mesh, offset) = match piece.armor_type {
+                ArmorType::Helmet => (
+                    meshes.add(Sphere::new(0.16)),
+                    Vec3::new(0.0, PLAYER_HEIGHT * 0.92, 0.0),
+                ),
+                ArmorType::Vest => (
+                    meshes.add(Cuboid::new(0.46, 0.5, 0.3)),
+                    Vec3::new(0.0, PLAYER_HEIGHT * 0.62, 0.0),
+                ),
+            };
+            let material = materials.add(StandardMaterial {
+                base_color: ItemType::Armor(piece.armor_type).color(),
+                metallic: 0.2,
+                perceptual_roughness: 0.6,
+                ..default()
+            });
+            let visual = commands.spawn((
+                ArmorVisual { slot: piece.armor_type.slot() },
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_translation(offset),
+                Visibility::Inherited,
+            )).id();
+            commands.entity(model_root).add_child(visual);
+        }
+    }
+}
+
 /// Helper to convert PeerId to u64
 pub fn peer_id_to_u64(peer_id: PeerId) -> u64 {
     match peer_id {
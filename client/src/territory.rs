@@ -0,0 +1,278 @@
+//! Territory HUD: capture status, capture announcements and the territory map
+//!
+//! While standing in a replicated `ControlPoint` a status line shows who is taking
+//! it and how far along they are. `CaptureAnnouncement`s from the server show under
+//! it for a few seconds. A small map in the bottom-left corner plots every control
+//! point, coloured by owner, around the map spawn together with the local player.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use shared::{CaptureAnnouncement, ControlPoint, LocalPlayer, Player, Team, SPAWN_POSITION, TERRITORY_RADIUS};
+
+use crate::crosshair::Pickable;
+
+/// Seconds an announcement stays up
+const ANNOUNCEMENT_DURATION: f32 = 5.0;
+
+/// Map panel size (px); it spans `TERRITORY_RADIUS` either side of the spawn
+const MAP_SIZE: f32 = 180.0;
+
+/// Control point / player marker size on the map (px)
+const MAP_POINT_SIZE: f32 = 12.0;
+const MAP_PLAYER_SIZE: f32 = 6.0;
+
+const NEUTRAL_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+const CONTESTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+
+/// Latest capture announcement and how long it has left
+#[derive(Resource, Default)]
+pub struct CaptureAnnouncements {
+    pub current: Option<(CaptureAnnouncement, f32)>,
+}
+
+/// Capture status / announcement text
+#[derive(Component)]
+pub struct TerritoryHudText;
+
+/// Map panel
+#[derive(Component)]
+pub struct TerritoryMap;
+
+/// A control point on the map
+#[derive(Component)]
+pub struct MapPointMarker {
+    pub point: Entity,
+}
+
+/// The local player on the map
+#[derive(Component)]
+pub struct MapPlayerMarker;
+
+/// Spawn the (hidden) territory HUD and map
+pub fn spawn_territory_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            TerritoryHudText,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(76.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                Node {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..default()
+                },
+            ));
+        });
+
+    commands
+        .spawn((
+            TerritoryMap,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                width: Val::Px(MAP_SIZE),
+                height: Val::Px(MAP_SIZE),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.45)),
+            BorderColor::from(Color::srgba(1.0, 1.0, 1.0, 0.3)),
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MapPlayerMarker,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(MAP_PLAYER_SIZE),
+                    height: Val::Px(MAP_PLAYER_SIZE),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE),
+                ZIndex(1),
+            ));
+        });
+}
+
+/// Store capture announcements from the server
+pub fn handle_capture_announcements(
+    time: Res<Time>,
+    mut receiver: Query<&mut MessageReceiver<CaptureAnnouncement>, (With<crate::GameClient>, With<Connected>)>,
+    mut announcements: ResMut<CaptureAnnouncements>,
+) {
+    if let Some((_, remaining)) = announcements.current.as_mut() {
+        *remaining -= time.delta_secs();
+        if *remaining <= 0.0 {
+            announcements.current = None;
+        }
+    }
+
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    for announcement in receiver.receive() {
+        announcements.current = Some((announcement, ANNOUNCEMENT_DURATION));
+    }
+}
+
+/// Marker colour for a control point
+fn point_color(point: &ControlPoint) -> Color {
+    if point.contested {
+        CONTESTED_COLOR
+    } else {
+        point.owner.map(|t| t.color()).unwrap_or(NEUTRAL_COLOR)
+    }
+}
+
+/// Map-panel offset (px, top-left of a marker of `size`) for a world position
+fn map_offset(world: Vec2, size: f32) -> Vec2 {
+    let spawn = Vec2::new(SPAWN_POSITION[0], SPAWN_POSITION[2]);
+    let normalized = ((world - spawn) / TERRITORY_RADIUS).clamp(Vec2::splat(-1.0), Vec2::ONE);
+    (normalized * 0.5 + 0.5) * MAP_SIZE - Vec2::splat(size * 0.5)
+}
+
+/// Capture status while inside a point, plus the latest announcement
+pub fn update_territory_hud(
+    announcements: Res<CaptureAnnouncements>,
+    points: Query<&ControlPoint>,
+    local_player: Query<(&Transform, Option<&Team>), (With<LocalPlayer>, With<Player>)>,
+    mut hud: Query<(&mut Visibility, &Children), With<TerritoryHudText>>,
+    mut texts: Query<(&mut Text, &mut TextColor)>,
+) {
+    let Ok((mut visibility, children)) = hud.single_mut() else {
+        return;
+    };
+    let local = local_player.single().ok();
+
+    let mut lines = Vec::new();
+    let mut color = Color::WHITE;
+    let inside = local.and_then(|(transform, _)| points.iter().find(|p| p.contains(transform.translation)));
+    if let Some(point) = inside {
+        let status = match (point.contested, point.capturing, point.owner) {
+            (true, ..) => format!("POINT {} CONTESTED", point.label()),
+            (false, Some(team), _) => {
+                format!("{} CAPTURING {}  {:.0}%", team.name().to_uppercase(), point.label(), point.progress * 100.0)
+            }
+            (false, None, Some(owner)) => format!("POINT {}  -  HELD BY {}", point.label(), owner.name().to_uppercase()),
+            (false, None, None) => format!("POINT {}  -  NEUTRAL", point.label()),
+        };
+        lines.push(status);
+        color = point_color(point);
+    }
+    if let Some((announcement, _)) = &announcements.current {
+        let our_team = local.and_then(|(_, team)| team.copied());
+        let team_name = announcement.team.name().to_uppercase();
+        let line = if our_team == Some(announcement.team) {
+            format!("WE CAPTURED POINT {}", announcement.label)
+        } else if our_team.is_some() && our_team == announcement.previous {
+            format!("WE LOST POINT {} TO {}", announcement.label, team_name)
+        } else {
+            format!("{} CAPTURED POINT {}", team_name, announcement.label)
+        };
+        lines.push(line);
+        color = announcement.team.color();
+    }
+
+    if lines.is_empty() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+    let content = lines.join("\n");
+    for child in children.iter() {
+        if let Ok((mut text, mut text_color)) = texts.get_mut(child) {
+            if text.0 != content {
+                text.0 = content.clone();
+            }
+            text_color.0 = color;
+        }
+    }
+}
+
+/// Add markers for new control points and keep the map colours / player marker current
+pub fn update_territory_map(
+    mut commands: Commands,
+    points: Query<(Entity, &ControlPoint)>,
+    local_player: Query<&Transform, (With<LocalPlayer>, With<Player>)>,
+    mut map: Query<(Entity, &mut Visibility), With<TerritoryMap>>,
+    mut markers: Query<(Entity, &MapPointMarker, &mut BackgroundColor), Without<MapPlayerMarker>>,
+    mut player_marker: Query<&mut Node, With<MapPlayerMarker>>,
+) {
+    let Ok((map_entity, mut visibility)) = map.single_mut() else {
+        return;
+    };
+    *visibility = if points.is_empty() { Visibility::Hidden } else { Visibility::Visible };
+
+    // Points that replicated in without a marker yet
+    let marked: Vec<Entity> = markers.iter().map(|(_, marker, _)| marker.point).collect();
+    for (entity, point) in points.iter().filter(|(entity, _)| !marked.contains(entity)) {
+        let offset = map_offset(point.center, MAP_POINT_SIZE);
+        let marker = commands
+            .spawn((
+                MapPointMarker { point: entity },
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(offset.x),
+                    top: Val::Px(offset.y),
+                    width: Val::Px(MAP_POINT_SIZE),
+                    height: Val::Px(MAP_POINT_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(point_color(point)),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(point.label()),
+                    TextFont {
+                        font_size: 9.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                ));
+            })
+            .id();
+        commands.entity(map_entity).add_child(marker);
+    }
+
+    for (marker_entity, marker, mut background) in markers.iter_mut() {
+        match points.get(marker.point) {
+            Ok((_, point)) => background.0 = point_color(point),
+            Err(_) => commands.entity(marker_entity).despawn(),
+        }
+    }
+
+    if let (Ok(transform), Ok(mut node)) = (local_player.single(), player_marker.single_mut()) {
+        let offset = map_offset(Vec2::new(transform.translation.x, transform.translation.z), MAP_PLAYER_SIZE);
+        node.left = Val::Px(offset.x);
+        node.top = Val::Px(offset.y);
+    }
+}
+
+/// Despawn the territory HUD and map when leaving gameplay
+pub fn despawn_territory_hud(
+    mut commands: Commands,
+    roots: Query<Entity, Or<(With<TerritoryHudText>, With<TerritoryMap>)>>,
+) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,163 @@
+//! Trading system - track the open trade window, report trade results
+//!
+//! Talk to a trader and ask to see its wares to trade (see `dialogue`). The trade window
+//! is integrated into the inventory (see `ui::inventory`) and closes with it; every request
+//! is answered by the server with a `TradeResponse`, shown in the window for a few seconds.
+
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
+use shared::{
+    Health, LocalPlayer, NpcPosition, PlayerPosition, TradeResponse, TraderStock, TRADE_RANGE,
+};
+use lightyear::prelude::*;
+use lightyear::prelude::client::Connected;
+
+use crate::input::InputState;
+use crate::states::GameState;
+use crate::ui::inventory::InventoryOpen;
+
+/// Seconds a trade result stays in the window
+const TRADE_FEEDBACK_TIME: f32 = 3.0;
+
+/// Plugin for trading with vendor NPCs
+pub struct TradePlugin;
+
+impl Plugin for TradePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NearbyTrader>();
+        app.init_resource::<OpenTrade>();
+        app.init_resource::<TradeFeedback>();
+
+        app.add_systems(Update, (
+            detect_nearby_traders,
+            auto_close_trade,
+            receive_trade_responses,
+        ).chain().run_if(in_state(GameState::Playing)));
+
+        app.add_systems(OnExit(GameState::Playing), cleanup_trade_ui);
+    }
+}
+
+/// Resource tracking the nearest living trader to the player
+#[derive(Resource, Default)]
+pub struct NearbyTrader {
+    pub entity: Option<Entity>,
+}
+
+/// Resource tracking which trader's window is open
+#[derive(Resource, Default)]
+pub struct OpenTrade {
+    pub entity: Option<Entity>,
+}
+
+/// Latest trade result for the trade window
+#[derive(Resource, Default)]
+pub struct TradeFeedback {
+    pub message: String,
+    pub failed: bool,
+    /// Seconds until it's cleared
+    pub timer: f32,
+}
+
+/// Find the nearest living trader within trading range
+fn detect_nearby_traders(
+    mut nearby: ResMut<NearbyTrader>,
+    local_player: Query<&PlayerPosition, With<LocalPlayer>>,
+    traders: Query<(Entity, &NpcPosition, &Health), With<TraderStock>>,
+    input_state: Res<InputState>,
+) {
+    if input_state.in_vehicle || input_state.is_dead {
+        nearby.entity = None;
+        return;
+    }
+    let Ok(player_pos) = local_player.single() else {
+        nearby.entity = None;
+        return;
+    };
+
+    nearby.entity = traders
+        .iter()
+        .filter(|(_, pos, health)| !health.is_dead() && pos.0.distance(player_pos.0) <= TRADE_RANGE)
+        .min_by(|(_, a, _), (_, b, _)| {
+            a.0.distance_squared(player_pos.0).total_cmp(&b.0.distance_squared(player_pos.0))
+        })
+        .map(|(entity, ..)| entity);
+}
+
+/// Close the trade window when the player walks away, the trader dies or the inventory
+/// is closed (I / ESC)
+fn auto_close_trade(
+    nearby: Res<NearbyTrader>,
+    mut open_trade: ResMut<OpenTrade>,
+    mut inventory_open: ResMut<InventoryOpen>,
+    mut input_state: ResMut<InputState>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    mut cursor_opts: Query<&mut CursorOptions>,
+) {
+    let Some(open_entity) = open_trade.entity else {
+        return;
+    };
+    if !inventory_open.0 {
+        open_trade.entity = None;
+        return;
+    }
+    if nearby.entity != Some(open_entity) {
+        open_trade.entity = None;
+        inventory_open.0 = false;
+        input_state.inventory_open = false;
+        set_cursor_free(false, &windows, &mut cursor_opts);
+    }
+}
+
+/// Show the server's answer to our trade requests
+fn receive_trade_responses(
+    time: Res<Time>,
+    mut feedback: ResMut<TradeFeedback>,
+    mut receiver: Query<&mut MessageReceiver<TradeResponse>, (With<crate::GameClient>, With<Connected>)>,
+) {
+    feedback.timer -= time.delta_secs();
+    if feedback.timer <= 0.0 && !feedback.message.is_empty() {
+        *feedback = TradeFeedback::default();
+    }
+
+    let Ok(mut receiver) = receiver.single_mut() else {
+        return;
+    };
+    for response in receiver.receive() {
+        let (message, failed) = match response.result {
+            Ok(receipt) => (
+                format!(
+                    "{} {}x {} for {} coins",
+                    if receipt.bought { "Bought" } else { "Sold" },
+                    receipt.quantity,
+                    receipt.item.display_name(),
+                    receipt.coins
+                ),
+                false,
+            ),
+            Err(error) => (error.message().to_string(), true),
+        };
+        *feedback = TradeFeedback { message, failed, timer: TRADE_FEEDBACK_TIME };
+    }
+}
+
+/// Release (for the window) or re-lock the cursor
+pub(crate) fn set_cursor_free(
+    free: bool,
+    windows: &Query<Entity, With<PrimaryWindow>>,
+    cursor_opts: &mut Query<&mut CursorOptions>,
+) {
+    let Ok(window_entity) = windows.single() else {
+        return;
+    };
+    if let Ok(mut cursor) = cursor_opts.get_mut(window_entity) {
+        cursor.grab_mode = if free { CursorGrabMode::None } else { CursorGrabMode::Locked };
+        cursor.visible = free;
+    }
+}
+
+/// Cleanup trade state
+fn cleanup_trade_ui(mut open_trade: ResMut<OpenTrade>, mut feedback: ResMut<TradeFeedback>) {
+    open_trade.entity = None;
+    *feedback = TradeFeedback::default();
+}
@@ -1,14 +1,18 @@
 //! Inventory UI - Valheim-style inventory grid
 //!
 //! Press I to open/close inventory.
-//! Right-click slots to drop items.
+//! Right-click slots to drop items, Shift+right-click armor to wear it.
+//! Right-click a worn helmet/vest to take it off.
+//! At a trader, click its goods to buy and right-click them to sell (Shift for 10).
 
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
 use shared::{
     Inventory, LocalPlayer, INVENTORY_SLOTS, HOTBAR_SLOTS, CHEST_SLOTS,
     DropRequest, InventoryMoveRequest, HotbarSelection, ReliableChannel, ItemStack,
-    ChestStorage, ChestTransferRequest,
+    ChestStorage, ChestTransferRequest, LootableCorpse,
+    ArmorSlot, EquippedArmor, EquipArmorRequest, UnequipArmorRequest,
+    ItemType, Npc, PriceLine, TradeRequest, TraderStock,
 };
 use lightyear::prelude::*;
 use lightyear::prelude::client::Connected;
@@ -17,6 +21,7 @@ use super::styles::*;
 use crate::input::InputState;
 use crate::chest::OpenChest;
 use crate::states::GameState;
+use crate::trade::{OpenTrade, TradeFeedback};
 
 /// Plugin for inventory UI
 pub struct InventoryPlugin;
@@ -34,7 +39,11 @@ impl Plugin for InventoryPlugin {
             handle_drag_and_drop,
             update_inventory_slots,
             update_chest_slots,
+            update_equipment_slots,
+            update_trade_slots,
             handle_slot_interactions,
+            handle_equipment_slot_interactions,
+            handle_trade_slot_interactions,
         ).chain());
     }
 }
@@ -83,10 +92,54 @@ pub struct ChestSlotQuantity {
     pub index: usize,
 }
 
+/// Worn armor slot (helmet / vest) shown above the inventory grid
+#[derive(Component)]
+pub struct EquipmentSlot {
+    pub slot: ArmorSlot,
+}
+
+/// Marker for equipment slot icon
+#[derive(Component)]
+pub struct EquipmentSlotIcon {
+    pub slot: ArmorSlot,
+}
+
+/// Marker for equipment slot durability text
+#[derive(Component)]
+pub struct EquipmentSlotDurability {
+    pub slot: ArmorSlot,
+}
+
 /// Marker for the chest panel (so we can despawn it separately)
 #[derive(Component)]
 pub struct ChestPanel;
 
+/// Marker for a trader's goods slot (index into its price list)
+#[derive(Component)]
+pub struct TradeSlot {
+    pub index: usize,
+}
+
+/// Marker for trade slot item icon
+#[derive(Component)]
+pub struct TradeSlotIcon {
+    pub index: usize,
+}
+
+/// Marker for trade slot stock text
+#[derive(Component)]
+pub struct TradeSlotStock {
+    pub index: usize,
+}
+
+/// Marker for the player's coin count in the trade panel
+#[derive(Component)]
+pub struct TradeCoinsText;
+
+/// Marker for the last trade result in the trade panel
+#[derive(Component)]
+pub struct TradeFeedbackText;
+
 /// While dragging: which slot we started from + a floating icon under the cursor
 #[derive(Resource, Default)]
 pub struct DragState {
@@ -160,6 +213,9 @@ fn spawn_inventory_ui(
     mut commands: Commands,
     inventory_open: Res<InventoryOpen>,
     open_chest: Res<OpenChest>,
+    open_trade: Res<OpenTrade>,
+    corpses: Query<(), With<LootableCorpse>>,
+    traders: Query<&TraderStock>,
     existing_ui: Query<Entity, With<InventoryUI>>,
 ) {
     // Only spawn if opened and not already existing
@@ -168,6 +224,8 @@ fn spawn_inventory_ui(
     }
     
     let chest_is_open = open_chest.entity.is_some();
+    let looting_corpse = open_chest.entity.is_some_and(|entity| corpses.contains(entity));
+    let trader = open_trade.entity.filter(|_| !chest_is_open).and_then(|entity| traders.get(entity).ok());
     
     // Root container - centered on screen
     commands.spawn((
@@ -200,7 +258,7 @@ fn spawn_inventory_ui(
             )).with_children(|panel| {
                 // Title
                 panel.spawn((
-                    Text::new("CHEST"),
+                    Text::new(if looting_corpse { "LOOT" } else { "CHEST" }),
                     TextFont {
                         font_size: 24.0,
                         ..default()
@@ -232,11 +290,89 @@ fn spawn_inventory_ui(
             });
         }
         
+        // Trade panel (only at a trader) - LEFT side
+        if let Some(trader) = trader {
+            let lines = trader.price_list();
+            parent.spawn((
+                Node {
+                    width: Val::Px(480.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(16.0)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    ..default()
+                },
+                BackgroundColor(MENU_BACKGROUND),
+                BorderColor::from(Color::srgb(0.5, 0.35, 0.2)), // Wood-ish
+            )).with_children(|panel| {
+                // Title
+                panel.spawn((
+                    Text::new(trader.kind.display_name().to_uppercase()),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.8, 0.6, 0.3)), // Golden-brown
+                    Node {
+                        margin: UiRect::bottom(Val::Px(4.0)),
+                        ..default()
+                    },
+                ));
+                
+                // Coins carried
+                panel.spawn((
+                    TradeCoinsText,
+                    Text::new(""),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(ItemType::Coins.color()),
+                    Node {
+                        margin: UiRect::bottom(Val::Px(12.0)),
+                        ..default()
+                    },
+                ));
+                
+                // Goods grid (6 columns, one slot per price list line)
+                panel.spawn((
+                    Node {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::flex(6, 1.0),
+                        grid_template_rows: RepeatedGridTrack::flex(lines.len().div_ceil(6) as u16, 1.0),
+                        row_gap: Val::Px(6.0),
+                        column_gap: Val::Px(6.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Auto,
+                        ..default()
+                    },
+                )).with_children(|grid| {
+                    for (i, line) in lines.iter().enumerate() {
+                        spawn_trade_slot(grid, i, line);
+                    }
+                });
+                
+                // Last trade result
+                panel.spawn((
+                    TradeFeedbackText,
+                    Text::new(""),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                    Node {
+                        margin: UiRect::top(Val::Px(12.0)),
+                        ..default()
+                    },
+                ));
+            });
+        }
+        
         // Inventory panel - RIGHT side (or center if no chest)
         parent.spawn((
             Node {
                 width: Val::Px(480.0),
-                height: Val::Px(380.0),
+                height: Val::Px(460.0),
                 flex_direction: FlexDirection::Column,
                 padding: UiRect::all(Val::Px(16.0)),
                 border: UiRect::all(Val::Px(3.0)),
@@ -259,6 +395,32 @@ fn spawn_inventory_ui(
                 },
             ));
             
+            // Equipment row (helmet + vest)
+            panel.spawn((
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(6.0),
+                    margin: UiRect::bottom(Val::Px(12.0)),
+                    ..default()
+                },
+            )).with_children(|row| {
+                row.spawn((
+                    Text::new("ARMOR"),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_MUTED),
+                    Node {
+                        margin: UiRect::right(Val::Px(6.0)),
+                        ..default()
+                    },
+                ));
+                spawn_equipment_slot(row, ArmorSlot::Head);
+                spawn_equipment_slot(row, ArmorSlot::Body);
+            });
+            
             // Grid container (6 columns x 4 rows = 24 slots)
             panel.spawn((
                 Node {
@@ -280,8 +442,10 @@ fn spawn_inventory_ui(
             // Instructions
             let hint = if chest_is_open {
                 "Drag items between chest and inventory • Right-click to drop • Press E or ESC to close"
+            } else if trader.is_some() {
+                "Click goods to buy • Right-click goods to sell • Shift for 10 • Press I or ESC to close"
             } else {
-                "Drag with left-click to move • Right-click to drop • Press I or ESC to close"
+                "Drag with left-click to move • Right-click to drop • Shift+right-click to wear armor • Press I or ESC to close"
             };
             panel.spawn((
                 Text::new(hint),
@@ -348,8 +512,40 @@ fn spawn_chest_slot(parent: &mut ChildSpawnerCommands, index: usize) {
 
 /// Spawn a single inventory slot
 fn spawn_slot(parent: &mut ChildSpawnerCommands, index: usize) {
+    spawn_item_slot(parent, InventorySlot { index }, SlotIcon { index }, SlotQuantity { index });
+}
+
+/// Spawn a trader's goods slot (stock in the corner) with its prices underneath
+fn spawn_trade_slot(parent: &mut ChildSpawnerCommands, index: usize, line: &PriceLine) {
+    let price = |price: Option<u32>| price.map_or_else(|| "-".to_string(), |coins| coins.to_string());
+    parent.spawn(Node {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(2.0),
+        ..default()
+    }).with_children(|column| {
+        spawn_item_slot(column, TradeSlot { index }, TradeSlotIcon { index }, TradeSlotStock { index });
+        column.spawn((
+            Text::new(format!("Buy {}\nSell {}", price(line.buy), price(line.sell))),
+            TextFont {
+                font_size: 11.0,
+                ..default()
+            },
+            TextColor(TEXT_MUTED),
+        ));
+    });
+}
+
+/// Spawn an item slot widget: a button with an icon and a quantity in the corner, tagged
+/// with the given markers
+fn spawn_item_slot(
+    parent: &mut ChildSpawnerCommands,
+    marker: impl Bundle,
+    icon: impl Bundle,
+    quantity: impl Bundle,
+) {
     parent.spawn((
-        InventorySlot { index },
+        marker,
         Button,
         Node {
             width: Val::Px(64.0),
@@ -364,7 +560,7 @@ fn spawn_slot(parent: &mut ChildSpawnerCommands, index: usize) {
     )).with_children(|slot| {
         // Item icon (colored square)
         slot.spawn((
-            SlotIcon { index },
+            icon,
             Node {
                 width: Val::Px(46.0),
                 height: Val::Px(46.0),
@@ -376,7 +572,7 @@ fn spawn_slot(parent: &mut ChildSpawnerCommands, index: usize) {
         
         // Quantity text (bottom-right corner)
         slot.spawn((
-            SlotQuantity { index },
+            quantity,
             Text::new(""),
             TextFont {
                 font_size: 16.0,
@@ -393,6 +589,52 @@ fn spawn_slot(parent: &mut ChildSpawnerCommands, index: usize) {
     });
 }
 
+/// Spawn a single equipment (armor) slot
+fn spawn_equipment_slot(parent: &mut ChildSpawnerCommands, slot: ArmorSlot) {
+    parent.spawn((
+        EquipmentSlot { slot },
+        Button,
+        Node {
+            width: Val::Px(64.0),
+            height: Val::Px(64.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(SLOT_EMPTY),
+        BorderColor::from(SLOT_BORDER),
+    )).with_children(|s| {
+        s.spawn((
+            EquipmentSlotIcon { slot },
+            Node {
+                width: Val::Px(46.0),
+                height: Val::Px(46.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+        ));
+        
+        // Durability percentage (bottom-right corner)
+        s.spawn((
+            EquipmentSlotDurability { slot },
+            Text::new(""),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(2.0),
+                right: Val::Px(4.0),
+                ..default()
+            },
+        ));
+    });
+}
+
 /// Despawn inventory UI when closed
 fn despawn_inventory_ui(
     mut commands: Commands,
@@ -614,13 +856,15 @@ fn update_inventory_slots(
     }
 }
 
-/// Handle slot interactions (right-click to drop)
+/// Handle slot interactions (right-click to drop, Shift+right-click to wear armor)
 fn handle_slot_interactions(
     inventory_open: Res<InventoryOpen>,
     mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
     slots: Query<(&InventorySlot, &Interaction)>,
     local_player: Query<&Inventory, With<LocalPlayer>>,
     mut client_query: Query<&mut MessageSender<DropRequest>, (With<crate::GameClient>, With<Connected>)>,
+    mut equip_sender: Query<&mut MessageSender<EquipArmorRequest>, (With<crate::GameClient>, With<Connected>)>,
 ) {
     if !inventory_open.0 {
         return;
@@ -637,6 +881,19 @@ fn handle_slot_interactions(
     
     for (slot, interaction) in slots.iter() {
         if *interaction == Interaction::Hovered || *interaction == Interaction::Pressed {
+            // Shift+right-click on armor wears it instead of dropping it
+            let shift = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+            let is_armor = inventory
+                .get_slot(slot.index)
+                .is_some_and(|stack| stack.item_type.as_armor_type().is_some());
+            if shift && is_armor {
+                if let Ok(mut sender) = equip_sender.single_mut() {
+                    let _ = sender.send::<ReliableChannel>(EquipArmorRequest { slot_index: slot.index as u8 });
+                    info!("Requesting equip armor from slot {}", slot.index);
+                }
+                continue;
+            }
+            
             // Check if slot has an item
             if inventory.get_slot(slot.index).is_some() {
                 // Send drop request
@@ -649,6 +906,71 @@ fn handle_slot_interactions(
     }
 }
 
+/// Update equipment slot visuals based on worn armor
+fn update_equipment_slots(
+    inventory_open: Res<InventoryOpen>,
+    local_player: Query<&EquippedArmor, With<LocalPlayer>>,
+    mut slots: Query<(&EquipmentSlot, &mut BackgroundColor, &Interaction)>,
+    mut icons: Query<(&EquipmentSlotIcon, &mut BackgroundColor), Without<EquipmentSlot>>,
+    mut durability_texts: Query<(&EquipmentSlotDurability, &mut Text)>,
+) {
+    if !inventory_open.0 {
+        return;
+    }
+    
+    let armor = local_player.single().copied().unwrap_or_default();
+    
+    for (slot, mut bg, interaction) in slots.iter_mut() {
+        *bg = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BackgroundColor(SLOT_HOVERED),
+            Interaction::None if armor.get(slot.slot).is_some() => BackgroundColor(SLOT_NORMAL),
+            Interaction::None => BackgroundColor(SLOT_EMPTY),
+        };
+    }
+    
+    for (icon, mut bg) in icons.iter_mut() {
+        *bg = match armor.get(icon.slot) {
+            Some(piece) => BackgroundColor(shared::ItemType::Armor(piece.armor_type).color()),
+            None => BackgroundColor(Color::NONE),
+        };
+    }
+    
+    for (durability, mut text) in durability_texts.iter_mut() {
+        **text = match armor.get(durability.slot) {
+            Some(piece) => format!("{:.0}%", piece.durability_fraction() * 100.0),
+            None => String::new(),
+        };
+    }
+}
+
+/// Right-click a worn armor slot to take it off
+fn handle_equipment_slot_interactions(
+    inventory_open: Res<InventoryOpen>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    slots: Query<(&EquipmentSlot, &Interaction)>,
+    local_player: Query<&EquippedArmor, With<LocalPlayer>>,
+    mut client_query: Query<&mut MessageSender<UnequipArmorRequest>, (With<crate::GameClient>, With<Connected>)>,
+) {
+    if !inventory_open.0 || !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+    
+    let Ok(armor) = local_player.single() else {
+        return;
+    };
+    
+    for (slot, interaction) in slots.iter() {
+        if *interaction == Interaction::Hovered || *interaction == Interaction::Pressed {
+            if armor.get(slot.slot).is_some() {
+                if let Ok(mut sender) = client_query.single_mut() {
+                    let _ = sender.send::<ReliableChannel>(UnequipArmorRequest { slot: slot.slot });
+                    info!("Requesting unequip armor {:?}", slot.slot);
+                }
+            }
+        }
+    }
+}
+
 /// Update chest slot visuals based on chest contents
 fn update_chest_slots(
     inventory_open: Res<InventoryOpen>,
@@ -866,3 +1188,108 @@ fn handle_chest_slot_interactions(
         }
     }
 }
+
+/// Update trade slot visuals, the coin count and the last trade result
+fn update_trade_slots(
+    inventory_open: Res<InventoryOpen>,
+    open_trade: Res<OpenTrade>,
+    feedback: Res<TradeFeedback>,
+    traders: Query<&TraderStock>,
+    local_player: Query<&Inventory, With<LocalPlayer>>,
+    mut slots: Query<(&TradeSlot, &mut BackgroundColor, &Interaction)>,
+    mut icons: Query<(&TradeSlotIcon, &mut BackgroundColor), Without<TradeSlot>>,
+    mut stock_texts: Query<(&TradeSlotStock, &mut Text), (Without<TradeCoinsText>, Without<TradeFeedbackText>)>,
+    mut coins_text: Query<&mut Text, (With<TradeCoinsText>, Without<TradeFeedbackText>)>,
+    mut feedback_text: Query<(&mut Text, &mut TextColor), With<TradeFeedbackText>>,
+) {
+    if !inventory_open.0 {
+        return;
+    }
+    
+    let Some(trader) = open_trade.entity.and_then(|entity| traders.get(entity).ok()) else {
+        return;
+    };
+    let lines = trader.price_list();
+    
+    for (slot, mut bg, interaction) in slots.iter_mut() {
+        *bg = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BackgroundColor(SLOT_HOVERED),
+            Interaction::None if trader.available(slot.index) > 0 => BackgroundColor(SLOT_NORMAL),
+            Interaction::None => BackgroundColor(SLOT_EMPTY),
+        };
+    }
+    
+    for (icon, mut bg) in icons.iter_mut() {
+        if let Some(line) = lines.get(icon.index) {
+            *bg = BackgroundColor(line.item.color());
+        }
+    }
+    
+    for (stock, mut text) in stock_texts.iter_mut() {
+        **text = format!("{}", trader.available(stock.index));
+    }
+    
+    let coins = local_player.single().map_or(0, |inventory| inventory.count_item(ItemType::Coins));
+    for mut text in coins_text.iter_mut() {
+        **text = format!("Coins: {}", coins);
+    }
+    
+    for (mut text, mut color) in feedback_text.iter_mut() {
+        if **text != feedback.message {
+            **text = feedback.message.clone();
+        }
+        *color = TextColor(if feedback.failed { ACCENT_RED } else { TEXT_COLOR });
+    }
+}
+
+/// Click a trader's goods to buy, right-click to sell (Shift for 10 at a time).
+/// Gear sells one piece at a time, never the one in the selected hotbar slot.
+fn handle_trade_slot_interactions(
+    inventory_open: Res<InventoryOpen>,
+    open_trade: Res<OpenTrade>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    slots: Query<(&TradeSlot, &Interaction)>,
+    traders: Query<(&Npc, &TraderStock)>,
+    local_player: Query<(&Inventory, &HotbarSelection), With<LocalPlayer>>,
+    mut client_query: Query<&mut MessageSender<TradeRequest>, (With<crate::GameClient>, With<Connected>)>,
+) {
+    if !inventory_open.0 {
+        return;
+    }
+    
+    let buy = mouse.just_pressed(MouseButton::Left);
+    if !buy && !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+    
+    let Some((trader, stock)) = open_trade.entity.and_then(|entity| traders.get(entity).ok()) else {
+        return;
+    };
+    
+    let shift = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+    let quantity = if shift { 10 } else { 1 };
+    
+    for (slot, interaction) in slots.iter() {
+        if *interaction == Interaction::Hovered || *interaction == Interaction::Pressed {
+            // Pick the first matching piece of gear that isn't in hand
+            let sell_slot = stock.price_list().get(slot.index).and_then(|line| {
+                let (inventory, hotbar) = local_player.single().ok()?;
+                inventory
+                    .iter_items()
+                    .find(|(index, stack)| stack.item_type == line.item && *index != hotbar.index as usize)
+                    .map(|(index, _)| index as u8)
+            });
+            if let Ok(mut sender) = client_query.single_mut() {
+                let _ = sender.send::<ReliableChannel>(TradeRequest {
+                    trader: trader.id,
+                    line: slot.index as u8,
+                    quantity,
+                    buy,
+                    slot: if buy { None } else { sell_slot },
+                });
+                info!("Requesting {} {}x of trade line {}", if buy { "buy" } else { "sell" }, quantity, slot.index);
+            }
+        }
+    }
+}
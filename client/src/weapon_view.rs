@@ -6,12 +6,14 @@
 use bevy::prelude::*;
 use lightyear::prelude::*;
 use shared::{
-    weapons::WeaponType, EquippedWeapon, LocalPlayer, SelectHotbarSlot, HotbarSelection, ReliableChannel, Player,
+    weapons::{MeleeSwing, WeaponType}, EquippedWeapon, LocalPlayer, SelectHotbarSlot, HotbarSelection,
+    ReliableChannel, Player,
 };
 
 use std::collections::{HashMap, HashSet};
 
 use crate::input::{CameraMode, InputState};
+use crate::weapons::ReloadState;
 
 /// Marker for the first-person weapon model
 #[derive(Component)]
@@ -49,12 +51,58 @@ pub struct WeaponNameText;
 #[derive(Component)]
 pub struct HotbarSlotsText;
 
+/// Marker for fire mode text (SEMI / BURST 3 / AUTO)
+#[derive(Component)]
+pub struct FireModeText;
+
 /// Resource tracking which weapon model is currently shown
 #[derive(Resource, Default)]
 pub struct CurrentWeaponView {
     pub weapon_type: Option<WeaponType>,
 }
 
+/// Melee swing animation playing on a player (local swings start on click,
+/// remote ones when the server's `MeleeSwing` arrives)
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MeleeSwingAnimation {
+    pub started_at: f32,
+    pub weapon_type: WeaponType,
+}
+
+impl MeleeSwingAnimation {
+    /// Offset and rotation to layer on top of the held weapon's rest pose,
+    /// or None once the swing has finished.
+    pub fn pose(&self, now: f32) -> Option<(Vec3, Quat)> {
+        let melee = self.weapon_type.melee_stats()?;
+        let elapsed = now - self.started_at;
+        if !(0.0..melee.swing_duration()).contains(&elapsed) {
+            return None;
+        }
+        
+        // Keyframes: rest -> pulled back to the right -> struck across to the left -> rest
+        let rest = (Vec3::ZERO, 0.0, 0.0);
+        let wound = (Vec3::new(0.12, 0.1, 0.08), -0.7, 0.5);
+        let struck = (Vec3::new(-0.22, -0.05, -0.2), 0.9, -0.4);
+        let strike_time = (melee.recovery * 0.3).min(0.15);
+        
+        let lerp = |a: (Vec3, f32, f32), b: (Vec3, f32, f32), t: f32| {
+            let t = t.clamp(0.0, 1.0);
+            let t = t * t * (3.0 - 2.0 * t); // smoothstep
+            (a.0.lerp(b.0, t), a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+        };
+        let (offset, yaw, pitch) = if elapsed < melee.wind_up {
+            lerp(rest, wound, elapsed / melee.wind_up)
+        } else if elapsed < melee.wind_up + strike_time {
+            lerp(wound, struck, (elapsed - melee.wind_up) / strike_time)
+        } else {
+            let recovery_time = melee.recovery - strike_time;
+            lerp(struck, rest, (elapsed - melee.wind_up - strike_time) / recovery_time)
+        };
+        
+        Some((offset, Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0)))
+    }
+}
+
 /// Handle weapon switching with number keys
 pub fn handle_weapon_switch(
     keyboard: Res<ButtonInput<KeyCode>>,
@@ -134,6 +182,17 @@ pub fn spawn_weapon_hud(mut commands: Commands) {
                 TextColor(Color::srgba(1.0, 0.9, 0.6, 1.0)),
             ));
             
+            // Fire mode (toggle with V)
+            parent.spawn((
+                FireModeText,
+                Text::new("AUTO"),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.8, 0.8, 0.8, 0.8)),
+            ));
+            
             // Hotbar slots display (dynamically updated)
             parent.spawn((
                 HotbarSlotsText,
@@ -167,11 +226,13 @@ pub fn despawn_weapon_hud(
 /// Update HUD to show current weapon and ammo
 pub fn update_weapon_hud(
     local_player: Query<(&EquippedWeapon, &shared::Inventory, &HotbarSelection), With<LocalPlayer>>,
-    mut weapon_text: Query<&mut Text, (With<WeaponNameText>, Without<AmmoText>, Without<HotbarSlotsText>)>,
-    mut ammo_text: Query<&mut Text, (With<AmmoText>, Without<WeaponNameText>, Without<HotbarSlotsText>)>,
-    mut hotbar_text: Query<&mut Text, (With<HotbarSlotsText>, Without<WeaponNameText>, Without<AmmoText>)>,
+    mut weapon_text: Query<&mut Text, (With<WeaponNameText>, Without<AmmoText>, Without<HotbarSlotsText>, Without<FireModeText>)>,
+    mut ammo_text: Query<&mut Text, (With<AmmoText>, Without<WeaponNameText>, Without<HotbarSlotsText>, Without<FireModeText>)>,
+    mut hotbar_text: Query<&mut Text, (With<HotbarSlotsText>, Without<WeaponNameText>, Without<AmmoText>, Without<FireModeText>)>,
+    mut fire_mode_text: Query<&mut Text, (With<FireModeText>, Without<WeaponNameText>, Without<AmmoText>, Without<HotbarSlotsText>)>,
     mut hud_visibility: Query<&mut Visibility, With<WeaponHUD>>,
     input_state: Res<InputState>,
+    reload_state: Res<ReloadState>,
 ) {
     // Hide HUD in vehicle
     for mut vis in hud_visibility.iter_mut() {
@@ -210,17 +271,33 @@ pub fn update_weapon_hud(
         **text = slots.join("  ");
     }
     
-    // Update ammo - reserve comes from inventory (no ammo display when Unarmed)
-    if weapon.weapon_type == WeaponType::Unarmed {
+    // Update ammo - reserve comes from inventory (no ammo display for fists/melee)
+    if weapon.weapon_type.is_melee() {
         for mut text in ammo_text.iter_mut() {
             **text = String::new();
         }
+        for mut text in fire_mode_text.iter_mut() {
+            **text = String::new();
+        }
         return;
     }
     
+    // Fire mode - hint at the toggle only when there is something to toggle
+    for mut text in fire_mode_text.iter_mut() {
+        **text = if weapon.weapon_type.fire_modes().len() > 1 {
+            format!("{} [V]", weapon.fire_mode.label())
+        } else {
+            weapon.fire_mode.label()
+        };
+    }
+    
     let reserve_ammo = inventory.count_item(weapon.weapon_type.ammo_type());
     for mut text in ammo_text.iter_mut() {
-        **text = format!("{} / {}", weapon.ammo_in_mag, reserve_ammo);
+        **text = if reload_state.active {
+            format!("{} / {}  RELOADING {:.0}%", weapon.ammo_in_mag, reserve_ammo, reload_state.progress * 100.0)
+        } else {
+            format!("{} / {}", weapon.ammo_in_mag, reserve_ammo)
+        };
     }
 }
 
@@ -233,6 +310,9 @@ fn weapon_name(weapon: WeaponType) -> String {
         WeaponType::Shotgun => "Shotgun".to_string(),
         WeaponType::SMG => "SMG".to_string(),
         WeaponType::Unarmed => "Unarmed".to_string(),
+        WeaponType::Knife => "Knife".to_string(),
+        WeaponType::Axe => "Axe".to_string(),
+        WeaponType::Sledgehammer => "Sledgehammer".to_string(),
     }
 }
 
@@ -247,6 +327,9 @@ fn hotbar_item_name(item_type: &shared::ItemType) -> String {
             WeaponType::Shotgun => "Shotgun".to_string(),
             WeaponType::SMG => "SMG".to_string(),
             WeaponType::Unarmed => "-".to_string(),
+            WeaponType::Knife => "Knife".to_string(),
+            WeaponType::Axe => "Axe".to_string(),
+            WeaponType::Sledgehammer => "Sledge".to_string(),
         },
         ItemType::PistolAmmo => "9mm".to_string(),
         ItemType::RifleAmmo => "5.56".to_string(),
@@ -254,6 +337,15 @@ fn hotbar_item_name(item_type: &shared::ItemType) -> String {
         ItemType::SniperRounds => ".308".to_string(),
         ItemType::Wood => "Wood".to_string(),
         ItemType::Stone => "Stone".to_string(),
+        ItemType::Armor(a) => match a {
+            shared::ArmorType::Vest => "Vest".to_string(),
+            shared::ArmorType::Helmet => "Helmet".to_string(),
+        },
+        ItemType::Explosive(e) => match e {
+            shared::ExplosiveType::Grenade => "Grenade".to_string(),
+            shared::ExplosiveType::Charge => "Charge".to_string(),
+        },
+        ItemType::Coins => "Coins".to_string(),
     }
 }
 
@@ -276,10 +368,9 @@ pub fn update_first_person_weapon(
         return;
     };
     
-    // Hide weapon in third-person or vehicle
+    // Hide weapon in third-person or vehicle (bare fists still get a model)
     let should_show = input_state.camera_mode == CameraMode::FirstPerson
-        && !input_state.in_vehicle
-        && weapon.weapon_type != WeaponType::Unarmed;
+        && !input_state.in_vehicle;
     
     // Check if we need to change the model
     let needs_update = current_view.weapon_type != Some(weapon.weapon_type);
@@ -590,7 +681,33 @@ fn spawn_weapon_model(
         }
         
         WeaponType::Unarmed => {
-            // No model
+            // Bare fists, one per side of the view
+            let skin_material = materials.add(StandardMaterial {
+                base_color: Color::srgb(0.85, 0.65, 0.5),
+                perceptual_roughness: 0.9,
+                ..default()
+            });
+            let fist = meshes.add(Cuboid::new(0.07, 0.06, 0.09));
+            let forearm = meshes.add(Cuboid::new(0.055, 0.05, 0.2));
+            
+            commands.entity(weapon_entity).with_children(|parent| {
+                for x in [0.0, -0.45] {
+                    parent.spawn((
+                        Mesh3d(fist.clone()),
+                        MeshMaterial3d(skin_material.clone()),
+                        Transform::from_translation(Vec3::new(x, 0.0, 0.0)),
+                    ));
+                    parent.spawn((
+                        Mesh3d(forearm.clone()),
+                        MeshMaterial3d(grip_material.clone()),
+                        Transform::from_translation(Vec3::new(x, -0.02, 0.14)),
+                    ));
+                }
+            });
+        }
+        
+        WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => {
+            spawn_melee_weapon_parts(commands, meshes, materials, weapon_entity, weapon_type, 1.0);
         }
     }
     
@@ -598,13 +715,75 @@ fn spawn_weapon_model(
     commands.entity(camera_entity).add_child(weapon_entity);
 }
 
+/// Spawn the parts of a hand-held melee weapon (handle pointing up, head on top)
+fn spawn_melee_weapon_parts(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    weapon_entity: Entity,
+    weapon_type: WeaponType,
+    scale: f32,
+) {
+    let steel_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.7, 0.72, 0.75),
+        metallic: 0.9,
+        perceptual_roughness: 0.25,
+        ..default()
+    });
+    
+    let wood_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.3, 0.18),
+        metallic: 0.0,
+        perceptual_roughness: 0.85,
+        ..default()
+    });
+    
+    // (handle length, handle radius, head size, head offset along the handle)
+    let (handle_len, handle_radius, head, head_offset) = match weapon_type {
+        WeaponType::Knife => (0.09, 0.012, Vec3::new(0.006, 0.16, 0.03), Vec3::new(0.0, 0.125, 0.0)),
+        WeaponType::Axe => (0.45, 0.015, Vec3::new(0.025, 0.08, 0.13), Vec3::new(0.0, 0.2, -0.05)),
+        WeaponType::Sledgehammer => (0.55, 0.017, Vec3::new(0.08, 0.08, 0.2), Vec3::new(0.0, 0.27, 0.0)),
+        _ => return,
+    };
+    let handle = meshes.add(Cylinder::new(handle_radius * scale, handle_len * scale));
+    let head_mesh = meshes.add(Cuboid::new(head.x * scale, head.y * scale, head.z * scale));
+    let handle_material = if weapon_type == WeaponType::Knife {
+        materials.add(StandardMaterial {
+            base_color: Color::srgb(0.08, 0.06, 0.04),
+            perceptual_roughness: 0.8,
+            ..default()
+        })
+    } else {
+        wood_material
+    };
+    
+    // Held upright and tilted forward, grip at the origin
+    let tilt = Quat::from_rotation_x(-0.5);
+    commands.entity(weapon_entity).with_children(|parent| {
+        parent.spawn((
+            Mesh3d(handle),
+            MeshMaterial3d(handle_material),
+            Transform::from_translation(tilt * Vec3::new(0.0, handle_len * 0.3 * scale, 0.0))
+                .with_rotation(tilt),
+        ));
+        parent.spawn((
+            Mesh3d(head_mesh),
+            MeshMaterial3d(steel_material),
+            Transform::from_translation(tilt * (head_offset * scale)).with_rotation(tilt),
+        ));
+    });
+}
+
 /// Add slight weapon sway/bob for visual polish
 pub fn animate_weapon(
     mut weapons: Query<&mut Transform, With<FirstPersonWeapon>>,
+    local_swing: Query<&MeleeSwingAnimation, With<LocalPlayer>>,
     input_state: Res<InputState>,
+    reload_state: Res<ReloadState>,
     time: Res<Time>,
 ) {
     let t = time.elapsed_secs();
+    let swing_pose = local_swing.iter().next().and_then(|swing| swing.pose(t));
     
     for mut transform in weapons.iter_mut() {
         // Base position
@@ -620,7 +799,89 @@ pub fn animate_weapon(
             offset.x += (t * 4.0).sin() * 0.004;
         }
         
-        transform.translation = offset;
+        let (swing_offset, swing_rotation) = swing_pose.unwrap_or((Vec3::ZERO, Quat::IDENTITY));
+        let (reload_offset, reload_rotation) = reload_pose(&reload_state, t);
+        transform.translation = offset + swing_offset + reload_offset;
+        transform.rotation = swing_rotation * reload_rotation;
+    }
+}
+
+/// First-person reload pose driven by the server's reload progress.
+///
+/// Magazine reloads dip and roll the weapon over the whole reload; shell-by-shell
+/// reloads hold it tilted and nudge it for each shell.
+fn reload_pose(reload_state: &ReloadState, t: f32) -> (Vec3, Quat) {
+    if !reload_state.active {
+        return (Vec3::ZERO, Quat::IDENTITY);
+    }
+    // Ease in over the first 15% and out over the last 15%
+    let p = reload_state.progress;
+    let envelope = (p / 0.15).min(1.0).min((1.0 - p) / 0.15).clamp(0.0, 1.0);
+    let envelope = envelope * envelope * (3.0 - 2.0 * envelope);
+    
+    if reload_state.per_shell {
+        let nudge = (t * 9.0).sin().max(0.0) * 0.015;
+        let offset = Vec3::new(-0.05, -0.08 + nudge, 0.03) * envelope;
+        let rotation = Quat::from_rotation_z(0.5 * envelope) * Quat::from_rotation_x(0.15 * envelope);
+        (offset, rotation)
+    } else {
+        let offset = Vec3::new(-0.04, -0.14, 0.05) * envelope;
+        let rotation = Quat::from_rotation_z(0.7 * envelope) * Quat::from_rotation_x(-0.35 * envelope);
+        (offset, rotation)
+    }
+}
+
+/// Start swing animations for remote players when the server reports a swing
+pub fn start_remote_melee_swings(
+    mut commands: Commands,
+    swings: Query<(Entity, &MeleeSwing), (Added<MeleeSwing>, Without<LocalPlayer>)>,
+    time: Res<Time>,
+) {
+    for (entity, swing) in swings.iter() {
+        commands.entity(entity).insert(MeleeSwingAnimation {
+            started_at: time.elapsed_secs(),
+            weapon_type: swing.weapon_type,
+        });
+    }
+}
+
+/// Animate third-person melee weapons (local and remote) and clear finished swings
+pub fn animate_third_person_swings(
+    mut commands: Commands,
+    swinging: Query<(Entity, &MeleeSwingAnimation, Has<LocalPlayer>)>,
+    mut local_weapon: Query<&mut Transform, (With<ThirdPersonWeapon>, Without<RemoteThirdPersonWeapon>)>,
+    mut remote_weapons: Query<(&RemoteThirdPersonWeapon, &mut Transform), Without<ThirdPersonWeapon>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    let rest_offset = Vec3::new(0.2, 0.15, -0.35);
+    let rest_rotation = Quat::from_rotation_y(-0.1);
+    // Whole-arm motion reads better from the outside than the first-person pose
+    let arm_scale = 2.0;
+    
+    let mut poses: HashMap<Entity, (Vec3, Quat)> = HashMap::new();
+    let mut local_pose = None;
+    for (entity, swing, is_local) in swinging.iter() {
+        match swing.pose(now) {
+            Some(pose) if is_local => local_pose = Some(pose),
+            Some(pose) => {
+                poses.insert(entity, pose);
+            }
+            None => {
+                commands.entity(entity).remove::<MeleeSwingAnimation>();
+            }
+        }
+    }
+    
+    for mut transform in local_weapon.iter_mut() {
+        let (offset, rotation) = local_pose.unwrap_or((Vec3::ZERO, Quat::IDENTITY));
+        transform.translation = rest_offset + offset * arm_scale;
+        transform.rotation = rest_rotation * rotation;
+    }
+    for (weapon, mut transform) in remote_weapons.iter_mut() {
+        let (offset, rotation) = poses.get(&weapon.owner).copied().unwrap_or((Vec3::ZERO, Quat::IDENTITY));
+        transform.translation = rest_offset + offset * arm_scale;
+        transform.rotation = rest_rotation * rotation;
     }
 }
 
@@ -862,6 +1123,9 @@ fn spawn_third_person_weapon(
                 ));
             });
         }
+        WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => {
+            spawn_melee_weapon_parts(commands, meshes, materials, weapon_entity, weapon_type, scale);
+        }
         WeaponType::Unarmed => {
             // No model
         }
@@ -994,6 +1258,9 @@ fn spawn_remote_third_person_weapon(
                 ));
             });
         }
+        WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => {
+            spawn_melee_weapon_parts(commands, meshes, materials, weapon_entity, weapon_type, scale);
+        }
         WeaponType::Unarmed => {
             // No model
         }
@@ -10,12 +10,12 @@ use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
 use lightyear::prelude::*;
 use shared::{
     weapons::{
-        ballistics, WeaponDebugMode, WeaponType,
-        RECOIL_RECOVERY_SPEED, RECOIL_BURST_RESET_TIME, RECOIL_ADS_MULTIPLIER, RECOIL_ACCUMULATION_MULT,
+        ballistics, AdjustZeroRequest, CycleFireModeRequest, FireMode, Reloading, WeaponDebugMode, WeaponType,
+        RECOIL_RECOVERY_SPEED, RECOIL_BURST_RESET_TIME,
     },
     Bullet, BulletImpact, BulletImpactSurface, BulletVelocity, EquippedWeapon, HitConfirm, LocalTracer,
-    ChunkCoord, LocalPlayer, Player, PlayerPosition, ShootRequest, ReloadRequest, ReliableChannel, WorldTerrain,
-    Vehicle,
+    ChunkCoord, LocalPlayer, Player, PlayerPosition, ShootRequest, ReloadRequest, InterruptReloadRequest, ReliableChannel,
+    WorldTerrain, Vehicle, Wind,
 };
 
 /// Marker for the debug overlay UI
@@ -69,6 +69,7 @@ pub fn setup_weapon_audio_assets(
 use crate::crosshair;
 use crate::input::InputState;
 use crate::states::GameState;
+use crate::weapon_view::MeleeSwingAnimation;
 
 /// Prevent the "click to focus/grab cursor" from also firing a shot.
 ///
@@ -91,14 +92,16 @@ pub struct ShootingState {
     pub out_of_ammo_this_frame: bool,
     /// Track weapon type that fired (for shotgun vs other sounds)
     pub weapon_fired: Option<WeaponType>,
-    /// Accumulated vertical recoil (pitch) from rapid fire
+    /// Accumulated vertical recoil (pitch) over the current burst
     pub accumulated_recoil_pitch: f32,
-    /// Accumulated horizontal recoil (yaw) from rapid fire
+    /// Accumulated horizontal recoil (yaw) over the current burst
     pub accumulated_recoil_yaw: f32,
-    /// Number of shots in current burst (resets after pause)
+    /// Number of shots in current burst (resets after pause, indexes the recoil pattern)
     pub shots_in_burst: u32,
     /// Last time out of ammo sound was played (to avoid spam)
     pub last_out_of_ammo_time: f32,
+    /// Rounds fired during the current trigger pull (for semi/burst limits)
+    pub shots_this_pull: u32,
 }
 
 impl Default for ShootingState {
@@ -113,14 +116,35 @@ impl Default for ShootingState {
             accumulated_recoil_yaw: 0.0,
             shots_in_burst: 0,
             last_out_of_ammo_time: -10.0,
+            shots_this_pull: 0,
         }
     }
 }
 
-/// Resource to track reload state for audio
+/// Local mirror of the server's replicated `Reloading` (HUD, audio, animation)
 #[derive(Resource, Default)]
 pub struct ReloadState {
     pub reload_requested_this_frame: bool,
+    /// A reload is in progress on the server
+    pub active: bool,
+    /// Weapon being reloaded
+    pub weapon_type: Option<WeaponType>,
+    /// Progress 0.0-1.0 (from the local clock since the reload appeared)
+    pub progress: f32,
+    /// Local time the reload appeared
+    pub started_at: f32,
+    /// Sprinting already asked the server to stop this reload
+    pub interrupt_sent: bool,
+    /// Reloading from an empty magazine
+    pub empty: bool,
+    /// Shell-by-shell reload (interruptible by firing/sprinting)
+    pub per_shell: bool,
+    /// Shells inserted so far (per-shell reloads)
+    pub shells_loaded: u32,
+    /// The server started a reload this frame (for audio)
+    pub started_this_frame: bool,
+    /// A shell went in this frame (for audio)
+    pub shell_inserted_this_frame: bool,
 }
 
 // Recoil settings are now imported from shared::weapons
@@ -181,15 +205,17 @@ pub struct DebugBulletTrails {
 
 /// Handle shooting input and send requests to server
 pub fn handle_shoot_input(
+    mut commands: Commands,
     mouse: Res<ButtonInput<MouseButton>>,
     mut shooting_state: ResMut<ShootingState>,
     // In Lightyear 0.25, we send messages via MessageSender component - typed on message type
     mut client_query: Query<&mut MessageSender<ShootRequest>, (With<crate::GameClient>, With<Connected>)>,
     mut input_state: ResMut<InputState>,
-    local_player: Query<&EquippedWeapon, With<LocalPlayer>>,
+    local_player: Query<(Entity, &EquippedWeapon), With<LocalPlayer>>,
     camera: Query<&Transform, With<Camera3d>>,
     time: Res<Time>,
     game_state: Res<State<GameState>>,
+    reload_state: Res<ReloadState>,
     mut last_warn_time: Local<f32>,
     // Cursor state - don't shoot if cursor isn't grabbed yet (first click grabs, doesn't fire)
     windows: Query<Entity, With<PrimaryWindow>>,
@@ -256,7 +282,7 @@ pub fn handle_shoot_input(
         return;
     }
     
-    let Ok(weapon) = local_player.single() else {
+    let Ok((player_entity, weapon)) = local_player.single() else {
         return;
     };
     
@@ -268,23 +294,65 @@ pub fn handle_shoot_input(
     let fire_pressed = mouse.pressed(MouseButton::Left);
     let current_time = time.elapsed_secs();
     
+    // A fresh press starts a new trigger pull
+    if fire_pressed && !shooting_state.fire_held {
+        shooting_state.shots_this_pull = 0;
+    }
+    
+    // Melee: swing instead of firing (server resolves the hit after the wind-up)
+    if weapon.weapon_type.is_melee() {
+        let cooldown_passed = (current_time - shooting_state.last_fire_time) >= weapon.weapon_type.fire_cooldown();
+        if fire_pressed && cooldown_passed {
+            shooting_state.last_fire_time = current_time;
+            if let Ok(mut sender) = client_query.single_mut() {
+                let _ = sender.send::<ReliableChannel>(ShootRequest {
+                    direction: camera_transform.forward().as_vec3(),
+                    pitch: input_state.pitch,
+                    aiming: false,
+                    shot_index: 0,
+                    recoil_offset: Vec2::ZERO,
+                });
+            }
+            commands.entity(player_entity).insert(MeleeSwingAnimation {
+                started_at: current_time,
+                weapon_type: weapon.weapon_type,
+            });
+        }
+        shooting_state.fire_held = fire_pressed;
+        return;
+    }
+    
     // Check fire rate
     let cooldown = weapon.weapon_type.fire_cooldown();
     let cooldown_passed = (current_time - shooting_state.last_fire_time) >= cooldown;
     let has_ammo = weapon.ammo_in_mag > 0;
     
     // Out of ammo click (only on just pressed, not held, and with rate limiting)
-    if fire_pressed && !has_ammo && cooldown_passed {
+    if fire_pressed && !has_ammo && cooldown_passed && !reload_state.active {
         if current_time - shooting_state.last_out_of_ammo_time > 0.3 {
             shooting_state.out_of_ammo_this_frame = true;
             shooting_state.last_out_of_ammo_time = current_time;
         }
     }
     
-    let can_fire = has_ammo && cooldown_passed;
+    // Reloads block firing; a partly loaded shotgun may fire (the server cancels its reload)
+    let reload_blocks = reload_state.active && !(reload_state.per_shell && has_ammo);
+    let can_fire = has_ammo && cooldown_passed && !reload_blocks;
     
-    if fire_pressed && can_fire {
+    // Fire mode: semi stops after one round per pull, a burst finishes even if released
+    let burst_in_progress = matches!(weapon.fire_mode, FireMode::Burst(_)) && shooting_state.shots_this_pull > 0;
+    // The server counts rounds closer than the pull interval as one pull, so a quick
+    // re-press waits out the trigger reset instead of getting rejected
+    let trigger_reset = weapon.fire_mode == FireMode::Auto
+        || shooting_state.shots_this_pull > 0
+        || current_time - shooting_state.last_fire_time >= weapon.weapon_type.trigger_pull_interval();
+    let trigger_active = (fire_pressed || burst_in_progress)
+        && trigger_reset
+        && weapon.fire_mode.allows_shot(shooting_state.shots_this_pull);
+    
+    if trigger_active && can_fire {
         shooting_state.last_fire_time = current_time;
+        shooting_state.shots_this_pull += 1;
         
         // Get aim direction from camera
         let direction = camera_transform.forward().as_vec3();
@@ -292,12 +360,23 @@ pub fn handle_shoot_input(
         // Use the toggled ADS state from input (not mouse.pressed since we switched to toggle)
         let aiming = input_state.aiming;
         
+        // A new burst starts the pattern over; leftover recovery just stays in the aim
+        if shooting_state.shots_in_burst == 0 {
+            shooting_state.accumulated_recoil_pitch = 0.0;
+            shooting_state.accumulated_recoil_yaw = 0.0;
+        }
+        
         // Send shoot request to server via MessageSender
         if let Ok(mut sender) = client_query.single_mut() {
             let _ = sender.send::<ReliableChannel>(ShootRequest {
                 direction,
                 pitch: input_state.pitch,
                 aiming,
+                shot_index: shooting_state.shots_in_burst,
+                recoil_offset: Vec2::new(
+                    shooting_state.accumulated_recoil_pitch,
+                    shooting_state.accumulated_recoil_yaw,
+                ),
             });
         } else if current_time - *last_warn_time > 1.0 {
             // If this fires, you'll hear local SFX but the server will never spawn bullets / consume ammo.
@@ -306,26 +385,12 @@ pub fn handle_shoot_input(
         }
 
         // === APPLY RECOIL ===
-        let stats = weapon.weapon_type.stats();
-        
-        // Accumulation multiplier based on burst length (more shots = more recoil)
-        let burst_mult = RECOIL_ACCUMULATION_MULT.powi(shooting_state.shots_in_burst as i32);
-        
-        // ADS reduces recoil
-        let ads_mult = if aiming { RECOIL_ADS_MULTIPLIER } else { 1.0 };
-        
-        // Calculate recoil for this shot
-        let vertical_recoil = stats.recoil_vertical * burst_mult * ads_mult;
-        let horizontal_recoil = stats.recoil_horizontal * burst_mult * ads_mult;
-        
-        // Random horizontal direction (left or right)
-        let h_direction = if rand::random::<bool>() { 1.0 } else { -1.0 };
-        // Add some randomness to horizontal (not always max)
-        let h_random = 0.3 + rand::random::<f32>() * 0.7;
+        // Same pattern entry the server uses for this round (ADS halves the kick)
+        let kick = weapon.weapon_type.recoil_kick(shooting_state.shots_in_burst, aiming);
         
         // Apply recoil to camera pitch (kick up) and yaw (kick sideways)
-        input_state.pitch += vertical_recoil;
-        input_state.yaw += horizontal_recoil * h_direction * h_random;
+        input_state.pitch += kick.x;
+        input_state.yaw += kick.y;
         
         // Clamp pitch to valid range
         input_state.pitch = input_state.pitch.clamp(
@@ -333,9 +398,9 @@ pub fn handle_shoot_input(
             std::f32::consts::FRAC_PI_2 - 0.01
         );
         
-        // Track accumulated recoil (for recovery system)
-        shooting_state.accumulated_recoil_pitch += vertical_recoil;
-        shooting_state.accumulated_recoil_yaw += horizontal_recoil * h_direction * h_random;
+        // Track accumulated recoil (reported with the next shot, recovered after the burst)
+        shooting_state.accumulated_recoil_pitch += kick.x;
+        shooting_state.accumulated_recoil_yaw += kick.y;
         
         // Increment burst counter
         shooting_state.shots_in_burst += 1;
@@ -348,7 +413,10 @@ pub fn handle_shoot_input(
     shooting_state.fire_held = fire_pressed;
 }
 
-/// Recover recoil over time when not shooting
+/// Recover recoil over time once a burst has ended
+///
+/// Nothing recovers mid-burst: the server expects each round to carry the
+/// full pattern offset, and pulling against the climb is the player's job.
 pub fn recover_recoil(
     mut shooting_state: ResMut<ShootingState>,
     mut input_state: ResMut<InputState>,
@@ -361,6 +429,9 @@ pub fn recover_recoil(
     if current_time - shooting_state.last_fire_time > RECOIL_BURST_RESET_TIME {
         shooting_state.shots_in_burst = 0;
     }
+    if shooting_state.shots_in_burst > 0 {
+        return;
+    }
     
     // Recover recoil gradually (pull aim back down)
     if shooting_state.accumulated_recoil_pitch.abs() > 0.001 {
@@ -400,10 +471,11 @@ pub fn recover_recoil(
     }
 }
 
-/// Handle reload input - sends request to server
+/// Handle reload input - sends request to server (and asks it to stop shell-by-shell reloads when sprinting)
 pub fn handle_reload_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut client_query: Query<&mut MessageSender<ReloadRequest>, (With<crate::GameClient>, With<Connected>)>,
+    mut interrupt_query: Query<&mut MessageSender<InterruptReloadRequest>, (With<crate::GameClient>, With<Connected>)>,
     local_player: Query<(&EquippedWeapon, &shared::Inventory), With<LocalPlayer>>,
     input_state: Res<InputState>,
     mut reload_state: ResMut<ReloadState>,
@@ -421,12 +493,24 @@ pub fn handle_reload_input(
         return;
     }
     
+    // Already reloading (server-driven); sprinting stops a shell-by-shell reload
+    if reload_state.active {
+        let sprinting = input_state.shift && input_state.forward && !input_state.inventory_open;
+        if reload_state.per_shell && sprinting && !reload_state.interrupt_sent {
+            if let Ok(mut sender) = interrupt_query.single_mut() {
+                let _ = sender.send::<ReliableChannel>(InterruptReloadRequest);
+            }
+            reload_state.interrupt_sent = true;
+        }
+        return;
+    }
+    
     if keyboard.just_pressed(KeyCode::KeyR) {
         if let Ok((weapon, inventory)) = local_player.single() {
             // Only send reload request if we actually need ammo and have reserve in inventory
             let stats = weapon.weapon_type.stats();
             let reserve_in_inventory = inventory.count_item(weapon.weapon_type.ammo_type());
-            if weapon.ammo_in_mag < stats.magazine_size && reserve_in_inventory > 0 {
+            if weapon.weapon_type.reload_timing().is_some() && weapon.ammo_in_mag < stats.magazine_size && reserve_in_inventory > 0 {
                 if let Ok(mut sender) = client_query.single_mut() {
                     let _ = sender.send::<ReliableChannel>(ReloadRequest);
                 }
@@ -437,6 +521,98 @@ pub fn handle_reload_input(
     }
 }
 
+/// Handle fire mode toggle (V) - the server cycles the mode and replicates it back
+pub fn handle_fire_mode_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut client_query: Query<&mut MessageSender<CycleFireModeRequest>, (With<crate::GameClient>, With<Connected>)>,
+    local_player: Query<&EquippedWeapon, With<LocalPlayer>>,
+    input_state: Res<InputState>,
+) {
+    if input_state.is_dead || input_state.in_vehicle || input_state.inventory_open {
+        return;
+    }
+    
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        let Ok(weapon) = local_player.single() else {
+            return;
+        };
+        // Nothing to cycle on single-mode weapons
+        if weapon.weapon_type.fire_modes().len() < 2 {
+            return;
+        }
+        if let Ok(mut sender) = client_query.single_mut() {
+            let _ = sender.send::<ReliableChannel>(CycleFireModeRequest);
+        }
+    }
+}
+
+/// Handle scope zeroing (Page Up / Page Down) - the server steps the zero and replicates it back
+pub fn handle_zero_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut client_query: Query<&mut MessageSender<AdjustZeroRequest>, (With<crate::GameClient>, With<Connected>)>,
+    local_player: Query<&EquippedWeapon, With<LocalPlayer>>,
+    input_state: Res<InputState>,
+) {
+    if input_state.is_dead || input_state.in_vehicle || input_state.inventory_open {
+        return;
+    }
+    
+    let steps = if keyboard.just_pressed(KeyCode::PageUp) {
+        1
+    } else if keyboard.just_pressed(KeyCode::PageDown) {
+        -1
+    } else {
+        return;
+    };
+    
+    let Ok(weapon) = local_player.single() else {
+        return;
+    };
+    if weapon.weapon_type.zero_ranges().is_empty() {
+        return;
+    }
+    if let Ok(mut sender) = client_query.single_mut() {
+        let _ = sender.send::<ReliableChannel>(AdjustZeroRequest { steps });
+    }
+}
+
+/// Mirror the local player's replicated `Reloading` into `ReloadState`
+pub fn sync_reload_state(
+    time: Res<Time>,
+    local_player: Query<Option<&Reloading>, With<LocalPlayer>>,
+    mut reload_state: ResMut<ReloadState>,
+) {
+    let now = time.elapsed_secs();
+    let reloading = local_player.iter().next().flatten();
+    let was_active = reload_state.active;
+    let previous_shells = reload_state.shells_loaded;
+    
+    match reloading {
+        Some(reload) => {
+            if !was_active {
+                reload_state.started_at = now;
+                reload_state.interrupt_sent = false;
+            }
+            reload_state.active = true;
+            reload_state.weapon_type = Some(reload.weapon_type);
+            reload_state.progress = reload.progress(now - reload_state.started_at);
+            reload_state.empty = reload.empty;
+            reload_state.per_shell = reload.is_per_shell();
+            reload_state.shells_loaded = reload.shells_loaded;
+            reload_state.started_this_frame = !was_active;
+            reload_state.shell_inserted_this_frame = was_active && reload.shells_loaded > previous_shells;
+        }
+        None => {
+            reload_state.active = false;
+            reload_state.weapon_type = None;
+            reload_state.progress = 0.0;
+            reload_state.shells_loaded = 0;
+            reload_state.started_this_frame = false;
+            reload_state.shell_inserted_this_frame = false;
+        }
+    }
+}
+
 /// Play weapon sound effects based on shooting/reload state
 pub fn play_weapon_sounds(
     mut commands: Commands,
@@ -472,23 +648,35 @@ pub fn play_weapon_sounds(
         ));
     }
     
-    // Play reload sound
-    if reload_state.reload_requested_this_frame {
+    // Play reload sound when the server actually starts the reload (magazine weapons)
+    if reload_state.started_this_frame && !reload_state.per_shell {
         commands.spawn((
             AudioPlayer::new(audio.gun_reload.clone()),
             PlaybackSettings::DESPAWN.with_volume(Volume::Linear(0.5)),
         ));
     }
+    
+    // Shell-by-shell: short, quick click per shell
+    if reload_state.shell_inserted_this_frame {
+        commands.spawn((
+            AudioPlayer::new(audio.gun_reload.clone()),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::Linear(0.3))
+                .with_speed(1.8),
+        ));
+    }
 }
 
 /// Update local tracers (simulating bullet flight for prediction)
 pub fn update_local_tracers(
     mut commands: Commands,
     mut tracers: Query<(Entity, &mut LocalTracer, &mut BulletVelocity, &mut Transform)>,
+    wind: Query<&Wind>,
     time: Res<Time>,
 ) {
     let dt = time.delta_secs();
     let current_time = time.elapsed_secs();
+    let wind = wind.iter().next().map(|w| w.velocity).unwrap_or(Vec3::ZERO);
     
     for (entity, tracer, mut velocity, mut transform) in tracers.iter_mut() {
         // Check lifetime
@@ -497,11 +685,13 @@ pub fn update_local_tracers(
             continue;
         }
         
-        // Step physics
-        let (new_pos, new_vel) = ballistics::step_bullet_physics(
+        // Step physics (same model as the server, including wind)
+        let (new_pos, new_vel) = ballistics::step_bullet_physics_in_wind(
             transform.translation,
             velocity.0,
             dt,
+            tracer.weapon_type.ballistic_coefficient(),
+            wind,
         );
         
         transform.translation = new_pos;
@@ -581,12 +771,14 @@ pub fn handle_bullet_impacts(
     terrain: Option<Res<WorldTerrain>>,
     debug_mode: Res<WeaponDebugMode>,
     mut debug_trails: ResMut<DebugBulletTrails>,
+    wind: Query<&Wind>,
 ) {
     let Some(weapon_visuals) = weapon_visuals else {
         return;
     };
 
     let now = time.elapsed_secs();
+    let wind = wind.iter().next().map(|w| w.velocity).unwrap_or(Vec3::ZERO);
 
     // Keep trails bounded even if debug is toggled on/off
     debug_trails
@@ -602,11 +794,12 @@ pub fn handle_bullet_impacts(
         let offset = if normal.length_squared() > 0.001 { normal * 0.03 } else { Vec3::Y * 0.03 };
 
         match impact.surface {
-            BulletImpactSurface::Terrain | BulletImpactSurface::PracticeWall => {
+            BulletImpactSurface::Terrain | BulletImpactSurface::PracticeWall | BulletImpactSurface::Vehicle => {
                 // Choose marker visuals by surface
                 let (base_color, radius) = match impact.surface {
                     BulletImpactSurface::Terrain => (Color::srgba(1.0, 0.5, 0.0, 0.85), 0.35),
                     BulletImpactSurface::PracticeWall => (Color::srgba(1.0, 0.0, 0.0, 0.9), 0.25),
+                    BulletImpactSurface::Vehicle => (Color::srgba(0.8, 0.8, 0.85, 0.9), 0.2),
                     _ => unreachable!(),
                 };
 
@@ -679,7 +872,13 @@ pub fn handle_bullet_impacts(
             points.push(pos);
 
             for _ in 0..steps {
-                let (new_pos, new_vel) = ballistics::step_bullet_physics(pos, vel, dt);
+                let (new_pos, new_vel) = ballistics::step_bullet_physics_in_wind(
+                    pos,
+                    vel,
+                    dt,
+                    impact.weapon_type.ballistic_coefficient(),
+                    wind,
+                );
                 pos = new_pos;
                 vel = new_vel;
                 points.push(pos);
@@ -1130,12 +1329,12 @@ pub fn handle_hit_confirms(
 
     for confirm in receiver.receive() {
         info!(
-            "Hit confirmed! Damage: {:.1}, Headshot: {}, Kill: {}",
-            confirm.damage, confirm.headshot, confirm.kill
+            "Hit confirmed! Damage: {:.1}, Armor absorbed: {:.1}, Headshot: {}, Kill: {}",
+            confirm.damage, confirm.armor_absorbed, confirm.headshot, confirm.kill
         );
         
         // Spawn hit marker
-        crosshair::spawn_hit_marker(&mut commands, &time, confirm.kill);
+        crosshair::spawn_hit_marker(&mut commands, &time, confirm.kill, confirm.armor_absorbed > 0.0);
     }
 }
 
@@ -0,0 +1,180 @@
+//! Battle-royale safe zone wall and timer
+//!
+//! The replicated `SafeZone` is drawn as a translucent open cylinder (a unit mesh
+//! scaled to the current circle every frame) and a HUD line under the match status
+//! shows the zone clock, plus a warning with the distance back in when the local
+//! player is outside.
+
+use bevy::prelude::*;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use shared::{LocalPlayer, Player, SafeZone};
+
+use crate::crosshair::Pickable;
+
+/// Segments around the wall
+const ZONE_WALL_SEGMENTS: u32 = 96;
+
+/// Wall extent (m): low enough to reach valley floors, high enough to see from hills
+const ZONE_WALL_BOTTOM: f32 = -100.0;
+const ZONE_WALL_HEIGHT: f32 = 500.0;
+
+/// The zone wall mesh
+#[derive(Component)]
+pub struct ZoneWall;
+
+/// Zone timer / warning text
+#[derive(Component)]
+pub struct ZoneHudText;
+
+/// Open unit cylinder (radius 1, y from 0 to 1), facing inwards and outwards
+fn zone_wall_mesh() -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    for i in 0..=ZONE_WALL_SEGMENTS {
+        let u = i as f32 / ZONE_WALL_SEGMENTS as f32;
+        let (sin, cos) = (u * std::f32::consts::TAU).sin_cos();
+        for y in [0.0, 1.0] {
+            positions.push([cos, y, sin]);
+            normals.push([cos, 0.0, sin]);
+            uvs.push([u, y]);
+        }
+    }
+    for i in 0..ZONE_WALL_SEGMENTS {
+        let base = i * 2;
+        indices.extend_from_slice(&[base, base + 1, base + 2, base + 1, base + 3, base + 2]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh
+}
+
+/// Spawn the wall when a zone appears, fit it to the circle and remove it with the zone
+pub fn sync_zone_wall(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    zones: Query<&SafeZone>,
+    mut walls: Query<(Entity, &mut Transform), With<ZoneWall>>,
+) {
+    let Some(zone) = zones.iter().next() else {
+        for (entity, _) in walls.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    let transform = Transform::from_xyz(zone.center.x, ZONE_WALL_BOTTOM, zone.center.y)
+        .with_scale(Vec3::new(zone.radius, ZONE_WALL_HEIGHT, zone.radius));
+
+    if walls.is_empty() {
+        commands.spawn((
+            ZoneWall,
+            Mesh3d(meshes.add(zone_wall_mesh())),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(0.2, 0.45, 1.0, 0.25),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            })),
+            transform,
+        ));
+        return;
+    }
+    for (_, mut wall_transform) in walls.iter_mut() {
+        *wall_transform = transform;
+    }
+}
+
+/// Spawn the (hidden) zone HUD line
+pub fn spawn_zone_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            ZoneHudText,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(44.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                Node {
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Zone clock, and a warning while the local player is outside
+pub fn update_zone_hud(
+    zones: Query<&SafeZone>,
+    local_player: Query<&Transform, (With<LocalPlayer>, With<Player>)>,
+    mut hud: Query<(&mut Visibility, &Children), With<ZoneHudText>>,
+    mut texts: Query<(&mut Text, &mut TextColor)>,
+) {
+    let Ok((mut visibility, children)) = hud.single_mut() else {
+        return;
+    };
+    let Some(zone) = zones.iter().next() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    let seconds = zone.time_remaining.ceil() as u32;
+    let mut line = if zone.is_final() {
+        "FINAL ZONE".to_string()
+    } else if zone.shrinking {
+        format!("ZONE SHRINKING  {:02}:{:02}", seconds / 60, seconds % 60)
+    } else {
+        format!("ZONE SHRINKS IN  {:02}:{:02}", seconds / 60, seconds % 60)
+    };
+
+    let outside = local_player.single().ok().filter(|t| !zone.contains(t.translation));
+    if let Some(transform) = outside {
+        let distance = Vec2::new(transform.translation.x, transform.translation.z).distance(zone.center) - zone.radius;
+        line.push_str(&format!(
+            "  |  OUTSIDE THE ZONE: {:.0} m  (-{:.0} HP/s)",
+            distance.max(0.0),
+            zone.damage_per_second()
+        ));
+    }
+    let color = if outside.is_some() { Color::srgb(1.0, 0.35, 0.3) } else { Color::srgb(0.6, 0.8, 1.0) };
+
+    for child in children.iter() {
+        if let Ok((mut text, mut text_color)) = texts.get_mut(child) {
+            if text.0 != line {
+                text.0 = line.clone();
+            }
+            text_color.0 = color;
+        }
+    }
+}
+
+/// Despawn the zone HUD and wall when leaving gameplay
+pub fn despawn_zone_hud(mut commands: Commands, roots: Query<Entity, Or<(With<ZoneHudText>, With<ZoneWall>)>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,187 @@
+//! Battle royale: the shrinking safe zone, match-start loot and last-team-standing
+//!
+//! `tick_match` starts a round through `start_round` (zone entity, scattered loot,
+//! players dropped empty-handed around the first circle). Every fixed tick
+//! `tick_safe_zone` advances the zone, damages players caught outside and, once only
+//! one team (or solo player) is left alive, flags the winner for `tick_match` to end
+//! the match with.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use shared::{
+    ground_clearance_center, is_walkable_zone_center, pick_spawn_point, zone_drop_points, ArmorType,
+    DamageCause, EquippedArmor, EquippedWeapon, ExplosiveType, GameModeKind, Health, HotbarSelection, Inventory,
+    ItemStack, ItemType, Player, PlayerPosition, SafeZone, Team, WeaponType, WorldTerrain, FIXED_TIMESTEP_HZ,
+    SPAWN_POSITION, WORLD_SEED, ZONE_DAMAGE_INTERVAL, ZONE_INITIAL_RADIUS, weapons::damage::HitZone,
+};
+
+use crate::combat_log::{CombatHit, CombatLog, CombatantRef};
+use crate::game_mode::GameMode;
+use crate::inventory::{spawn_ground_item, spawn_ground_item_from_stack};
+use crate::systems::RespawnTimer;
+
+/// Ground items scattered inside the first circle each round
+const LOOT_SPAWN_COUNT: usize = 150;
+
+/// Drop points around the first circle
+const DROP_POINT_COUNT: usize = 16;
+
+/// Weapons in the loot table (the rest of the rolls are ammo, armor and grenades)
+const LOOT_WEAPONS: [WeaponType; 5] =
+    [WeaponType::Pistol, WeaponType::SMG, WeaponType::Shotgun, WeaponType::AssaultRifle, WeaponType::Sniper];
+
+/// One side still in the fight: a team, or a player without one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Side {
+    Team(Team),
+    Solo(PeerId),
+}
+
+/// Per-round battle royale bookkeeping
+#[derive(Resource, Default)]
+pub struct BattleRoyale {
+    /// Seconds until the next zone damage tick
+    damage_timer: f32,
+    /// Sides alive when the round went live (`None` until the zone's first tick)
+    contenders: Option<usize>,
+    /// Set once the round is decided: `Some(winner)` (`Some(None)` when nobody survived)
+    pub outcome: Option<Option<Team>>,
+}
+
+/// Zone seed for a round (every round gets its own circles and loot)
+fn round_seed(round: u32) -> u64 {
+    ((WORLD_SEED as u64) << 32) | round as u64
+}
+
+/// Spawn the round's safe zone and loot, returning the zone for placing players
+pub fn start_round(commands: &mut Commands, terrain: &WorldTerrain, round: u32, br: &mut BattleRoyale) -> SafeZone {
+    *br = BattleRoyale::default();
+
+    let center = Vec2::new(SPAWN_POSITION[0], SPAWN_POSITION[2]);
+    let zone = SafeZone::new(round_seed(round), center, ZONE_INITIAL_RADIUS, |p| {
+        is_walkable_zone_center(terrain, p)
+    });
+    commands.spawn((zone.clone(), Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All))));
+
+    let spawned = spawn_loot(commands, terrain, &zone);
+    info!(
+        "Battle royale round {}: zone at ({:.0}, {:.0}) r={:.0}, {} loot items",
+        round, zone.center.x, zone.center.y, zone.radius, spawned
+    );
+    zone
+}
+
+/// Scatter loot on walkable ground inside the first circle
+fn spawn_loot(commands: &mut Commands, terrain: &WorldTerrain, zone: &SafeZone) -> usize {
+    let mut rng = StdRng::seed_from_u64(zone.seed);
+    let mut spawned = 0;
+    for _ in 0..LOOT_SPAWN_COUNT {
+        let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+        let distance = rng.gen::<f32>().sqrt() * zone.radius;
+        let point = zone.center + Vec2::from_angle(angle) * distance;
+        let roll = rng.gen::<f32>();
+        if !is_walkable_zone_center(terrain, point) {
+            continue;
+        }
+        let position = Vec3::new(point.x, terrain.get_height(point.x, point.y) + 0.3, point.y);
+
+        if roll < 0.35 {
+            let weapon = LOOT_WEAPONS[rng.gen_range(0..LOOT_WEAPONS.len())];
+            spawn_ground_item_from_stack(commands, &ItemStack::new_weapon_full_mag(weapon), position);
+        } else if roll < 0.7 {
+            let weapon = LOOT_WEAPONS[rng.gen_range(0..LOOT_WEAPONS.len())];
+            let ammo = weapon.ammo_type();
+            spawn_ground_item(commands, ammo, ammo.max_stack_size().min(60), position);
+        } else if roll < 0.85 {
+            let armor = if rng.gen_bool(0.5) { ArmorType::Vest } else { ArmorType::Helmet };
+            spawn_ground_item(commands, ItemType::Armor(armor), 1, position);
+        } else {
+            spawn_ground_item(commands, ItemType::Explosive(ExplosiveType::Grenade), 2, position);
+        }
+        spawned += 1;
+    }
+    spawned
+}
+
+/// Where to drop a player at round start: the ring point furthest from anyone already dropped
+pub fn drop_position(zone: &SafeZone, others: &[Vec2], terrain: &WorldTerrain) -> Vec3 {
+    let point = pick_spawn_point(&zone_drop_points(zone, DROP_POINT_COUNT), others).unwrap_or(zone.center);
+    Vec3::new(point.x, terrain.get_height(point.x, point.y) + ground_clearance_center(), point.y)
+}
+
+/// Battle royale players start with nothing but their fists
+pub fn give_empty_loadout(
+    inventory: &mut Inventory,
+    weapon: &mut EquippedWeapon,
+    armor: &mut EquippedArmor,
+    hotbar: &mut HotbarSelection,
+) {
+    *inventory = Inventory::new();
+    *weapon = EquippedWeapon::new(WeaponType::Unarmed);
+    *armor = EquippedArmor::default();
+    hotbar.index = 0;
+}
+
+/// Advance the zone, damage players outside it and detect the last side standing
+pub fn tick_safe_zone(
+    game_mode: Res<GameMode>,
+    terrain: Res<WorldTerrain>,
+    mut br: ResMut<BattleRoyale>,
+    mut combat_log: ResMut<CombatLog>,
+    mut zones: Query<&mut SafeZone>,
+    mut players: Query<(&Player, Option<&Team>, &mut Health, &PlayerPosition, Has<RespawnTimer>)>,
+) {
+    if game_mode.kind != GameModeKind::BattleRoyale || br.outcome.is_some() {
+        return;
+    }
+    let Ok(mut zone) = zones.single_mut() else {
+        return;
+    };
+
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+    zone.tick(dt, |p| is_walkable_zone_center(&terrain, p));
+
+    br.damage_timer -= dt;
+    if br.damage_timer <= 0.0 {
+        br.damage_timer += ZONE_DAMAGE_INTERVAL;
+        let damage = zone.damage_per_second() * ZONE_DAMAGE_INTERVAL;
+        for (player, _, mut health, position, respawning) in players.iter_mut() {
+            if respawning || health.is_dead() || zone.contains(position.0) {
+                continue;
+            }
+            let kill = health.take_damage(damage);
+            combat_log.record(CombatHit {
+                attacker: CombatantRef::Environment,
+                victim: CombatantRef::Player(player.client_id),
+                cause: DamageCause::Environment,
+                hit_zone: HitZone::Chest,
+                damage,
+                armor_absorbed: 0.0,
+                distance: 0.0,
+                kill,
+            });
+        }
+    }
+
+    // Last side standing (a round that started with a single side plays until the clock runs out)
+    let alive: HashSet<Side> = players
+        .iter()
+        .filter(|(_, _, health, _, respawning)| !health.is_dead() && !respawning)
+        .map(|(player, team, ..)| team.map(|t| Side::Team(*t)).unwrap_or(Side::Solo(player.client_id)))
+        .collect();
+    let contenders = *br.contenders.get_or_insert(alive.len());
+    if alive.is_empty() || (contenders >= 2 && alive.len() == 1) {
+        let winner = alive.iter().next().and_then(|side| match side {
+            Side::Team(team) => Some(*team),
+            Side::Solo(_) => None,
+        });
+        info!("Battle royale decided: {:?} left standing", winner.map(|t| t.name()));
+        br.outcome = Some(winner);
+    }
+}
@@ -10,8 +10,8 @@ use std::collections::HashMap;
 
 use shared::{
     BuildingType, PlaceBuildingRequest, PlacedBuilding, BuildingPosition,
-    Inventory, WorldTerrain, Player, ChunkCoord, TerrainDeltaChunk,
-    structures::{generate_medieval_town, MedievalTownBuilding, MEDIEVAL_SPACING},
+    Inventory, WorldTerrain, Player, ChunkCoord, TerrainDeltaChunk, Team, TeamOwner,
+    structures::{generate_medieval_town, MedievalTownBuilding, MEDIEVAL_TOWN_CENTER, MEDIEVAL_TOWN_RADIUS},
     terrain::WORLD_SEED,
 };
 
@@ -28,6 +28,35 @@ pub struct DeltaChunkEntities {
     pub map: HashMap<ChunkCoord, Entity>,
 }
 
+/// Spawn or update the replicated `TerrainDeltaChunk` entities for edited chunks
+pub fn upsert_delta_chunk_entities(
+    commands: &mut Commands,
+    terrain: &WorldTerrain,
+    delta_entities: &mut DeltaChunkEntities,
+    delta_query: &mut Query<&mut TerrainDeltaChunk>,
+    affected_chunks: &[ChunkCoord],
+) {
+    for coord in affected_chunks {
+        if let Some(delta_data) = terrain.get_delta_chunk(*coord) {
+            let chunk_component = TerrainDeltaChunk::from_delta_data(*coord, delta_data);
+            
+            if let Some(&existing_entity) = delta_entities.map.get(coord) {
+                // Update existing entity
+                if let Ok(mut existing) = delta_query.get_mut(existing_entity) {
+                    *existing = chunk_component;
+                }
+            } else {
+                // Spawn new entity
+                let entity = commands.spawn((
+                    chunk_component,
+                    Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+                )).id();
+                delta_entities.map.insert(*coord, entity);
+            }
+        }
+    }
+}
+
 /// Handle building placement requests from clients
 pub fn handle_place_building_requests(
     mut commands: Commands,
@@ -38,7 +67,7 @@ pub fn handle_place_building_requests(
         &RemoteId,
         &mut MessageReceiver<PlaceBuildingRequest>,
     ), With<ClientOf>>,
-    mut player_inventories: Query<(&Player, &mut Inventory)>,
+    mut player_inventories: Query<(&Player, &mut Inventory, Option<&Team>)>,
     mut delta_query: Query<&mut TerrainDeltaChunk>,
 ) {
     for (_client_entity, remote_id, mut receiver) in clients.iter_mut() {
@@ -50,11 +79,11 @@ pub fn handle_place_building_requests(
             
             // Find the player entity for this client
             let peer_id = remote_id.0;
-            let player_result = player_inventories.iter_mut().find(|(player, _)| {
+            let player_result = player_inventories.iter_mut().find(|(player, _, _)| {
                 player.client_id == peer_id
             });
             
-            let Some((_, mut inventory)) = player_result else {
+            let Some((_, mut inventory, team)) = player_result else {
                 warn!("Client has no player entity for building placement");
                 continue;
             };
@@ -106,25 +135,13 @@ pub fn handle_place_building_requests(
             );
             
             // Upsert TerrainDeltaChunk entities for affected chunks
-            for coord in &affected_chunks {
-                if let Some(delta_data) = terrain.get_delta_chunk(*coord) {
-                    let chunk_component = TerrainDeltaChunk::from_delta_data(*coord, delta_data);
-                    
-                    if let Some(&existing_entity) = delta_entities.map.get(coord) {
-                        // Update existing entity
-                        if let Ok(mut existing) = delta_query.get_mut(existing_entity) {
-                            *existing = chunk_component;
-                        }
-                    } else {
-                        // Spawn new entity
-                        let entity = commands.spawn((
-                            chunk_component,
-                            Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
-                        )).id();
-                        delta_entities.map.insert(*coord, entity);
-                    }
-                }
-            }
+            upsert_delta_chunk_entities(
+                &mut commands,
+                &terrain,
+                &mut delta_entities,
+                &mut delta_query,
+                &affected_chunks,
+            );
             
             // Spawn the building entity
             let building_entity = commands.spawn((
@@ -136,6 +153,11 @@ pub fn handle_place_building_requests(
                 Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
             )).id();
             
+            // Owned by the placer's team (chests inside are team-only)
+            if let Some(team) = team {
+                commands.entity(building_entity).insert(TeamOwner(*team));
+            }
+            
             info!(
                 "Spawned building entity {:?} at {:?}",
                 building_entity, building_pos
@@ -222,9 +244,8 @@ pub fn spawn_medieval_town(
     commands.insert_resource(MedievalTownSpawned);
 
     // Town center position - in grassland biome, away from desert and player spawn
-    // Located at (350, terrain_height, 350)
-    let town_center_x = 350.0;
-    let town_center_z = 350.0;
+    let town_center_x = MEDIEVAL_TOWN_CENTER.x;
+    let town_center_z = MEDIEVAL_TOWN_CENTER.y;
     let terrain_height = terrain.get_height(town_center_x, town_center_z);
     let town_center = Vec3::new(town_center_x, terrain_height, town_center_z);
 
@@ -232,7 +253,7 @@ pub fn spawn_medieval_town(
 
     // First, flatten the entire town area to create a smooth foundation
     // Town spans 3x3 blocks, so total radius is ~1.5 * SPACING from center
-    let town_radius = MEDIEVAL_SPACING * 1.7; // ~90m radius for the whole town
+    let town_radius = MEDIEVAL_TOWN_RADIUS; // ~90m radius for the whole town
     let town_half_extents = Vec2::new(town_radius, town_radius);
 
     // Apply a large-scale flatten to the whole town area
@@ -271,7 +292,7 @@ pub fn spawn_medieval_town(
     let mut total_chunks_affected = town_flatten_chunks.len();
 
     // Spawn each building with additional local terrain flattening
-    for MedievalTownBuilding { building_type, position, rotation } in buildings {
+    for &MedievalTownBuilding { building_type, position, rotation } in &buildings {
         let def = building_type.definition();
 
         // Get terrain height at building position (now on flattened ground)
@@ -323,5 +344,5 @@ pub fn spawn_medieval_town(
 
     // Spawn NPCs for the town
     let mut npc_id_start = 1000_u64; // Start IDs at 1000 to avoid conflicts with other NPCs
-    npc::spawn_medieval_town_npcs(&mut commands, &terrain, town_center, &mut npc_id_start);
+    npc::spawn_medieval_town_npcs(&mut commands, &terrain, town_center, &buildings, &mut npc_id_start);
 }
@@ -13,7 +13,7 @@ use shared::{
     vehicle_def, Vehicle, VehicleType,
     Health, InVehicle, Npc, NpcPosition, Player, PlayerPosition, PlayerVelocity, PropKind,
     VehicleState, WorldTerrain, ChunkCoord, PlacedBuilding, BuildingPosition,
-    DesertStructureKind, StructureCollider, generate_chunk_structures,
+    DesertStructureKind, StructureCollider, StructureSpawn, generate_chunk_structures,
     building::point_in_any_build_zone,
 };
 
@@ -490,6 +490,18 @@ pub fn resolve_npc_static_collisions(
     }
 }
 
+/// Push an NPC capsule at `pos` out of props and settlement structures, for systems that
+/// move NPCs before `resolve_npc_static_collisions` runs
+pub(crate) fn push_npc_out_of_static(
+    derived: &DerivedColliderLibrary,
+    colliders: &StaticColliders,
+    structure_colliders: &StructureColliders,
+    pos: &mut Vec3,
+) {
+    let _ = resolve_capsule_vs_static(derived, colliders, pos, None, NPC_RADIUS, NPC_HEIGHT, STEP_UP_HEIGHT);
+    let _ = resolve_capsule_vs_structures(structure_colliders, pos, None, NPC_RADIUS, NPC_HEIGHT, STEP_UP_HEIGHT);
+}
+
 /// Resolve vehicle collisions against static colliders (server-authoritative).
 pub fn resolve_vehicle_static_collisions(
     derived: Option<Res<DerivedColliderLibrary>>,
@@ -1655,6 +1667,16 @@ fn get_structure_bounding_radius(collider: &StructureCollider, scale: f32) -> f3
     }
 }
 
+/// Whether a sphere overlaps a settlement structure (used to bake NPC navigation)
+pub(crate) fn structure_blocks_sphere(spawn: &StructureSpawn, center: Vec3, radius: f32) -> bool {
+    let collider = spawn.kind.collider();
+    let reach = radius + get_structure_bounding_radius(&collider, spawn.scale);
+    if center.distance_squared(spawn.position) >= reach * reach {
+        return false;
+    }
+    sphere_vs_structure(center, radius, &collider, spawn.position, spawn.rotation, spawn.scale).is_some()
+}
+
 /// Test sphere against a structure collider
 fn sphere_vs_structure(
     sphere_center: Vec3,
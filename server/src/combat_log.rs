@@ -0,0 +1,173 @@
+//! Server-side kill feed and damage logs
+//!
+//! Hit handlers (bullets, melee, explosives) record every hit into `CombatLog`.
+//! Once per tick, after all damage has been applied, `broadcast_combat_log`
+//! resolves player names from `PlayerProfiles`, appends the hits to both players'
+//! damage logs, broadcasts a `KillFeedEntry` for each kill and sends dead players
+//! the log of the life they just lost.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    weapons::damage::HitZone, Combatant, DamageCause, DamageLog, DamageLogEntry, DamageLogReport, KillFeedEntry,
+    NpcArchetype, ReliableChannel,
+};
+
+use crate::persistence::PlayerProfiles;
+use crate::systems::peer_id_to_u64;
+
+/// One side of a recorded hit (names are resolved when the hit is broadcast)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CombatantRef {
+    Player(PeerId),
+    Npc { id: u64, archetype: NpcArchetype },
+    Environment,
+}
+
+/// A hit recorded by one of the damage systems this tick
+#[derive(Clone, Debug)]
+pub struct CombatHit {
+    pub attacker: CombatantRef,
+    pub victim: CombatantRef,
+    pub cause: DamageCause,
+    pub hit_zone: HitZone,
+    /// Damage after armor
+    pub damage: f32,
+    pub armor_absorbed: f32,
+    pub distance: f32,
+    pub kill: bool,
+}
+
+/// Pending hits plus every connected player's damage log for their current life
+#[derive(Resource, Default)]
+pub struct CombatLog {
+    pending: Vec<CombatHit>,
+    logs: HashMap<PeerId, DamageLog>,
+    /// (attacker, victim) of kills already broadcast, for match scoring
+    kills: Vec<(CombatantRef, CombatantRef)>,
+}
+
+impl CombatLog {
+    /// Record a hit (broadcast at the end of the tick)
+    pub fn record(&mut self, hit: CombatHit) {
+        self.pending.push(hit);
+    }
+
+    /// Take the kills broadcast since the last call
+    pub fn take_kills(&mut self) -> Vec<(CombatantRef, CombatantRef)> {
+        std::mem::take(&mut self.kills)
+    }
+}
+
+/// Attacker for a hit from a `u64` owner ID (bullets, explosives), if that player is still around
+pub fn player_ref(owner_id: u64, players: impl IntoIterator<Item = PeerId>) -> CombatantRef {
+    players
+        .into_iter()
+        .find(|peer_id| peer_id_to_u64(*peer_id) == owner_id)
+        .map(CombatantRef::Player)
+        .unwrap_or(CombatantRef::Environment)
+}
+
+fn resolve(combatant: CombatantRef, profiles: &PlayerProfiles) -> Combatant {
+    match combatant {
+        CombatantRef::Player(peer_id) => {
+            let name = profiles
+                .peer_to_name
+                .get(&peer_id)
+                .and_then(|lower| profiles.profiles.get(lower))
+                .map(|profile| profile.player_name.clone())
+                .unwrap_or_else(|| format!("Player {}", peer_id_to_u64(peer_id)));
+            Combatant::Player { id: peer_id_to_u64(peer_id), name }
+        }
+        CombatantRef::Npc { id, archetype } => Combatant::Npc { id, archetype },
+        CombatantRef::Environment => Combatant::Environment,
+    }
+}
+
+/// Broadcast this tick's kills and update / deliver damage logs
+pub fn broadcast_combat_log(
+    time: Res<Time>,
+    mut combat_log: ResMut<CombatLog>,
+    profiles: Res<PlayerProfiles>,
+    mut client_links: Query<
+        (&RemoteId, &mut MessageSender<KillFeedEntry>, &mut MessageSender<DamageLogReport>),
+        (With<ClientOf>, With<Connected>),
+    >,
+) {
+    if combat_log.pending.is_empty() {
+        return;
+    }
+    let now = time.elapsed_secs();
+    let pending = std::mem::take(&mut combat_log.pending);
+
+    for hit in pending {
+        let attacker = resolve(hit.attacker, &profiles);
+        let victim = resolve(hit.victim, &profiles);
+        let entry = |dealt: bool, other: &Combatant| DamageLogEntry {
+            time: now,
+            dealt,
+            other: other.clone(),
+            cause: hit.cause,
+            hit_zone: hit.hit_zone,
+            damage: hit.damage,
+            armor_absorbed: hit.armor_absorbed,
+            distance: hit.distance,
+            fatal: hit.kill,
+        };
+
+        if let CombatantRef::Player(attacker_id) = hit.attacker {
+            if hit.attacker != hit.victim {
+                combat_log.logs.entry(attacker_id).or_default().push(entry(true, &victim));
+            }
+        }
+        if let CombatantRef::Player(victim_id) = hit.victim {
+            combat_log.logs.entry(victim_id).or_default().push(entry(false, &attacker));
+        }
+
+        if !hit.kill {
+            continue;
+        }
+        combat_log.kills.push((hit.attacker, hit.victim));
+
+        info!(
+            "Kill feed: {} killed {} with {} ({:.0}m{})",
+            attacker.display_name(),
+            victim.display_name(),
+            hit.cause.label(),
+            hit.distance,
+            if hit.hit_zone == HitZone::Head { ", headshot" } else { "" }
+        );
+
+        let feed_entry = KillFeedEntry {
+            killer: attacker,
+            victim,
+            cause: hit.cause,
+            headshot: hit.hit_zone == HitZone::Head,
+            distance: hit.distance,
+        };
+        let report = match hit.victim {
+            CombatantRef::Player(victim_id) => combat_log
+                .logs
+                .get_mut(&victim_id)
+                .map(|log| (victim_id, DamageLogReport { entries: log.take() })),
+            _ => None,
+        };
+
+        for (remote_id, mut feed_sender, mut report_sender) in client_links.iter_mut() {
+            feed_sender.send::<ReliableChannel>(feed_entry.clone());
+            if let Some((victim_id, report)) = &report {
+                if remote_id.0 == *victim_id {
+                    report_sender.send::<ReliableChannel>(report.clone());
+                }
+            }
+        }
+    }
+
+    // Forget players who left
+    let connected: Vec<PeerId> = client_links.iter().map(|(remote_id, ..)| remote_id.0).collect();
+    combat_log.logs.retain(|peer_id, _| connected.contains(peer_id));
+}
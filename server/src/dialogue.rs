@@ -0,0 +1,323 @@
+//! Server-side NPC dialogue
+//!
+//! Dialogue trees are read from `shared/assets/dialogue/<kind>.ron` at startup; like
+//! behaviour trees, a missing or broken file falls back to the built-in copy. Each player
+//! has at most one conversation going: `Conversations` remembers the node it's at and the
+//! responses that were offered, so a `DialogueChoiceRequest` only picks from what the
+//! player saw. Conditions are checked again when a response is picked and its actions run
+//! all or nothing. While they talk the NPC stops and faces the player (the `Converse`
+//! behaviour leaf; anything that outranks it in the NPC's tree ends the conversation),
+//! and its voice lines go out to every player in earshot.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    apply_dialogue_actions, default_dialogue_source, dialogue_file_name, dialogue_kind, BehaviourLeaf,
+    DialogueChoiceRequest, DialogueFacts, DialogueKind, DialogueNode, DialogueTree, DialogueUpdate, DialogueView,
+    EndDialogueRequest, Health, Inventory, Npc, NpcActiveBehaviour, NpcIndoors, NpcPosition, NpcVoiceLine, Player,
+    PlayerPosition, QuestLog, ReliableChannel, StartDialogueRequest, TraderStock, WorldTime, DIALOGUE_RANGE,
+    DIALOGUE_VOICE_RANGE,
+};
+
+/// Directory the dialogue files are loaded from (relative to the repo root)
+const DIALOGUE_DIR: &str = "shared/assets/dialogue";
+
+/// A conversation ends once the player is this far from the NPC (m); a little over
+/// `DIALOGUE_RANGE` so a step back doesn't cut it off
+const DIALOGUE_LEAVE_RANGE: f32 = DIALOGUE_RANGE + 2.0;
+
+/// Dialogue tree per kind
+#[derive(Resource)]
+pub struct NpcDialogues {
+    trees: HashMap<DialogueKind, DialogueTree>,
+}
+
+impl Default for NpcDialogues {
+    fn default() -> Self {
+        let trees = DialogueKind::ALL.into_iter().map(|kind| (kind, load_tree(kind))).collect();
+        Self { trees }
+    }
+}
+
+impl NpcDialogues {
+    pub fn tree(&self, kind: DialogueKind) -> &DialogueTree {
+        &self.trees[&kind]
+    }
+}
+
+fn load_tree(kind: DialogueKind) -> DialogueTree {
+    let path = format!("{}/{}", DIALOGUE_DIR, dialogue_file_name(kind));
+    let loaded = match std::fs::read_to_string(&path) {
+        Ok(source) => DialogueTree::from_ron(&source)
+            .inspect_err(|e| warn!("Invalid dialogue tree {}: {}, using built-in", path, e))
+            .ok(),
+        Err(e) => {
+            warn!("Couldn't read dialogue tree {}: {}, using built-in", path, e);
+            None
+        }
+    };
+    loaded.unwrap_or_else(|| {
+        DialogueTree::from_ron(default_dialogue_source(kind)).expect("built-in dialogue trees are valid")
+    })
+}
+
+/// A conversation in progress
+pub struct Conversation {
+    pub player: Entity,
+    pub npc: Entity,
+    npc_id: u64,
+    kind: DialogueKind,
+    node: String,
+    /// Indices of the responses offered, in the order the player saw them
+    options: Vec<usize>,
+    /// The NPC's AI hasn't run since the conversation started (it can't be conversing yet)
+    awaiting_npc: bool,
+}
+
+/// Who is talking to whom
+#[derive(Resource, Default)]
+pub struct Conversations {
+    active: HashMap<PeerId, Conversation>,
+}
+
+impl Conversations {
+    /// Player an NPC is talking to, if any
+    pub fn partner(&self, npc: Entity) -> Option<Entity> {
+        self.active.values().find(|conversation| conversation.npc == npc).map(|conversation| conversation.player)
+    }
+}
+
+/// A request from one client, in the order they're handled
+enum DialogueInput {
+    Start(u64),
+    Choose(u8),
+    End,
+}
+
+/// What a node shows the player, and the responses that offers
+fn present(tree: &DialogueTree, node: &DialogueNode, facts: &DialogueFacts) -> (DialogueView, Vec<usize>) {
+    let options = node.available_responses(facts);
+    let view = DialogueView {
+        speaker: tree.speaker.clone(),
+        text: node.text.clone(),
+        responses: options.iter().map(|&index| node.responses[index].text.clone()).collect(),
+    };
+    (view, options)
+}
+
+fn closed(npc: u64, notices: Vec<String>, open_trade: bool) -> DialogueUpdate {
+    DialogueUpdate { npc, view: None, notices, open_trade }
+}
+
+/// Start, advance and end conversations, then send out the voice lines they triggered
+pub fn handle_dialogue_requests(
+    dialogues: Res<NpcDialogues>,
+    mut conversations: ResMut<Conversations>,
+    world_time: Query<&WorldTime>,
+    mut client_links: Query<
+        (
+            &RemoteId,
+            &mut MessageReceiver<StartDialogueRequest>,
+            &mut MessageReceiver<DialogueChoiceRequest>,
+            &mut MessageReceiver<EndDialogueRequest>,
+            &mut MessageSender<DialogueUpdate>,
+        ),
+        With<ClientOf>,
+    >,
+    mut voice_links: Query<(&RemoteId, &mut MessageSender<NpcVoiceLine>), With<ClientOf>>,
+    mut players: Query<(Entity, &Player, &PlayerPosition, &Health, &mut Inventory, &mut QuestLog)>,
+    npcs: Query<(Entity, &Npc, &NpcPosition, &Health, Has<TraderStock>), (Without<Player>, Without<NpcIndoors>)>,
+) {
+    let time_of_day = world_time.single().map_or(0.5, |time| time.normalized_time());
+    let active = &mut conversations.active;
+    // (npc id, where it stands, clip)
+    let mut voice_lines: Vec<(u64, Vec3, u8)> = Vec::new();
+
+    for (remote_id, mut starts, mut choices, mut ends, mut sender) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+        let inputs: Vec<DialogueInput> = starts
+            .receive()
+            .map(|request| DialogueInput::Start(request.npc))
+            .chain(choices.receive().map(|request| DialogueInput::Choose(request.option)))
+            .chain(ends.receive().map(|_| DialogueInput::End))
+            .collect();
+        if inputs.is_empty() {
+            continue;
+        }
+        let Some((player_entity, _, player_pos, player_health, mut inventory, mut quest_log)) =
+            players.iter_mut().find(|(_, p, ..)| p.client_id == peer_id)
+        else {
+            continue;
+        };
+
+        for input in inputs {
+            match input {
+                DialogueInput::Start(npc_id) => {
+                    if player_health.is_dead() {
+                        continue;
+                    }
+                    let npc = npcs.iter().find(|(_, npc, pos, health, _)| {
+                        npc.id == npc_id && !health.is_dead() && pos.0.distance(player_pos.0) <= DIALOGUE_RANGE
+                    });
+                    let Some((npc_entity, npc, npc_pos, _, trader)) = npc else {
+                        continue;
+                    };
+                    let Some(kind) = dialogue_kind(npc.archetype, trader) else {
+                        continue;
+                    };
+                    if active.iter().any(|(other, conversation)| *other != peer_id && conversation.npc == npc_entity) {
+                        let notice = "They're talking to someone else".to_string();
+                        sender.send::<ReliableChannel>(closed(npc_id, vec![notice], false));
+                        continue;
+                    }
+
+                    let tree = dialogues.tree(kind);
+                    let facts = DialogueFacts { inventory: &inventory, quests: &quest_log, time_of_day };
+                    let Some((node_id, node)) = tree.opening(&facts).and_then(|id| tree.node(id).map(|node| (id, node)))
+                    else {
+                        continue;
+                    };
+                    let (view, options) = present(tree, node, &facts);
+                    if let Some(clip) = node.voice {
+                        voice_lines.push((npc_id, npc_pos.0, clip));
+                    }
+                    debug!("Player {:?} started talking to NPC {} ({:?})", peer_id, npc_id, kind);
+                    active.insert(peer_id, Conversation {
+                        player: player_entity,
+                        npc: npc_entity,
+                        npc_id,
+                        kind,
+                        node: node_id.to_string(),
+                        options,
+                        awaiting_npc: true,
+                    });
+                    sender.send::<ReliableChannel>(DialogueUpdate {
+                        npc: npc_id,
+                        view: Some(view),
+                        notices: Vec::new(),
+                        open_trade: false,
+                    });
+                }
+                DialogueInput::Choose(option) => {
+                    let Some(conversation) = active.get_mut(&peer_id) else {
+                        continue;
+                    };
+                    let tree = dialogues.tree(conversation.kind);
+                    let Some(node) = tree.node(&conversation.node) else {
+                        continue;
+                    };
+                    let Some(response) =
+                        conversation.options.get(option as usize).and_then(|&index| node.responses.get(index))
+                    else {
+                        continue;
+                    };
+
+                    // Things may have changed since the responses were offered
+                    let facts = DialogueFacts { inventory: &inventory, quests: &quest_log, time_of_day };
+                    let result = if facts.all(&response.conditions) {
+                        apply_dialogue_actions(&response.actions, &mut inventory, &mut quest_log).map(Some)
+                    } else {
+                        Ok(None)
+                    };
+
+                    let (next, notices) = match result {
+                        Ok(Some(outcome)) => {
+                            for notice in &outcome.notices {
+                                info!("Player {:?} talking to NPC {}: {}", peer_id, conversation.npc_id, notice);
+                            }
+                            if outcome.open_trade || response.next.is_none() {
+                                let npc_id = conversation.npc_id;
+                                active.remove(&peer_id);
+                                sender.send::<ReliableChannel>(closed(npc_id, outcome.notices, outcome.open_trade));
+                                continue;
+                            }
+                            (response.next.clone(), outcome.notices)
+                        }
+                        // Show the node again with what's on offer now
+                        Ok(None) => (None, Vec::new()),
+                        Err(error) => (None, vec![error.message().to_string()]),
+                    };
+
+                    let node_id = next.unwrap_or_else(|| conversation.node.clone());
+                    let Some(node) = tree.node(&node_id) else {
+                        continue;
+                    };
+                    let facts = DialogueFacts { inventory: &inventory, quests: &quest_log, time_of_day };
+                    let (view, options) = present(tree, node, &facts);
+                    if node_id != conversation.node {
+                        if let (Some(clip), Ok((_, _, npc_pos, ..))) = (node.voice, npcs.get(conversation.npc)) {
+                            voice_lines.push((conversation.npc_id, npc_pos.0, clip));
+                        }
+                    }
+                    conversation.node = node_id;
+                    conversation.options = options;
+                    sender.send::<ReliableChannel>(DialogueUpdate {
+                        npc: conversation.npc_id,
+                        view: Some(view),
+                        notices,
+                        open_trade: false,
+                    });
+                }
+                DialogueInput::End => {
+                    if let Some(conversation) = active.remove(&peer_id) {
+                        debug!("Player {:?} stopped talking to NPC {}", peer_id, conversation.npc_id);
+                        sender.send::<ReliableChannel>(closed(conversation.npc_id, Vec::new(), false));
+                    }
+                }
+            }
+        }
+    }
+
+    if voice_lines.is_empty() {
+        return;
+    }
+    // Everyone in earshot hears the line, not just the player being talked to
+    for (remote_id, mut voice_sender) in voice_links.iter_mut() {
+        let Some(listener) = players.iter().find(|(_, p, ..)| p.client_id == remote_id.0).map(|(_, _, pos, ..)| pos.0)
+        else {
+            continue;
+        };
+        for &(npc, position, clip) in &voice_lines {
+            if position.distance(listener) <= DIALOGUE_VOICE_RANGE {
+                voice_sender.send::<ReliableChannel>(NpcVoiceLine { npc, clip });
+            }
+        }
+    }
+}
+
+/// End conversations once the player walks off, either side dies, the player leaves or
+/// something more pressing than talking (a fight, danger) takes over the NPC's behaviour
+pub fn end_stale_conversations(
+    mut conversations: ResMut<Conversations>,
+    mut client_links: Query<(&RemoteId, &mut MessageSender<DialogueUpdate>), With<ClientOf>>,
+    players: Query<(&PlayerPosition, &Health), With<Player>>,
+    npcs: Query<(&NpcPosition, &Health, &NpcActiveBehaviour), (With<Npc>, Without<NpcIndoors>)>,
+) {
+    let mut ended: Vec<(PeerId, u64)> = Vec::new();
+    conversations.active.retain(|&peer_id, conversation| {
+        let player = players.get(conversation.player).ok().filter(|(_, health)| !health.is_dead());
+        let npc = npcs.get(conversation.npc).ok().filter(|(_, health, _)| !health.is_dead());
+        let conversing = conversation.awaiting_npc
+            || npc.is_some_and(|(.., behaviour)| behaviour.0 == BehaviourLeaf::Converse.label());
+        conversation.awaiting_npc = false;
+        let keep = conversing && matches!(
+            (player, npc),
+            (Some((player_pos, _)), Some((npc_pos, ..))) if player_pos.0.distance(npc_pos.0) <= DIALOGUE_LEAVE_RANGE
+        );
+        if !keep {
+            debug!("Conversation between player {:?} and NPC {} ended", peer_id, conversation.npc_id);
+            ended.push((peer_id, conversation.npc_id));
+        }
+        keep
+    });
+
+    for (remote_id, mut sender) in client_links.iter_mut() {
+        for &(_, npc_id) in ended.iter().filter(|(peer_id, _)| *peer_id == remote_id.0) {
+            sender.send::<ReliableChannel>(closed(npc_id, Vec::new(), false));
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! Server-side explosives
+//!
+//! Grenades and charges come out of the inventory via `ThrowRequest`. Grenades are
+//! simulated as bouncing projectiles; charges stick to the terrain, prop or structure
+//! surface the player aims at. Both detonate when their fuse runs out. Detonations damage
+//! players, NPCs and vehicles in line of sight and dig craters that replicate through
+//! the same `TerrainDeltaChunk` entities as building flattening.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+use std::collections::HashMap;
+
+use shared::{
+    weapons::{
+        blast_falloff, damage::HitZone, step_throwable_physics, Explosion, Explosive, ThrowRequest, PLACE_RANGE,
+        THROWABLE_RADIUS,
+    },
+    apply_armored_hit, vehicle_def, DamageCause, DamageReceived, EquippedArmor, Health, HitConfirm, InVehicle, Inventory,
+    ItemType, Npc, NpcDamageEvent, NpcIndoors, NpcPosition, Player, PlayerPosition, PlayerVelocity, ReliableChannel,
+    TerrainDeltaChunk, Team, Vehicle, VehicleState, WorldTerrain, are_allies, FIXED_TIMESTEP_HZ, PLAYER_HEIGHT,
+};
+
+use crate::building::{upsert_delta_chunk_entities, DeltaChunkEntities};
+use crate::colliders::{DerivedColliderLibrary, StaticColliders, StructureColliders};
+use crate::combat_log::{player_ref, CombatHit, CombatLog, CombatantRef};
+use crate::systems::peer_id_to_u64;
+use crate::teams::{FriendlyFire, ReflectedDamage, ReflectedHit};
+use crate::weapons::{segment_props_intersection, segment_structures_intersection, segment_terrain_intersection};
+
+/// Minimum time between throws for one player (seconds)
+const THROW_COOLDOWN: f32 = 1.0;
+
+/// Throw origin height above the capsule center (matches the first-person camera)
+const THROW_EYE_HEIGHT: f32 = PLAYER_HEIGHT * 0.4;
+
+/// Other explosives within this fraction of a blast radius are set off too
+const CHAIN_REACTION_FRACTION: f32 = 0.5;
+
+/// Delay before a chain-reacting explosive goes off (seconds)
+const CHAIN_REACTION_DELAY: f32 = 0.15;
+
+/// Server-only motion state of a live explosive
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ExplosiveMotion {
+    pub velocity: Vec3,
+    /// Settled on the ground (no more physics)
+    pub resting: bool,
+}
+
+/// Nearest terrain, prop or structure surface along the aim within `PLACE_RANGE`,
+/// as (point, normal)
+fn placement_surface(
+    eye: Vec3,
+    direction: Vec3,
+    terrain: &WorldTerrain,
+    static_colliders: &StaticColliders,
+    derived_colliders: Option<&DerivedColliderLibrary>,
+    structure_colliders: &StructureColliders,
+) -> Option<(Vec3, Vec3)> {
+    let end = eye + direction * PLACE_RANGE;
+    [
+        segment_terrain_intersection(terrain, eye, end),
+        derived_colliders.and_then(|derived| segment_props_intersection(eye, end, static_colliders, derived)),
+        segment_structures_intersection(eye, end, structure_colliders),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by(|a, b| a.0.total_cmp(&b.0))
+    .map(|(_, point, normal)| (point, normal))
+}
+
+/// Handle throw requests: take one explosive from the inventory and launch it, or
+/// place it on the surface the player aims at
+pub fn handle_throw_requests(
+    mut commands: Commands,
+    time: Res<Time>,
+    terrain: Res<WorldTerrain>,
+    static_colliders: Res<StaticColliders>,
+    derived_colliders: Option<Res<DerivedColliderLibrary>>,
+    structure_colliders: Res<StructureColliders>,
+    mut client_links: Query<(&RemoteId, &mut MessageReceiver<ThrowRequest>), With<ClientOf>>,
+    mut players: Query<(&Player, &PlayerPosition, &PlayerVelocity, &mut Inventory, &Health, Has<InVehicle>)>,
+    mut last_throw: Local<HashMap<PeerId, f32>>,
+) {
+    let now = time.elapsed_secs();
+
+    for (remote_id, mut receiver) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+
+        for request in receiver.receive() {
+            let Some((player, position, velocity, mut inventory, health, in_vehicle)) =
+                players.iter_mut().find(|(p, ..)| p.client_id == peer_id)
+            else {
+                continue;
+            };
+
+            if health.is_dead() || in_vehicle {
+                continue;
+            }
+            if last_throw.get(&peer_id).is_some_and(|t| now - t < THROW_COOLDOWN) {
+                continue;
+            }
+
+            let stats = request.explosive_type.stats();
+            let direction = request.direction.normalize_or(Vec3::NEG_Z);
+            let eye = position.0 + Vec3::Y * THROW_EYE_HEIGHT;
+            let (spawn_pos, motion) = if request.explosive_type.is_placed() {
+                // Nothing within reach to stick it to: keep it in the inventory
+                let Some((point, normal)) = placement_surface(
+                    eye,
+                    direction,
+                    &terrain,
+                    &static_colliders,
+                    derived_colliders.as_deref(),
+                    &structure_colliders,
+                ) else {
+                    continue;
+                };
+                (point + normal * THROWABLE_RADIUS, ExplosiveMotion { velocity: Vec3::ZERO, resting: true })
+            } else {
+                // Throws get a slight upward arc and inherit the player's movement
+                let launch_dir = (direction + Vec3::Y * 0.15).normalize();
+                let launch_velocity = launch_dir * stats.throw_speed + velocity.0;
+                (eye + direction * 0.6, ExplosiveMotion { velocity: launch_velocity, resting: false })
+            };
+
+            if inventory.remove_item(ItemType::Explosive(request.explosive_type), 1) == 0 {
+                continue;
+            }
+            last_throw.insert(peer_id, now);
+
+            commands.spawn((
+                Explosive {
+                    explosive_type: request.explosive_type,
+                    owner_id: peer_id_to_u64(player.client_id),
+                    detonate_at: now + stats.fuse,
+                },
+                motion,
+                PlayerPosition(spawn_pos),
+                Transform::from_translation(spawn_pos),
+                Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+            ));
+
+            let verb = if request.explosive_type.is_placed() { "placed" } else { "threw" };
+            info!("Player {:?} {} {:?}", peer_id, verb, request.explosive_type);
+        }
+    }
+}
+
+/// Step explosive physics (bouncing off terrain until they come to rest)
+pub fn simulate_explosives(
+    terrain: Res<WorldTerrain>,
+    mut explosives: Query<(&Explosive, &mut ExplosiveMotion, &mut Transform, &mut PlayerPosition)>,
+) {
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+
+    for (explosive, mut motion, mut transform, mut position) in explosives.iter_mut() {
+        if motion.resting {
+            continue;
+        }
+
+        let stats = explosive.explosive_type.stats();
+        let step = step_throwable_physics(transform.translation, motion.velocity, dt, &stats, &terrain);
+
+        transform.translation = step.position;
+        position.0 = step.position;
+        motion.velocity = step.velocity;
+        motion.resting = step.resting;
+    }
+}
+
+/// Whether a blast at `from` reaches `to` without terrain, props or structures in between
+fn blast_reaches(
+    from: Vec3,
+    to: Vec3,
+    terrain: &WorldTerrain,
+    static_colliders: &StaticColliders,
+    derived_colliders: Option<&DerivedColliderLibrary>,
+    structure_colliders: &StructureColliders,
+) -> bool {
+    // Ignore hits right at the target (e.g. the ground a player stands on)
+    const END_TOLERANCE: f32 = 0.95;
+
+    let blocked = |hit: Option<(f32, Vec3, Vec3)>| hit.is_some_and(|(t, _, _)| t < END_TOLERANCE);
+    if blocked(segment_terrain_intersection(terrain, from, to)) {
+        return false;
+    }
+    if let Some(derived) = derived_colliders {
+        if blocked(segment_props_intersection(from, to, static_colliders, derived)) {
+            return false;
+        }
+    }
+    !blocked(segment_structures_intersection(from, to, structure_colliders))
+}
+
+/// Detonate explosives whose fuse has run out
+pub fn detonate_explosives(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut terrain: ResMut<WorldTerrain>,
+    mut delta_entities: ResMut<DeltaChunkEntities>,
+    mut delta_query: Query<&mut TerrainDeltaChunk>,
+    derived_colliders: Option<Res<DerivedColliderLibrary>>,
+    static_colliders: Res<StaticColliders>,
+    structure_colliders: Res<StructureColliders>,
+    mut explosives: Query<(Entity, &mut Explosive, &Transform)>,
+    mut players: Query<
+        (Entity, &Player, &PlayerPosition, &mut Health, Option<&mut EquippedArmor>, Option<&Team>),
+        Without<Npc>,
+    >,
+    mut npcs: Query<(Entity, &Npc, &NpcPosition, &mut Health), (Without<Player>, Without<NpcIndoors>)>,
+    mut vehicles: Query<(&Vehicle, &mut VehicleState)>,
+    mut client_links: Query<
+        (
+            &RemoteId,
+            &mut MessageSender<HitConfirm>,
+            &mut MessageSender<DamageReceived>,
+            &mut MessageSender<Explosion>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    mut combat_log: ResMut<CombatLog>,
+    friendly_fire: Res<FriendlyFire>,
+    mut reflected: ResMut<ReflectedDamage>,
+) {
+    let now = time.elapsed_secs();
+
+    let due: Vec<(Entity, Explosive, Vec3)> = explosives
+        .iter()
+        .filter(|(_, explosive, _)| now >= explosive.detonate_at)
+        .map(|(entity, explosive, transform)| (entity, explosive.clone(), transform.translation))
+        .collect();
+
+    for (entity, explosive, blast_pos) in due {
+        commands.entity(entity).despawn();
+        let stats = explosive.explosive_type.stats();
+        let thrower = player_ref(explosive.owner_id, players.iter().map(|(_, player, ..)| player.client_id));
+        let thrower_player = players
+            .iter()
+            .find(|(_, player, ..)| peer_id_to_u64(player.client_id) == explosive.owner_id);
+        let thrower_entity = thrower_player.map(|(entity, ..)| entity);
+        let thrower_team = thrower_player.and_then(|(.., team)| team.copied());
+        // Trace from slightly above the charge so the ground it sits on doesn't block
+        let blast_origin = blast_pos + Vec3::Y * 0.3;
+        let reaches = |to: Vec3| {
+            blast_reaches(
+                blast_origin,
+                to,
+                &terrain,
+                &static_colliders,
+                derived_colliders.as_deref(),
+                &structure_colliders,
+            )
+        };
+
+        // Set off nearby explosives
+        for (other, mut other_explosive, transform) in explosives.iter_mut() {
+            if other != entity
+                && now < other_explosive.detonate_at
+                && transform.translation.distance(blast_pos) < stats.radius * CHAIN_REACTION_FRACTION
+            {
+                other_explosive.detonate_at = other_explosive.detonate_at.min(now + CHAIN_REACTION_DELAY);
+            }
+        }
+
+        // Players (the thrower included)
+        for (_, victim, position, mut health, mut armor, team) in players.iter_mut() {
+            if health.is_dead() {
+                continue;
+            }
+            let distance = position.0.distance(blast_pos);
+            let falloff = blast_falloff(distance, stats.radius);
+            if falloff <= 0.0 {
+                continue;
+            }
+            let head = position.0 + Vec3::Y * THROW_EYE_HEIGHT;
+            if !reaches(position.0) && !reaches(head) {
+                continue;
+            }
+
+            // Friendly fire (the thrower's own blast damage is never scaled)
+            let victim_id = victim.client_id;
+            let friendly = matches!(thrower, CombatantRef::Player(id) if id != victim_id)
+                && are_allies(thrower_team, team.copied());
+            let (victim_mult, reflected_mult) = friendly_fire.policy.multipliers(friendly);
+            if let CombatantRef::Player(attacker) = thrower {
+                if reflected_mult > 0.0 {
+                    reflected.push(ReflectedHit {
+                        attacker,
+                        damage: stats.damage * falloff * reflected_mult,
+                        cause: DamageCause::Explosive(explosive.explosive_type),
+                        hit_zone: HitZone::Chest,
+                        distance,
+                    });
+                }
+            }
+            if victim_mult <= 0.0 {
+                continue;
+            }
+
+            let blast = stats.blast_weapon_stats(stats.damage * falloff);
+            let armored = apply_armored_hit(armor.as_deref_mut(), &blast, 0.0, HitZone::Chest, victim_mult);
+            let is_kill = health.take_damage(armored.damage);
+
+            combat_log.record(CombatHit {
+                attacker: thrower,
+                victim: CombatantRef::Player(victim_id),
+                cause: DamageCause::Explosive(explosive.explosive_type),
+                hit_zone: HitZone::Chest,
+                damage: armored.damage,
+                armor_absorbed: armored.absorbed,
+                distance,
+                kill: is_kill,
+            });
+
+            info!(
+                "{:?} from {} hit {:?} at {:.1}m for {:.1} damage ({:.1} absorbed, kill: {})",
+                explosive.explosive_type, explosive.owner_id, victim_id, distance, armored.damage, armored.absorbed, is_kill
+            );
+
+            let direction = Vec3::new(blast_pos.x - position.0.x, 0.0, blast_pos.z - position.0.z).normalize_or_zero();
+            for (remote_id, mut hit_sender, mut dmg_sender, _explosion_sender) in client_links.iter_mut() {
+                if remote_id.0 == victim_id {
+                    dmg_sender.send::<ReliableChannel>(DamageReceived {
+                        direction,
+                        damage: armored.damage,
+                        health_remaining: health.current,
+                    });
+                } else if peer_id_to_u64(remote_id.0) == explosive.owner_id {
+                    hit_sender.send::<ReliableChannel>(HitConfirm {
+                        target_id: peer_id_to_u64(victim_id),
+                        damage: armored.damage,
+                        headshot: false,
+                        kill: is_kill,
+                        hit_zone: HitZone::Chest,
+                        armor_absorbed: armored.absorbed,
+                    });
+                }
+            }
+        }
+
+        // NPCs
+        for (npc_entity, npc, position, mut health) in npcs.iter_mut() {
+            if health.is_dead() {
+                continue;
+            }
+            let distance = position.0.distance(blast_pos);
+            let falloff = blast_falloff(distance, stats.radius);
+            if falloff <= 0.0 || !reaches(position.0) {
+                continue;
+            }
+
+            let damage_amount = stats.damage * falloff;
+            let is_kill = health.take_damage(damage_amount);
+
+            combat_log.record(CombatHit {
+                attacker: thrower,
+                victim: CombatantRef::Npc { id: npc.id, archetype: npc.archetype },
+                cause: DamageCause::Explosive(explosive.explosive_type),
+                hit_zone: HitZone::Chest,
+                damage: damage_amount,
+                armor_absorbed: 0.0,
+                distance,
+                kill: is_kill,
+            });
+            commands.entity(npc_entity).insert(NpcDamageEvent {
+                damage_source_position: blast_pos,
+                damage_amount,
+                attacker: thrower_entity,
+            });
+
+            for (remote_id, mut hit_sender, _dmg_sender, _explosion_sender) in client_links.iter_mut() {
+                if peer_id_to_u64(remote_id.0) == explosive.owner_id {
+                    hit_sender.send::<ReliableChannel>(HitConfirm {
+                        target_id: npc.id,
+                        damage: damage_amount,
+                        headshot: false,
+                        kill: is_kill,
+                        hit_zone: HitZone::Chest,
+                        armor_absorbed: 0.0,
+                    });
+                }
+            }
+        }
+
+        // Vehicles have no health; the blast shoves them away
+        for (vehicle, mut state) in vehicles.iter_mut() {
+            let half_length = vehicle_def(vehicle.vehicle_type).size.x * 0.5;
+            let distance = (state.position.distance(blast_pos) - half_length).max(0.0);
+            let falloff = blast_falloff(distance, stats.radius);
+            if falloff <= 0.0 || !reaches(state.position) {
+                continue;
+            }
+            let away = (state.position - blast_pos).normalize_or(Vec3::Y);
+            let push = (away + Vec3::Y * 0.5).normalize();
+            state.velocity += push * stats.vehicle_impulse * falloff;
+        }
+
+        // Crater (only when the blast is at ground level)
+        let ground = terrain.get_height(blast_pos.x, blast_pos.z);
+        if blast_pos.y - ground < stats.crater_radius * 0.5 {
+            let affected = terrain.apply_crater(blast_pos, stats.crater_radius, stats.crater_depth);
+            upsert_delta_chunk_entities(&mut commands, &terrain, &mut delta_entities, &mut delta_query, &affected);
+        }
+
+        let explosion = Explosion {
+            explosive_type: explosive.explosive_type,
+            position: blast_pos,
+            radius: stats.radius,
+        };
+        for (_remote_id, _hit_sender, _dmg_sender, mut explosion_sender) in client_links.iter_mut() {
+            explosion_sender.send::<ReliableChannel>(explosion.clone());
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Server-side game modes and match lifecycle
+//!
+//! The mode comes from the `GAME_MODE` env var (`sandbox`, `tdm`, `br` or
+//! `territory`, which runs without matches; see `territory`). For match modes a
+//! replicated `MatchState` entity is spawned once the server starts and advanced
+//! every fixed tick next to the death / respawn systems. Kills broadcast by the
+//! combat log score for the killer's team while the match is live. A new round
+//! resets every player to the starting loadout at their team's spawn and clears
+//! dropped items and live explosives; buildings, terrain damage and chest contents
+//! carry over between rounds. The end of a match sends everyone the scoreboard.
+//! Battle royale rounds instead drop players empty-handed into a fresh safe zone
+//! (see `battle_royale`) and end early once one team is left standing. Player
+//! profiles aren't saved in match modes, so match loadouts never replace the
+//! sandbox one.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    are_allies, ground_clearance_center, pick_spawn_point, sort_scoreboard, team_spawn_points, EquippedArmor,
+    EquippedWeapon, Explosive, GameModeKind, GroundItem, Health, HotbarSelection, Inventory, MatchPhase, MatchRules,
+    MatchScoreboard, MatchState, Player, PlayerName, PlayerPosition, PlayerVelocity, ReliableChannel, SafeZone,
+    ScoreboardRow, Team, WeaponType, WorldTerrain, FIXED_TIMESTEP_HZ,
+};
+
+use crate::battle_royale::{self, BattleRoyale};
+use crate::combat_log::{CombatLog, CombatantRef};
+use crate::systems::RespawnTimer;
+
+/// Env var selecting the game mode
+const GAME_MODE_ENV: &str = "GAME_MODE";
+
+/// Env vars overriding the score / time limit
+const SCORE_LIMIT_ENV: &str = "MATCH_SCORE_LIMIT";
+const TIME_LIMIT_ENV: &str = "MATCH_TIME_LIMIT";
+
+/// Server game mode and its rules
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct GameMode {
+    pub kind: GameModeKind,
+    pub rules: MatchRules,
+}
+
+impl GameMode {
+    /// Whether this mode runs matches (sandbox and territory don't)
+    pub fn has_matches(&self) -> bool {
+        matches!(self.kind, GameModeKind::TeamDeathmatch | GameModeKind::BattleRoyale)
+    }
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        let kind = match std::env::var(GAME_MODE_ENV) {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                warn!("{}, falling back to sandbox", e);
+                GameModeKind::Sandbox
+            }),
+            Err(_) => GameModeKind::Sandbox,
+        };
+
+        let mut rules = MatchRules::for_mode(kind);
+        if let Some(limit) = std::env::var(SCORE_LIMIT_ENV).ok().and_then(|v| v.parse().ok()) {
+            rules.score_limit = limit;
+        }
+        if let Some(limit) = std::env::var(TIME_LIMIT_ENV).ok().and_then(|v| v.parse().ok()) {
+            rules.time_limit = limit;
+        }
+
+        info!("Game mode: {} ({:?})", kind.label(), rules);
+        Self { kind, rules }
+    }
+}
+
+/// Kills / deaths this match
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlayerMatchStats {
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+/// Per-player stats for the current match
+#[derive(Resource, Default)]
+pub struct MatchStats {
+    pub players: HashMap<PeerId, PlayerMatchStats>,
+}
+
+/// Marker: the match entity has been spawned
+#[derive(Resource)]
+pub struct MatchSpawned;
+
+/// Spawn the replicated match entity once the server is up (match modes only)
+pub fn spawn_match_once(mut commands: Commands, game_mode: Res<GameMode>, spawned: Option<Res<MatchSpawned>>) {
+    if spawned.is_some() || !game_mode.has_matches() {
+        return;
+    }
+    commands.insert_resource(MatchSpawned);
+
+    commands.spawn((
+        MatchState::new(game_mode.kind, &game_mode.rules),
+        Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+    ));
+    info!("Spawned {} match (warmup)", game_mode.kind.label());
+}
+
+/// Where a player on `team` should (re)spawn: their team's spawn point furthest from any enemy
+pub fn team_spawn_position(team: Team, enemies: &[Vec2], terrain: &WorldTerrain) -> Vec3 {
+    let point = pick_spawn_point(&team_spawn_points(team), enemies).unwrap_or(Vec2::ZERO);
+    let ground_y = terrain.get_height(point.x, point.y);
+    Vec3::new(point.x, ground_y + ground_clearance_center(), point.y)
+}
+
+/// Positions (XZ) of living players not on `team`
+pub fn enemy_positions(team: Option<Team>, players: impl IntoIterator<Item = (Option<Team>, Vec3, bool)>) -> Vec<Vec2> {
+    players
+        .into_iter()
+        .filter(|(other, _, alive)| *alive && !are_allies(team, *other))
+        .map(|(_, position, _)| Vec2::new(position.x, position.z))
+        .collect()
+}
+
+/// Reset a player's gear to the starting loadout
+pub fn give_starting_loadout(
+    inventory: &mut Inventory,
+    weapon: &mut EquippedWeapon,
+    armor: &mut EquippedArmor,
+    hotbar: &mut HotbarSelection,
+) {
+    *inventory = Inventory::with_starting_items();
+    *weapon = EquippedWeapon::new(WeaponType::AssaultRifle);
+    *armor = EquippedArmor::default();
+    hotbar.index = 0;
+}
+
+/// Score kills, advance the match clock and run phase changes
+pub fn tick_match(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    terrain: Res<WorldTerrain>,
+    mut combat_log: ResMut<CombatLog>,
+    mut stats: ResMut<MatchStats>,
+    mut br: ResMut<BattleRoyale>,
+    mut matches: Query<&mut MatchState>,
+    mut players: Query<(
+        Entity,
+        &Player,
+        Option<&Team>,
+        Option<&PlayerName>,
+        &mut Health,
+        &mut PlayerPosition,
+        &mut PlayerVelocity,
+        &mut Inventory,
+        &mut EquippedWeapon,
+        (&mut EquippedArmor, &mut HotbarSelection),
+    )>,
+    world_items: Query<Entity, Or<(With<GroundItem>, With<Explosive>)>>,
+    zones: Query<Entity, With<SafeZone>>,
+    mut client_links: Query<&mut MessageSender<MatchScoreboard>, (With<ClientOf>, With<Connected>)>,
+) {
+    // Always drain so kills don't pile up in sandbox mode
+    let kills = combat_log.take_kills();
+    let Ok(mut state) = matches.single_mut() else {
+        return;
+    };
+
+    // Scoring: enemy player kills only (no points for suicides, team kills or NPCs)
+    let teams: HashMap<PeerId, Option<Team>> =
+        players.iter().map(|(_, player, team, ..)| (player.client_id, team.copied())).collect();
+    for (attacker, victim) in kills {
+        if !state.is_live() {
+            break;
+        }
+        if let CombatantRef::Player(victim_id) = victim {
+            stats.players.entry(victim_id).or_default().deaths += 1;
+        }
+        let (CombatantRef::Player(attacker_id), CombatantRef::Player(victim_id)) = (attacker, victim) else {
+            continue;
+        };
+        let attacker_team = teams.get(&attacker_id).copied().flatten();
+        let victim_team = teams.get(&victim_id).copied().flatten();
+        if attacker_id == victim_id || are_allies(attacker_team, victim_team) {
+            continue;
+        }
+        if let Some(team) = attacker_team {
+            if state.record_kill(team) {
+                stats.players.entry(attacker_id).or_default().kills += 1;
+            }
+        }
+    }
+
+    // Warmup waits for at least one player
+    if state.phase == MatchPhase::Warmup && players.is_empty() {
+        return;
+    }
+
+    // A decided battle royale round ends right away, won by the last team standing
+    let outcome = br.outcome.filter(|_| state.is_live());
+    let phase = if outcome.is_some() {
+        state.end(&game_mode.rules);
+        Some(MatchPhase::PostMatch)
+    } else {
+        let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+        state.tick(dt, &game_mode.rules)
+    };
+    let Some(phase) = phase else {
+        return;
+    };
+    info!(
+        "{} round {}: {} (scores {:?})",
+        game_mode.kind.label(),
+        state.round,
+        phase.label(),
+        state.scores
+    );
+
+    match phase {
+        MatchPhase::Warmup | MatchPhase::Live => {
+            // Fresh round: clear the world, full health and starting gear at each team's spawn
+            for entity in world_items.iter().chain(zones.iter()) {
+                commands.entity(entity).despawn();
+            }
+            stats.players.clear();
+            let zone = (phase == MatchPhase::Live && game_mode.kind == GameModeKind::BattleRoyale)
+                .then(|| battle_royale::start_round(&mut commands, &terrain, state.round, &mut br));
+
+            let mut placed: Vec<(Option<Team>, Vec3, bool)> = Vec::new();
+            for (entity, player, team, _, mut health, mut position, mut velocity, mut inventory, mut weapon, (mut armor, mut hotbar)) in
+                players.iter_mut()
+            {
+                let team = team.copied();
+                match (&zone, team) {
+                    (Some(zone), _) => {
+                        let others: Vec<Vec2> = placed.iter().map(|(_, p, _)| Vec2::new(p.x, p.z)).collect();
+                        position.0 = battle_royale::drop_position(zone, &others, &terrain);
+                        battle_royale::give_empty_loadout(&mut inventory, &mut weapon, &mut armor, &mut hotbar);
+                    }
+                    (None, Some(team)) => {
+                        position.0 =
+                            team_spawn_position(team, &enemy_positions(Some(team), placed.iter().copied()), &terrain);
+                        give_starting_loadout(&mut inventory, &mut weapon, &mut armor, &mut hotbar);
+                    }
+                    (None, None) => give_starting_loadout(&mut inventory, &mut weapon, &mut armor, &mut hotbar),
+                }
+                velocity.0 = Vec3::ZERO;
+                health.current = health.max;
+                commands.entity(entity).remove::<RespawnTimer>();
+                placed.push((team, position.0, true));
+                info!("Reset player {:?} for the new round", player.client_id);
+            }
+        }
+        MatchPhase::PostMatch => {
+            // The zone goes away with the match (the next warmup clears the loot)
+            for entity in zones.iter() {
+                commands.entity(entity).despawn();
+            }
+            let mut rows: Vec<ScoreboardRow> = players
+                .iter()
+                .map(|(_, player, team, name, ..)| {
+                    let player_stats = stats.players.get(&player.client_id).copied().unwrap_or_default();
+                    ScoreboardRow {
+                        name: name.map(|n| n.0.clone()).unwrap_or_else(|| format!("{:?}", player.client_id)),
+                        team: team.copied(),
+                        kills: player_stats.kills,
+                        deaths: player_stats.deaths,
+                    }
+                })
+                .collect();
+            sort_scoreboard(&mut rows);
+
+            let scoreboard = MatchScoreboard {
+                round: state.round,
+                winner: outcome.unwrap_or_else(|| state.leader()),
+                scores: state.scores,
+                rows,
+            };
+            info!("Match over, winner: {:?}", scoreboard.winner.map(|t| t.name()));
+            for mut sender in client_links.iter_mut() {
+                sender.send::<ReliableChannel>(scoreboard.clone());
+            }
+        }
+        MatchPhase::Overtime => {}
+    }
+}
@@ -1,6 +1,6 @@
 //! Server-side inventory management
 //!
-//! Handles item pickups, drops, and death drops.
+//! Handles item pickups, drops, death drops and NPC corpse loot.
 
 use bevy::prelude::*;
 use lightyear::prelude::*;
@@ -12,9 +12,12 @@ use shared::{
     INVENTORY_SLOTS, HOTBAR_SLOTS, CHEST_SLOTS, PICKUP_RANGE, CHEST_RANGE,
     Player, PlayerPosition, Health,
     WorldTerrain,
-    EquippedWeapon, WeaponType,
+    EquippedWeapon, WeaponType, ExplosiveType,
     ChestStorage, ChestPosition,
     OpenChestRequest, CloseChestRequest, ChestTransferRequest,
+    ArmorPiece, ArmorType, EquippedArmor, EquipArmorRequest, UnequipArmorRequest,
+    BuildingPosition, PlacedBuilding, Team, TeamOwner, can_access,
+    Npc, NpcPosition, LootableCorpse, roll_npc_loot,
 };
 use std::collections::HashMap;
 
@@ -96,9 +99,7 @@ pub fn sync_equipped_weapon_from_hotbar(
             }
             
             // Update to the new weapon
-            equipped.weapon_type = desired;
-            equipped.aiming = false;
-            equipped.last_fire_time = -10.0;
+            equipped.switch_to(desired);
             equipped.reserve_ammo = 0;
             
             // Load ammo from the new slot
@@ -123,6 +124,62 @@ pub fn sync_equipped_weapon_from_hotbar(
     }
 }
 
+/// Handle requests to wear an armor item from the inventory.
+/// Whatever was previously worn in that slot goes back into the same inventory slot.
+pub fn handle_equip_armor_requests(
+    mut client_links: Query<(&RemoteId, &mut MessageReceiver<EquipArmorRequest>), With<ClientOf>>,
+    mut players: Query<(&Player, &Health, &mut Inventory, &mut EquippedArmor)>,
+) {
+    for (remote_id, mut receiver) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+        
+        for request in receiver.receive() {
+            let Some((_, health, mut inventory, mut armor)) = players.iter_mut().find(|(p, _, _, _)| p.client_id == peer_id) else {
+                continue;
+            };
+            
+            if health.is_dead() {
+                continue;
+            }
+            
+            let slot_index = request.slot_index as usize;
+            let Some(piece) = inventory.get_slot(slot_index).and_then(ArmorPiece::from_stack) else {
+                continue;
+            };
+            
+            let previous = armor.equip(piece);
+            inventory.set_slot(slot_index, previous.map(|p| p.to_stack()));
+            info!("Player {:?} equipped {}", peer_id, piece.armor_type.display_name());
+        }
+    }
+}
+
+/// Handle requests to take off armor (moved back into the first free inventory slot)
+pub fn handle_unequip_armor_requests(
+    mut client_links: Query<(&RemoteId, &mut MessageReceiver<UnequipArmorRequest>), With<ClientOf>>,
+    mut players: Query<(&Player, &mut Inventory, &mut EquippedArmor)>,
+) {
+    for (remote_id, mut receiver) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+        
+        for request in receiver.receive() {
+            let Some((_, mut inventory, mut armor)) = players.iter_mut().find(|(p, _, _)| p.client_id == peer_id) else {
+                continue;
+            };
+            
+            // Only take it off if there is room for it
+            if armor.get(request.slot).is_none() || inventory.find_empty_slot().is_none() {
+                continue;
+            }
+            
+            if let Some(piece) = armor.unequip(request.slot) {
+                let _ = inventory.add_stack(piece.to_stack());
+                info!("Player {:?} unequipped {}", peer_id, piece.armor_type.display_name());
+            }
+        }
+    }
+}
+
 /// Tracks which hotbar slot was previously active (for ammo save/load)
 #[derive(Component, Default, Clone)]
 pub struct PreviousHotbarSlot {
@@ -221,12 +278,20 @@ pub fn handle_drop_requests(
     }
 }
 
-/// Drop all inventory items when a player dies
+/// Drop all inventory items (and worn armor) when a player dies
 pub fn drop_inventory_on_death(
     mut commands: Commands,
-    mut players: Query<(&Player, &PlayerPosition, &mut Inventory, &Health), Changed<Health>>,
+    mut players: Query<(&Player, &PlayerPosition, &mut Inventory, &mut EquippedArmor, &Health), Changed<Health>>,
 ) {
-    for (player, position, mut inventory, health) in players.iter_mut() {
+    for (player, position, mut inventory, mut armor, health) in players.iter_mut() {
+        if health.is_dead() && (armor.helmet.is_some() || armor.vest.is_some()) {
+            // Worn armor drops at the body with its remaining durability
+            for (i, piece) in armor.take_all().into_iter().enumerate() {
+                let offset = Vec3::new(0.0, 0.3 + i as f32 * 0.3, 0.0);
+                spawn_ground_item_from_stack(&mut commands, &piece.to_stack(), position.0 + offset);
+            }
+        }
+        
         if health.is_dead() && !inventory.is_empty() {
             info!("Player {:?} died, dropping inventory", player.client_id);
             
@@ -286,6 +351,29 @@ pub fn drop_inventory_on_death(
     }
 }
 
+/// Turn newly dead NPCs into lootable corpses holding their rolled loot. The loot
+/// lives on the NPC entity, so it despawns with the body.
+pub fn fill_npc_corpses(
+    mut commands: Commands,
+    npcs: Query<(Entity, &Npc, &NpcPosition, &Health), (Changed<Health>, Without<LootableCorpse>)>,
+) {
+    for (entity, npc, position, health) in npcs.iter() {
+        if !health.is_dead() {
+            continue;
+        }
+        let loot = roll_npc_loot(npc.archetype, npc.id);
+        if loot.is_empty() {
+            continue;
+        }
+        debug!("NPC {} ({:?}) died carrying {} stacks of loot", npc.id, npc.archetype, loot.len());
+        commands.entity(entity).insert((
+            ChestStorage::with_items(loot),
+            ChestPosition(position.0),
+            LootableCorpse,
+        ));
+    }
+}
+
 /// Spawn a ground item in the world
 pub fn spawn_ground_item(
     commands: &mut Commands,
@@ -331,6 +419,8 @@ pub fn spawn_test_items(
         (ItemType::ShotgunShells, 15, Vec3::new(4.0, 0.0, 3.0)),
         (ItemType::Stone, 25, Vec3::new(5.0, 0.0, 3.0)),
         (ItemType::Wood, 50, Vec3::new(6.0, 0.0, 3.0)),
+        (ItemType::Explosive(ExplosiveType::Grenade), 5, Vec3::new(11.0, 0.0, 5.0)),
+        (ItemType::Explosive(ExplosiveType::Charge), 2, Vec3::new(12.0, 0.0, 5.0)),
     ];
     
     for (item_type, quantity, offset) in test_items {
@@ -351,11 +441,38 @@ pub fn spawn_test_items(
     ));
     info!("Spawned test weapon: Shotgun (empty mag) at {:?}", weapon_pos);
     
+    // Spawn a helmet and a vest so armor can be tested
+    for (armor_type, offset) in [
+        (ArmorType::Helmet, Vec3::new(8.0, 0.0, 3.0)),
+        (ArmorType::Vest, Vec3::new(9.0, 0.0, 3.0)),
+    ] {
+        let ground_y = terrain.get_height(offset.x, offset.z);
+        let pos = Vec3::new(offset.x, ground_y + 0.5, offset.z);
+        spawn_ground_item_from_stack(&mut commands, &ItemStack::new_armor(armor_type, armor_type.max_durability()), pos);
+        info!("Spawned test armor: {} at {:?}", armor_type.display_name(), pos);
+    }
+
+    // Spawn one of each melee weapon
+    for (weapon_type, offset) in [
+        (WeaponType::Knife, Vec3::new(8.0, 0.0, 5.0)),
+        (WeaponType::Axe, Vec3::new(9.0, 0.0, 5.0)),
+        (WeaponType::Sledgehammer, Vec3::new(10.0, 0.0, 5.0)),
+    ] {
+        let ground_y = terrain.get_height(offset.x, offset.z);
+        let pos = Vec3::new(offset.x, ground_y + 0.5, offset.z);
+        commands.spawn((
+            GroundItem::new_weapon(weapon_type, 0),
+            GroundItemPosition(pos),
+            Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+        ));
+        info!("Spawned test melee weapon: {:?} at {:?}", weapon_type, pos);
+    }
+
     // Spawn a test chest with some weapons and ammo
     let chest_offset = Vec3::new(3.0, 0.0, 5.0);
     let chest_y = terrain.get_height(chest_offset.x, chest_offset.z);
     let chest_pos = Vec3::new(chest_offset.x, chest_y + 0.5, chest_offset.z);
-    spawn_chest(&mut commands, chest_pos, vec![
+    spawn_chest(&mut commands, chest_pos, None, vec![
         ItemStack::new_weapon(WeaponType::Sniper, 5), // Sniper with 5 rounds loaded
         ItemStack::new(ItemType::SniperRounds, 20),
         ItemStack::new(ItemType::RifleAmmo, 60),
@@ -377,24 +494,44 @@ pub struct OpenChests {
     pub map: HashMap<PeerId, Entity>,
 }
 
-/// Spawn a chest in the world with initial items
+/// Spawn a chest in the world with initial items (`owner` = only that team may open it)
 pub fn spawn_chest(
     commands: &mut Commands,
     position: Vec3,
+    owner: Option<Team>,
     items: Vec<ItemStack>,
 ) -> Entity {
-    commands.spawn((
+    let chest = commands.spawn((
         ChestStorage::with_items(items),
         ChestPosition(position),
         Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
-    )).id()
+    )).id();
+    if let Some(team) = owner {
+        commands.entity(chest).insert(TeamOwner(team));
+    }
+    chest
+}
+
+/// Team a chest belongs to: its own owner, else the owner of the building it sits in
+fn chest_owner(
+    position: Vec3,
+    owner: Option<&TeamOwner>,
+    buildings: &Query<(&PlacedBuilding, &BuildingPosition, &TeamOwner)>,
+) -> Option<Team> {
+    owner.map(|owner| owner.0).or_else(|| {
+        buildings
+            .iter()
+            .find(|(building, building_pos, _)| building.contains(building_pos.0, position))
+            .map(|(_, _, owner)| owner.0)
+    })
 }
 
 /// Handle open chest requests from clients
 pub fn handle_open_chest_requests(
     mut client_links: Query<(&RemoteId, &mut MessageReceiver<OpenChestRequest>), With<ClientOf>>,
-    players: Query<(&Player, &PlayerPosition)>,
-    chests: Query<(Entity, &ChestPosition)>,
+    players: Query<(&Player, &PlayerPosition, Option<&Team>)>,
+    chests: Query<(Entity, &ChestPosition, Option<&TeamOwner>)>,
+    buildings: Query<(&PlacedBuilding, &BuildingPosition, &TeamOwner)>,
     mut open_chests: ResMut<OpenChests>,
 ) {
     for (remote_id, mut receiver) in client_links.iter_mut() {
@@ -402,22 +539,29 @@ pub fn handle_open_chest_requests(
         
         for _request in receiver.receive() {
             // Find player position
-            let Some((_, player_pos)) = players.iter().find(|(p, _)| p.client_id == peer_id) else {
+            let Some((_, player_pos, team)) = players.iter().find(|(p, _, _)| p.client_id == peer_id) else {
                 continue;
             };
             
             // Find the closest chest within range
-            let mut closest: Option<(Entity, f32)> = None;
-            for (chest_entity, chest_pos) in chests.iter() {
+            let mut closest: Option<(Entity, f32, Vec3, Option<&TeamOwner>)> = None;
+            for (chest_entity, chest_pos, owner) in chests.iter() {
                 let distance = player_pos.0.distance(chest_pos.0);
                 if distance <= CHEST_RANGE {
                     if closest.is_none() || distance < closest.unwrap().1 {
-                        closest = Some((chest_entity, distance));
+                        closest = Some((chest_entity, distance, chest_pos.0, owner));
                     }
                 }
             }
             
-            if let Some((chest_entity, _)) = closest {
+            if let Some((chest_entity, _, chest_pos, owner)) = closest {
+                // Team chests (or chests in a team's building) are locked to that team
+                let owner = chest_owner(chest_pos, owner, &buildings);
+                if !can_access(team.copied(), owner) {
+                    info!("Player {:?} denied access to {:?} team chest {:?}", peer_id, owner, chest_entity);
+                    continue;
+                }
+
                 // Track this chest as open for this player
                 open_chests.map.insert(peer_id, chest_entity);
                 info!("Player {:?} opened chest {:?}", peer_id, chest_entity);
@@ -2,14 +2,29 @@
 //! 
 //! Updated for Lightyear 0.25 / Bevy 0.17
 
+mod battle_royale;
 mod building;
+mod combat_log;
+mod dialogue;
 mod systems;
 mod npc;
+mod npc_behaviour;
+mod npc_combat;
+mod npc_crowd;
+mod npc_perception;
+mod npc_population;
 mod weapons;
 mod world;
 mod colliders;
 mod inventory;
+mod explosives;
+mod game_mode;
+mod melee;
+mod navmesh;
 mod persistence;
+mod teams;
+mod territory;
+mod trade;
 
 use bevy::prelude::*;
 use bevy::app::ScheduleRunnerPlugin;
@@ -148,12 +163,45 @@ fn main() {
     // Chest open tracking
     app.init_resource::<inventory::OpenChests>();
 
+    // Kill feed + per-player damage logs
+    app.init_resource::<combat_log::CombatLog>();
+
+    // Friendly fire policy + reflected damage queue
+    app.init_resource::<teams::FriendlyFire>();
+    app.init_resource::<teams::ReflectedDamage>();
+
+    // Game mode (GAME_MODE env var) + per-match player stats + battle royale / territory state
+    app.init_resource::<game_mode::GameMode>();
+    app.init_resource::<game_mode::MatchStats>();
+    app.init_resource::<battle_royale::BattleRoyale>();
+    app.init_resource::<territory::Territory>();
+
     // Delta chunk entity tracking for terrain modifications
     app.init_resource::<building::DeltaChunkEntities>();
 
     // Spatial grid for O(1) obstacle lookups (used by NPC AI pathfinding)
     app.init_resource::<SpatialObstacleGrid>();
     app.init_resource::<npc::ObstacleGridState>();
+    app.init_resource::<navmesh::NavMesh>();
+
+    // NPC combat profiles (NPC_AGGRESSION env var overrides)
+    app.init_resource::<npc_combat::NpcCombatSettings>();
+
+    // Gunshots queued for NPC hearing
+    app.init_resource::<npc_perception::NpcNoises>();
+
+    // NPC-vs-NPC local avoidance state
+    app.init_resource::<npc_crowd::NpcCrowd>();
+
+    // Population director (regional spawns and respawns)
+    app.init_resource::<npc_population::NpcPopulation>();
+
+    // Per-archetype NPC behaviour trees (shared/assets/behaviours/*.ron)
+    app.init_resource::<npc_behaviour::NpcBehaviours>();
+
+    // NPC dialogue trees (shared/assets/dialogue/*.ron) + conversations and quest progress
+    app.init_resource::<dialogue::NpcDialogues>();
+    app.init_resource::<dialogue::Conversations>();
 
     // Player profile persistence
     app.insert_resource(PlayerProfiles::new(
@@ -179,6 +227,10 @@ fn main() {
     
     // Spawn WorldTime after server is started
     app.add_systems(Update, world::spawn_world_time_once.run_if(server_is_started));
+    // Spawn the match (match modes only) after server is started
+    app.add_systems(Update, game_mode::spawn_match_once.run_if(server_is_started));
+    // Spawn control points + team chests (territory mode only) after server is started
+    app.add_systems(Update, territory::spawn_territory_once.run_if(server_is_started));
 
     // Spawn vehicles after server is started
     app.add_systems(Update, spawn_vehicles_once);
@@ -196,8 +248,9 @@ fn main() {
     app.add_systems(
         FixedUpdate,
         (
-            // World time (day/night cycle)
+            // World time (day/night cycle) and wind
             world::tick_world_time,
+            world::tick_wind,
             // Static collider streaming (keep colliders near active players)
             colliders::stream_static_colliders,
             colliders::invalidate_colliders_for_new_buildings,
@@ -210,8 +263,10 @@ fn main() {
             systems::handle_vehicle_interactions,
             systems::simulate_vehicles,
             systems::simulate_players,
-            // Death & respawn
+            // Death, match lifecycle (battle royale zone first) & respawn
             systems::check_player_deaths,
+            battle_royale::tick_safe_zone,
+            game_mode::tick_match,
             systems::tick_respawn_timers,
             // Auto-save
             systems::periodic_player_save,
@@ -223,14 +278,30 @@ fn main() {
     app.add_systems(
         FixedUpdate,
         (
-            // Spatial grid sync (O(1) obstacle lookups for pathfinding)
-            npc::sync_obstacle_grid,
-            // NPC AI - damage reaction before AI tick
-            npc::react_to_damage,
-            npc::tick_npc_ai,
-            // Dead NPC cleanup (add despawn timer, tick timer and despawn)
-            npc::add_despawn_timer_to_dead_npcs,
-            npc::tick_dead_npc_despawn_timers,
+            // Spatial grid sync (O(1) obstacle lookups), then drop stale navmesh chunks and hand
+            // out this tick's pathfinding budget
+            (npc::sync_obstacle_grid, navmesh::invalidate_nav_chunks, navmesh::refill_nav_budget).chain(),
+            // NPC AI - perception and damage reaction (squad mates too) before AI tick, then
+            // chasing / attacking, then NPCs stepping around each other and replicating the
+            // stance their hitboxes are posed with
+            (npc_perception::tick_npc_perception, npc_crowd::alarm_squads, npc::react_to_damage).chain(),
+            (
+                npc_crowd::record_crowd_positions,
+                npc::tick_npc_ai,
+                npc_combat::tick_npc_combat,
+                npc_crowd::avoid_npc_collisions,
+                npc::sync_npc_hitbox_stance,
+            )
+                .chain(),
+            // Dead NPC cleanup (add despawn timer, tick timer and despawn), then the population
+            // director queues replacements and keeps regions around players stocked
+            (
+                npc::add_despawn_timer_to_dead_npcs,
+                npc::tick_dead_npc_despawn_timers,
+                npc_population::note_npc_deaths,
+                npc_population::direct_npc_population,
+            )
+                .chain(),
             // World prop collisions (server-authoritative)
             colliders::resolve_vehicle_static_collisions,
             colliders::resolve_player_static_collisions,
@@ -246,8 +317,14 @@ fn main() {
             inventory::handle_close_chest_requests,
             inventory::handle_chest_transfer_requests,
             inventory::auto_close_distant_chests,
+            // NPC conversations (server-authoritative), then ending abandoned ones
+            (dialogue::handle_dialogue_requests, dialogue::end_stale_conversations).chain(),
+            // Trading with vendor NPCs (server-authoritative), then restocking
+            (trade::handle_trade_requests, trade::tick_trader_restock).chain(),
             // Building placement (server-authoritative)
             building::handle_place_building_requests,
+            // Settlement capture + territory income (territory mode only)
+            territory::tick_territory,
         )
             .chain()
             .run_if(server_is_started),
@@ -256,15 +333,32 @@ fn main() {
     app.add_systems(
         FixedUpdate,
         (
-            // Weapon systems
+            // Armor equip / unequip (before hits so new armor applies this tick)
+            inventory::handle_equip_armor_requests,
+            inventory::handle_unequip_armor_requests,
+            // Weapon systems (fire mode first so a toggle applies to this tick's shots)
+            weapons::handle_fire_mode_requests,
+            weapons::handle_zero_requests,
             weapons::handle_shoot_requests,
             weapons::handle_reload_request,
+            weapons::tick_reloads,
+            // Melee swings (resolved after their wind-up)
+            melee::resolve_melee_swings,
             weapons::simulate_bullets,
+            // Near misses (suppression + crack/whiz audio)
+            (weapons::decay_suppression, weapons::detect_near_misses).chain(),
             weapons::detect_bullet_hits,
             weapons::detect_bullet_world_hits,
+            // Grenades / charges (thrown, bounced, detonated)
+            explosives::handle_throw_requests,
+            explosives::simulate_explosives,
+            explosives::detonate_explosives,
+            // Reflected friendly fire, then kill feed / damage logs (after every damage source this tick)
+            teams::apply_reflected_damage,
+            combat_log::broadcast_combat_log,
             weapons::cleanup_bullets,
-            // Inventory death
-            inventory::drop_inventory_on_death,
+            // Inventory death (players drop everything, NPC bodies hold their loot)
+            (inventory::drop_inventory_on_death, inventory::fill_npc_corpses).chain(),
         )
             .chain()
             .run_if(server_is_started),
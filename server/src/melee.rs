@@ -0,0 +1,393 @@
+//! Server-side melee combat
+//!
+//! Swings are started from `ShootRequest`s while a melee weapon (or bare fists) is
+//! equipped. The attacker gets a replicated `MeleeSwing` during the wind-up; once it
+//! elapses the swing is resolved by sweeping rays across the arc against the per-limb
+//! hitbox rigs of players and NPCs and the boxes of vehicles. The nearest hit wins, so a
+//! vehicle shields whoever stands behind it; vehicles have no health and get shoved
+//! instead. A hit only lands if terrain, props and structures leave the way from the
+//! attacker's eye clear. Hits on teammates go through the same `FriendlyFire` policy
+//! as bullets.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    weapons::{damage, sweep_directions, MeleeSwing},
+    apply_armored_hit, vehicle_def, BulletImpact, BulletImpactSurface, DamageCause, DamageReceived, EquippedArmor,
+    EquippedWeapon, Health, HitConfirm, HitboxRig, InVehicle, Npc, NpcDamageEvent, NpcIndoors, NpcPosition,
+    NpcRotation, Player, PlayerGrounded, PlayerKilled, PlayerPosition, PlayerRotation, PlayerVelocity,
+    ReliableChannel, RigHit, Team, Vehicle, VehicleState, WorldTerrain, are_allies, PLAYER_HEIGHT,
+};
+
+use crate::colliders::{DerivedColliderLibrary, StaticColliders, StructureColliders};
+
+use crate::combat_log::{CombatHit, CombatLog, CombatantRef};
+use crate::npc::{npc_hitbox_pose, NpcWander};
+use crate::npc_combat::has_line_of_sight;
+use crate::systems::peer_id_to_u64;
+use crate::teams::{FriendlyFire, ReflectedDamage, ReflectedHit};
+use crate::weapons::{player_hitbox_pose, segment_aabb_intersection};
+
+/// Swing origin height above the capsule center (matches the first-person camera).
+const MELEE_EYE_HEIGHT: f32 = PLAYER_HEIGHT * 0.4;
+
+/// Ray vs a vehicle's box, oriented like its model. `size` is (length, height, width)
+/// with the length along the vehicle's local Z. Returns (distance, point, normal).
+fn vehicle_raycast(
+    state: &VehicleState,
+    size: Vec3,
+    origin: Vec3,
+    dir: Vec3,
+    max_dist: f32,
+) -> Option<(f32, Vec3, Vec3)> {
+    let rotation = Quat::from_euler(EulerRot::YXZ, state.heading, state.pitch, -state.roll);
+    let to_local = rotation.inverse();
+    let half = Vec3::new(size.z, size.y, size.x) * 0.5;
+    let start = to_local * (origin - state.position);
+    let end = to_local * (origin + dir * max_dist - state.position);
+    let (t, point, normal) = segment_aabb_intersection(start, end, -half, half)?;
+    Some((t * max_dist, state.position + rotation * point, rotation * normal))
+}
+
+/// Start a melee swing if the weapon's swing cooldown allows it.
+///
+/// Returns false if the weapon isn't melee or is still recovering from the last swing.
+pub fn start_melee_swing(
+    commands: &mut Commands,
+    attacker: Entity,
+    weapon: &mut EquippedWeapon,
+    direction: Vec3,
+    current_time: f32,
+) -> bool {
+    let Some(stats) = weapon.weapon_type.melee_stats() else {
+        return false;
+    };
+    if !weapon.swing(current_time) {
+        return false;
+    }
+
+    commands.entity(attacker).insert(MeleeSwing {
+        weapon_type: weapon.weapon_type,
+        direction: direction.normalize_or(Vec3::NEG_Z),
+        resolve_at: current_time + stats.wind_up,
+    });
+    true
+}
+
+/// Resolve swings whose wind-up has elapsed.
+pub fn resolve_melee_swings(
+    mut commands: Commands,
+    time: Res<Time>,
+    swings: Query<(Entity, &Player, &PlayerPosition, &MeleeSwing, &EquippedWeapon, Has<InVehicle>)>,
+    mut players: Query<
+        (
+            Entity,
+            &Player,
+            &PlayerPosition,
+            &PlayerRotation,
+            &PlayerVelocity,
+            &PlayerGrounded,
+            &EquippedWeapon,
+            &mut Health,
+            Option<&mut EquippedArmor>,
+            Option<&Team>,
+        ),
+        Without<Npc>,
+    >,
+    mut npcs: Query<
+        (Entity, &Npc, &NpcPosition, &NpcRotation, Option<&NpcWander>, &mut Health),
+        (Without<Player>, Without<NpcIndoors>),
+    >,
+    mut vehicles: Query<(Entity, &Vehicle, &mut VehicleState)>,
+    terrain: Res<WorldTerrain>,
+    static_colliders: Res<StaticColliders>,
+    derived_colliders: Option<Res<DerivedColliderLibrary>>,
+    structure_colliders: Res<StructureColliders>,
+    mut client_links: Query<
+        (
+            &RemoteId,
+            &mut MessageSender<HitConfirm>,
+            &mut MessageSender<DamageReceived>,
+            &mut MessageSender<PlayerKilled>,
+            &mut MessageSender<BulletImpact>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    mut combat_log: ResMut<CombatLog>,
+    friendly_fire: Res<FriendlyFire>,
+    mut reflected: ResMut<ReflectedDamage>,
+) {
+    #[derive(Clone, Copy)]
+    enum Target {
+        Player(Entity),
+        Npc(Entity),
+    }
+
+    let now = time.elapsed_secs();
+
+    // Collect due swings first (the hit pass needs mutable access to victims)
+    let mut due: Vec<(Entity, PeerId, Vec3, MeleeSwing)> = Vec::new();
+    for (entity, player, position, swing, weapon, in_vehicle) in swings.iter() {
+        if now < swing.resolve_at {
+            continue;
+        }
+        commands.entity(entity).remove::<MeleeSwing>();
+
+        // Switching weapons or entering a vehicle during the wind-up cancels the swing
+        if weapon.weapon_type != swing.weapon_type || in_vehicle {
+            continue;
+        }
+        due.push((entity, player.client_id, position.0, swing.clone()));
+    }
+
+    for (attacker_entity, attacker_id, attacker_pos, swing) in due {
+        let Ok((.., attacker_health, _, attacker_team)) = players.get(attacker_entity) else {
+            continue;
+        };
+        if attacker_health.is_dead() {
+            continue;
+        }
+        let attacker_team = attacker_team.copied();
+        let Some(melee) = swing.weapon_type.melee_stats() else {
+            continue;
+        };
+        let stats = swing.weapon_type.stats();
+        let eye = attacker_pos + Vec3::Y * MELEE_EYE_HEIGHT;
+        let shooter_id = peer_id_to_u64(attacker_id);
+
+        // Pose every candidate rig once, then sweep the arc
+        let mut rigs: Vec<(Target, HitboxRig)> = Vec::new();
+        for (entity, _npc, position, rotation, wander, health) in npcs.iter() {
+            if health.is_dead() || position.0.distance(eye) > melee.reach + PLAYER_HEIGHT {
+                continue;
+            }
+            rigs.push((Target::Npc(entity), HitboxRig::posed(&npc_hitbox_pose(position.0, rotation.0, wander))));
+        }
+        for (entity, _player, position, rotation, velocity, grounded, weapon, health, _armor, _team) in players.iter() {
+            if entity == attacker_entity
+                || health.is_dead()
+                || position.0.distance(eye) > melee.reach + PLAYER_HEIGHT
+            {
+                continue;
+            }
+            let pose = player_hitbox_pose(position.0, rotation, velocity, grounded, weapon);
+            rigs.push((Target::Player(entity), HitboxRig::posed(&pose)));
+        }
+
+        let nearby_vehicles: Vec<(Entity, VehicleState, Vec3)> = vehicles
+            .iter()
+            .map(|(entity, vehicle, state)| (entity, state.clone(), vehicle_def(vehicle.vehicle_type).size))
+            .filter(|(_, state, size)| state.position.distance(eye) <= melee.reach + size.length() * 0.5)
+            .collect();
+        let clear = |point: Vec3| {
+            has_line_of_sight(
+                eye,
+                point,
+                &terrain,
+                &static_colliders,
+                derived_colliders.as_deref(),
+                &structure_colliders,
+            )
+        };
+
+        // Nearest rig or vehicle hit that isn't behind a wall, prop or the ground
+        let mut best: Option<(RigHit, Target)> = None;
+        let mut best_vehicle: Option<(f32, Vec3, Vec3, Entity)> = None;
+        for dir in sweep_directions(swing.direction, melee.arc) {
+            for (target, rig) in &rigs {
+                let Some(hit) = rig.raycast(eye, dir, melee.reach) else {
+                    continue;
+                };
+                if best.as_ref().is_some_and(|(b, _)| b.t <= hit.t) {
+                    continue;
+                }
+                if clear(hit.point) {
+                    best = Some((hit, *target));
+                }
+            }
+            for (entity, state, size) in &nearby_vehicles {
+                let Some((t, point, normal)) = vehicle_raycast(state, *size, eye, dir, melee.reach) else {
+                    continue;
+                };
+                if best_vehicle.is_some_and(|(b, ..)| b <= t) {
+                    continue;
+                }
+                if clear(point) {
+                    best_vehicle = Some((t, point, normal, *entity));
+                }
+            }
+        }
+
+        // A vehicle in front of everyone else takes the swing
+        if let Some((_, point, normal, vehicle_entity)) =
+            best_vehicle.filter(|(t, ..)| best.as_ref().is_none_or(|(hit, _)| *t < hit.t))
+        {
+            if let Ok((.., mut state)) = vehicles.get_mut(vehicle_entity) {
+                let push = Vec3::new(swing.direction.x, 0.0, swing.direction.z).normalize_or_zero();
+                state.velocity += push * melee.vehicle_impulse;
+            }
+
+            let impact = BulletImpact {
+                owner_id: shooter_id,
+                weapon_type: swing.weapon_type,
+                spawn_position: eye,
+                initial_velocity: swing.direction,
+                impact_position: point,
+                impact_normal: normal,
+                surface: BulletImpactSurface::Vehicle,
+            };
+            for (_remote_id, _hit_sender, _dmg_sender, _kill_sender, mut impact_sender) in client_links.iter_mut() {
+                impact_sender.send::<ReliableChannel>(impact.clone());
+            }
+            info!("Player {:?} hit a vehicle with {:?}", attacker_id, swing.weapon_type);
+            continue;
+        }
+
+        let Some((hit, target)) = best else {
+            continue;
+        };
+
+        let is_headshot = hit.hit_zone == damage::HitZone::Head;
+        let mut impact = BulletImpact {
+            owner_id: shooter_id,
+            weapon_type: swing.weapon_type,
+            spawn_position: eye,
+            initial_velocity: swing.direction,
+            impact_position: hit.point,
+            impact_normal: hit.normal,
+            surface: BulletImpactSurface::Player,
+        };
+
+        match target {
+            Target::Player(victim_entity) => {
+                let Ok((_, victim, victim_pos, .., mut health, mut armor, team)) = players.get_mut(victim_entity) else {
+                    continue;
+                };
+
+                // Friendly fire: same policy as bullets - scale the teammate's damage and/or reflect it
+                let distance = eye.distance(hit.point);
+                let (victim_mult, reflected_mult) =
+                    friendly_fire.policy.multipliers(are_allies(attacker_team, team.copied()));
+                if reflected_mult > 0.0 {
+                    reflected.push(ReflectedHit {
+                        attacker: attacker_id,
+                        damage: damage::calculate_damage(&stats, 0.0, hit.hit_zone) * reflected_mult,
+                        cause: DamageCause::Weapon(swing.weapon_type),
+                        hit_zone: hit.hit_zone,
+                        distance,
+                    });
+                }
+                if victim_mult <= 0.0 {
+                    for (_remote_id, _hit_sender, _dmg_sender, _kill_sender, mut impact_sender) in
+                        client_links.iter_mut()
+                    {
+                        impact_sender.send::<ReliableChannel>(impact.clone());
+                    }
+                    continue;
+                }
+
+                let armored = apply_armored_hit(armor.as_deref_mut(), &stats, 0.0, hit.hit_zone, victim_mult);
+                let is_kill = health.take_damage(armored.damage);
+                let victim_id = victim.client_id;
+
+                combat_log.record(CombatHit {
+                    attacker: CombatantRef::Player(attacker_id),
+                    victim: CombatantRef::Player(victim_id),
+                    cause: DamageCause::Weapon(swing.weapon_type),
+                    hit_zone: hit.hit_zone,
+                    damage: armored.damage,
+                    armor_absorbed: armored.absorbed,
+                    distance,
+                    kill: is_kill,
+                });
+
+                info!(
+                    "Melee hit! {:?} -> {:?} ({:?}) with {:?} for {:.1} damage ({:.1} absorbed, kill: {})",
+                    attacker_id, victim_id, hit.hit_zone, swing.weapon_type, armored.damage, armored.absorbed, is_kill
+                );
+
+                let damage_direction = Vec3::new(eye.x - victim_pos.0.x, 0.0, eye.z - victim_pos.0.z).normalize_or_zero();
+                for (remote_id, mut hit_sender, mut dmg_sender, mut kill_sender, mut impact_sender) in
+                    client_links.iter_mut()
+                {
+                    impact_sender.send::<ReliableChannel>(impact.clone());
+
+                    if remote_id.0 == attacker_id {
+                        hit_sender.send::<ReliableChannel>(HitConfirm {
+                            target_id: peer_id_to_u64(victim_id),
+                            damage: armored.damage,
+                            headshot: is_headshot,
+                            kill: is_kill,
+                            hit_zone: hit.hit_zone,
+                            armor_absorbed: armored.absorbed,
+                        });
+                    }
+
+                    if remote_id.0 == victim_id {
+                        dmg_sender.send::<ReliableChannel>(DamageReceived {
+                            direction: damage_direction,
+                            damage: armored.damage,
+                            health_remaining: health.current,
+                        });
+                        if is_kill {
+                            kill_sender.send::<ReliableChannel>(PlayerKilled {
+                                killer_id: shooter_id,
+                                weapon: swing.weapon_type,
+                                headshot: is_headshot,
+                            });
+                        }
+                    }
+                }
+            }
+            Target::Npc(npc_entity) => {
+                let Ok((_, npc, .., mut health)) = npcs.get_mut(npc_entity) else {
+                    continue;
+                };
+                let damage_amount = damage::calculate_damage(&stats, 0.0, hit.hit_zone);
+                let is_kill = health.take_damage(damage_amount);
+
+                combat_log.record(CombatHit {
+                    attacker: CombatantRef::Player(attacker_id),
+                    victim: CombatantRef::Npc { id: npc.id, archetype: npc.archetype },
+                    cause: DamageCause::Weapon(swing.weapon_type),
+                    hit_zone: hit.hit_zone,
+                    damage: damage_amount,
+                    armor_absorbed: 0.0,
+                    distance: eye.distance(hit.point),
+                    kill: is_kill,
+                });
+
+                commands.entity(npc_entity).insert(NpcDamageEvent {
+                    damage_source_position: eye,
+                    damage_amount,
+                    attacker: Some(attacker_entity),
+                });
+
+                info!(
+                    "Melee hit NPC! {:?} -> npc:{} ({:?}) with {:?} for {:.1} damage (kill: {})",
+                    attacker_id, npc.id, hit.hit_zone, swing.weapon_type, damage_amount, is_kill
+                );
+
+                impact.surface = BulletImpactSurface::Npc;
+                for (remote_id, mut hit_sender, _dmg_sender, _kill_sender, mut impact_sender) in
+                    client_links.iter_mut()
+                {
+                    impact_sender.send::<ReliableChannel>(impact.clone());
+
+                    if remote_id.0 == attacker_id {
+                        hit_sender.send::<ReliableChannel>(HitConfirm {
+                            target_id: npc.id,
+                            damage: damage_amount,
+                            headshot: is_headshot,
+                            kill: is_kill,
+                            hit_zone: hit.hit_zone,
+                            armor_absorbed: 0.0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
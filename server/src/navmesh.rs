@@ -0,0 +1,646 @@
+//! Hierarchical NPC navigation
+//!
+//! The world is cut along the terrain's 64 m chunks, each a 32x32 grid of 2 m cells. Per
+//! chunk we bake which cells an NPC can stand in (terrain steps, building footprints from the
+//! `SpatialObstacleGrid`, settlement structures), the entrances where walkable cells meet
+//! across each border, and the cost and cell path between every pair of entrances.
+//!
+//! Short queries run a plain cell A* over the baked chunks. Long ones run A* over the
+//! entrance graph instead and stitch the cached cell paths together, so a route across
+//! several settlements expands a few hundred entrances rather than flooding every 2 m cell.
+//! Both searches have a hard node cap, and a single query bakes at most
+//! `MAX_BAKES_PER_QUERY` new chunks. On top of that all queries in a tick share a budget of
+//! searches and bakes (`refill_nav_budget`); once it's spent `find_path` defers the query to
+//! the next tick instead of running it.
+//!
+//! Chunks are baked the first time a query touches them and dropped again when a building is
+//! placed or removed over them or their terrain is deformed (`invalidate_nav_chunks`). Each
+//! chunk also caches the paths from the cells queries started or ended in to its entrances,
+//! which go with it when it's dropped.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use bevy::prelude::*;
+
+use shared::{
+    generate_chunk_structures, ground_clearance_center, BuildingPosition, ChunkCoord, PlacedBuilding,
+    SpatialObstacleGrid, StructureSpawn, TerrainDeltaChunk, WorldTerrain, CHUNK_SIZE, NPC_RADIUS,
+};
+
+use crate::colliders::structure_blocks_sphere;
+
+const NAV_CELL_SIZE: f32 = 2.0; // meters
+const NAV_MAX_STEP: f32 = 1.2; // max height delta allowed between neighbor cells
+const CELLS_PER_CHUNK: i32 = (CHUNK_SIZE / NAV_CELL_SIZE) as i32;
+
+/// Border openings wider than this many cells get one entrance per stretch
+const ENTRANCE_MAX_WIDTH: usize = 8;
+
+/// Node caps for the cell search (short queries) and the entrance search (long ones)
+const LOCAL_MAX_NODES: usize = 4000;
+const ABSTRACT_MAX_NODES: usize = 3000;
+
+/// Queries whose ends are this many chunks apart or fewer try the cell search first
+const LOCAL_QUERY_CHUNKS: i32 = 1;
+
+/// New chunks a single query may bake (the rest are treated as impassable until later queries)
+const MAX_BAKES_PER_QUERY: usize = 48;
+
+/// Path queries and chunk bakes all NPCs together may run per tick
+const MAX_SEARCHES_PER_TICK: usize = 32;
+const MAX_BAKES_PER_TICK: usize = 64;
+
+/// Start/goal cells whose entrance paths a chunk keeps (the cache starts over when full)
+const MAX_CACHED_CONNECTIONS: usize = 64;
+
+/// Paths between a cell and its chunk's entrances, as (entrance, cost, path)
+type Connections = Vec<(GridPos, f32, Vec<GridPos>)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct GridPos {
+    x: i32,
+    z: i32,
+}
+
+impl GridPos {
+    fn chunk(self) -> ChunkCoord {
+        ChunkCoord::new(self.x.div_euclid(CELLS_PER_CHUNK), self.z.div_euclid(CELLS_PER_CHUNK))
+    }
+
+    /// Index into a `NavChunk`'s cell arrays
+    fn local_index(self) -> usize {
+        (self.z.rem_euclid(CELLS_PER_CHUNK) * CELLS_PER_CHUNK + self.x.rem_euclid(CELLS_PER_CHUNK)) as usize
+    }
+
+    fn offset(self, dx: i32, dz: i32) -> Self {
+        GridPos { x: self.x + dx, z: self.z + dz }
+    }
+}
+
+fn world_to_grid(p: Vec3) -> GridPos {
+    GridPos {
+        x: (p.x / NAV_CELL_SIZE).round() as i32,
+        z: (p.z / NAV_CELL_SIZE).round() as i32,
+    }
+}
+
+fn grid_to_world(terrain: &WorldTerrain, g: GridPos) -> Vec3 {
+    let x = g.x as f32 * NAV_CELL_SIZE;
+    let z = g.z as f32 * NAV_CELL_SIZE;
+    let y = terrain.get_height(x, z) + ground_clearance_center();
+    Vec3::new(x, y, z)
+}
+
+fn heuristic(a: GridPos, b: GridPos) -> f32 {
+    // Euclidean in grid-space
+    let dx = (a.x - b.x) as f32;
+    let dz = (a.z - b.z) as f32;
+    (dx * dx + dz * dz).sqrt()
+}
+
+#[derive(Clone, Copy, Debug)]
+struct OpenNode {
+    f_cost: i32,
+    pos: GridPos,
+}
+
+impl Eq for OpenNode {}
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.f_cost == other.f_cost && self.pos == other.pos
+    }
+}
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reverse for min-heap behavior.
+        other
+            .f_cost
+            .cmp(&self.f_cost)
+            .then_with(|| self.pos.x.cmp(&other.pos.x))
+            .then_with(|| self.pos.z.cmp(&other.pos.z))
+    }
+}
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Cell A* over the 8-neighborhood; returns the path (start included) and its cost
+fn grid_a_star(
+    start: GridPos,
+    goal: GridPos,
+    max_nodes: usize,
+    passable: impl Fn(GridPos, GridPos) -> bool,
+) -> Option<(Vec<GridPos>, f32)> {
+    if start == goal {
+        return Some((vec![start], 0.0));
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<GridPos, GridPos> = HashMap::new();
+    let mut g_score: HashMap<GridPos, f32> = HashMap::new();
+
+    g_score.insert(start, 0.0);
+    open.push(OpenNode {
+        f_cost: (heuristic(start, goal) * 1000.0) as i32,
+        pos: start,
+    });
+
+    let mut expanded = 0_usize;
+    while let Some(OpenNode { pos: current, .. }) = open.pop() {
+        expanded += 1;
+        if expanded > max_nodes {
+            return None;
+        }
+
+        let current_g = g_score.get(&current).copied().unwrap_or(f32::INFINITY);
+        if current == goal {
+            // Reconstruct
+            let mut path = vec![current];
+            let mut cur = current;
+            while let Some(prev) = came_from.get(&cur).copied() {
+                path.push(prev);
+                cur = prev;
+            }
+            path.reverse();
+            return Some((path, current_g));
+        }
+
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let n = current.offset(dx, dz);
+            if !passable(current, n) {
+                continue;
+            }
+
+            let step_cost = if dx != 0 && dz != 0 { 1.4142 } else { 1.0 };
+            let tentative_g = current_g + step_cost;
+            if tentative_g < g_score.get(&n).copied().unwrap_or(f32::INFINITY) {
+                came_from.insert(n, current);
+                g_score.insert(n, tentative_g);
+
+                let f = tentative_g + heuristic(n, goal);
+                open.push(OpenNode {
+                    f_cost: (f * 1000.0) as i32,
+                    pos: n,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Walkability and ground height of one cell
+fn probe_cell(
+    terrain: &WorldTerrain,
+    obstacles: &SpatialObstacleGrid,
+    structures: &[&StructureSpawn],
+    cell: GridPos,
+) -> (bool, f32) {
+    let center = grid_to_world(terrain, cell);
+    let blocked = obstacles.point_blocked(Vec2::new(center.x, center.z))
+        || structures.iter().any(|spawn| structure_blocks_sphere(spawn, center, NPC_RADIUS));
+    (!blocked, center.y)
+}
+
+/// An edge of the entrance graph
+#[derive(Clone, Debug)]
+struct NavEdge {
+    to: GridPos,
+    cost: f32,
+    /// Cells after the edge's start, up to and including `to`
+    path: Vec<GridPos>,
+}
+
+/// Baked navigation data for one chunk
+struct NavChunk {
+    /// Per cell (row-major, see `GridPos::local_index`)
+    walkable: Vec<bool>,
+    heights: Vec<f32>,
+    /// Entrance cell -> edges to the chunk's other entrances and across the border
+    entrances: HashMap<GridPos, Vec<NavEdge>>,
+    /// (cell, outbound) -> paths between that cell and the entrances (see `NavMesh::connect`)
+    connections: HashMap<(GridPos, bool), Connections>,
+}
+
+impl NavChunk {
+    fn passable(&self, from: GridPos, to: GridPos) -> bool {
+        let (from, to) = (from.local_index(), to.local_index());
+        self.walkable[to] && (self.heights[to] - self.heights[from]).abs() <= NAV_MAX_STEP
+    }
+}
+
+/// Bake a chunk, asking `probe` (see `probe_cell`) about each cell and the cells across its border
+fn bake_chunk(coord: ChunkCoord, probe: impl Fn(GridPos) -> (bool, f32)) -> NavChunk {
+    let base = GridPos { x: coord.x * CELLS_PER_CHUNK, z: coord.z * CELLS_PER_CHUNK };
+    let cells = (CELLS_PER_CHUNK * CELLS_PER_CHUNK) as usize;
+    let mut walkable = vec![false; cells];
+    let mut heights = vec![0.0; cells];
+    for z in 0..CELLS_PER_CHUNK {
+        for x in 0..CELLS_PER_CHUNK {
+            let cell = base.offset(x, z);
+            let (ok, height) = probe(cell);
+            walkable[cell.local_index()] = ok;
+            heights[cell.local_index()] = height;
+        }
+    }
+    let mut chunk = NavChunk { walkable, heights, entrances: HashMap::new(), connections: HashMap::new() };
+
+    // Openings along each border: runs of walkable cells facing walkable cells across it.
+    // Both chunks scan a shared border in the same direction, so they agree on the entrances.
+    let last = CELLS_PER_CHUNK - 1;
+    let borders = [
+        (base.offset(last, 0), (0, 1), (1, 0)),
+        (base, (0, 1), (-1, 0)),
+        (base.offset(0, last), (1, 0), (0, 1)),
+        (base, (1, 0), (0, -1)),
+    ];
+    let mut crossings = Vec::new();
+    for (origin, (ax, az), (ox, oz)) in borders {
+        let mut run: Vec<(GridPos, GridPos)> = Vec::new();
+        for k in 0..=CELLS_PER_CHUNK {
+            // One step past the end closes the last run
+            let crossing = if k < CELLS_PER_CHUNK {
+                let inside = origin.offset(ax * k, az * k);
+                let outside = inside.offset(ox, oz);
+                let (outside_ok, outside_height) = probe(outside);
+                let i = inside.local_index();
+                let open = chunk.walkable[i] && outside_ok && (chunk.heights[i] - outside_height).abs() <= NAV_MAX_STEP;
+                open.then_some((inside, outside))
+            } else {
+                None
+            };
+            match crossing {
+                Some(pair) => run.push(pair),
+                None => {
+                    crossings.extend(run.chunks(ENTRANCE_MAX_WIDTH).map(|stretch| stretch[stretch.len() / 2]));
+                    run.clear();
+                }
+            }
+        }
+    }
+
+    for (inside, outside) in crossings {
+        chunk.entrances.entry(inside).or_default().push(NavEdge { to: outside, cost: 1.0, path: vec![outside] });
+    }
+
+    // Paths between every pair of entrances, kept inside the chunk
+    let entrances: Vec<GridPos> = chunk.entrances.keys().copied().collect();
+    let mut links = Vec::new();
+    for (i, &from) in entrances.iter().enumerate() {
+        for &to in &entrances[i + 1..] {
+            let within = |a: GridPos, b: GridPos| b.chunk() == coord && chunk.passable(a, b);
+            if let Some((path, cost)) = grid_a_star(from, to, cells, within) {
+                let back: Vec<GridPos> = path.iter().rev().skip(1).copied().collect();
+                links.push((from, NavEdge { to, cost, path: path[1..].to_vec() }));
+                links.push((to, NavEdge { to: from, cost, path: back }));
+            }
+        }
+    }
+    for (from, edge) in links {
+        chunk.entrances.entry(from).or_default().push(edge);
+    }
+
+    chunk
+}
+
+/// Baked navigation chunks
+#[derive(Resource, Default)]
+pub struct NavMesh {
+    chunks: HashMap<ChunkCoord, NavChunk>,
+    /// Settlement structures per chunk (deterministic, generated once)
+    structures: HashMap<ChunkCoord, Vec<StructureSpawn>>,
+    /// Footprint (center, radius) of every building seen, to invalidate it on removal
+    buildings: HashMap<Entity, (Vec2, f32)>,
+    /// What's left of this tick's budget
+    searches_left: usize,
+    bakes_left: usize,
+    /// A bake was refused during the current query because its budget ran out
+    bake_refused: bool,
+}
+
+impl NavMesh {
+    /// Path from `start` to `goal` as cell centers (start cell included); empty if none was found.
+    /// None when this tick's budget is spent: the query should be asked again next tick.
+    pub fn find_path(
+        &mut self,
+        terrain: &WorldTerrain,
+        obstacles: &SpatialObstacleGrid,
+        start_world: Vec3,
+        goal_world: Vec3,
+    ) -> Option<Vec<Vec3>> {
+        let start = world_to_grid(start_world);
+        let goal = world_to_grid(goal_world);
+        if start == goal {
+            return Some(vec![goal_world]);
+        }
+        if self.searches_left == 0 {
+            return None;
+        }
+        self.searches_left -= 1;
+
+        let query_budget = MAX_BAKES_PER_QUERY.min(self.bakes_left);
+        let mut budget = query_budget;
+        self.bake_refused = false;
+        let (start_chunk, goal_chunk) = (start.chunk(), goal.chunk());
+        let close = (start_chunk.x - goal_chunk.x).abs() <= LOCAL_QUERY_CHUNKS
+            && (start_chunk.z - goal_chunk.z).abs() <= LOCAL_QUERY_CHUNKS;
+
+        let mut cells = None;
+        if close {
+            for x in start_chunk.x.min(goal_chunk.x)..=start_chunk.x.max(goal_chunk.x) {
+                for z in start_chunk.z.min(goal_chunk.z)..=start_chunk.z.max(goal_chunk.z) {
+                    self.ensure_chunk(terrain, obstacles, ChunkCoord::new(x, z), &mut budget);
+                }
+            }
+            cells = grid_a_star(start, goal, LOCAL_MAX_NODES, |a, b| self.passable(a, b)).map(|(path, _)| path);
+        }
+        let cells = cells.or_else(|| self.hierarchical_path(terrain, obstacles, start, goal, &mut budget));
+        self.bakes_left -= query_budget - budget;
+
+        // Cut short by the tick's bake budget rather than the query's own: try again next tick
+        if cells.is_none() && self.bake_refused && query_budget < MAX_BAKES_PER_QUERY {
+            return None;
+        }
+        Some(
+            cells
+                .map(|cells| cells.into_iter().map(|cell| grid_to_world(terrain, cell)).collect())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Step between neighboring cells of baked chunks
+    fn passable(&self, from: GridPos, to: GridPos) -> bool {
+        let (Some(from_chunk), Some(to_chunk)) = (self.chunks.get(&from.chunk()), self.chunks.get(&to.chunk())) else {
+            return false;
+        };
+        let (from, to) = (from.local_index(), to.local_index());
+        to_chunk.walkable[to] && (to_chunk.heights[to] - from_chunk.heights[from]).abs() <= NAV_MAX_STEP
+    }
+
+    /// Bake a chunk if it isn't cached; false if it isn't and the budget is spent
+    fn ensure_chunk(
+        &mut self,
+        terrain: &WorldTerrain,
+        obstacles: &SpatialObstacleGrid,
+        coord: ChunkCoord,
+        budget: &mut usize,
+    ) -> bool {
+        if self.chunks.contains_key(&coord) {
+            return true;
+        }
+        if *budget == 0 {
+            self.bake_refused = true;
+            return false;
+        }
+        *budget -= 1;
+
+        // Structures reach at most one chunk past their own; border probes look one cell further
+        let nearby = coord.chunks_in_radius(2);
+        for near in &nearby {
+            self.structures
+                .entry(*near)
+                .or_insert_with(|| generate_chunk_structures(&terrain.generator, *near));
+        }
+        let structures: Vec<&StructureSpawn> = nearby.iter().flat_map(|near| &self.structures[near]).collect();
+        let chunk = bake_chunk(coord, |cell| probe_cell(terrain, obstacles, &structures, cell));
+        trace!("Baked nav chunk {:?} ({} entrances)", coord, chunk.entrances.len());
+        self.chunks.insert(coord, chunk);
+        true
+    }
+
+    /// Paths between a cell and each entrance of its chunk, as (entrance, cost, path).
+    /// Outbound paths run from the cell to the entrance, inbound ones from the entrance
+    /// to the cell (each without its first cell). Cached on the chunk.
+    fn connect(&mut self, cell: GridPos, outbound: bool) -> Connections {
+        let coord = cell.chunk();
+        let Some(chunk) = self.chunks.get_mut(&coord) else {
+            return Vec::new();
+        };
+        if let Some(cached) = chunk.connections.get(&(cell, outbound)) {
+            return cached.clone();
+        }
+
+        let cells = (CELLS_PER_CHUNK * CELLS_PER_CHUNK) as usize;
+        let within = |a: GridPos, b: GridPos| b.chunk() == coord && chunk.passable(a, b);
+        let connections: Connections = chunk
+            .entrances
+            .keys()
+            .filter_map(|&entrance| {
+                let (from, to) = if outbound { (cell, entrance) } else { (entrance, cell) };
+                let (path, cost) = grid_a_star(from, to, cells, within)?;
+                Some((entrance, cost, path[1..].to_vec()))
+            })
+            .collect();
+        if chunk.connections.len() >= MAX_CACHED_CONNECTIONS {
+            chunk.connections.clear();
+        }
+        chunk.connections.insert((cell, outbound), connections.clone());
+        connections
+    }
+
+    /// A* over the entrance graph, then stitch the cached cell paths together
+    fn hierarchical_path(
+        &mut self,
+        terrain: &WorldTerrain,
+        obstacles: &SpatialObstacleGrid,
+        start: GridPos,
+        goal: GridPos,
+        budget: &mut usize,
+    ) -> Option<Vec<GridPos>> {
+        let (start_chunk, goal_chunk) = (start.chunk(), goal.chunk());
+        if !self.ensure_chunk(terrain, obstacles, start_chunk, budget)
+            || !self.ensure_chunk(terrain, obstacles, goal_chunk, budget)
+        {
+            return None;
+        }
+
+        let start_edges: Vec<NavEdge> = self
+            .connect(start, true)
+            .into_iter()
+            .map(|(to, cost, path)| NavEdge { to, cost, path })
+            .collect();
+        // Entrance -> (cost, path on to the goal)
+        let goal_edges: HashMap<GridPos, (f32, Vec<GridPos>)> = self
+            .connect(goal, false)
+            .into_iter()
+            .map(|(entrance, cost, path)| (entrance, (cost, path)))
+            .collect();
+        if start_edges.is_empty() || goal_edges.is_empty() {
+            return None;
+        }
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<GridPos, (GridPos, Vec<GridPos>)> = HashMap::new();
+        let mut g_score: HashMap<GridPos, f32> = HashMap::new();
+        g_score.insert(start, 0.0);
+        open.push(OpenNode { f_cost: (heuristic(start, goal) * 1000.0) as i32, pos: start });
+
+        let mut expanded = 0_usize;
+        while let Some(OpenNode { pos: current, .. }) = open.pop() {
+            expanded += 1;
+            if expanded > ABSTRACT_MAX_NODES {
+                return None;
+            }
+
+            if current == goal {
+                let mut segments = Vec::new();
+                let mut cur = current;
+                while cur != start {
+                    let (prev, segment) = came_from.remove(&cur)?;
+                    segments.push(segment);
+                    cur = prev;
+                }
+                let mut cells = vec![start];
+                for segment in segments.into_iter().rev() {
+                    cells.extend(segment);
+                }
+                return Some(cells);
+            }
+
+            let current_g = g_score.get(&current).copied().unwrap_or(f32::INFINITY);
+            let mut edges = Vec::new();
+            if self.ensure_chunk(terrain, obstacles, current.chunk(), budget) {
+                if let Some(chunk_edges) = self.chunks[&current.chunk()].entrances.get(&current) {
+                    edges.extend(chunk_edges.iter().cloned());
+                }
+            }
+            if current == start {
+                edges.extend(start_edges.iter().cloned());
+            }
+            if let Some((cost, path)) = goal_edges.get(&current) {
+                edges.push(NavEdge { to: goal, cost: *cost, path: path.clone() });
+            }
+
+            for edge in edges {
+                let tentative_g = current_g + edge.cost;
+                if tentative_g < g_score.get(&edge.to).copied().unwrap_or(f32::INFINITY) {
+                    g_score.insert(edge.to, tentative_g);
+                    let f = tentative_g + heuristic(edge.to, goal);
+                    open.push(OpenNode { f_cost: (f * 1000.0) as i32, pos: edge.to });
+                    came_from.insert(edge.to, (current, edge.path));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Drop every chunk with a cell within `radius` of `center` (plus one cell, since the
+    /// neighbors' entrances depend on the border cells)
+    fn invalidate_area(&mut self, center: Vec2, radius: f32) {
+        let reach = radius + NAV_CELL_SIZE;
+        let min = ChunkCoord::from_world_pos(Vec3::new(center.x - reach, 0.0, center.y - reach));
+        let max = ChunkCoord::from_world_pos(Vec3::new(center.x + reach, 0.0, center.y + reach));
+        for x in min.x..=max.x {
+            for z in min.z..=max.z {
+                if self.chunks.remove(&ChunkCoord::new(x, z)).is_some() {
+                    trace!("Invalidated nav chunk ({}, {})", x, z);
+                }
+            }
+        }
+    }
+}
+
+/// Hand out this tick's path search and chunk bake budget
+pub fn refill_nav_budget(mut navmesh: ResMut<NavMesh>) {
+    navmesh.searches_left = MAX_SEARCHES_PER_TICK;
+    navmesh.bakes_left = MAX_BAKES_PER_TICK;
+}
+
+/// Drop nav chunks under new or removed buildings and deformed terrain
+pub fn invalidate_nav_chunks(
+    mut navmesh: ResMut<NavMesh>,
+    new_buildings: Query<(Entity, &PlacedBuilding, &BuildingPosition), Added<PlacedBuilding>>,
+    mut removed_buildings: RemovedComponents<PlacedBuilding>,
+    changed_deltas: Query<&TerrainDeltaChunk, Changed<TerrainDeltaChunk>>,
+) {
+    for (entity, building, position) in new_buildings.iter() {
+        let def = building.building_type.definition();
+        let half_extents = Vec2::new(
+            def.footprint.x / 2.0 + def.flatten_radius,
+            def.footprint.y / 2.0 + def.flatten_radius,
+        );
+        let footprint = (Vec2::new(position.0.x, position.0.z), half_extents.length());
+        navmesh.buildings.insert(entity, footprint);
+        navmesh.invalidate_area(footprint.0, footprint.1);
+    }
+
+    for entity in removed_buildings.read() {
+        if let Some((center, radius)) = navmesh.buildings.remove(&entity) {
+            navmesh.invalidate_area(center, radius);
+        }
+    }
+
+    for delta in changed_deltas.iter() {
+        // Border vertices are shared, so the neighbors change too
+        for coord in delta.coord.chunks_in_radius(1) {
+            navmesh.chunks.remove(&coord);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three flat chunks in a row (x 0..96, z 0..32) with a wall across the middle one at
+    /// x = 40, open at z = 20 when `gap` is set; everything else is blocked
+    fn row_of_chunks(gap: bool) -> NavMesh {
+        let probe = |cell: GridPos| {
+            let inside = (0..3 * CELLS_PER_CHUNK).contains(&cell.x) && (0..CELLS_PER_CHUNK).contains(&cell.z);
+            let wall = cell.x == 40 && !(gap && cell.z == 20);
+            (inside && !wall, 0.0)
+        };
+        let mut navmesh = NavMesh::default();
+        for x in 0..3 {
+            let coord = ChunkCoord::new(x, 0);
+            navmesh.chunks.insert(coord, bake_chunk(coord, probe));
+        }
+        navmesh
+    }
+
+    #[test]
+    fn test_entrance_graph_routes_through_the_gap() {
+        let terrain = WorldTerrain::default();
+        let obstacles = SpatialObstacleGrid::default();
+        let (start, goal) = (GridPos { x: 4, z: 4 }, GridPos { x: 90, z: 28 });
+
+        let mut navmesh = row_of_chunks(true);
+        let path = navmesh.hierarchical_path(&terrain, &obstacles, start, goal, &mut 0).unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+        for pair in path.windows(2) {
+            assert!((pair[0].x - pair[1].x).abs() <= 1 && (pair[0].z - pair[1].z).abs() <= 1);
+            assert!(navmesh.passable(pair[0], pair[1]));
+        }
+        assert!(path.contains(&GridPos { x: 40, z: 20 }));
+
+        let mut walled = row_of_chunks(false);
+        assert!(walled.hierarchical_path(&terrain, &obstacles, start, goal, &mut 0).is_none());
+    }
+
+    #[test]
+    fn test_invalidation_drops_chunks_and_their_cached_connections() {
+        let terrain = WorldTerrain::default();
+        let obstacles = SpatialObstacleGrid::default();
+        let (start, goal) = (GridPos { x: 4, z: 4 }, GridPos { x: 90, z: 28 });
+        let mut navmesh = row_of_chunks(true);
+        navmesh.hierarchical_path(&terrain, &obstacles, start, goal, &mut 0).unwrap();
+        assert!(navmesh.chunks[&start.chunk()].connections.contains_key(&(start, true)));
+        assert!(navmesh.chunks[&goal.chunk()].connections.contains_key(&(goal, false)));
+
+        // A building in the first chunk drops it (and its cache) but leaves the far one alone
+        navmesh.invalidate_area(Vec2::new(10.0, 10.0), 4.0);
+        assert!(!navmesh.chunks.contains_key(&start.chunk()));
+        assert!(navmesh.chunks.contains_key(&ChunkCoord::new(1, 0)));
+        assert!(navmesh.chunks[&goal.chunk()].connections.contains_key(&(goal, false)));
+
+        // With no bakes left the start chunk can't come back, so the query is deferred
+        navmesh.searches_left = MAX_SEARCHES_PER_TICK;
+        let (start_world, goal_world) = (grid_to_world(&terrain, start), grid_to_world(&terrain, goal));
+        assert_eq!(navmesh.find_path(&terrain, &obstacles, start_world, goal_world), None);
+    }
+}
@@ -2,23 +2,38 @@
 //!
 //! Goals for v1:
 //! - Spawn a simple damageable NPC near the player spawn.
-//! - Wander around using lightweight grid A* pathfinding over the heightfield terrain.
+//! - Wander around using hierarchical pathfinding over the baked navmesh (`navmesh`).
 //! - Server-authoritative position/rotation replicated to clients.
+//!
+//! What an NPC does each tick is decided by its archetype's behaviour tree
+//! (`npc_behaviour`); the state functions here carry out the chosen leaf.
+
+use std::collections::HashMap;
 
 use bevy::prelude::*;
 use lightyear::prelude::*;
 use lightyear::prelude::server::Started;
 
 use shared::{
-    ground_clearance_center, npc_capsule_endpoints, npc_head_center, Npc, NpcArchetype, NpcPosition,
+    ground_clearance_center, HitboxPose, HitboxStance, Npc, NpcArchetype, NpcHitboxStance, NpcPosition,
     NpcRotation, NpcDamageEvent, WorldTerrain, FIXED_TIMESTEP_HZ, Health,
-    PlacedBuilding, BuildingPosition,
-    SpatialObstacleGrid, ObstacleEntry,
+    PlacedBuilding, BuildingPosition, Player, PlayerPosition,
+    SpatialObstacleGrid, ObstacleEntry, NpcAggression, NpcAttack, NpcActiveBehaviour, NpcIndoors,
+    SchedulePlace, WorldTime, BuildingType, MedievalTownBuilding, NpcAlert, NpcAwareness, TraderStock,
     // NPC constants from shared
-    npc_max_health, NPC_MOVE_SPEED, NPC_TURN_SPEED, NPC_WANDER_RADIUS,
-    NPC_IDLE_TIME_MIN, NPC_IDLE_TIME_MAX, NPC_MIN_TARGET_DIST, DEAD_NPC_DESPAWN_TIME,
+    npc_max_health, NPC_MOVE_SPEED, NPC_TURN_SPEED,
+    NPC_IDLE_TIME_MIN, NPC_IDLE_TIME_MAX, NPC_MIN_TARGET_DIST, DEAD_NPC_DESPAWN_TIME, NPC_CHASE_SPEED_MULT,
 };
 
+use crate::dialogue::Conversations;
+use crate::navmesh::NavMesh;
+use crate::npc_behaviour::{NpcBehaviours, NpcBrain};
+use crate::npc_combat::NpcCombatSettings;
+use crate::npc_crowd::{FollowTarget, NpcSquad, COMPANION_SLOT, GUARD_FORMATION};
+use crate::npc_perception::SIGHT_CHECK_INTERVAL;
+use crate::npc_population::NpcOrigin;
+use crate::trade::{TraderRestock, TOWN_STALLS};
+
 // =============================================================================
 // SPATIAL GRID (obstacle caching for O(1) lookups)
 // =============================================================================
@@ -113,6 +128,7 @@ pub fn spawn_npcs_once(
             NpcRotation(0.0),
             Health::new(npc_max_health(NpcArchetype::Barbarian)),
             NpcWander::new(pos, 18.0, npc_id),
+            NpcOrigin::Fixed,
             Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
         ));
 
@@ -130,6 +146,7 @@ pub fn spawn_npcs_once(
         NpcRotation(0.0),
         Health::new(npc_max_health(NpcArchetype::Knight)),
         NpcWander::new(knight_pos, 8.0, 100), // Smaller wander radius for dialogue NPCs
+        NpcOrigin::Fixed,
         Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
     ));
     trace!("Spawned Knight NPC at {:?}", knight_pos);
@@ -145,9 +162,29 @@ pub fn spawn_npcs_once(
         NpcRotation(0.0),
         Health::new(npc_max_health(NpcArchetype::RogueHooded)),
         NpcWander::new(rogue_pos, 10.0, 101),
+        NpcOrigin::Fixed,
         Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
     ));
     trace!("Spawned RogueHooded NPC at {:?}", rogue_pos);
+
+    // Hostile Rangers camp out past the edge of the spawn area
+    for (i, (x, z)) in [(140.0_f32, -60.0_f32), (-120.0, 110.0)].iter().enumerate() {
+        let npc_id = 200 + i as u64;
+        let pos = Vec3::new(*x, terrain.get_height(*x, *z) + ground_clearance_center(), *z);
+        commands.spawn((
+            Npc {
+                id: npc_id,
+                archetype: NpcArchetype::Ranger,
+            },
+            NpcPosition(pos),
+            NpcRotation(0.0),
+            Health::new(npc_max_health(NpcArchetype::Ranger)),
+            NpcWander::new(pos, 20.0, npc_id),
+            NpcOrigin::Fixed,
+            Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+        ));
+        trace!("Spawned Ranger NPC {} at {:?}", npc_id, pos);
+    }
 }
 
 /// Spawn NPCs for the medieval town.
@@ -155,17 +192,35 @@ pub fn spawn_npcs_once(
 /// Distributes ~150 NPCs around the town:
 /// - 2 Knights near the central manors
 /// - ~110 RogueHooded throughout the town
+/// - 3 RogueHooded traders at stalls on the town square (`TOWN_STALLS`)
 /// - ~38 Barbarians as guards
+///
+/// Each gets `NpcPlaces` (house door, workplace, tavern, patrol route) from `buildings`
+/// for its day/night schedule.
 pub fn spawn_medieval_town_npcs(
     commands: &mut Commands,
     terrain: &WorldTerrain,
     town_center: Vec3,
+    buildings: &[MedievalTownBuilding],
     npc_id_start: &mut u64,
 ) {
     use shared::structures::MEDIEVAL_SPACING;
 
     let mut rng = XorShift64::new(42_u64 ^ (town_center.x as u64) ^ (town_center.z as u64));
 
+    let doors: Vec<(BuildingType, Vec3)> = buildings
+        .iter()
+        .map(|building| (building.building_type, town_door(terrain, building)))
+        .collect();
+    let manor_doors: Vec<Vec3> =
+        doors.iter().filter(|(kind, _)| *kind == BuildingType::House10).map(|(_, door)| *door).collect();
+    let house_doors: Vec<Vec3> =
+        doors.iter().filter(|(kind, _)| *kind != BuildingType::House10).map(|(_, door)| *door).collect();
+    // The biggest house by the square doubles as the tavern
+    let tavern_doors: Vec<Vec3> =
+        doors.iter().filter(|(kind, _)| *kind == BuildingType::House09).map(|(_, door)| *door).collect();
+    let tavern = nearest_point(&tavern_doors, town_center).or_else(|| nearest_point(&manor_doors, town_center));
+
     // Spawn 2 Knights near the manors (east and west of town square)
     for (i, x_offset) in [18.0_f32, -18.0].iter().enumerate() {
         let npc_id = *npc_id_start;
@@ -185,6 +240,13 @@ pub fn spawn_medieval_town_npcs(
             NpcRotation(if i == 0 { std::f32::consts::PI } else { 0.0 }), // Face center
             Health::new(npc_max_health(NpcArchetype::Knight)),
             NpcWander::new(pos, 12.0, npc_id), // Small wander radius for dialogue NPCs
+            NpcOrigin::Fixed,
+            NpcPlaces {
+                home: nearest_point(&manor_doors, pos),
+                work: Some(pos),
+                tavern,
+                route: Vec::new(),
+            },
             Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
         ));
         trace!("Spawned medieval town Knight {} at {:?}", npc_id, pos);
@@ -215,6 +277,11 @@ pub fn spawn_medieval_town_npcs(
             let y = terrain.get_height(x, z) + ground_clearance_center();
             let pos = Vec3::new(x, y, z);
 
+            // Works at a stall somewhere around the town square
+            let work_x = town_center.x + (rng.next_f32() - 0.5) * 30.0;
+            let work_z = town_center.z + (rng.next_f32() - 0.5) * 30.0;
+            let work = Vec3::new(work_x, terrain.get_height(work_x, work_z) + ground_clearance_center(), work_z);
+
             commands.spawn((
                 Npc {
                     id: npc_id,
@@ -224,13 +291,21 @@ pub fn spawn_medieval_town_npcs(
                 NpcRotation(rng.next_f32() * std::f32::consts::TAU),
                 Health::new(npc_max_health(NpcArchetype::RogueHooded)),
                 NpcWander::new(pos, 20.0, npc_id), // Medium wander radius
+                NpcOrigin::Fixed,
+                NpcPlaces {
+                    home: nearest_point(&house_doors, pos),
+                    work: Some(work),
+                    tavern,
+                    route: Vec::new(),
+                },
                 Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
             ));
             trace!("Spawned medieval town RogueHooded {} at {:?}", npc_id, pos);
         }
     }
 
-    // Add 14 more RogueHooded in the town square area
+    // Add 14 more RogueHooded in the town square area, strolling in pairs
+    let mut pair_leader: Option<Entity> = None;
     for _ in 0..14 {
         let npc_id = *npc_id_start;
         *npc_id_start += 1;
@@ -240,7 +315,7 @@ pub fn spawn_medieval_town_npcs(
         let y = terrain.get_height(x, z) + ground_clearance_center();
         let pos = Vec3::new(x, y, z);
 
-        commands.spawn((
+        let entity = commands.spawn((
             Npc {
                 id: npc_id,
                 archetype: NpcArchetype::RogueHooded,
@@ -249,11 +324,59 @@ pub fn spawn_medieval_town_npcs(
             NpcRotation(rng.next_f32() * std::f32::consts::TAU),
             Health::new(npc_max_health(NpcArchetype::RogueHooded)),
             NpcWander::new(pos, 25.0, npc_id),
+            NpcOrigin::Fixed,
+            NpcPlaces {
+                home: nearest_point(&house_doors, pos),
+                work: Some(pos),
+                tavern,
+                route: Vec::new(),
+            },
             Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
-        ));
+        )).id();
+        let squad = match pair_leader.take() {
+            Some(leader) => NpcSquad { leader, slot: COMPANION_SLOT },
+            None => {
+                pair_leader = Some(entity);
+                NpcSquad { leader: entity, slot: Vec2::ZERO }
+            }
+        };
+        commands.entity(entity).insert(squad);
         trace!("Spawned medieval town square RogueHooded {} at {:?}", npc_id, pos);
     }
 
+    // Traders keep their stalls around the square by day
+    for (kind, offset) in TOWN_STALLS {
+        let npc_id = *npc_id_start;
+        *npc_id_start += 1;
+
+        let x = town_center.x + offset.x;
+        let z = town_center.z + offset.y;
+        let y = terrain.get_height(x, z) + ground_clearance_center();
+        let pos = Vec3::new(x, y, z);
+
+        commands.spawn((
+            Npc {
+                id: npc_id,
+                archetype: NpcArchetype::RogueHooded,
+            },
+            NpcPosition(pos),
+            NpcRotation(offset.x.atan2(offset.y)), // Face the square
+            Health::new(npc_max_health(NpcArchetype::RogueHooded)),
+            NpcWander::new(pos, 8.0, npc_id),
+            NpcOrigin::Fixed,
+            NpcPlaces {
+                home: nearest_point(&house_doors, pos),
+                work: Some(pos),
+                tavern,
+                route: Vec::new(),
+            },
+            TraderStock::new(kind),
+            TraderRestock::new(kind),
+            Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+        ));
+        trace!("Spawned medieval town {} trader {} at {:?}", kind.display_name(), npc_id, pos);
+    }
+
     // Spawn Barbarians as guards (~38 total, 3 per block + entrances)
     // Place them on the outer edges of blocks (near streets)
     let barbarians_per_block = 3;
@@ -261,6 +384,18 @@ pub fn spawn_medieval_town_npcs(
         let block_center_x = town_center.x + *bx as f32 * MEDIEVAL_SPACING;
         let block_center_z = town_center.z + *bz as f32 * MEDIEVAL_SPACING;
 
+        // Patrol loop through the street crossings at the block's corners
+        let half = MEDIEVAL_SPACING * 0.5;
+        let route: Vec<Vec3> = [(-half, -half), (half, -half), (half, half), (-half, half)]
+            .iter()
+            .map(|(dx, dz)| {
+                let (x, z) = (block_center_x + dx, block_center_z + dz);
+                Vec3::new(x, terrain.get_height(x, z) + ground_clearance_center(), z)
+            })
+            .collect();
+
+        // The block's guards form a squad: the first leads, the others fall in behind it
+        let mut squad_leader: Option<Entity> = None;
         for i in 0..barbarians_per_block {
             let npc_id = *npc_id_start;
             *npc_id_start += 1;
@@ -274,7 +409,7 @@ pub fn spawn_medieval_town_npcs(
             let y = terrain.get_height(guard_x, guard_z) + ground_clearance_center();
             let pos = Vec3::new(guard_x, y, guard_z);
 
-            commands.spawn((
+            let entity = commands.spawn((
                 Npc {
                     id: npc_id,
                     archetype: NpcArchetype::Barbarian,
@@ -283,8 +418,22 @@ pub fn spawn_medieval_town_npcs(
                 NpcRotation(rng.next_f32() * std::f32::consts::TAU),
                 Health::new(npc_max_health(NpcArchetype::Barbarian)),
                 NpcWander::new(pos, 15.0, npc_id),
+                NpcOrigin::Fixed,
+                NpcPlaces {
+                    // Without a leader, guards walk the loop starting from different corners
+                    route: route.iter().cycle().skip(i).take(route.len()).copied().collect(),
+                    ..default()
+                },
                 Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
-            ));
+            )).id();
+            let squad = match squad_leader {
+                Some(leader) => NpcSquad { leader, slot: GUARD_FORMATION[(i - 1) % GUARD_FORMATION.len()] },
+                None => {
+                    squad_leader = Some(entity);
+                    NpcSquad { leader: entity, slot: Vec2::ZERO }
+                }
+            };
+            commands.entity(entity).insert(squad);
             trace!("Spawned medieval town Barbarian guard {} at {:?}", npc_id, pos);
         }
     }
@@ -326,6 +475,7 @@ pub fn spawn_medieval_town_npcs(
             NpcRotation(rng.next_f32() * std::f32::consts::TAU),
             Health::new(npc_max_health(NpcArchetype::Barbarian)),
             NpcWander::new(pos, 12.0, npc_id),
+            NpcOrigin::Fixed,
             Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
         ));
         trace!("Spawned medieval town entrance Barbarian {} at {:?}", npc_id, pos);
@@ -337,16 +487,29 @@ pub fn spawn_medieval_town_npcs(
     );
 }
 
+/// Standing spot just outside a town building's front door
+fn town_door(terrain: &WorldTerrain, building: &MedievalTownBuilding) -> Vec3 {
+    let def = building.building_type.definition();
+    // Buildings face along (cos r, sin r); step clear of the footprint and its flattened apron
+    let reach = def.footprint.max_element() * 0.5 + def.flatten_radius + 1.0;
+    let x = building.position.x + building.rotation.cos() * reach;
+    let z = building.position.z + building.rotation.sin() * reach;
+    Vec3::new(x, terrain.get_height(x, z) + ground_clearance_center(), z)
+}
+
+/// Closest of `points` to `to` on the XZ plane
+fn nearest_point(points: &[Vec3], to: Vec3) -> Option<Vec3> {
+    let dist_sq = |point: &Vec3| point.xz().distance_squared(to.xz());
+    points.iter().copied().min_by(|a, b| dist_sq(a).total_cmp(&dist_sq(b)))
+}
+
 // =============================================================================
 // AI / PATHFINDING
 // =============================================================================
 
 // NPC movement constants are now in shared/src/npc.rs:
 // NPC_MOVE_SPEED, NPC_TURN_SPEED, NPC_WANDER_RADIUS, NPC_IDLE_TIME_MIN/MAX, NPC_MIN_TARGET_DIST
-
-const GRID_CELL_SIZE: f32 = 2.0; // meters
-const GRID_MAX_STEP: f32 = 1.2; // max height delta allowed between neighbor cells
-const GRID_MAX_NODES: usize = 4000; // hard cap per path search (safety) - increased for larger paths
+// Path queries go through the baked navigation chunks in `navmesh`.
 
 /// NPC AI state
 pub enum NpcState {
@@ -357,6 +520,67 @@ pub enum NpcState {
         flee_timer: f32,
         panic_speed_boost: f32,
     },
+    /// Fighting a player (driven by `npc_combat::tick_npc_combat`)
+    Attacking {
+        target: Entity,
+        /// Seconds until the next swing / shot may start
+        cooldown: f32,
+        /// Seconds until a started melee swing lands
+        swing_timer: Option<f32>,
+        /// Seconds the target has been out of sight
+        lost_sight: f32,
+        /// Seconds until the chase path is recomputed
+        repath_timer: f32,
+    },
+}
+
+impl NpcState {
+    /// Start fighting `target`
+    pub fn attacking(target: Entity) -> Self {
+        NpcState::Attacking {
+            target,
+            cooldown: 0.0,
+            swing_timer: None,
+            lost_sight: 0.0,
+            repath_timer: 0.0,
+        }
+    }
+}
+
+/// Where `NpcState::Idle` picks its next wander target
+#[derive(Clone, Copy, Debug)]
+pub struct WanderArea {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Places a town NPC's schedule sends it to (server-only; wilderness NPCs have none)
+#[derive(Component, Clone, Debug, Default)]
+pub struct NpcPlaces {
+    /// Front door of its house
+    pub home: Option<Vec3>,
+    pub work: Option<Vec3>,
+    pub tavern: Option<Vec3>,
+    /// Guard patrol loop
+    pub route: Vec<Vec3>,
+}
+
+impl NpcPlaces {
+    pub fn get(&self, place: SchedulePlace) -> Option<Vec3> {
+        match place {
+            SchedulePlace::Home => self.home,
+            SchedulePlace::Work => self.work,
+            SchedulePlace::Tavern => self.tavern,
+        }
+    }
+}
+
+/// Where the last damage came from
+#[derive(Clone, Copy, Debug)]
+pub struct NpcThreat {
+    pub source: Vec3,
+    /// Set by a new hit so a running `Flee` restarts from the new source
+    pub fresh: bool,
 }
 
 #[derive(Component)]
@@ -373,6 +597,16 @@ pub struct NpcWander {
     pub current_speed_multiplier: f32,
     pub idle_rotation_target: f32,
     pub idle_rotation_speed: f32,
+    /// Last thing that hurt the NPC (cleared once it got away or fought it off)
+    pub threat: Option<NpcThreat>,
+    /// Player the NPC wants to fight (the `Attack` leaf acts on it)
+    pub combat_target: Option<Entity>,
+    /// Next point of a `Patrol` leaf
+    pub patrol_index: usize,
+    /// What it has seen or heard lately (`npc_perception`)
+    pub awareness: NpcAwareness,
+    /// Seconds until its next look around for players
+    pub look_timer: f32,
 }
 
 impl NpcWander {
@@ -380,6 +614,8 @@ impl NpcWander {
         let mut rng = XorShift64::new(seed ^ 0xC0FFEE_u64);
         // Start idling briefly so it doesn't immediately run off
         let idle_timer = 0.5 + rng.next_f32() * 1.0;
+        // Spread sight checks over ticks
+        let look_timer = rng.next_f32() * SIGHT_CHECK_INTERVAL;
         Self {
             home,
             target: home,
@@ -391,52 +627,99 @@ impl NpcWander {
             current_speed_multiplier: 1.0,
             idle_rotation_target: 0.0,
             idle_rotation_speed: 0.3,
+            threat: None,
+            combat_target: None,
+            patrol_index: 0,
+            awareness: NpcAwareness::default(),
+            look_timer,
         }
     }
+
+    /// Stop where it stands and idle for a random while
+    pub fn rest(&mut self) {
+        self.idle_timer = NPC_IDLE_TIME_MIN + self.rng.next_f32() * (NPC_IDLE_TIME_MAX - NPC_IDLE_TIME_MIN);
+        self.idle_rotation_target = self.rng.next_f32() * std::f32::consts::TAU;
+        self.state = NpcState::Idle;
+        self.path.clear();
+        self.waypoint = 0;
+    }
 }
 
-/// React to damage events by entering flee state
+/// React to damage events: remember the threat, and fighters pick the player who hurt them
+/// as their target (the behaviour tree decides whether to fight or flee)
 pub fn react_to_damage(
     mut commands: Commands,
-    mut npcs: Query<(Entity, &mut NpcWander, &NpcPosition, &NpcDamageEvent)>,
+    settings: Res<NpcCombatSettings>,
+    mut npcs: Query<(Entity, &Npc, &mut NpcWander, &NpcDamageEvent)>,
+    players: Query<&Health, With<Player>>,
 ) {
-    for (entity, mut wander, _pos, damage_event) in npcs.iter_mut() {
-        // Randomize flee parameters
-        let flee_duration = 5.0 + wander.rng.next_f32() * 3.0; // 5-8 seconds
-        let panic_boost = 1.5 + wander.rng.next_f32() * 0.3;   // 1.5-1.8x speed
-
-        // Enter flee state (NPC will calculate flee direction in tick_fleeing_state)
-        wander.state = NpcState::Fleeing {
-            from_position: damage_event.damage_source_position,
-            flee_timer: flee_duration,
-            panic_speed_boost: panic_boost,
-        };
+    for (entity, npc, mut wander, damage_event) in npcs.iter_mut() {
+        // Remove the damage event component (consumed)
+        commands.entity(entity).remove::<NpcDamageEvent>();
 
-        // Clear current path so flee logic takes over immediately
-        wander.path.clear();
-        wander.waypoint = 0;
+        let source = damage_event.damage_source_position;
+        wander.threat = Some(NpcThreat { source, fresh: true });
 
-        trace!(
-            "NPC fleeing from damage source at {:?}, duration {:.1}s, speed boost {:.2}x",
-            damage_event.damage_source_position,
-            flee_duration,
-            panic_boost
-        );
+        let attacker = damage_event.attacker.filter(|&e| players.get(e).is_ok_and(|health| !health.is_dead()));
+        wander.awareness.notice(NpcAlert::Alarmed, source, attacker);
 
-        // Remove the damage event component (consumed)
-        commands.entity(entity).remove::<NpcDamageEvent>();
+        let profile = settings.profile(npc.archetype);
+        let fighter = profile.aggression != NpcAggression::Passive && profile.attack != NpcAttack::None;
+        // Already fighting: stay on the current target
+        if !fighter || wander.combat_target.is_some() {
+            continue;
+        }
+        if let Some(attacker) = attacker {
+            wander.combat_target = Some(attacker);
+            debug!("NPC {} ({:?}) fighting back against {:?}", npc.id, npc.archetype, attacker);
+        }
     }
 }
 
-/// Tick wandering NPC AI (server-authoritative).
+/// Tick NPC AI (server-authoritative): run each NPC's behaviour tree and record the active leaf.
 pub fn tick_npc_ai(
+    mut commands: Commands,
     terrain: Res<WorldTerrain>,
     obstacle_grid: Res<SpatialObstacleGrid>,
-    mut npcs: Query<(&Npc, &mut NpcPosition, &mut NpcRotation, &Health, &mut NpcWander)>,
+    mut navmesh: ResMut<NavMesh>,
+    behaviours: Res<NpcBehaviours>,
+    conversations: Res<Conversations>,
+    world_time: Query<&WorldTime>,
+    players: Query<(&PlayerPosition, &Health), (With<Player>, Without<Npc>)>,
+    mut npcs: Query<
+        (
+            Entity,
+            &Npc,
+            &mut NpcPosition,
+            &mut NpcRotation,
+            &Health,
+            &mut NpcWander,
+            Option<&NpcPlaces>,
+            Option<&mut NpcActiveBehaviour>,
+            Has<NpcIndoors>,
+            Option<&NpcSquad>,
+            Has<TraderStock>,
+        ),
+        Without<Player>,
+    >,
 ) {
     let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
-
-    for (npc, mut pos, mut rot, health, mut wander) in npcs.iter_mut() {
+    let world_time = world_time.single().ok();
+    let night = world_time.is_some_and(|time| !time.is_day());
+    let time_of_day = world_time.map_or(0.5, |time| time.normalized_time());
+
+    // Where squad leaders are out and about (followers keep formation on them)
+    let leaders: HashMap<Entity, (Vec3, f32)> = npcs
+        .iter()
+        .filter(|(entity, _, _, _, health, _, _, _, indoors, squad, _)| {
+            !health.is_dead() && !indoors && squad.is_some_and(|squad| squad.leader == *entity)
+        })
+        .map(|(entity, _, pos, rot, ..)| (entity, (pos.0, rot.0)))
+        .collect();
+
+    for (entity, npc, mut pos, mut rot, health, mut wander, places, active, was_indoors, squad, trader) in
+        npcs.iter_mut()
+    {
         if health.is_dead() {
             // Stop movement when dead
             wander.path.clear();
@@ -444,41 +727,75 @@ pub fn tick_npc_ai(
             continue;
         }
 
-        match &wander.state {
-            NpcState::Fleeing { from_position, flee_timer, panic_speed_boost } => {
-                let from_pos = *from_position;
-                let timer = *flee_timer;
-                let boost = *panic_speed_boost;
-                tick_fleeing_state(
-                    &mut wander,
-                    &mut pos,
-                    &mut rot,
-                    &terrain,
-                    &obstacle_grid,
-                    from_pos,
-                    timer,
-                    boost,
-                    dt,
-                    npc.id,
-                );
+        let nearest_player = players
+            .iter()
+            .filter(|(_, health)| !health.is_dead())
+            .map(|(position, _)| position.0)
+            .min_by(|a, b| a.distance_squared(pos.0).total_cmp(&b.distance_squared(pos.0)));
+
+        let talking_to = conversations
+            .partner(entity)
+            .and_then(|player| players.get(player).ok())
+            .map(|(position, _)| position.0);
+
+        let mut brain = NpcBrain {
+            npc_id: npc.id,
+            wander: &mut wander,
+            pos: &mut pos,
+            rot: &mut rot,
+            navmesh: &mut navmesh,
+            health_fraction: health.percentage(),
+            terrain: &terrain,
+            obstacles: &obstacle_grid,
+            places,
+            nearest_player,
+            night,
+            time_of_day,
+            dt,
+            active: None,
+            indoors: false,
+            leader: squad.filter(|squad| squad.leader != entity).and_then(|squad| {
+                let &(position, yaw) = leaders.get(&squad.leader)?;
+                Some(FollowTarget { position, yaw, slot: squad.slot })
+            }),
+            stall: places.and_then(|places| places.work).filter(|_| trader),
+            talking_to,
+        };
+        behaviours.tree(npc.archetype).tick(&mut brain);
+        let label = brain.active.unwrap_or("None");
+
+        if brain.indoors != was_indoors {
+            if brain.indoors {
+                commands.entity(entity).insert(NpcIndoors);
+                debug!("NPC {} went indoors", npc.id);
+            } else {
+                commands.entity(entity).remove::<NpcIndoors>();
+                debug!("NPC {} came outside", npc.id);
             }
-            NpcState::Idle => {
-                tick_idle_state(&mut wander, &mut rot, &terrain, &obstacle_grid, pos.0, dt, npc.id);
+        }
+
+        match active {
+            Some(mut active) => {
+                if active.0 != label {
+                    active.0 = label.to_string();
+                }
             }
-            NpcState::Walking => {
-                tick_walking_state(&mut wander, &mut pos, &mut rot, &terrain, dt, npc.id);
+            None => {
+                commands.entity(entity).insert(NpcActiveBehaviour(label.to_string()));
             }
         }
     }
 }
 
 /// Tick idle state: rotate slowly, count down timer
-fn tick_idle_state(
+pub(crate) fn tick_idle_state(
     wander: &mut NpcWander,
     rot: &mut NpcRotation,
+    navmesh: &mut NavMesh,
     terrain: &WorldTerrain,
     obstacles: &SpatialObstacleGrid,
     current_pos: Vec3,
+    area: WanderArea,
     dt: f32,
     npc_id: u64,
 ) {
@@ -507,13 +824,17 @@ fn tick_idle_state(
             wander.target = pick_random_target(
                 terrain,
                 obstacles,
-                wander.home,
+                area.center,
                 current_pos,
-                NPC_WANDER_RADIUS,
-                NPC_MIN_TARGET_DIST,
+                area.radius,
+                NPC_MIN_TARGET_DIST.min(area.radius * 0.5),
                 &mut wander.rng,
             );
-            wander.path = find_path_a_star(terrain, obstacles, current_pos, wander.target);
+            // Out of pathfinding budget this tick: stay idle and pick again next tick
+            let Some(path) = navmesh.find_path(terrain, obstacles, current_pos, wander.target) else {
+                return;
+            };
+            wander.path = path;
             wander.waypoint = 0;
 
             if wander.path.is_empty() {
@@ -550,7 +871,7 @@ fn tick_idle_state(
 
 /// Smoothly rotate current angle toward target angle at a given speed.
 /// Returns the new angle after rotation.
-fn smooth_rotate_toward(current: f32, target: f32, turn_speed: f32, dt: f32) -> f32 {
+pub(crate) fn smooth_rotate_toward(current: f32, target: f32, turn_speed: f32, dt: f32) -> f32 {
     use std::f32::consts::PI;
 
     // Normalize angle difference to [-PI, PI]
@@ -572,7 +893,7 @@ fn smooth_rotate_toward(current: f32, target: f32, turn_speed: f32, dt: f32) ->
 }
 
 /// Tick walking state: follow path, handle waypoints, add variety
-fn tick_walking_state(
+pub(crate) fn tick_walking_state(
     wander: &mut NpcWander,
     pos: &mut NpcPosition,
     rot: &mut NpcRotation,
@@ -653,10 +974,11 @@ fn tick_walking_state(
 }
 
 /// Tick fleeing state: run away from threat, decrease timer
-fn tick_fleeing_state(
+pub(crate) fn tick_fleeing_state(
     wander: &mut NpcWander,
     pos: &mut NpcPosition,
     rot: &mut NpcRotation,
+    navmesh: &mut NavMesh,
     terrain: &WorldTerrain,
     obstacles: &SpatialObstacleGrid,
     from_position: Vec3,
@@ -669,11 +991,7 @@ fn tick_fleeing_state(
 
     // Check if flee duration expired
     if flee_timer <= 0.0 {
-        wander.idle_timer = NPC_IDLE_TIME_MIN + wander.rng.next_f32() * (NPC_IDLE_TIME_MAX - NPC_IDLE_TIME_MIN);
-        wander.idle_rotation_target = wander.rng.next_f32() * std::f32::consts::TAU;
-        wander.state = NpcState::Idle;
-        wander.path.clear();
-        wander.waypoint = 0;
+        wander.rest();
         trace!("NPC {} stopped fleeing, returning to idle", npc_id);
         return;
     }
@@ -689,8 +1007,9 @@ fn tick_fleeing_state(
         let flee_y = terrain.get_height(flee_target_xz.x, flee_target_xz.y);
         let flee_target = Vec3::new(flee_target_xz.x, flee_y + ground_clearance_center(), flee_target_xz.y);
 
-        // Try to pathfind to flee target (avoiding obstacles)
-        wander.path = find_path_a_star(terrain, obstacles, pos.0, flee_target);
+        // Try to pathfind to flee target (avoiding obstacles); fleeing can't wait for the
+        // next tick's pathfinding budget, so run straight at it if this one's spent
+        wander.path = navmesh.find_path(terrain, obstacles, pos.0, flee_target).unwrap_or_else(|| vec![flee_target]);
         wander.waypoint = 0;
 
         if wander.path.is_empty() {
@@ -842,169 +1161,6 @@ fn pick_random_target(
     Vec3::new(x, y, z)
 }
 
-// =============================================================================
-// PATHFINDING (grid A*)
-// =============================================================================
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct GridPos {
-    x: i32,
-    z: i32,
-}
-
-fn world_to_grid(p: Vec3) -> GridPos {
-    GridPos {
-        x: (p.x / GRID_CELL_SIZE).round() as i32,
-        z: (p.z / GRID_CELL_SIZE).round() as i32,
-    }
-}
-
-fn grid_to_world(terrain: &WorldTerrain, g: GridPos) -> Vec3 {
-    let x = g.x as f32 * GRID_CELL_SIZE;
-    let z = g.z as f32 * GRID_CELL_SIZE;
-    let y = terrain.get_height(x, z) + ground_clearance_center();
-    Vec3::new(x, y, z)
-}
-
-fn heuristic(a: GridPos, b: GridPos) -> f32 {
-    // Euclidean in grid-space
-    let dx = (a.x - b.x) as f32;
-    let dz = (a.z - b.z) as f32;
-    (dx * dx + dz * dz).sqrt()
-}
-
-fn find_path_a_star(
-    terrain: &WorldTerrain,
-    obstacles: &SpatialObstacleGrid,
-    start_world: Vec3,
-    goal_world: Vec3,
-) -> Vec<Vec3> {
-    use std::cmp::Ordering;
-    use std::collections::{BinaryHeap, HashMap};
-
-    let start = world_to_grid(start_world);
-    let goal = world_to_grid(goal_world);
-
-    if start == goal {
-        return vec![goal_world];
-    }
-
-    #[derive(Clone, Copy, Debug)]
-    struct OpenNode {
-        f_cost: i32,
-        pos: GridPos,
-    }
-
-    impl Eq for OpenNode {}
-    impl PartialEq for OpenNode {
-        fn eq(&self, other: &Self) -> bool {
-            self.f_cost == other.f_cost && self.pos == other.pos
-        }
-    }
-    impl Ord for OpenNode {
-        fn cmp(&self, other: &Self) -> Ordering {
-            // Reverse for min-heap behavior.
-            other
-                .f_cost
-                .cmp(&self.f_cost)
-                .then_with(|| self.pos.x.cmp(&other.pos.x))
-                .then_with(|| self.pos.z.cmp(&other.pos.z))
-        }
-    }
-    impl PartialOrd for OpenNode {
-        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-            Some(self.cmp(other))
-        }
-    }
-
-    let mut open = BinaryHeap::new();
-    let mut came_from: HashMap<GridPos, GridPos> = HashMap::new();
-    let mut g_score: HashMap<GridPos, f32> = HashMap::new();
-    let mut height_cache: HashMap<GridPos, f32> = HashMap::new();
-
-    let height = |p: GridPos, terrain: &WorldTerrain, cache: &mut HashMap<GridPos, f32>| -> f32 {
-        if let Some(h) = cache.get(&p) {
-            return *h;
-        }
-        let w = grid_to_world(terrain, p);
-        let h = w.y;
-        cache.insert(p, h);
-        h
-    };
-
-    g_score.insert(start, 0.0);
-    open.push(OpenNode {
-        f_cost: (heuristic(start, goal) * 1000.0) as i32,
-        pos: start,
-    });
-
-    let neighbors = |p: GridPos| -> [GridPos; 8] {
-        [
-            GridPos { x: p.x + 1, z: p.z },
-            GridPos { x: p.x - 1, z: p.z },
-            GridPos { x: p.x, z: p.z + 1 },
-            GridPos { x: p.x, z: p.z - 1 },
-            GridPos { x: p.x + 1, z: p.z + 1 },
-            GridPos { x: p.x + 1, z: p.z - 1 },
-            GridPos { x: p.x - 1, z: p.z + 1 },
-            GridPos { x: p.x - 1, z: p.z - 1 },
-        ]
-    };
-
-    let mut expanded = 0_usize;
-    while let Some(OpenNode { pos: current, .. }) = open.pop() {
-        expanded += 1;
-        if expanded > GRID_MAX_NODES {
-            return Vec::new();
-        }
-
-        if current == goal {
-            // Reconstruct
-            let mut path = vec![current];
-            let mut cur = current;
-            while let Some(prev) = came_from.get(&cur).copied() {
-                path.push(prev);
-                cur = prev;
-            }
-            path.reverse();
-            return path.into_iter().map(|gp| grid_to_world(terrain, gp)).collect();
-        }
-
-        let current_h = height(current, terrain, &mut height_cache);
-
-        for n in neighbors(current) {
-            let n_h = height(n, terrain, &mut height_cache);
-            if (n_h - current_h).abs() > GRID_MAX_STEP {
-                continue; // too steep / cliff
-            }
-
-            // Check if neighbor is inside any obstacle footprint (O(1) spatial lookup)
-            let neighbor_world = grid_to_world(terrain, n);
-            let neighbor_xz = Vec2::new(neighbor_world.x, neighbor_world.z);
-            if obstacles.point_blocked(neighbor_xz) {
-                continue; // Skip - inside an obstacle
-            }
-
-            let diag = (n.x != current.x) && (n.z != current.z);
-            let step_cost = if diag { 1.4142 } else { 1.0 };
-
-            let tentative_g = g_score.get(&current).copied().unwrap_or(f32::INFINITY) + step_cost;
-            if tentative_g < g_score.get(&n).copied().unwrap_or(f32::INFINITY) {
-                came_from.insert(n, current);
-                g_score.insert(n, tentative_g);
-
-                let f = tentative_g + heuristic(n, goal);
-                open.push(OpenNode {
-                    f_cost: (f * 1000.0) as i32,
-                    pos: n,
-                });
-            }
-        }
-    }
-
-    Vec::new()
-}
-
 // =============================================================================
 // SMALL DETERMINISTIC RNG
 // =============================================================================
@@ -1037,10 +1193,43 @@ impl XorShift64 {
     }
 }
 
-// Silence unused warnings until we hook these helpers into bullet hit detection / debug.
-#[allow(dead_code)]
-fn _hitbox_helpers_smoke_test(p: Vec3) {
-    let _ = npc_head_center(p);
-    let _ = npc_capsule_endpoints(p);
+/// Pose an NPC's hitbox rig from its AI state (NPCs only hold a weapon while fighting).
+pub fn npc_hitbox_pose(center: Vec3, yaw: f32, wander: Option<&NpcWander>) -> HitboxPose {
+    let speed_xz = match wander.map(|w| &w.state) {
+        Some(NpcState::Walking) => NPC_MOVE_SPEED * wander.map_or(1.0, |w| w.current_speed_multiplier),
+        Some(NpcState::Fleeing { panic_speed_boost, .. }) => NPC_MOVE_SPEED * panic_speed_boost,
+        // A chase path is only kept while out of attack range
+        Some(NpcState::Attacking { .. }) if wander.is_some_and(|w| !w.path.is_empty()) => {
+            NPC_MOVE_SPEED * NPC_CHASE_SPEED_MULT
+        }
+        _ => 0.0,
+    };
+    HitboxPose {
+        center,
+        yaw,
+        stance: HitboxStance::from_movement(speed_xz, true),
+        armed: matches!(wander.map(|w| &w.state), Some(NpcState::Attacking { .. })),
+    }
+}
+
+/// Replicate the stance hit detection poses each NPC with, for the client's debug rig
+pub fn sync_npc_hitbox_stance(
+    mut commands: Commands,
+    mut npcs: Query<(Entity, &NpcPosition, &NpcRotation, &NpcWander, Option<&mut NpcHitboxStance>)>,
+) {
+    for (entity, position, rotation, wander, replicated) in npcs.iter_mut() {
+        let pose = npc_hitbox_pose(position.0, rotation.0, Some(wander));
+        let stance = NpcHitboxStance { stance: pose.stance, armed: pose.armed };
+        match replicated {
+            Some(mut replicated) => {
+                if *replicated != stance {
+                    *replicated = stance;
+                }
+            }
+            None => {
+                commands.entity(entity).insert(stance);
+            }
+        }
+    }
 }
 
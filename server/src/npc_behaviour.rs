@@ -0,0 +1,489 @@
+//! NPC behaviour trees: loading per-archetype trees and carrying out their leaves
+//!
+//! Trees are read from `shared/assets/behaviours/<archetype>.ron` at startup, so they can
+//! be tweaked without a rebuild; a missing or broken file falls back to the built-in copy.
+//! `NpcBrain` is the `BehaviourContext` the trees run against: conditions read the NPC's
+//! blackboard (`NpcWander::threat`, `combat_target`, `awareness`, health, nearby players,
+//! time of day) and leaves drive the movement states in `npc`. Town NPCs also carry
+//! `NpcPlaces` (home, work, tavern, patrol route) for their archetype's `Schedule` to send
+//! them to. Fighting itself runs in `npc_combat::tick_npc_combat` while the `Attack` leaf
+//! keeps the NPC in `NpcState::Attacking`.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use shared::{
+    behaviour_file_name, default_behaviour_source, ground_clearance_center, BehaviourCondition, BehaviourContext,
+    BehaviourLeaf, BehaviourStatus, BehaviourTree, NpcAlert, NpcArchetype, NpcPosition, NpcRotation, SchedulePlace,
+    SpatialObstacleGrid, WorldTerrain, NPC_TURN_SPEED,
+};
+
+use crate::npc::{
+    smooth_rotate_toward, tick_fleeing_state, tick_idle_state, tick_walking_state, NpcPlaces, NpcState, NpcThreat,
+    NpcWander, WanderArea,
+};
+use crate::navmesh::NavMesh;
+use crate::npc_crowd::FollowTarget;
+
+/// Directory the behaviour files are loaded from (relative to the repo root)
+const BEHAVIOUR_DIR: &str = "shared/assets/behaviours";
+
+/// `MoveTo` / `Patrol` count a point as reached within this distance (m, XZ).
+/// Paths end on the nearest grid cell, so this has to cover half a cell diagonal.
+const ARRIVE_RADIUS: f32 = 1.5;
+
+/// `Investigate` stops this far short of what it's looking into (m, XZ)
+const INVESTIGATE_DISTANCE: f32 = 4.0;
+
+/// Followers closer than this to their slot walk straight at it; further out they path (m)
+const FOLLOW_DIRECT_DISTANCE: f32 = 10.0;
+
+/// A follower's path is redone once its slot has moved this far from the path's end (m)
+const FOLLOW_REPATH_DISTANCE: f32 = 4.0;
+
+/// Followers further than this from their slot hurry (m)
+const FOLLOW_CATCH_UP_DISTANCE: f32 = 3.0;
+const FOLLOW_CATCH_UP_SPEED: f32 = 1.4;
+
+/// Behaviour tree per archetype
+#[derive(Resource)]
+pub struct NpcBehaviours {
+    trees: HashMap<NpcArchetype, BehaviourTree>,
+}
+
+impl Default for NpcBehaviours {
+    fn default() -> Self {
+        let trees = NpcArchetype::ALL
+            .into_iter()
+            .map(|archetype| (archetype, load_tree(archetype)))
+            .collect();
+        Self { trees }
+    }
+}
+
+impl NpcBehaviours {
+    pub fn tree(&self, archetype: NpcArchetype) -> &BehaviourTree {
+        &self.trees[&archetype]
+    }
+}
+
+fn load_tree(archetype: NpcArchetype) -> BehaviourTree {
+    let path = format!("{}/{}", BEHAVIOUR_DIR, behaviour_file_name(archetype));
+    let loaded = match std::fs::read_to_string(&path) {
+        Ok(source) => BehaviourTree::from_ron(&source)
+            .inspect_err(|e| warn!("Invalid behaviour tree {}: {}, using built-in", path, e))
+            .ok(),
+        Err(e) => {
+            warn!("Couldn't read behaviour tree {}: {}, using built-in", path, e);
+            None
+        }
+    };
+    loaded.unwrap_or_else(|| {
+        BehaviourTree::from_ron(default_behaviour_source(archetype)).expect("built-in behaviour trees are valid")
+    })
+}
+
+/// One NPC's view of the world while its tree ticks
+pub struct NpcBrain<'a> {
+    pub npc_id: u64,
+    pub wander: &'a mut NpcWander,
+    pub pos: &'a mut NpcPosition,
+    pub rot: &'a mut NpcRotation,
+    pub navmesh: &'a mut NavMesh,
+    pub health_fraction: f32,
+    pub terrain: &'a WorldTerrain,
+    pub obstacles: &'a SpatialObstacleGrid,
+    pub places: Option<&'a NpcPlaces>,
+    /// Closest living player
+    pub nearest_player: Option<Vec3>,
+    pub night: bool,
+    /// `WorldTime::normalized_time`
+    pub time_of_day: f32,
+    pub dt: f32,
+    /// Last leaf that didn't fail this tick (the one in charge)
+    pub active: Option<&'static str>,
+    /// Set by `Indoors` once the NPC is home
+    pub indoors: bool,
+    /// Squad leader to keep formation on (followers only)
+    pub leader: Option<FollowTarget>,
+    /// Trade stall it keeps (traders only)
+    pub stall: Option<Vec3>,
+    /// Player talking to it (see `dialogue`)
+    pub talking_to: Option<Vec3>,
+}
+
+impl BehaviourContext for NpcBrain<'_> {
+    fn check(&mut self, condition: &BehaviourCondition) -> bool {
+        match condition {
+            BehaviourCondition::Damaged => self.wander.threat.is_some(),
+            BehaviourCondition::HasTarget => self.wander.combat_target.is_some(),
+            BehaviourCondition::HealthBelow(fraction) => self.health_fraction < *fraction,
+            BehaviourCondition::PlayerWithin(range) => self.player_distance().is_some_and(|d| d <= *range),
+            BehaviourCondition::Night => self.night,
+            BehaviourCondition::Alerted(level) => self.wander.awareness.alert >= *level,
+            BehaviourCondition::Not(inner) => !self.check(inner),
+        }
+    }
+
+    fn health_fraction(&self) -> f32 {
+        self.health_fraction
+    }
+
+    fn time_of_day(&self) -> f32 {
+        self.time_of_day
+    }
+
+    fn run(&mut self, leaf: &BehaviourLeaf) -> BehaviourStatus {
+        let status = match leaf {
+            BehaviourLeaf::MoveTo { offset } => self.move_to(*offset),
+            BehaviourLeaf::Wander { radius } => {
+                let area = WanderArea { center: self.wander.home, radius: *radius };
+                self.roam(area)
+            }
+            BehaviourLeaf::Flee { duration } => self.flee(*duration),
+            BehaviourLeaf::Attack => self.attack(),
+            BehaviourLeaf::Patrol { points, pause } => {
+                let points: Vec<Vec3> = points.iter().map(|offset| self.home_point(*offset)).collect();
+                self.patrol(&points, *pause)
+            }
+            BehaviourLeaf::Talk { range } => self.talk(*range),
+            BehaviourLeaf::Sleep => self.sleep(),
+            BehaviourLeaf::GoTo { place, radius } => self.go_to(*place, *radius),
+            BehaviourLeaf::Indoors => self.stay_indoors(),
+            BehaviourLeaf::PatrolRoute { pause } => match self.places {
+                Some(places) if !places.route.is_empty() => self.patrol(&places.route, *pause),
+                _ => BehaviourStatus::Failure,
+            },
+            BehaviourLeaf::Investigate => self.investigate(),
+            BehaviourLeaf::Follow => self.follow(),
+            BehaviourLeaf::Tend { range } => self.tend(*range),
+            BehaviourLeaf::Converse => self.converse(),
+        };
+        if status != BehaviourStatus::Failure {
+            self.active = Some(leaf.label());
+        }
+        status
+    }
+}
+
+impl NpcBrain<'_> {
+    /// Horizontal distance to the closest living player
+    fn player_distance(&self) -> Option<f32> {
+        self.nearest_player
+            .map(|player| Vec2::new(player.x - self.pos.0.x, player.z - self.pos.0.z).length())
+    }
+
+    /// Ground point at an XZ offset from home
+    fn home_point(&self, offset: Vec2) -> Vec3 {
+        let x = self.wander.home.x + offset.x;
+        let z = self.wander.home.z + offset.y;
+        Vec3::new(x, self.terrain.get_height(x, z) + ground_clearance_center(), z)
+    }
+
+    fn near(&self, point: Vec3) -> bool {
+        Vec2::new(point.x - self.pos.0.x, point.z - self.pos.0.z).length() <= ARRIVE_RADIUS
+    }
+
+    /// Walk toward `goal`, pathing there first if needed; true once there
+    fn walk_to(&mut self, goal: Vec3) -> bool {
+        if self.near(goal) {
+            return true;
+        }
+
+        let heading_there = !self.wander.path.is_empty()
+            && Vec2::new(self.wander.target.x - goal.x, self.wander.target.z - goal.z).length() < 0.1;
+        if !heading_there {
+            let here = self.pos.0;
+            // Out of pathfinding budget this tick: wait and ask again next tick
+            let Some(path) = self.navmesh.find_path(self.terrain, self.obstacles, here, goal) else {
+                self.stand_still();
+                return false;
+            };
+            self.wander.target = goal;
+            self.wander.path = path;
+            if self.wander.path.is_empty() {
+                self.wander.path.push(goal); // Fallback
+            }
+            self.wander.waypoint = 0;
+            // Skip waypoints that are very close to current position (e.g., the start cell)
+            while self.wander.waypoint < self.wander.path.len() {
+                let wp = self.wander.path[self.wander.waypoint];
+                if Vec2::new(wp.x - here.x, wp.z - here.z).length() < 1.0 {
+                    self.wander.waypoint += 1;
+                } else {
+                    break;
+                }
+            }
+            self.wander.current_speed_multiplier = 1.0;
+        } else if self.wander.waypoint >= self.wander.path.len() {
+            // Walked the whole path; the goal itself isn't reachable any closer
+            return true;
+        }
+
+        self.wander.state = NpcState::Walking;
+        tick_walking_state(self.wander, self.pos, self.rot, self.terrain, self.dt, self.npc_id);
+        false
+    }
+
+    fn move_to(&mut self, offset: Vec2) -> BehaviourStatus {
+        let goal = self.home_point(offset);
+        if self.walk_to(goal) {
+            self.wander.state = NpcState::Idle;
+            BehaviourStatus::Success
+        } else {
+            BehaviourStatus::Running
+        }
+    }
+
+    /// Wander around `area` (random targets with idle pauses)
+    fn roam(&mut self, area: WanderArea) -> BehaviourStatus {
+        match self.wander.state {
+            NpcState::Idle => tick_idle_state(
+                self.wander,
+                self.rot,
+                self.navmesh,
+                self.terrain,
+                self.obstacles,
+                self.pos.0,
+                area,
+                self.dt,
+                self.npc_id,
+            ),
+            NpcState::Walking => {
+                tick_walking_state(self.wander, self.pos, self.rot, self.terrain, self.dt, self.npc_id)
+            }
+            // Coming out of a fight or a flight: catch its breath first
+            NpcState::Fleeing { .. } | NpcState::Attacking { .. } => self.wander.rest(),
+        }
+        BehaviourStatus::Running
+    }
+
+    fn flee(&mut self, duration: f32) -> BehaviourStatus {
+        let Some(threat) = self.wander.threat else {
+            return BehaviourStatus::Failure;
+        };
+
+        if threat.fresh || !matches!(self.wander.state, NpcState::Fleeing { .. }) {
+            let flee_timer = duration * (1.0 + self.wander.rng.next_f32() * 0.5);
+            let panic_speed_boost = 1.5 + self.wander.rng.next_f32() * 0.3; // 1.5-1.8x speed
+            self.wander.state = NpcState::Fleeing { from_position: threat.source, flee_timer, panic_speed_boost };
+            // Clear current path so flee logic takes over immediately
+            self.wander.path.clear();
+            self.wander.waypoint = 0;
+            self.wander.threat = Some(NpcThreat { fresh: false, ..threat });
+            trace!(
+                "NPC {} fleeing from {:?}, duration {:.1}s, speed boost {:.2}x",
+                self.npc_id,
+                threat.source,
+                flee_timer,
+                panic_speed_boost
+            );
+        }
+
+        let NpcState::Fleeing { from_position, flee_timer, panic_speed_boost } = self.wander.state else {
+            return BehaviourStatus::Failure;
+        };
+        tick_fleeing_state(
+            self.wander,
+            self.pos,
+            self.rot,
+            self.navmesh,
+            self.terrain,
+            self.obstacles,
+            from_position,
+            flee_timer,
+            panic_speed_boost,
+            self.dt,
+            self.npc_id,
+        );
+
+        if matches!(self.wander.state, NpcState::Fleeing { .. }) {
+            BehaviourStatus::Running
+        } else {
+            // Got away
+            self.wander.threat = None;
+            BehaviourStatus::Success
+        }
+    }
+
+    fn attack(&mut self) -> BehaviourStatus {
+        let Some(target) = self.wander.combat_target else {
+            return BehaviourStatus::Failure;
+        };
+        if !matches!(self.wander.state, NpcState::Attacking { target: current, .. } if current == target) {
+            self.wander.state = NpcState::attacking(target);
+            self.wander.path.clear();
+            self.wander.waypoint = 0;
+        }
+        BehaviourStatus::Running
+    }
+
+    fn patrol(&mut self, points: &[Vec3], pause: f32) -> BehaviourStatus {
+        if points.is_empty() {
+            return BehaviourStatus::Failure;
+        }
+        let index = self.wander.patrol_index % points.len();
+        let goal = points[index];
+
+        // Pausing (at the last point, or briefly on the way)
+        if matches!(self.wander.state, NpcState::Idle) && self.wander.idle_timer > 0.0 && !self.near(goal) {
+            self.wander.idle_timer -= self.dt;
+            return BehaviourStatus::Running;
+        }
+
+        if self.walk_to(goal) {
+            self.wander.patrol_index = (index + 1) % points.len();
+            self.wander.state = NpcState::Idle;
+            self.wander.idle_timer = pause;
+            self.wander.path.clear();
+            self.wander.waypoint = 0;
+        }
+        BehaviourStatus::Running
+    }
+
+    fn go_to(&mut self, place: SchedulePlace, radius: f32) -> BehaviourStatus {
+        let Some(spot) = self.places.and_then(|places| places.get(place)) else {
+            return BehaviourStatus::Failure;
+        };
+
+        let away = Vec2::new(spot.x - self.pos.0.x, spot.z - self.pos.0.z).length() > radius + ARRIVE_RADIUS;
+        // A roam target inside the area may route briefly outside it; keep walking it
+        let roaming_inside = matches!(self.wander.state, NpcState::Walking)
+            && Vec2::new(self.wander.target.x - spot.x, self.wander.target.z - spot.z).length() <= radius;
+        if away && !roaming_inside {
+            self.walk_to(spot);
+        } else {
+            self.roam(WanderArea { center: spot, radius });
+        }
+        BehaviourStatus::Running
+    }
+
+    fn stay_indoors(&mut self) -> BehaviourStatus {
+        let Some(home) = self.places.and_then(|places| places.home) else {
+            return BehaviourStatus::Failure;
+        };
+        if self.walk_to(home) {
+            self.stand_still();
+            self.indoors = true;
+        }
+        BehaviourStatus::Running
+    }
+
+    fn talk(&mut self, range: f32) -> BehaviourStatus {
+        let Some(player) = self.nearest_player else {
+            return BehaviourStatus::Failure;
+        };
+        if self.player_distance().is_none_or(|d| d > range) {
+            return BehaviourStatus::Failure;
+        }
+
+        self.stand_still();
+        self.face(player);
+        BehaviourStatus::Running
+    }
+
+    fn tend(&mut self, range: f32) -> BehaviourStatus {
+        let Some(stall) = self.stall else {
+            return BehaviourStatus::Failure;
+        };
+        if self.walk_to(stall) {
+            self.stand_still();
+            if let Some(player) = self.nearest_player.filter(|_| self.player_distance().is_some_and(|d| d <= range)) {
+                self.face(player);
+            }
+        }
+        BehaviourStatus::Running
+    }
+
+    fn converse(&mut self) -> BehaviourStatus {
+        let Some(player) = self.talking_to else {
+            return BehaviourStatus::Failure;
+        };
+        self.stand_still();
+        self.face(player);
+        BehaviourStatus::Running
+    }
+
+    fn investigate(&mut self) -> BehaviourStatus {
+        let awareness = self.wander.awareness;
+        let Some(focus) = awareness.focus.filter(|_| awareness.alert != NpcAlert::Calm) else {
+            return BehaviourStatus::Failure;
+        };
+
+        let distance = Vec2::new(focus.x - self.pos.0.x, focus.z - self.pos.0.z).length();
+        if distance > INVESTIGATE_DISTANCE {
+            self.walk_to(focus);
+        } else {
+            self.stand_still();
+            self.face(focus);
+        }
+        BehaviourStatus::Running
+    }
+
+    fn follow(&mut self) -> BehaviourStatus {
+        let Some(leader) = self.leader else {
+            return BehaviourStatus::Failure;
+        };
+        let slot_xz = leader.slot_position();
+        let slot_y = self.terrain.get_height(slot_xz.x, slot_xz.y) + ground_clearance_center();
+        let slot = Vec3::new(slot_xz.x, slot_y, slot_xz.y);
+        let distance = Vec2::new(slot.x - self.pos.0.x, slot.z - self.pos.0.z).length();
+
+        if distance <= ARRIVE_RADIUS {
+            // In formation: face the way the leader does
+            self.stand_still();
+            self.rot.0 = smooth_rotate_toward(self.rot.0, leader.yaw, NPC_TURN_SPEED, self.dt);
+            return BehaviourStatus::Running;
+        }
+
+        if distance <= FOLLOW_DIRECT_DISTANCE {
+            self.wander.path.clear();
+            self.wander.path.push(slot);
+            self.wander.waypoint = 0;
+        } else {
+            let path_current = self.wander.waypoint < self.wander.path.len()
+                && self
+                    .wander
+                    .path
+                    .last()
+                    .is_some_and(|end| Vec2::new(end.x - slot.x, end.z - slot.z).length() < FOLLOW_REPATH_DISTANCE);
+            // Out of pathfinding budget this tick: keep walking the old path and ask again next tick
+            let here = self.pos.0;
+            let path = if path_current { None } else { self.navmesh.find_path(self.terrain, self.obstacles, here, slot) };
+            if let Some(path) = path {
+                self.wander.path = path;
+                if self.wander.path.is_empty() {
+                    self.wander.path.push(slot);
+                }
+                self.wander.waypoint = 0;
+            }
+        }
+        self.wander.target = slot;
+        self.wander.current_speed_multiplier =
+            if distance > FOLLOW_CATCH_UP_DISTANCE { FOLLOW_CATCH_UP_SPEED } else { 1.0 };
+        self.wander.state = NpcState::Walking;
+        tick_walking_state(self.wander, self.pos, self.rot, self.terrain, self.dt, self.npc_id);
+        BehaviourStatus::Running
+    }
+
+    /// Turn on the spot toward `point`
+    fn face(&mut self, point: Vec3) {
+        let to = Vec2::new(point.x - self.pos.0.x, point.z - self.pos.0.z);
+        if to.length() > 0.01 {
+            let target_yaw = (-to.x).atan2(-to.y);
+            self.rot.0 = smooth_rotate_toward(self.rot.0, target_yaw, NPC_TURN_SPEED, self.dt);
+        }
+    }
+
+    fn sleep(&mut self) -> BehaviourStatus {
+        self.stand_still();
+        BehaviourStatus::Running
+    }
+
+    fn stand_still(&mut self) {
+        self.wander.state = NpcState::Idle;
+        self.wander.path.clear();
+        self.wander.waypoint = 0;
+    }
+}
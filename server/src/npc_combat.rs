@@ -0,0 +1,439 @@
+//! NPC combat: target selection, chasing, melee swings and ranged fire
+//!
+//! Each archetype has an `NpcCombatProfile` (see `shared::npc`) whose aggression the
+//! `NPC_AGGRESSION` env var can override. Hostile NPCs engage the nearest player they
+//! spot; defensive ones only turn on whoever hurt them or aims at them (`npc_perception`,
+//! `npc::react_to_damage`).
+//! Either way that only sets `NpcWander::combat_target`; the fight starts when the NPC's
+//! behaviour tree runs its `Attack` leaf, which puts it in `NpcState::Attacking`.
+//! While attacking an NPC chases until it is in range with line of sight,
+//! then swings its melee weapon or fires real bullets that go through the same
+//! ballistics and `detect_bullet_hits` as player rounds. It gives up when the target
+//! dies, stays out of sight for too long, or drags it past its leash.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    apply_armored_hit, ground_clearance_center, npc_aim_error, npc_owner_id, parse_aggression_overrides,
+    sweep_directions, in_swing_arc, weapons::{ballistics, damage, MeleeStats}, AudioEvent, AudioEventKind, Bullet,
+    BulletImpact, BulletImpactSurface, BulletPrevPosition, BulletVelocity, DamageCause, DamageReceived,
+    EquippedArmor, EquippedWeapon, Health, HitboxPose, HitboxRig, InVehicle, Npc, NpcAggression, NpcArchetype,
+    NpcAttack, NpcCombatProfile, NpcPosition, NpcRotation, Player, PlayerGrounded, PlayerKilled, PlayerPosition,
+    PlayerRotation, PlayerVelocity, ReliableChannel, RigHit, SpatialObstacleGrid, WorldTerrain,
+    npc_combat_profile, FIXED_TIMESTEP_HZ, NPC_CHASE_SPEED_MULT, NPC_HEIGHT, NPC_LOST_SIGHT_TIME,
+    NPC_MOVE_SPEED, NPC_TURN_SPEED, PLAYER_HEIGHT, PLAYER_RADIUS,
+};
+
+use crate::colliders::{DerivedColliderLibrary, StaticColliders, StructureColliders};
+use crate::combat_log::{CombatHit, CombatLog, CombatantRef};
+use crate::navmesh::NavMesh;
+use crate::npc_perception::NpcNoises;
+use crate::npc::{smooth_rotate_toward, NpcState, NpcWander};
+use crate::systems::RespawnTimer;
+use crate::weapons::{
+    player_hitbox_pose, segment_props_intersection, segment_structures_intersection, segment_terrain_intersection,
+};
+
+/// Env var with per-archetype aggression overrides (e.g. `barbarian=hostile,ranger=passive`)
+const NPC_AGGRESSION_ENV: &str = "NPC_AGGRESSION";
+
+/// Seconds between chase path searches
+const CHASE_REPATH_INTERVAL: f32 = 1.0;
+
+/// Eye and muzzle heights above the NPC center (m), matching players
+pub(crate) const NPC_EYE_HEIGHT: f32 = NPC_HEIGHT * 0.4;
+const NPC_MUZZLE_HEIGHT: f32 = NPC_HEIGHT * 0.29;
+
+/// NPCs aim at the target's chest (m above its center)
+pub(crate) const TARGET_AIM_HEIGHT: f32 = PLAYER_HEIGHT * 0.2;
+
+/// Melee NPCs close to this fraction of their weapon's reach before swinging
+const MELEE_ENGAGE_FRACTION: f32 = 0.8;
+
+/// Ranged NPCs only fire when facing within this angle of the target (radians)
+const FIRE_FACING_TOLERANCE: f32 = 0.3;
+
+/// Server NPC combat settings
+#[derive(Resource, Clone, Debug)]
+pub struct NpcCombatSettings {
+    /// Aggression overrides from `NPC_AGGRESSION`
+    overrides: Vec<(NpcArchetype, NpcAggression)>,
+}
+
+impl Default for NpcCombatSettings {
+    fn default() -> Self {
+        let overrides = match std::env::var(NPC_AGGRESSION_ENV) {
+            Ok(value) => parse_aggression_overrides(&value).unwrap_or_else(|e| {
+                warn!("{}, using default NPC aggression", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        if !overrides.is_empty() {
+            info!("NPC aggression overrides: {:?}", overrides);
+        }
+        Self { overrides }
+    }
+}
+
+impl NpcCombatSettings {
+    /// Combat profile for an archetype with any aggression override applied
+    pub fn profile(&self, archetype: NpcArchetype) -> NpcCombatProfile {
+        let mut profile = npc_combat_profile(archetype);
+        if let Some((_, aggression)) = self.overrides.iter().find(|(a, _)| *a == archetype) {
+            profile.aggression = *aggression;
+        }
+        profile
+    }
+}
+
+/// Server-only: the NPC that fired a bullet
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NpcShooter {
+    pub entity: Entity,
+    pub id: u64,
+    pub archetype: NpcArchetype,
+}
+
+/// Whether terrain, props and structures leave the segment clear
+pub(crate) fn has_line_of_sight(
+    from: Vec3,
+    to: Vec3,
+    terrain: &WorldTerrain,
+    static_colliders: &StaticColliders,
+    derived_colliders: Option<&DerivedColliderLibrary>,
+    structure_colliders: &StructureColliders,
+) -> bool {
+    segment_terrain_intersection(terrain, from, to).is_none()
+        && derived_colliders.is_none_or(|derived| {
+            segment_props_intersection(from, to, static_colliders, derived).is_none()
+        })
+        && segment_structures_intersection(from, to, structure_colliders).is_none()
+}
+
+/// Drop the fight and go back to idling (the behaviour tree picks what's next)
+fn disengage(wander: &mut NpcWander, npc_id: u64) {
+    wander.combat_target = None;
+    wander.threat = None;
+    wander.rest();
+    debug!("NPC {} disengaged", npc_id);
+}
+
+/// Follow the chase path one step, turning toward the next waypoint
+fn chase_step(wander: &mut NpcWander, pos: &mut NpcPosition, rot: &mut NpcRotation, terrain: &WorldTerrain, dt: f32) {
+    let Some(waypoint) = wander.path.get(wander.waypoint).copied() else {
+        return;
+    };
+    let to = waypoint - pos.0;
+    let dist_xz = Vec2::new(to.x, to.z).length();
+    if dist_xz < 0.6 {
+        wander.waypoint += 1;
+        return;
+    }
+
+    let dir_xz = Vec2::new(to.x, to.z).normalize_or_zero();
+    let target_yaw = (-dir_xz.x).atan2(-dir_xz.y);
+    rot.0 = smooth_rotate_toward(rot.0, target_yaw, NPC_TURN_SPEED * 1.5, dt);
+
+    // Move in the direction we're facing (turn first, like the other states)
+    let facing_dir = Vec2::new(-rot.0.sin(), -rot.0.cos());
+    let move_factor = dir_xz.dot(facing_dir).max(0.0);
+    let step = facing_dir * (NPC_MOVE_SPEED * NPC_CHASE_SPEED_MULT * move_factor * dt);
+    pos.0.x += step.x;
+    pos.0.z += step.y;
+    pos.0.y = terrain.get_height(pos.0.x, pos.0.z) + ground_clearance_center();
+}
+
+/// Acquire targets, chase them and attack (server-authoritative)
+pub fn tick_npc_combat(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<NpcCombatSettings>,
+    terrain: Res<WorldTerrain>,
+    obstacle_grid: Res<SpatialObstacleGrid>,
+    mut navmesh: ResMut<NavMesh>,
+    static_colliders: Res<StaticColliders>,
+    derived_colliders: Option<Res<DerivedColliderLibrary>>,
+    structure_colliders: Res<StructureColliders>,
+    mut npcs: Query<(Entity, &Npc, &mut NpcPosition, &mut NpcRotation, &Health, &mut NpcWander), Without<Player>>,
+    mut players: Query<
+        (
+            Entity,
+            &Player,
+            &PlayerPosition,
+            &PlayerRotation,
+            &PlayerVelocity,
+            &PlayerGrounded,
+            &EquippedWeapon,
+            &mut Health,
+            Option<&mut EquippedArmor>,
+            (Has<RespawnTimer>, Has<InVehicle>),
+        ),
+        Without<Npc>,
+    >,
+    mut client_links: Query<
+        (
+            &RemoteId,
+            &mut MessageSender<DamageReceived>,
+            &mut MessageSender<PlayerKilled>,
+            &mut MessageSender<BulletImpact>,
+            &mut MessageSender<AudioEvent>,
+        ),
+        (With<ClientOf>, With<Connected>),
+    >,
+    mut combat_log: ResMut<CombatLog>,
+    mut npc_noises: ResMut<NpcNoises>,
+) {
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+    let now = time.elapsed_secs();
+    let sight = |from: Vec3, to: Vec3| {
+        has_line_of_sight(
+            from,
+            to,
+            &terrain,
+            &static_colliders,
+            derived_colliders.as_deref(),
+            &structure_colliders,
+        )
+    };
+
+    for (npc_entity, npc, mut pos, mut rot, health, mut wander) in npcs.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+        let profile = settings.profile(npc.archetype);
+        if profile.attack == NpcAttack::None {
+            continue;
+        }
+        let eye = pos.0 + Vec3::Y * NPC_EYE_HEIGHT;
+
+        // Targets come from `npc_perception` / `react_to_damage`; the `Attack` leaf starts the fight
+        let NpcState::Attacking { target, mut cooldown, mut swing_timer, mut lost_sight, mut repath_timer } =
+            wander.state
+        else {
+            continue;
+        };
+
+        // Target gone, dead, respawning or driving off: back to normal life
+        let target_pos = players
+            .get(target)
+            .ok()
+            .filter(|(.., health, _, (respawning, in_vehicle))| !health.is_dead() && !respawning && !in_vehicle)
+            .map(|(_, _, position, ..)| position.0);
+        let Some(target_pos) = target_pos else {
+            disengage(&mut wander, npc.id);
+            continue;
+        };
+        let home_distance = Vec2::new(pos.0.x - wander.home.x, pos.0.z - wander.home.z).length();
+        if home_distance > profile.leash_range {
+            disengage(&mut wander, npc.id);
+            continue;
+        }
+
+        let target_chest = target_pos + Vec3::Y * TARGET_AIM_HEIGHT;
+        let visible = sight(eye, target_chest);
+        lost_sight = if visible { 0.0 } else { lost_sight + dt };
+        if lost_sight > NPC_LOST_SIGHT_TIME {
+            disengage(&mut wander, npc.id);
+            continue;
+        }
+        cooldown = (cooldown - dt).max(0.0);
+        repath_timer -= dt;
+
+        let to_target = Vec2::new(target_pos.x - pos.0.x, target_pos.z - pos.0.z);
+        let engage_range = match profile.attack {
+            NpcAttack::Melee(weapon) => weapon.melee_stats().map_or(0.0, |m| m.reach) * MELEE_ENGAGE_FRACTION,
+            NpcAttack::Ranged(_) => profile.attack_range,
+            NpcAttack::None => 0.0,
+        };
+        let in_range = visible && to_target.length() <= engage_range;
+
+        if in_range || swing_timer.is_some() {
+            // Stand and face the target
+            wander.path.clear();
+            wander.waypoint = 0;
+            let dir_xz = to_target.normalize_or_zero();
+            rot.0 = smooth_rotate_toward(rot.0, (-dir_xz.x).atan2(-dir_xz.y), NPC_TURN_SPEED * 1.5, dt);
+        } else {
+            let needs_path = repath_timer <= 0.0 || wander.waypoint >= wander.path.len();
+            // Out of pathfinding budget this tick: keep the old path (or head straight there) and retry
+            let path = if needs_path { navmesh.find_path(&terrain, &obstacle_grid, pos.0, target_pos) } else { None };
+            if let Some(path) = path {
+                wander.path = path;
+                wander.waypoint = 0;
+                if wander.path.is_empty() {
+                    wander.path.push(target_pos);
+                }
+                // Skip the start cell
+                while wander
+                    .path
+                    .get(wander.waypoint)
+                    .is_some_and(|wp| Vec2::new(wp.x - pos.0.x, wp.z - pos.0.z).length() < 1.0)
+                {
+                    wander.waypoint += 1;
+                }
+                repath_timer = CHASE_REPATH_INTERVAL;
+            } else if needs_path && wander.waypoint >= wander.path.len() {
+                wander.path = vec![target_pos];
+                wander.waypoint = 0;
+            }
+            chase_step(&mut wander, &mut pos, &mut rot, &terrain, dt);
+        }
+
+        let facing = Vec3::new(-rot.0.sin(), 0.0, -rot.0.cos());
+        match profile.attack {
+            NpcAttack::Melee(weapon) => {
+                let Some(melee) = weapon.melee_stats() else {
+                    continue;
+                };
+                let lands = match swing_timer {
+                    Some(timer) if timer > dt => {
+                        swing_timer = Some(timer - dt);
+                        false
+                    }
+                    Some(_) => {
+                        swing_timer = None;
+                        cooldown = melee.recovery + profile.attack_interval;
+                        true
+                    }
+                    None => {
+                        if in_range && cooldown <= 0.0 {
+                            swing_timer = Some(melee.wind_up);
+                        }
+                        false
+                    }
+                };
+
+                let victim = if lands { players.get_mut(target).ok() } else { None };
+                if let Some((_, victim, victim_pos, rotation, velocity, grounded, victim_weapon, mut victim_health, mut armor, _)) =
+                    victim
+                {
+                    let pose = player_hitbox_pose(victim_pos.0, rotation, velocity, grounded, victim_weapon);
+                    if let Some((hit, direction)) = npc_swing_hit(eye, facing, target_chest, &melee, &pose) {
+                        let stats = weapon.stats();
+                        let armored = apply_armored_hit(armor.as_deref_mut(), &stats, 0.0, hit.hit_zone, 1.0);
+                        let is_kill = victim_health.take_damage(armored.damage);
+                        let is_headshot = hit.hit_zone == damage::HitZone::Head;
+                        let victim_id = victim.client_id;
+
+                        combat_log.record(CombatHit {
+                            attacker: CombatantRef::Npc { id: npc.id, archetype: npc.archetype },
+                            victim: CombatantRef::Player(victim_id),
+                            cause: DamageCause::Weapon(weapon),
+                            hit_zone: hit.hit_zone,
+                            damage: armored.damage,
+                            armor_absorbed: armored.absorbed,
+                            distance: eye.distance(hit.point),
+                            kill: is_kill,
+                        });
+                        info!(
+                            "NPC melee hit! npc:{} ({:?}) -> {:?} ({:?}) with {:?} for {:.1} damage ({:.1} absorbed, kill: {})",
+                            npc.id, npc.archetype, victim_id, hit.hit_zone, weapon, armored.damage, armored.absorbed, is_kill
+                        );
+
+                        let impact = BulletImpact {
+                            owner_id: npc_owner_id(npc.id),
+                            weapon_type: weapon,
+                            spawn_position: eye,
+                            initial_velocity: direction,
+                            impact_position: hit.point,
+                            impact_normal: hit.normal,
+                            surface: BulletImpactSurface::Player,
+                        };
+                        let damage_direction =
+                            Vec3::new(eye.x - victim_pos.0.x, 0.0, eye.z - victim_pos.0.z).normalize_or_zero();
+                        for (remote_id, mut dmg_sender, mut kill_sender, mut impact_sender, _) in client_links.iter_mut() {
+                            impact_sender.send::<ReliableChannel>(impact.clone());
+                            if remote_id.0 != victim_id {
+                                continue;
+                            }
+                            dmg_sender.send::<ReliableChannel>(DamageReceived {
+                                direction: damage_direction,
+                                damage: armored.damage,
+                                health_remaining: victim_health.current,
+                            });
+                            if is_kill {
+                                kill_sender.send::<ReliableChannel>(PlayerKilled {
+                                    killer_id: npc_owner_id(npc.id),
+                                    weapon,
+                                    headshot: is_headshot,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            NpcAttack::Ranged(weapon) => {
+                let facing_xz = Vec2::new(facing.x, facing.z);
+                let aligned = facing_xz.dot(to_target.normalize_or_zero()) >= FIRE_FACING_TOLERANCE.cos();
+                if in_range && cooldown <= 0.0 && aligned {
+                    let stats = weapon.stats();
+                    cooldown = profile.attack_interval.max(1.0 / stats.fire_rate);
+
+                    // Same muzzle offset and bullet entities as a player's shot
+                    let muzzle = pos.0 + Vec3::Y * NPC_MUZZLE_HEIGHT;
+                    let aim_direction = (target_chest - muzzle).normalize_or(facing);
+                    let right = aim_direction.cross(Vec3::Y).normalize_or_zero();
+                    let spawn_pos = muzzle + aim_direction * 0.5 + right * 0.25;
+                    let spread = npc_aim_error(&profile, eye.distance(target_chest));
+
+                    for _ in 0..stats.pellet_count {
+                        let velocity = ballistics::apply_spread(aim_direction, spread) * stats.bullet_speed;
+                        commands.spawn((
+                            Bullet {
+                                owner_id: npc_owner_id(npc.id),
+                                weapon_type: weapon,
+                                spawn_position: spawn_pos,
+                                initial_velocity: velocity,
+                                spawn_time: now,
+                            },
+                            BulletVelocity(velocity),
+                            BulletPrevPosition(spawn_pos),
+                            PlayerPosition(spawn_pos),
+                            Transform::from_translation(spawn_pos),
+                            NpcShooter { entity: npc_entity, id: npc.id, archetype: npc.archetype },
+                            Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+                        ));
+                    }
+
+                    let audio_event = AudioEvent {
+                        player_id: npc_owner_id(npc.id),
+                        position: spawn_pos,
+                        kind: AudioEventKind::Gunshot { weapon_type: weapon },
+                    };
+                    for (.., mut audio_sender) in client_links.iter_mut() {
+                        audio_sender.send::<ReliableChannel>(audio_event.clone());
+                    }
+                    npc_noises.npc_gunshot(spawn_pos, weapon, npc.archetype);
+                    trace!("NPC {} ({:?}) fired {:?} (aim error {:.3} rad)", npc.id, npc.archetype, weapon, spread);
+                }
+            }
+            NpcAttack::None => {}
+        }
+
+        wander.state = NpcState::Attacking { target, cooldown, swing_timer, lost_sight, repath_timer };
+    }
+}
+
+/// Where a melee swing at the target lands on its rig, with the swing direction
+fn npc_swing_hit(
+    eye: Vec3,
+    facing: Vec3,
+    target_chest: Vec3,
+    melee: &MeleeStats,
+    pose: &HitboxPose,
+) -> Option<(RigHit, Vec3)> {
+    // The target stepped out of the swing during the wind-up
+    if !in_swing_arc(eye, facing, pose.center, melee.reach, melee.arc, PLAYER_RADIUS) {
+        return None;
+    }
+    let direction = (target_chest - eye).normalize_or(facing);
+    let rig = HitboxRig::posed(pose);
+    sweep_directions(direction, melee.arc)
+        .into_iter()
+        .filter_map(|dir| rig.raycast(eye, dir, melee.reach))
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+        .map(|hit| (hit, direction))
+}
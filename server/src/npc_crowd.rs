@@ -0,0 +1,226 @@
+//! NPC crowds: local avoidance between NPCs and squads that move and react together
+//!
+//! Movement states (`npc`, `npc_combat`) only steer around static obstacles. Each tick
+//! `record_crowd_positions` notes where every NPC stood, and once the AI has moved them
+//! `avoid_npc_collisions` treats that move as the preferred velocity and replaces it with
+//! an ORCA velocity (`shared::crowd`) that keeps clear of nearby NPCs. All NPCs are solved
+//! against the same snapshot, sorted by NPC ID, so the result doesn't depend on query order.
+//! A sidestep never ends inside a building footprint and is clipped against props and
+//! structures; an NPC that was walking where it faced turns with its corrected move.
+//!
+//! `NpcSquad` ties NPCs to a leader: followers keep a formation slot around it (the
+//! behaviour tree's `Follow` leaf) and `alarm_squads` puts the whole squad on alert when
+//! any member is hit.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use shared::{
+    ground_clearance_center, orca_velocity, AgentGrid, CrowdAgent, Health, Npc, NpcAggression, NpcAlert, NpcAttack,
+    NpcDamageEvent, NpcIndoors, NpcPosition, NpcRotation, Player, SpatialObstacleGrid, WorldTerrain,
+    CROWD_NEIGHBOR_DIST, FIXED_TIMESTEP_HZ, NPC_MOVE_SPEED, NPC_RADIUS, NPC_TURN_SPEED,
+};
+
+use crate::colliders::{push_npc_out_of_static, DerivedColliderLibrary, StaticColliders, StructureColliders};
+use crate::npc::{smooth_rotate_toward, NpcThreat, NpcWander};
+use crate::npc_combat::NpcCombatSettings;
+
+/// Personal space kept on top of the capsule radius (m)
+const CROWD_PADDING: f32 = 0.15;
+
+/// Standing NPCs step aside at up to this speed (m/s)
+const CROWD_SIDESTEP_SPEED: f32 = NPC_MOVE_SPEED * 0.5;
+
+/// An NPC counts as walking where it faces within this angle of its move (radians)
+const CROWD_FACING_TOLERANCE: f32 = std::f32::consts::FRAC_PI_4;
+
+/// Formation slots (right, forward in m from the leader) for a guard squad's followers
+pub const GUARD_FORMATION: [Vec2; 2] = [Vec2::new(-1.5, -2.0), Vec2::new(1.5, -2.0)];
+
+/// Slot of a companion walking alongside its leader
+pub const COMPANION_SLOT: Vec2 = Vec2::new(1.2, 0.0);
+
+/// Server-only: member of a squad led by `leader` (the leader points at itself)
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NpcSquad {
+    pub leader: Entity,
+    /// Offset from the leader in its frame (x = right, y = forward)
+    pub slot: Vec2,
+}
+
+/// Where a follower should be, for the `Follow` leaf
+#[derive(Clone, Copy, Debug)]
+pub struct FollowTarget {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub slot: Vec2,
+}
+
+impl FollowTarget {
+    /// The follower's slot in world space (XZ)
+    pub fn slot_position(&self) -> Vec2 {
+        let right = Vec2::new(self.yaw.cos(), -self.yaw.sin());
+        let forward = Vec2::new(-self.yaw.sin(), -self.yaw.cos());
+        Vec2::new(self.position.x, self.position.z) + right * self.slot.x + forward * self.slot.y
+    }
+}
+
+/// Crowd state carried between the avoidance systems
+#[derive(Resource, Default)]
+pub struct NpcCrowd {
+    /// Positions before this tick's AI moved anyone
+    before: HashMap<Entity, Vec3>,
+    /// Velocities NPCs actually moved with last tick
+    velocities: HashMap<Entity, Vec2>,
+    grid: AgentGrid,
+}
+
+/// Note where every NPC stands before the AI moves them
+pub fn record_crowd_positions(mut crowd: ResMut<NpcCrowd>, npcs: Query<(Entity, &NpcPosition), With<Npc>>) {
+    crowd.before.clear();
+    crowd.before.extend(npcs.iter().map(|(entity, pos)| (entity, pos.0)));
+}
+
+/// Yaw of a horizontal move (NPCs face -Z at yaw 0)
+fn move_yaw(velocity: Vec2) -> f32 {
+    (-velocity.x).atan2(-velocity.y)
+}
+
+/// Replace each NPC's move this tick with one that avoids the NPCs around it
+pub fn avoid_npc_collisions(
+    mut crowd: ResMut<NpcCrowd>,
+    terrain: Res<WorldTerrain>,
+    obstacles: Res<SpatialObstacleGrid>,
+    derived: Option<Res<DerivedColliderLibrary>>,
+    colliders: Res<StaticColliders>,
+    structure_colliders: Res<StructureColliders>,
+    mut npcs: Query<
+        (Entity, &Npc, &mut NpcPosition, &mut NpcRotation, &Health),
+        (Without<NpcIndoors>, Without<Player>),
+    >,
+) {
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+    let crowd = &mut *crowd;
+
+    // (entity, start, preferred velocity) sorted by NPC ID, with the solver's view alongside
+    let mut movers: Vec<(u64, Entity, Vec3, Vec2)> = npcs
+        .iter()
+        .filter(|(.., health)| !health.is_dead())
+        .filter_map(|(entity, npc, pos, ..)| {
+            let start = *crowd.before.get(&entity)?;
+            let preferred = Vec2::new(pos.0.x - start.x, pos.0.z - start.z) / dt;
+            Some((npc.id, entity, start, preferred))
+        })
+        .collect();
+    movers.sort_by_key(|(id, ..)| *id);
+
+    let agents: Vec<CrowdAgent> = movers
+        .iter()
+        .map(|&(id, entity, start, preferred)| CrowdAgent {
+            id,
+            position: Vec2::new(start.x, start.z),
+            velocity: crowd.velocities.get(&entity).copied().unwrap_or(preferred),
+            radius: NPC_RADIUS + CROWD_PADDING,
+        })
+        .collect();
+
+    crowd.grid.clear();
+    for (index, agent) in agents.iter().enumerate() {
+        crowd.grid.insert(index, agent.position);
+    }
+
+    let mut neighbor_indices = Vec::new();
+    let mut neighbors = Vec::new();
+    let mut velocities = HashMap::with_capacity(movers.len());
+    for (index, &(_, entity, start, preferred)) in movers.iter().enumerate() {
+        crowd.grid.neighbors(&agents, index, CROWD_NEIGHBOR_DIST, &mut neighbor_indices);
+        if neighbor_indices.is_empty() {
+            velocities.insert(entity, preferred);
+            continue;
+        }
+        neighbors.clear();
+        neighbors.extend(neighbor_indices.iter().map(|&other| agents[other]));
+
+        let max_speed = preferred.length().max(CROWD_SIDESTEP_SPEED);
+        let avoiding = orca_velocity(&agents[index], preferred, max_speed, &neighbors, dt);
+        if avoiding.distance_squared(preferred) <= f32::EPSILON {
+            velocities.insert(entity, avoiding);
+            continue;
+        }
+
+        // Don't step into a building footprint: try half the step, else stay put
+        let start_xz = Vec2::new(start.x, start.z);
+        let inside_footprint = obstacles.point_blocked(start_xz);
+        let step = [avoiding, avoiding * 0.5]
+            .into_iter()
+            .find(|step| inside_footprint || !obstacles.point_blocked(start_xz + *step * dt))
+            .unwrap_or(Vec2::ZERO);
+
+        // Clip against props and structures so the move matches where the NPC ends up
+        let mut target = Vec3::new(start.x + step.x * dt, start.y, start.z + step.y * dt);
+        target.y = terrain.get_height(target.x, target.z) + ground_clearance_center();
+        if let Some(derived) = derived.as_deref() {
+            push_npc_out_of_static(derived, &colliders, &structure_colliders, &mut target);
+        }
+        let velocity = Vec2::new(target.x - start.x, target.z - start.z) / dt;
+        velocities.insert(entity, velocity);
+
+        let Ok((_, _, mut pos, mut rot, _)) = npcs.get_mut(entity) else {
+            continue;
+        };
+        pos.0.x = target.x;
+        pos.0.z = target.z;
+        pos.0.y = terrain.get_height(pos.0.x, pos.0.z) + ground_clearance_center();
+
+        // Walking where it faced: keep facing the way it actually moves
+        let facing_move = preferred.length_squared() > f32::EPSILON && {
+            let off = (rot.0 - move_yaw(preferred) + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+                - std::f32::consts::PI;
+            off.abs() <= CROWD_FACING_TOLERANCE
+        };
+        if facing_move && velocity.length_squared() > f32::EPSILON {
+            rot.0 = smooth_rotate_toward(rot.0, move_yaw(velocity), NPC_TURN_SPEED, dt);
+        }
+    }
+    crowd.velocities = velocities;
+}
+
+/// When a squad member gets hit, the rest of the squad is alarmed too: fighters turn on
+/// the attacker, everyone else flees with it
+pub fn alarm_squads(
+    settings: Res<NpcCombatSettings>,
+    mut npcs: Query<(&Npc, &NpcSquad, &mut NpcWander, &Health, Has<NpcDamageEvent>)>,
+    hits: Query<(&NpcSquad, &NpcDamageEvent)>,
+    players: Query<&Health, With<Player>>,
+) {
+    // Squads (by leader) with a member hit this tick, where from and by whom
+    let alarms: Vec<(Entity, Vec3, Option<Entity>)> = hits
+        .iter()
+        .map(|(squad, damage)| (squad.leader, damage.damage_source_position, damage.attacker))
+        .collect();
+    if alarms.is_empty() {
+        return;
+    }
+
+    for (npc, squad, mut wander, health, hit) in npcs.iter_mut() {
+        // The ones hit react for themselves in `react_to_damage`
+        if health.is_dead() || hit {
+            continue;
+        }
+        let Some(&(_, source, attacker)) = alarms.iter().find(|(leader, ..)| *leader == squad.leader) else {
+            continue;
+        };
+
+        let attacker = attacker.filter(|&e| players.get(e).is_ok_and(|health| !health.is_dead()));
+        wander.threat = Some(NpcThreat { source, fresh: true });
+        wander.awareness.notice(NpcAlert::Alarmed, source, attacker);
+
+        let profile = settings.profile(npc.archetype);
+        let fighter = profile.aggression != NpcAggression::Passive && profile.attack != NpcAttack::None;
+        if fighter && wander.combat_target.is_none() {
+            wander.combat_target = attacker;
+        }
+        debug!("NPC {} alarmed by a squad mate being hit", npc.id);
+    }
+}
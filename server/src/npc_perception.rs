@@ -0,0 +1,197 @@
+//! NPC perception: what NPCs see and hear
+//!
+//! Every `SIGHT_CHECK_INTERVAL` each NPC looks for players inside its view cone and
+//! sight range, with line of sight through terrain, props and structures. Gunshots are
+//! queued in `NpcNoises` wherever an `AudioEventKind::Gunshot` goes out and heard out to
+//! the weapon's loudness radius. Both raise `NpcWander::awareness` (see
+//! `shared::perception`); alarms then turn into decisions the behaviour tree acts on:
+//! fighters pick a combat target, everyone else gets a threat to flee from. Curious NPCs
+//! are left to the tree's `Investigate` leaf, and so are fighters alarmed by a shooter
+//! they can't see or that is past their leash. NPCs shrug off their own kind's gunfire.
+
+use bevy::prelude::*;
+
+use shared::{
+    hearing_alert, in_view_cone, is_aimed_at, weapons::WeaponType, EquippedWeapon, Health, InVehicle, Npc,
+    NpcAggression, NpcAlert, NpcArchetype, NpcAttack, NpcCombatProfile, NpcIndoors, NpcPosition, NpcRotation, Player,
+    PlayerPosition, PlayerRotation, WorldTerrain, FIXED_TIMESTEP_HZ, NPC_SIGHT_RANGE,
+};
+
+use crate::colliders::{DerivedColliderLibrary, StaticColliders, StructureColliders};
+use crate::npc::{NpcState, NpcThreat, NpcWander};
+use crate::npc_combat::{has_line_of_sight, NpcCombatSettings, NPC_EYE_HEIGHT, TARGET_AIM_HEIGHT};
+use crate::systems::RespawnTimer;
+
+/// Seconds between an NPC's looks around (line-of-sight checks are the expensive part)
+pub const SIGHT_CHECK_INTERVAL: f32 = 0.25;
+
+/// A sound NPCs can hear
+#[derive(Clone, Copy, Debug)]
+pub struct NpcNoise {
+    pub position: Vec3,
+    /// Heard out to this distance (m)
+    pub loudness: f32,
+    /// Player who made it, if any
+    pub source: Option<Entity>,
+    /// Kind of NPC who made it, if any
+    pub npc_archetype: Option<NpcArchetype>,
+}
+
+/// Noises made since the last perception pass
+#[derive(Resource, Default)]
+pub struct NpcNoises {
+    pending: Vec<NpcNoise>,
+}
+
+impl NpcNoises {
+    /// Queue a gunshot (alongside its `AudioEventKind::Gunshot`)
+    pub fn gunshot(&mut self, position: Vec3, weapon: WeaponType, source: Option<Entity>) {
+        self.pending.push(NpcNoise { position, loudness: weapon.loudness_radius(), source, npc_archetype: None });
+    }
+
+    /// Queue a gunshot fired by an NPC
+    pub fn npc_gunshot(&mut self, position: Vec3, weapon: WeaponType, archetype: NpcArchetype) {
+        self.pending.push(NpcNoise {
+            position,
+            loudness: weapon.loudness_radius(),
+            source: None,
+            npc_archetype: Some(archetype),
+        });
+    }
+
+    fn take(&mut self) -> Vec<NpcNoise> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Let NPCs see and hear, and act on anything alarming
+pub fn tick_npc_perception(
+    mut noises: ResMut<NpcNoises>,
+    settings: Res<NpcCombatSettings>,
+    terrain: Res<WorldTerrain>,
+    static_colliders: Res<StaticColliders>,
+    derived_colliders: Option<Res<DerivedColliderLibrary>>,
+    structure_colliders: Res<StructureColliders>,
+    mut npcs: Query<(&Npc, &NpcPosition, &NpcRotation, &Health, &mut NpcWander, Has<NpcIndoors>), Without<Player>>,
+    players: Query<
+        (Entity, &PlayerPosition, &PlayerRotation, &EquippedWeapon, &Health, (Has<RespawnTimer>, Has<InVehicle>)),
+        (With<Player>, Without<Npc>),
+    >,
+) {
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+    let noises = noises.take();
+    let players: Vec<(Entity, Vec3, f32, WeaponType)> = players
+        .iter()
+        .filter(|(.., health, (respawning, in_vehicle))| !health.is_dead() && !respawning && !in_vehicle)
+        .map(|(entity, position, rotation, weapon, ..)| (entity, position.0, rotation.0, weapon.weapon_type))
+        .collect();
+
+    for (npc, pos, rot, health, mut wander, indoors) in npcs.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+        wander.awareness.decay(dt);
+        // Tucked up indoors: nothing gets through
+        if indoors {
+            continue;
+        }
+        let profile = settings.profile(npc.archetype);
+        let eye = pos.0 + Vec3::Y * NPC_EYE_HEIGHT;
+
+        for noise in &noises {
+            if noise.npc_archetype == Some(npc.archetype) {
+                continue;
+            }
+            let alert = hearing_alert(noise.position.distance(eye), noise.loudness);
+            // A shooter past the leash or out of sight is only a place to look into
+            let in_reach = alert == NpcAlert::Alarmed
+                && noise.source.is_some()
+                && noise.position.distance(wander.home) <= profile.leash_range
+                && has_line_of_sight(
+                    eye,
+                    noise.position,
+                    &terrain,
+                    &static_colliders,
+                    derived_colliders.as_deref(),
+                    &structure_colliders,
+                );
+            let suspect = noise.source.filter(|_| in_reach);
+            respond(&mut wander, &profile, npc.id, alert, noise.position, suspect, false);
+        }
+
+        wander.look_timer -= dt;
+        if wander.look_timer > 0.0 {
+            continue;
+        }
+        wander.look_timer = SIGHT_CHECK_INTERVAL;
+
+        // Hostile fighters go looking for trouble; everyone minds a gun pointed their way
+        let hunting = profile.aggression == NpcAggression::Hostile
+            && profile.attack != NpcAttack::None
+            && wander.combat_target.is_none()
+            && !matches!(wander.state, NpcState::Fleeing { .. });
+        let home = wander.home;
+        let sight_range = profile.sight_range.max(NPC_SIGHT_RANGE);
+        let spotted = players
+            .iter()
+            .filter(|(_, position, ..)| in_view_cone(eye, rot.0, *position, sight_range))
+            .map(|(entity, position, yaw, weapon)| {
+                let aimed_at = weapon.melee_stats().is_none() && is_aimed_at(*position, *yaw, pos.0);
+                (*entity, *position, aimed_at)
+            })
+            .filter(|(_, position, aimed_at)| {
+                *aimed_at
+                    || (hunting
+                        && position.distance(eye) <= profile.sight_range
+                        && position.distance(home) <= profile.leash_range)
+            })
+            .filter(|(_, position, _)| {
+                has_line_of_sight(
+                    eye,
+                    *position + Vec3::Y * TARGET_AIM_HEIGHT,
+                    &terrain,
+                    &static_colliders,
+                    derived_colliders.as_deref(),
+                    &structure_colliders,
+                )
+            })
+            .min_by(|a, b| a.1.distance(eye).total_cmp(&b.1.distance(eye)));
+        if let Some((player, position, aimed_at)) = spotted {
+            respond(&mut wander, &profile, npc.id, NpcAlert::Alarmed, position, Some(player), aimed_at);
+        }
+    }
+}
+
+/// Note a stimulus and, if it alarms the NPC, decide between fighting and fleeing.
+/// Hostile fighters go after the culprit, defensive ones only when it's aiming at them,
+/// and NPCs that don't fight run from it. With no culprit to go after, a fighter is left
+/// to investigate.
+fn respond(
+    wander: &mut NpcWander,
+    profile: &NpcCombatProfile,
+    npc_id: u64,
+    alert: NpcAlert,
+    focus: Vec3,
+    suspect: Option<Entity>,
+    aimed_at: bool,
+) {
+    let rose = wander.awareness.notice(alert, focus, suspect);
+    if alert != NpcAlert::Alarmed {
+        return;
+    }
+
+    let fighter = profile.aggression != NpcAggression::Passive && profile.attack != NpcAttack::None;
+    if !fighter {
+        if rose || wander.threat.is_none() {
+            wander.threat = Some(NpcThreat { source: focus, fresh: true });
+            debug!("NPC {} alarmed by something at {:?}", npc_id, focus);
+        }
+        return;
+    }
+
+    let engages = profile.aggression == NpcAggression::Hostile || aimed_at;
+    if let Some(player) = suspect.filter(|_| engages && wander.combat_target.is_none()) {
+        wander.combat_target = Some(player);
+        debug!("NPC {} spotted {:?}", npc_id, player);
+    }
+}
@@ -0,0 +1,256 @@
+//! NPC population director
+//!
+//! Keeps the population regions around players (`shared::population`) stocked. Every
+//! `POPULATION_TICK_INTERVAL` it spawns missing residents at deterministic spawn points
+//! out of every player's sight, despawns idle residents no player is near, and replaces
+//! killed NPCs once `NPC_RESPAWN_COOLDOWN` has passed. Hand-placed NPCs (spawn area,
+//! town) come back at home with their schedule places and trade but outside their old
+//! squad; region residents are replaced anywhere in their region.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use shared::{
+    ground_clearance_center, npc_max_health, population_regions_near, spawn_sight, Health, Npc, NpcAlert,
+    NpcArchetype, NpcPosition, NpcRotation, Player, PlayerPosition, PlayerRotation, PopulationRegion, RegionKey,
+    SpatialObstacleGrid, SpawnSight, TraderKind, TraderStock, WorldTerrain, FIXED_TIMESTEP_HZ, NPC_RESPAWN_COOLDOWN,
+    POPULATION_ACTIVE_RADIUS, POPULATION_DESPAWN_RADIUS,
+};
+
+use crate::colliders::{DerivedColliderLibrary, StaticColliders, StructureColliders};
+use crate::npc::{NpcPlaces, NpcWander};
+use crate::npc_combat::{has_line_of_sight, NPC_EYE_HEIGHT, TARGET_AIM_HEIGHT};
+use crate::systems::RespawnTimer;
+use crate::trade::TraderRestock;
+
+/// Seconds between director passes
+const POPULATION_TICK_INTERVAL: f32 = 1.0;
+
+/// Spawn points tried per missing NPC before waiting for the next pass
+const SPAWN_ATTEMPTS: u32 = 8;
+
+/// At most this many NPCs spawn per pass, so walking into a region doesn't hitch
+const MAX_SPAWNS_PER_PASS: usize = 4;
+
+/// First NPC ID handed out by the director (hand-placed NPCs stay below it)
+const POPULATION_ID_START: u64 = 100_000;
+
+/// Server-only: where an NPC came from, so it can be replaced once killed
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NpcOrigin {
+    /// Placed by hand: respawns at its home
+    Fixed,
+    /// Spawned by the director for this region
+    Region(RegionKey),
+}
+
+/// A killed NPC waiting to be replaced
+struct PendingRespawn {
+    origin: NpcOrigin,
+    archetype: NpcArchetype,
+    home: Vec3,
+    places: Option<NpcPlaces>,
+    /// What it traded in (fully restocked on its return)
+    trader: Option<TraderKind>,
+    /// Seconds until it may come back
+    timer: f32,
+}
+
+/// Director state
+#[derive(Resource)]
+pub struct NpcPopulation {
+    /// Seconds until the next pass
+    timer: f32,
+    next_id: u64,
+    /// Spawns tried so far per region (seeds its next spawn point)
+    serials: HashMap<RegionKey, u32>,
+    respawns: Vec<PendingRespawn>,
+}
+
+impl Default for NpcPopulation {
+    fn default() -> Self {
+        Self {
+            timer: 0.0,
+            next_id: POPULATION_ID_START,
+            serials: HashMap::new(),
+            respawns: Vec::new(),
+        }
+    }
+}
+
+/// Queue a replacement for every NPC that just died
+pub fn note_npc_deaths(
+    mut commands: Commands,
+    mut population: ResMut<NpcPopulation>,
+    npcs: Query<(Entity, &Npc, &Health, &NpcOrigin, &NpcWander, Option<&NpcPlaces>, Option<&TraderStock>)>,
+) {
+    for (entity, npc, health, origin, wander, places, trader) in npcs.iter() {
+        if !health.is_dead() {
+            continue;
+        }
+        population.respawns.push(PendingRespawn {
+            origin: *origin,
+            archetype: npc.archetype,
+            home: wander.home,
+            places: places.cloned(),
+            trader: trader.map(|stock| stock.kind),
+            timer: NPC_RESPAWN_COOLDOWN,
+        });
+        // Only counted once; the corpse itself is left to the dead NPC cleanup
+        commands.entity(entity).remove::<NpcOrigin>();
+        debug!("NPC {} ({:?}) will be replaced in {}s", npc.id, npc.archetype, NPC_RESPAWN_COOLDOWN);
+    }
+}
+
+/// Spawn, despawn and respawn NPCs around players
+pub fn direct_npc_population(
+    mut commands: Commands,
+    mut population: ResMut<NpcPopulation>,
+    terrain: Res<WorldTerrain>,
+    obstacles: Res<SpatialObstacleGrid>,
+    static_colliders: Res<StaticColliders>,
+    derived_colliders: Option<Res<DerivedColliderLibrary>>,
+    structure_colliders: Res<StructureColliders>,
+    npcs: Query<(Entity, &Npc, &NpcPosition, &Health, &NpcWander, &NpcOrigin)>,
+    players: Query<(&PlayerPosition, &PlayerRotation, &Health, Has<RespawnTimer>), With<Player>>,
+) {
+    population.timer -= 1.0 / FIXED_TIMESTEP_HZ as f32;
+    if population.timer > 0.0 {
+        return;
+    }
+    population.timer = POPULATION_TICK_INTERVAL;
+    let population = &mut *population;
+
+    let players: Vec<(Vec3, f32)> = players
+        .iter()
+        .filter(|(_, _, health, respawning)| !health.is_dead() && !respawning)
+        .map(|(position, rotation, ..)| (position.0, rotation.0))
+        .collect();
+    let hidden = |point: Vec3| {
+        players.iter().all(|&(position, yaw)| match spawn_sight(position, yaw, point) {
+            SpawnSight::TooClose => false,
+            SpawnSight::OutOfView => true,
+            SpawnSight::InView => !has_line_of_sight(
+                position + Vec3::Y * NPC_EYE_HEIGHT,
+                point + Vec3::Y * TARGET_AIM_HEIGHT,
+                &terrain,
+                &static_colliders,
+                derived_colliders.as_deref(),
+                &structure_colliders,
+            ),
+        })
+    };
+
+    // Residents nobody is around to see go away until a player comes back
+    let mut residents: HashMap<(RegionKey, NpcArchetype), u32> = HashMap::new();
+    for (entity, npc, pos, health, wander, origin) in npcs.iter() {
+        let NpcOrigin::Region(key) = *origin else {
+            continue;
+        };
+        let calm =
+            wander.awareness.alert == NpcAlert::Calm && wander.combat_target.is_none() && wander.threat.is_none();
+        let watched = players.iter().any(|(position, _)| position.distance(pos.0) <= POPULATION_DESPAWN_RADIUS);
+        if !health.is_dead() && calm && !watched {
+            trace!("Despawning idle NPC {} ({:?}) far from players", npc.id, npc.archetype);
+            commands.entity(entity).despawn();
+            continue;
+        }
+        *residents.entry((key, npc.archetype)).or_default() += 1;
+    }
+
+    // Count down replacements; hand-placed NPCs come back once home is out of sight
+    for respawn in population.respawns.iter_mut() {
+        respawn.timer -= POPULATION_TICK_INTERVAL;
+    }
+    let mut pending = Vec::with_capacity(population.respawns.len());
+    for respawn in std::mem::take(&mut population.respawns) {
+        match respawn.origin {
+            NpcOrigin::Region(key) if respawn.timer > 0.0 => {
+                *residents.entry((key, respawn.archetype)).or_default() += 1;
+                pending.push(respawn);
+            }
+            // Its region refills it below
+            NpcOrigin::Region(_) => {}
+            NpcOrigin::Fixed if respawn.timer > 0.0 || !hidden(respawn.home) => pending.push(respawn),
+            NpcOrigin::Fixed => {
+                let id = population.next_id;
+                population.next_id += 1;
+                let entity =
+                    spawn_population_npc(&mut commands, respawn.archetype, id, respawn.home, 0.0, NpcOrigin::Fixed);
+                if let Some(places) = respawn.places {
+                    commands.entity(entity).insert(places);
+                }
+                if let Some(kind) = respawn.trader {
+                    commands.entity(entity).insert((TraderStock::new(kind), TraderRestock::new(kind)));
+                }
+                debug!("Respawned {:?} NPC {} at {:?}", respawn.archetype, id, respawn.home);
+            }
+        }
+    }
+    population.respawns = pending;
+
+    // Fill the regions around players, in key order so IDs and spawn points don't depend
+    // on player order
+    let regions: BTreeMap<RegionKey, PopulationRegion> = players
+        .iter()
+        .flat_map(|(position, _)| {
+            population_regions_near(&terrain.generator, Vec2::new(position.x, position.z), POPULATION_ACTIVE_RADIUS)
+        })
+        .map(|region| (region.key, region))
+        .collect();
+    let mut spawned = 0;
+    for region in regions.values() {
+        for &(archetype, target) in region.targets() {
+            let count = residents.get(&(region.key, archetype)).copied().unwrap_or(0);
+            for _ in count..target {
+                if spawned >= MAX_SPAWNS_PER_PASS {
+                    return;
+                }
+                let serial = population.serials.entry(region.key).or_default();
+                let attempt_serial = *serial;
+                *serial += 1;
+
+                let spot = (0..SPAWN_ATTEMPTS).find_map(|attempt| {
+                    let xz = region.spawn_candidate(attempt_serial, attempt);
+                    if obstacles.point_blocked(xz) {
+                        return None;
+                    }
+                    let point = Vec3::new(xz.x, terrain.get_height(xz.x, xz.y) + ground_clearance_center(), xz.y);
+                    hidden(point).then_some(point)
+                });
+                let Some(spot) = spot else {
+                    // Everywhere tried is in plain view: try again next pass
+                    break;
+                };
+
+                let id = population.next_id;
+                population.next_id += 1;
+                let yaw = (attempt_serial as f32 * 2.4).rem_euclid(std::f32::consts::TAU);
+                spawn_population_npc(&mut commands, archetype, id, spot, yaw, NpcOrigin::Region(region.key));
+                spawned += 1;
+                trace!("Spawned {:?} NPC {} for {:?} at {:?}", archetype, id, region.key, spot);
+            }
+        }
+    }
+}
+
+fn spawn_population_npc(
+    commands: &mut Commands,
+    archetype: NpcArchetype,
+    id: u64,
+    pos: Vec3,
+    yaw: f32,
+    origin: NpcOrigin,
+) -> Entity {
+    commands.spawn((
+        Npc { id, archetype },
+        NpcPosition(pos),
+        NpcRotation(yaw),
+        Health::new(npc_max_health(archetype)),
+        NpcWander::new(pos, 20.0, id),
+        origin,
+        Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
+    )).id()
+}
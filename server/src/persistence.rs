@@ -1,13 +1,17 @@
 //! Player persistence - disk I/O for player profiles
 //!
-//! Handles loading and saving player profiles to disk using bincode serialization.
-//! Uses atomic writes (temp file + rename) to prevent corruption.
+//! Handles loading and saving player profiles (and territory ownership) to disk
+//! using bincode serialization. Uses atomic writes (temp file + rename) to prevent
+//! corruption.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use bevy::prelude::*;
 use lightyear::prelude::PeerId;
-use shared::{PlayerProfile, PROFILE_VERSION, NameRejectionReason};
+use shared::{
+    decode_profile, decode_territory, profile_version, PlayerProfile, PROFILE_VERSION, NameRejectionReason,
+    TerritorySave, TERRITORY_SAVE_VERSION,
+};
 
 /// Resource managing player profile persistence
 #[derive(Resource)]
@@ -45,8 +49,8 @@ impl PlayerProfiles {
     /// Load a player profile from disk
     ///
     /// Returns:
-    /// - Ok(profile) if file exists and is valid
-    /// - Err(message) if file doesn't exist or is corrupted/wrong version
+    /// - Ok(profile) if file exists and is valid (older versions are upgraded)
+    /// - Err(message) if file doesn't exist or is corrupted/from an unknown version
     pub fn load_profile(&self, name: &str) -> Result<PlayerProfile, String> {
         let name_lower = name.to_lowercase();
         let path = self.storage_dir.join(format!("{}.bin", name_lower));
@@ -59,27 +63,42 @@ impl PlayerProfiles {
         let bytes = std::fs::read(&path)
             .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
 
-        // Deserialize
-        let profile: PlayerProfile = bincode::deserialize(&bytes)
-            .map_err(|e| format!("Failed to deserialize {}: {}", path.display(), e))?;
-
-        // Check version
-        if profile.version != PROFILE_VERSION {
-            // Backup old version
-            let backup_path = self.storage_dir.join(format!("{}.v{}.backup", name_lower, profile.version));
-            if let Err(e) = std::fs::copy(&path, &backup_path) {
-                warn!("Failed to backup old profile version: {}", e);
+        // Decode by the version header so older layouts get upgraded instead of failing
+        // to deserialize; anything that can't be used is backed up before it's discarded
+        let version = profile_version(&bytes);
+        let profile = match decode_profile(&bytes) {
+            Ok(profile) => profile,
+            Err(e) => {
+                let backup_path = self.backup_profile(&path, &name_lower, version);
+                return Err(format!(
+                    "Failed to load {} (version {:?}): {}. Backed up to {:?}",
+                    path.display(), version, e, backup_path
+                ));
             }
-
-            return Err(format!(
-                "Profile version mismatch: found v{}, expected v{}. Backed up to {:?}",
-                profile.version, PROFILE_VERSION, backup_path
-            ));
+        };
+
+        // Keep a copy of the old layout; the next save overwrites it with the current one
+        if version != Some(PROFILE_VERSION) {
+            let backup_path = self.backup_profile(&path, &name_lower, version);
+            info!(
+                "Upgraded profile '{}' from version {:?} to v{} (backup {:?})",
+                name, version, PROFILE_VERSION, backup_path
+            );
         }
 
         Ok(profile)
     }
 
+    /// Copy a profile file aside before it gets discarded or rewritten
+    fn backup_profile(&self, path: &Path, name_lower: &str, version: Option<u32>) -> PathBuf {
+        let tag = version.map(|v| format!("v{}", v)).unwrap_or_else(|| "unknown".to_string());
+        let backup_path = self.storage_dir.join(format!("{}.{}.backup", name_lower, tag));
+        if let Err(e) = std::fs::copy(path, &backup_path) {
+            warn!("Failed to back up profile {}: {}", path.display(), e);
+        }
+        backup_path
+    }
+
     /// Save a player profile to disk (atomic write via temp file)
     ///
     /// This uses a temporary file + rename to ensure atomic writes and prevent
@@ -141,3 +160,40 @@ impl PlayerProfiles {
         self.name_to_peer.contains_key(&name.to_lowercase())
     }
 }
+
+/// Load saved territory ownership and team chests (an empty save if there is none yet)
+///
+/// Older layouts are upgraded; a save that can't be read is backed up so a fresh one
+/// doesn't overwrite it.
+pub fn load_territory(path: &Path) -> Result<TerritorySave, String> {
+    if !path.exists() {
+        return Ok(TerritorySave { version: TERRITORY_SAVE_VERSION, ..default() });
+    }
+
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    decode_territory(&bytes).map_err(|e| {
+        let backup_path = path.with_extension("backup");
+        if let Err(copy_error) = std::fs::copy(path, &backup_path) {
+            warn!("Failed to back up territory save: {}", copy_error);
+        }
+        format!("Failed to load {}: {}. Backed up to {:?}", path.display(), e, backup_path)
+    })
+}
+
+/// Save territory ownership and team chests (atomic write via temp file)
+pub fn save_territory(path: &Path, save: &TerritorySave) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Create dir error: {}", e))?;
+    }
+    let temp_path = path.with_extension("tmp");
+
+    let bytes = bincode::serialize(save)
+        .map_err(|e| format!("Serialize error: {}", e))?;
+    std::fs::write(&temp_path, &bytes)
+        .map_err(|e| format!("Write temp file error: {}", e))?;
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| format!("Rename error: {}", e))?;
+    Ok(())
+}
@@ -13,13 +13,15 @@ use shared::{
     Vehicle, VehicleState, VehicleDriver, VehicleInput, InVehicle, VehicleType,
     WorldTerrain, FIXED_TIMESTEP_HZ, SPAWN_POSITION, RESPAWN_TIME,
     Health, EquippedWeapon, WeaponType,
-    Inventory, HotbarSelection,
+    Inventory, HotbarSelection, EquippedArmor,
     PlayerProfile, SubmitPlayerName, NameSubmissionResult, NameRejectionReason,
-    ReliableChannel,
+    PlayerName, QuestLog, ReliableChannel, Team, GameModeKind, MatchState,
 };
 
 use crate::inventory::PreviousHotbarSlot;
 use crate::persistence::PlayerProfiles;
+use crate::game_mode::{enemy_positions, give_starting_loadout, team_spawn_position, GameMode};
+use crate::teams::assign_team;
 
 /// Component added to dead players while waiting to respawn
 #[derive(Component)]
@@ -67,6 +69,10 @@ pub fn handle_connections(
             MessageReceiver::<shared::ShootRequest>::default(),
             MessageReceiver::<shared::SwitchWeapon>::default(),
             MessageReceiver::<shared::ReloadRequest>::default(),
+            MessageReceiver::<shared::InterruptReloadRequest>::default(),
+            MessageReceiver::<shared::ThrowRequest>::default(),
+            MessageReceiver::<shared::CycleFireModeRequest>::default(),
+            MessageReceiver::<shared::AdjustZeroRequest>::default(),
             // Name submission
             MessageReceiver::<SubmitPlayerName>::default(),
         ));
@@ -77,12 +83,21 @@ pub fn handle_connections(
             MessageReceiver::<shared::DropRequest>::default(),
             MessageReceiver::<shared::SelectHotbarSlot>::default(),
             MessageReceiver::<shared::InventoryMoveRequest>::default(),
+            // Armor messages
+            MessageReceiver::<shared::EquipArmorRequest>::default(),
+            MessageReceiver::<shared::UnequipArmorRequest>::default(),
             // Chest messages
             MessageReceiver::<shared::OpenChestRequest>::default(),
             MessageReceiver::<shared::CloseChestRequest>::default(),
             MessageReceiver::<shared::ChestTransferRequest>::default(),
             // Building messages
             MessageReceiver::<shared::PlaceBuildingRequest>::default(),
+            // Trade messages
+            MessageReceiver::<shared::TradeRequest>::default(),
+            // Dialogue messages
+            MessageReceiver::<shared::StartDialogueRequest>::default(),
+            MessageReceiver::<shared::DialogueChoiceRequest>::default(),
+            MessageReceiver::<shared::EndDialogueRequest>::default(),
         ));
 
         commands.entity(client_entity).insert((
@@ -91,7 +106,16 @@ pub fn handle_connections(
             MessageSender::<shared::DamageReceived>::default(),
             MessageSender::<shared::PlayerKilled>::default(),
             MessageSender::<shared::BulletImpact>::default(),
+            MessageSender::<shared::Explosion>::default(),
+            MessageSender::<shared::AudioEvent>::default(),
+            MessageSender::<shared::KillFeedEntry>::default(),
+            MessageSender::<shared::DamageLogReport>::default(),
+            MessageSender::<shared::MatchScoreboard>::default(),
+            MessageSender::<shared::CaptureAnnouncement>::default(),
             MessageSender::<NameSubmissionResult>::default(),
+            MessageSender::<shared::TradeResponse>::default(),
+            MessageSender::<shared::DialogueUpdate>::default(),
+            MessageSender::<shared::NpcVoiceLine>::default(),
         ));
     }
 }
@@ -102,15 +126,19 @@ pub fn handle_player_name_submission(
     mut commands: Commands,
     terrain: Res<WorldTerrain>,
     mut profiles: ResMut<PlayerProfiles>,
+    game_mode: Res<GameMode>,
     mut client_links: Query<(Entity, &RemoteId, &mut MessageReceiver<SubmitPlayerName>, &mut MessageSender<NameSubmissionResult>), With<ClientOf>>,
     // Check if this peer already has a player spawned
-    existing_players: Query<&Player>,
+    existing_players: Query<(&Player, Option<&Team>)>,
 ) {
+    // Teams handed out this tick (not visible in the query until commands apply)
+    let mut spawning_teams: Vec<Team> = Vec::new();
+
     for (client_entity, remote_id, mut receiver, mut sender) in client_links.iter_mut() {
         let peer_id = remote_id.0;
 
         // Check if player already spawned for this peer (prevent duplicate spawns)
-        if existing_players.iter().any(|p| p.client_id == peer_id) {
+        if existing_players.iter().any(|(p, _)| p.client_id == peer_id) {
             continue;
         }
 
@@ -227,6 +255,20 @@ pub fn handle_player_name_submission(
             let mut equipped_weapon_component = equipped_weapon;
             equipped_weapon_component.ammo_in_mag = weapon_ammo;
 
+            // Worn armor was dropped with the rest of the inventory if the player died
+            let equipped_armor = if profile.is_dead {
+                EquippedArmor::default()
+            } else {
+                profile.equipped_armor
+            };
+
+            // Rejoin the saved team (outside match modes, which balance every join), else the smaller one
+            let team = profile.team.filter(|_| !game_mode.has_matches()).unwrap_or_else(|| {
+                assign_team(existing_players.iter().filter_map(|(_, team)| team), &spawning_teams)
+            });
+            spawning_teams.push(team);
+            info!("Player '{}' joins team {}", name, team.name());
+
             // Spawn player entity
             let player_entity = commands.spawn((
                 Player { client_id: peer_id },
@@ -237,8 +279,10 @@ pub fn handle_player_name_submission(
                 health,
                 equipped_weapon_component,
                 inventory,
+                equipped_armor,
                 HotbarSelection { index: hotbar_sel },
                 PreviousHotbarSlot { index: Some(hotbar_sel as usize) },
+                (team, PlayerName(name.clone()), profile.quest_log.clone()),
                 Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
                 ControlledBy {
                     owner: client_entity,
@@ -290,6 +334,7 @@ pub fn handle_player_name_submission(
 pub fn handle_disconnections(
     trigger: On<Add, Disconnected>,
     mut profiles: ResMut<PlayerProfiles>,
+    game_mode: Res<GameMode>,
     client_entities: Query<&RemoteId>,
     players: Query<(
         Entity,
@@ -301,8 +346,11 @@ pub fn handle_disconnections(
         &EquippedWeapon,
         &Inventory,
         &HotbarSelection,
+        &EquippedArmor,
         Option<&InVehicle>,
         Option<&RespawnTimer>,
+        Option<&Team>,
+        &QuestLog,
     )>,
     mut vehicles: Query<(&mut VehicleDriver, &VehicleState, &Vehicle)>,
     mut inputs: ResMut<ClientInputs>,
@@ -330,15 +378,9 @@ pub fn handle_disconnections(
     info!("Saving state for player '{}'", name_lower);
 
     // Find player entity for this peer
-    let mut found_player = None;
-    for (player_entity, player, pos, rot, vel, health, weapon, inventory, hotbar, in_vehicle, respawn_timer) in players.iter() {
-        if player.client_id == peer_id {
-            found_player = Some((player_entity, pos, rot, vel, health, weapon, inventory, hotbar, in_vehicle, respawn_timer));
-            break;
-        }
-    }
-
-    let Some((_player_entity, pos, rot, vel, health, weapon, inventory, hotbar, in_vehicle, respawn_timer)) = found_player else {
+    let Some((_, _, pos, rot, vel, health, weapon, inventory, hotbar, armor, in_vehicle, respawn_timer, team, quests)) =
+        players.iter().find(|(_, player, ..)| player.client_id == peer_id)
+    else {
         warn!("Player entity not found for disconnected peer {:?} - state not saved!", peer_id);
         // Still free up the name even if we can't save
         profiles.peer_to_name.remove(&peer_id);
@@ -390,6 +432,7 @@ pub fn handle_disconnections(
         // Inventory - copy all slots
         inventory_slots: *inventory.slots(),
         hotbar_selection: hotbar.index,
+        equipped_armor: *armor,
 
         // Vehicle state
         in_vehicle: in_veh,
@@ -416,17 +459,28 @@ pub fn handle_disconnections(
             .get(&name_lower)
             .map(|p| p.total_playtime_secs)
             .unwrap_or(0), // TODO: Increment with actual playtime
+
+        // Team
+        team: team.copied(),
+
+        // Quests
+        quest_log: quests.clone(),
     };
 
-    // Save to disk
-    if let Err(e) = profiles.save_profile(&profile) {
-        error!("Failed to save profile for '{}': {}", name_lower, e);
-    }
+    // Match modes hand out their own loadouts; the saved (sandbox) profile stays untouched
+    if game_mode.has_matches() {
+        info!("Match mode: not saving match state over the profile of '{}'", name_lower);
+    } else {
+        // Save to disk
+        if let Err(e) = profiles.save_profile(&profile) {
+            error!("Failed to save profile for '{}': {}", name_lower, e);
+        }
 
-    // Update in-memory profile
-    profiles.profiles.insert(name_lower.clone(), profile);
+        // Update in-memory profile
+        profiles.profiles.insert(name_lower.clone(), profile);
 
-    info!("Successfully saved state for player '{}'", name_lower);
+        info!("Successfully saved state for player '{}'", name_lower);
+    }
 
     // Clear any vehicles they were driving
     for (mut driver, _, _) in vehicles.iter_mut() {
@@ -658,6 +712,7 @@ const AUTO_SAVE_INTERVAL: f32 = 30.0;
 /// Periodically save all connected players
 pub fn periodic_player_save(
     profiles: Res<PlayerProfiles>,
+    game_mode: Res<GameMode>,
     players: Query<(
         &Player,
         &PlayerPosition,
@@ -667,13 +722,21 @@ pub fn periodic_player_save(
         &EquippedWeapon,
         &Inventory,
         &HotbarSelection,
+        &EquippedArmor,
         Option<&InVehicle>,
         Option<&RespawnTimer>,
+        Option<&Team>,
+        &QuestLog,
     )>,
     vehicles: Query<(&VehicleState, &Vehicle)>,
     time: Res<Time>,
     mut last_save_time: Local<f32>,
 ) {
+    // Match loadouts are handed out per round and must not overwrite the sandbox profile
+    if game_mode.has_matches() {
+        return;
+    }
+
     let now = time.elapsed_secs();
     if now - *last_save_time < AUTO_SAVE_INTERVAL {
         return;
@@ -682,7 +745,9 @@ pub fn periodic_player_save(
     *last_save_time = now;
 
     let mut saved_count = 0;
-    for (player, pos, rot, vel, health, weapon, inventory, hotbar, in_vehicle, respawn_timer) in players.iter() {
+    for (player, pos, rot, vel, health, weapon, inventory, hotbar, armor, in_vehicle, respawn_timer, team, quests)
+        in players.iter()
+    {
         // Get player name from tracking
         let Some(name_lower) = profiles.peer_to_name.get(&player.client_id) else {
             continue;
@@ -728,6 +793,7 @@ pub fn periodic_player_save(
             // Inventory
             inventory_slots: *inventory.slots(),
             hotbar_selection: hotbar.index,
+            equipped_armor: *armor,
 
             // Vehicle state
             in_vehicle: in_veh,
@@ -754,6 +820,10 @@ pub fn periodic_player_save(
                 .get(name_lower)
                 .map(|p| p.total_playtime_secs)
                 .unwrap_or(0),
+
+            // Team
+            team: team.copied(),
+            quest_log: quests.clone(),
         };
 
         // Save to disk
@@ -802,14 +872,45 @@ pub fn check_player_deaths(
 }
 
 /// Tick respawn timers and respawn players when ready
+///
+/// In match modes players respawn at their team's spawn point furthest from the
+/// enemy, with the starting loadout.
 pub fn tick_respawn_timers(
     mut commands: Commands,
     terrain: Res<WorldTerrain>,
-    mut players: Query<(Entity, &Player, &mut Health, &mut PlayerPosition, &mut PlayerVelocity, &mut RespawnTimer)>,
+    game_mode: Res<GameMode>,
+    matches: Query<&MatchState>,
+    mut players: Query<(
+        Entity,
+        &Player,
+        &mut Health,
+        &mut PlayerPosition,
+        &mut PlayerVelocity,
+        Option<&mut RespawnTimer>,
+        Option<&Team>,
+        (&mut Inventory, &mut EquippedWeapon, &mut EquippedArmor, &mut HotbarSelection),
+    )>,
 ) {
     let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+
+    // Battle royale: the eliminated sit out the rest of the round
+    let eliminated = game_mode.kind == GameModeKind::BattleRoyale && matches.iter().any(|m| m.is_live());
+
+    // Living players' positions (for picking spawns away from enemies)
+    let living: Vec<(Option<Team>, Vec3, bool)> = players
+        .iter()
+        .map(|(_, _, health, position, _, timer, team, _)| (team.copied(), position.0, !health.is_dead() && timer.is_none()))
+        .collect();
     
-    for (entity, player, mut health, mut position, mut velocity, mut timer) in players.iter_mut() {
+    for (entity, player, mut health, mut position, mut velocity, timer, team, (mut inventory, mut weapon, mut armor, mut hotbar)) in
+        players.iter_mut()
+    {
+        let Some(mut timer) = timer else {
+            continue;
+        };
+        if eliminated {
+            continue;
+        }
         timer.time_remaining -= dt;
         
         if timer.time_remaining <= 0.0 {
@@ -818,11 +919,20 @@ pub fn tick_respawn_timers(
             // Reset health
             health.current = health.max;
             
-            // Reset position to spawn point
-            let spawn_x = SPAWN_POSITION[0];
-            let spawn_z = SPAWN_POSITION[2];
-            let ground_y = terrain.get_height(spawn_x, spawn_z);
-            position.0 = Vec3::new(spawn_x, ground_y + ground_clearance_center(), spawn_z);
+            match team.copied().filter(|_| game_mode.has_matches()) {
+                Some(team) => {
+                    // Team spawn away from enemies, fresh loadout
+                    position.0 = team_spawn_position(team, &enemy_positions(Some(team), living.iter().copied()), &terrain);
+                    give_starting_loadout(&mut inventory, &mut weapon, &mut armor, &mut hotbar);
+                }
+                None => {
+                    // Reset position to spawn point
+                    let spawn_x = SPAWN_POSITION[0];
+                    let spawn_z = SPAWN_POSITION[2];
+                    let ground_y = terrain.get_height(spawn_x, spawn_z);
+                    position.0 = Vec3::new(spawn_x, ground_y + ground_clearance_center(), spawn_z);
+                }
+            }
             
             // Reset velocity
             velocity.0 = Vec3::ZERO;
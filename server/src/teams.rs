@@ -0,0 +1,104 @@
+//! Server-side teams and friendly fire
+//!
+//! Players are put on the smaller team when they spawn. Hit handlers look up the
+//! `FriendlyFire` policy when a player hits a teammate; under `Reflect` the damage
+//! is queued in `ReflectedDamage` and dealt to the attacker after every other
+//! damage source this tick, before the kill feed goes out.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+
+use shared::{
+    balanced_team, weapons::damage::HitZone, DamageCause, FriendlyFirePolicy, Health, Player, Team, TEAM_COUNT,
+};
+
+use crate::combat_log::{CombatHit, CombatLog, CombatantRef};
+
+/// Env var that overrides the friendly fire policy (off / reduced / full / reflect)
+const FRIENDLY_FIRE_ENV: &str = "FRIENDLY_FIRE";
+
+/// Server friendly fire policy
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct FriendlyFire {
+    pub policy: FriendlyFirePolicy,
+}
+
+impl Default for FriendlyFire {
+    fn default() -> Self {
+        let policy = match std::env::var(FRIENDLY_FIRE_ENV) {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                warn!("{}, using default friendly fire policy", e);
+                FriendlyFirePolicy::default()
+            }),
+            Err(_) => FriendlyFirePolicy::default(),
+        };
+        info!("Friendly fire policy: {:?}", policy);
+        Self { policy }
+    }
+}
+
+/// Damage a teammate hit would have done, to be dealt back to the attacker
+#[derive(Clone, Copy, Debug)]
+pub struct ReflectedHit {
+    pub attacker: PeerId,
+    pub damage: f32,
+    pub cause: DamageCause,
+    pub hit_zone: HitZone,
+    pub distance: f32,
+}
+
+/// Reflected friendly fire waiting to be applied this tick
+#[derive(Resource, Default)]
+pub struct ReflectedDamage {
+    pending: Vec<ReflectedHit>,
+}
+
+impl ReflectedDamage {
+    pub fn push(&mut self, hit: ReflectedHit) {
+        self.pending.push(hit);
+    }
+}
+
+/// Team for a newly spawning player (`spawning` = teams already handed out this tick)
+pub fn assign_team<'a>(existing: impl IntoIterator<Item = &'a Team>, spawning: &'a [Team]) -> Team {
+    let mut counts = [0usize; TEAM_COUNT];
+    for team in existing.into_iter().chain(spawning) {
+        if let Some(count) = counts.get_mut(team.0 as usize) {
+            *count += 1;
+        }
+    }
+    balanced_team(&counts)
+}
+
+/// Deal reflected friendly fire to the attackers
+pub fn apply_reflected_damage(
+    mut reflected: ResMut<ReflectedDamage>,
+    mut players: Query<(&Player, &mut Health)>,
+    mut combat_log: ResMut<CombatLog>,
+) {
+    for hit in reflected.pending.drain(..) {
+        let Some((_, mut health)) = players.iter_mut().find(|(p, _)| p.client_id == hit.attacker) else {
+            continue;
+        };
+        if health.is_dead() {
+            continue;
+        }
+
+        let kill = health.take_damage(hit.damage);
+        info!(
+            "Friendly fire reflected onto {:?}: {:.1} damage (kill: {})",
+            hit.attacker, hit.damage, kill
+        );
+
+        combat_log.record(CombatHit {
+            attacker: CombatantRef::Player(hit.attacker),
+            victim: CombatantRef::Player(hit.attacker),
+            cause: hit.cause,
+            hit_zone: hit.hit_zone,
+            damage: hit.damage,
+            armor_absorbed: 0.0,
+            distance: hit.distance,
+            kill,
+        });
+    }
+}
@@ -0,0 +1,183 @@
+//! Territory mode: settlement capture, ownership persistence and resource income
+//!
+//! With `GAME_MODE=territory` every settlement near spawn gets a replicated
+//! `ControlPoint` once the server starts, with owners restored from disk, and each
+//! team gets a chest at its base (contents restored too). Capture progress runs every
+//! fixed tick from the living players standing in each zone; a capture is announced
+//! to everyone and saved straight away. Owned points pay resources into their team's
+//! chest, and the save is rewritten whenever a team chest's contents change. Players
+//! keep their team across restarts through their profile (see `PlayerProfile::team`).
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    team_base, territory_control_points, CaptureAnnouncement, CaptureEvent, ChestStorage, ControlPoint,
+    GameModeKind, Health, Player, PlayerPosition, ReliableChannel, Team, TerritorySave, WorldTerrain, FIXED_TIMESTEP_HZ,
+    SPAWN_POSITION, TEAM_COUNT, TERRITORY_INCOME, TERRITORY_INCOME_INTERVAL, TERRITORY_SAVE_VERSION,
+};
+
+use crate::game_mode::GameMode;
+use crate::inventory::spawn_chest;
+use crate::persistence::{load_territory, save_territory};
+use crate::systems::RespawnTimer;
+
+/// Where control point ownership is kept between restarts
+const TERRITORY_SAVE_PATH: &str = "server_data/territory.bin";
+
+/// Team chest distance behind the team's base (m, away from the map spawn)
+const TEAM_CHEST_OFFSET: f32 = 6.0;
+
+/// Marks the chest territory income is paid into
+#[derive(Component)]
+pub struct TeamChest(pub Team);
+
+/// Territory bookkeeping
+#[derive(Resource)]
+pub struct Territory {
+    pub save_path: PathBuf,
+    /// Seconds until the next payout
+    income_timer: f32,
+}
+
+impl Default for Territory {
+    fn default() -> Self {
+        Self {
+            save_path: PathBuf::from(TERRITORY_SAVE_PATH),
+            income_timer: TERRITORY_INCOME_INTERVAL,
+        }
+    }
+}
+
+/// Marker: control points and team chests have been spawned
+#[derive(Resource)]
+pub struct TerritorySpawned;
+
+/// Spawn control points (owners restored from disk) and team chests once the server is up
+pub fn spawn_territory_once(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    terrain: Res<WorldTerrain>,
+    territory: Res<Territory>,
+    spawned: Option<Res<TerritorySpawned>>,
+) {
+    if spawned.is_some() || game_mode.kind != GameModeKind::Territory {
+        return;
+    }
+    commands.insert_resource(TerritorySpawned);
+
+    let save = load_territory(&territory.save_path).unwrap_or_else(|e| {
+        warn!("{}, starting with neutral territory", e);
+        TerritorySave::default()
+    });
+
+    let mut points = territory_control_points(&terrain.generator);
+    for point in points.iter_mut() {
+        point.owner = save.owners.iter().find(|(id, _)| *id == point.id).map(|(_, team)| *team);
+    }
+    info!(
+        "Territory: {} control points, {} owned",
+        points.len(),
+        points.iter().filter(|p| p.owner.is_some()).count()
+    );
+    for point in points {
+        commands.spawn((point, Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All))));
+    }
+
+    for index in 0..TEAM_COUNT {
+        let team = Team(index as u8);
+        let base = team_base(team);
+        let outward = (base - Vec2::new(SPAWN_POSITION[0], SPAWN_POSITION[2])).normalize_or_zero();
+        let point = base + outward * TEAM_CHEST_OFFSET;
+        let position = Vec3::new(point.x, terrain.get_height(point.x, point.y) + 0.5, point.y);
+        let storage = save.chests.iter().find(|(owner, _)| *owner == team).map(|(_, storage)| storage.clone());
+        let chest = spawn_chest(&mut commands, position, Some(team), Vec::new());
+        commands.entity(chest).insert((TeamChest(team), storage.unwrap_or_default()));
+        info!("Spawned {} team chest at {:?}", team.name(), position);
+    }
+}
+
+/// Run captures, announce and save ownership changes, and pay income into team chests
+pub fn tick_territory(
+    game_mode: Res<GameMode>,
+    mut territory: ResMut<Territory>,
+    mut points: Query<&mut ControlPoint>,
+    players: Query<(Option<&Team>, &Health, &PlayerPosition, Has<RespawnTimer>), With<Player>>,
+    mut chests: Query<(&TeamChest, &mut ChestStorage)>,
+    mut client_links: Query<&mut MessageSender<CaptureAnnouncement>, (With<ClientOf>, With<Connected>)>,
+) {
+    if game_mode.kind != GameModeKind::Territory {
+        return;
+    }
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+
+    let mut captured = false;
+    for mut point in points.iter_mut() {
+        let mut present = [0u32; TEAM_COUNT];
+        for (team, health, position, respawning) in players.iter() {
+            let Some(team) = team else {
+                continue;
+            };
+            if !respawning && !health.is_dead() && point.contains(position.0) {
+                if let Some(count) = present.get_mut(team.0 as usize) {
+                    *count += 1;
+                }
+            }
+        }
+
+        // Only touch the component when something changed (it's replicated)
+        let mut next = point.clone();
+        let event = next.tick(present, dt);
+        if next != *point {
+            *point = next;
+        }
+
+        if let Some(CaptureEvent::Captured { team, previous }) = event {
+            info!("{} captured point {} (from {:?})", team.name(), point.label(), previous.map(|t| t.name()));
+            let announcement = CaptureAnnouncement { point_id: point.id, label: point.label(), team, previous };
+            for mut sender in client_links.iter_mut() {
+                sender.send::<ReliableChannel>(announcement.clone());
+            }
+            captured = true;
+        }
+    }
+
+    territory.income_timer -= dt;
+    if territory.income_timer <= 0.0 {
+        territory.income_timer += TERRITORY_INCOME_INTERVAL;
+        for (team_chest, mut storage) in chests.iter_mut() {
+            // Only borrow the chest mutably when there's income, so idle teams don't rewrite the save
+            let owned = points.iter().filter(|p| p.owner == Some(team_chest.0)).count() as u32;
+            if owned > 0 {
+                pay_income(team_chest.0, owned, &mut storage);
+            }
+        }
+    }
+
+    // Captures, income and players moving items in or out all rewrite the save
+    let chests_changed = chests.iter_mut().any(|(_, storage)| storage.is_changed());
+    if captured || chests_changed {
+        let save = TerritorySave {
+            version: TERRITORY_SAVE_VERSION,
+            owners: points.iter().filter_map(|p| p.owner.map(|team| (p.id, team))).collect(),
+            chests: chests.iter().map(|(team_chest, storage)| (team_chest.0, storage.clone())).collect(),
+        };
+        if let Err(e) = save_territory(&territory.save_path, &save) {
+            error!("Failed to save territory: {}", e);
+        }
+    }
+}
+
+/// Pay `team`'s income for the `owned` points it holds into its chest
+fn pay_income(team: Team, owned: u32, storage: &mut ChestStorage) {
+    for (item_type, amount) in TERRITORY_INCOME {
+        let overflow = storage.add_item(item_type, amount * owned);
+        if overflow > 0 {
+            debug!("{} team chest full, {} {:?} lost", team.name(), overflow, item_type);
+        }
+    }
+    info!("Paid {} team income for {} points", team.name(), owned);
+}
@@ -0,0 +1,113 @@
+//! Server-side trading
+//!
+//! Vendor NPCs (`TraderStock`) keep stalls around the medieval town square. A trade
+//! request is only run when the trader is alive, out in the open (not `NpcIndoors`) and
+//! within `TRADE_RANGE` of the player;
+//! `TraderStock::trade` then updates the inventory and stock together or not at all, and
+//! every request is answered with a `TradeResponse`.
+
+use bevy::prelude::*;
+use lightyear::prelude::*;
+use lightyear::prelude::server::*;
+
+use shared::{
+    Health, HotbarSelection, Inventory, Npc, NpcIndoors, NpcPosition, Player, PlayerPosition, ReliableChannel,
+    TradeError, TradeRequest, TradeResponse, TraderKind, TraderStock, FIXED_TIMESTEP_HZ, TRADE_RANGE,
+};
+
+/// Stalls around the town square (XZ offset from its center) and who keeps them
+pub const TOWN_STALLS: [(TraderKind, Vec2); 3] = [
+    (TraderKind::General, Vec2::new(6.0, 6.0)),
+    (TraderKind::Armorer, Vec2::new(-6.0, 6.0)),
+    (TraderKind::Gunsmith, Vec2::new(0.0, -7.0)),
+];
+
+/// Server-only: seconds until each price list line restocks a unit
+#[derive(Component, Clone, Debug)]
+pub struct TraderRestock(Vec<f32>);
+
+impl TraderRestock {
+    pub fn new(kind: TraderKind) -> Self {
+        Self(kind.price_list().iter().map(|line| line.restock_time).collect())
+    }
+}
+
+/// Run buy / sell requests and answer each one
+pub fn handle_trade_requests(
+    mut client_links: Query<
+        (&RemoteId, &mut MessageReceiver<TradeRequest>, &mut MessageSender<TradeResponse>),
+        With<ClientOf>,
+    >,
+    mut players: Query<(&Player, &PlayerPosition, &Health, &HotbarSelection, &mut Inventory)>,
+    mut traders: Query<(&Npc, &NpcPosition, &Health, &mut TraderStock), Without<NpcIndoors>>,
+) {
+    for (remote_id, mut receiver, mut sender) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+
+        for request in receiver.receive() {
+            let Some((_, player_pos, player_health, hotbar, mut inventory)) =
+                players.iter_mut().find(|(p, ..)| p.client_id == peer_id)
+            else {
+                continue;
+            };
+            if player_health.is_dead() {
+                continue;
+            }
+
+            let trader = traders.iter_mut().find(|(npc, pos, health, _)| {
+                npc.id == request.trader && !health.is_dead() && pos.0.distance(player_pos.0) <= TRADE_RANGE
+            });
+            let result = match trader {
+                Some((_, _, _, mut stock)) => stock.trade(
+                    &mut inventory,
+                    request.line as usize,
+                    request.quantity,
+                    request.buy,
+                    request.slot.map(usize::from),
+                    hotbar.index as usize,
+                ),
+                None => Err(TradeError::TooFar),
+            };
+
+            match &result {
+                Ok(receipt) => info!(
+                    "Player {:?} {} {}x {} for {} coins at trader {}",
+                    peer_id,
+                    if receipt.bought { "bought" } else { "sold" },
+                    receipt.quantity,
+                    receipt.item.display_name(),
+                    receipt.coins,
+                    request.trader
+                ),
+                Err(error) => debug!("Player {:?} trade with {} refused: {:?}", peer_id, request.trader, error),
+            }
+            sender.send::<ReliableChannel>(TradeResponse { trader: request.trader, result });
+        }
+    }
+}
+
+/// Restock living traders one unit per line at a time, up to each line's max
+pub fn tick_trader_restock(mut traders: Query<(&mut TraderStock, &mut TraderRestock, &Health)>) {
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+
+    for (mut stock, mut restock, health) in traders.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+        for (line, entry) in stock.price_list().iter().enumerate() {
+            let Some(timer) = restock.0.get_mut(line) else {
+                continue;
+            };
+            // The clock only runs while the line is short (reading doesn't mark the stock changed)
+            if stock.available(line) >= entry.max_stock {
+                *timer = entry.restock_time;
+                continue;
+            }
+            *timer -= dt;
+            if *timer <= 0.0 {
+                stock.restock(line);
+                *timer = entry.restock_time;
+            }
+        }
+    }
+}
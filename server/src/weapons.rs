@@ -8,15 +8,26 @@ use lightyear::prelude::*;
 use lightyear::prelude::server::*;
 
 use shared::{
-    weapons::{ballistics, damage},
+    weapons::{
+        apply_recoil, apply_zero, ballistics, burst_shot_index, closest_approach, damage, is_supersonic,
+        recoil_offset_matches, recoil_step_matches, remove_recoil, AdjustZeroRequest, CycleFireModeRequest, ReloadStep,
+        Reloading, Suppression, NEAR_MISS_RADIUS,
+    },
     Bullet, BulletPrevPosition, BulletVelocity, EquippedWeapon, Health,
-    BulletImpact, BulletImpactSurface, HitConfirm, DamageReceived, PlayerKilled, ShootRequest, SwitchWeapon, ReloadRequest, ReliableChannel,
-    AudioEvent, AudioEventKind,
-    npc_capsule_endpoints, npc_head_center, Npc, NpcPosition, NpcDamageEvent, NPC_HEAD_RADIUS, NPC_HEIGHT, NPC_RADIUS,
-    Player, PlayerPosition, WorldTerrain, FIXED_TIMESTEP_HZ, PLAYER_HEIGHT, PLAYER_RADIUS,
+    BulletImpact, BulletImpactSurface, HitConfirm, DamageReceived, PlayerKilled, ShootRequest, SwitchWeapon,
+    ReloadRequest, InterruptReloadRequest, ReliableChannel,
+    AudioEvent, AudioEventKind, EquippedArmor, apply_armored_hit,
+    Npc, NpcIndoors, NpcPosition, NpcRotation, NpcDamageEvent, HitboxPose, HitboxRig, HitboxStance, RigHit,
+    Player, PlayerPosition, PlayerRotation, PlayerVelocity, PlayerGrounded, WeaponType,
+    InVehicle, Wind, WorldTerrain, DamageCause, Team, are_allies, FIXED_TIMESTEP_HZ, PLAYER_HEIGHT,
 };
 
 use crate::colliders::{DerivedColliderLibrary, StaticColliders, StructureColliders};
+use crate::combat_log::{CombatHit, CombatLog, CombatantRef};
+use crate::npc::NpcWander;
+use crate::npc_combat::NpcShooter;
+use crate::npc_perception::NpcNoises;
+use crate::teams::{FriendlyFire, ReflectedDamage, ReflectedHit};
 
 /// Server-only marker used to delay bullet despawn by a few frames.
 ///
@@ -49,8 +60,9 @@ fn peer_id_to_u64(peer_id: PeerId) -> u64 {
 pub fn handle_shoot_requests(
     mut commands: Commands,
     mut client_links: Query<(&RemoteId, &mut MessageReceiver<ShootRequest>), With<ClientOf>>,
-    mut players: Query<(&Player, &PlayerPosition, &mut EquippedWeapon)>,
+    mut players: Query<(Entity, &Player, &PlayerPosition, &mut EquippedWeapon, Option<&Reloading>, Option<&Suppression>)>,
     mut audio_senders: Query<&mut MessageSender<AudioEvent>, (With<ClientOf>, With<Connected>)>,
+    mut npc_noises: ResMut<NpcNoises>,
     time: Res<Time>,
 ) {
     let current_time = time.elapsed_secs();
@@ -63,39 +75,102 @@ pub fn handle_shoot_requests(
         
         for request in receiver.receive() {
             // Find the player who sent the request
-            let Some((player, position, mut weapon)) = players.iter_mut().find(|(p, _, _)| p.client_id == peer_id) else {
+            let Some((player_entity, player, position, mut weapon, reloading, suppression)) =
+                players.iter_mut().find(|(_, p, ..)| p.client_id == peer_id)
+            else {
                 continue;
             };
             
             // Update aiming state
             weapon.aiming = request.aiming;
             
-            // Check if can fire
-            if !weapon.can_fire(current_time) {
+            // No shots mid-reload - except that firing a partly loaded shotgun
+            // interrupts its shell-by-shell reload
+            if let Some(reload) = reloading {
+                if reload.is_per_shell() && weapon.ammo_in_mag > 0 {
+                    commands.entity(player_entity).remove::<Reloading>();
+                } else {
+                    continue;
+                }
+            }
+            
+            // Melee weapons (and fists) swing instead of firing bullets
+            if weapon.weapon_type.is_melee() {
+                crate::melee::start_melee_swing(
+                    &mut commands,
+                    player_entity,
+                    &mut weapon,
+                    request.direction,
+                    current_time,
+                );
                 continue;
             }
             
             // Get weapon stats
             let stats = weapon.weapon_type.stats();
             
-            // Fire the weapon (consumes ammo, updates cooldown)
-            if !weapon.fire(current_time) {
+            let since_last_shot = current_time - weapon.last_fire_time;
+            let shot_index = burst_shot_index(weapon.burst_shots, since_last_shot, request.shot_index);
+            
+            // Mid-burst the aim should roughly climb by the previous round's kick. Players
+            // pulling down hard or tracking a target can legitimately miss this, so it's only
+            // noted; the recoil re-apply below is what enforces the pattern.
+            if shot_index > 0 {
+                let kick = weapon.weapon_type.recoil_kick(shot_index - 1, request.aiming);
+                if !recoil_step_matches(weapon.last_shot_direction, request.direction, kick) {
+                    debug!(
+                        "Player {:?} aim didn't follow the recoil pattern on shot {} of burst",
+                        peer_id, shot_index
+                    );
+                }
+            }
+            
+            // Fire the weapon (consumes ammo, updates cooldown, enforces the fire mode's
+            // rounds-per-pull from shot timing so a client can't turn semi-auto into full-auto)
+            if !weapon.fire_in_mode(current_time) {
                 continue;
             }
             
+            // Recoil: walk the weapon's pattern with our own burst count. The reported
+            // direction should carry the recoil of every earlier round in the burst;
+            // if it doesn't, strip what the client claims and apply the pattern ourselves.
+            if shot_index == 0 {
+                weapon.recoil_offset = Vec2::ZERO;
+            }
+            let expected_recoil = weapon.recoil_offset;
+            let direction = if recoil_offset_matches(expected_recoil, request.recoil_offset) {
+                request.direction.normalize_or(Vec3::NEG_Z)
+            } else {
+                warn!(
+                    "Player {:?} reported recoil {:?} for shot {} of burst, pattern expects {:?}",
+                    peer_id, request.recoil_offset, shot_index, expected_recoil
+                );
+                apply_recoil(remove_recoil(request.direction, request.recoil_offset), expected_recoil)
+            };
+            weapon.recoil_offset += weapon.weapon_type.recoil_kick(shot_index, request.aiming);
+            weapon.burst_shots = shot_index + 1;
+            weapon.last_shot_direction = request.direction;
+            
+            // Zeroed sights: tilt the bore so the round meets the line of sight at the zero range
+            let aim_direction = if request.aiming {
+                apply_zero(direction, weapon.weapon_type.zero_angle(weapon.zero_range))
+            } else {
+                direction
+            };
+            
             // Calculate spawn position at gun muzzle height
             let gun_height = PLAYER_HEIGHT * 0.29;
-            let forward = request.direction.normalize();
+            let forward = aim_direction;
             let right = forward.cross(Vec3::Y).normalize_or_zero();
             let spawn_offset = forward * 0.5 + right * 0.25;
             let spawn_pos = position.0 + Vec3::new(0.0, gun_height, 0.0) + spawn_offset;
             
-            // Apply spread to direction
-            let spread = weapon.current_spread();
+            // Apply spread to direction (near misses shake the shooter's aim)
+            let spread = weapon.current_spread() + suppression.map(|s| s.extra_spread()).unwrap_or(0.0);
             
             // Spawn bullets (multiple for shotgun)
             for _ in 0..stats.pellet_count {
-                let spread_direction = ballistics::apply_spread(request.direction, spread);
+                let spread_direction = ballistics::apply_spread(aim_direction, spread);
                 let velocity = spread_direction * stats.bullet_speed;
                 
                 commands.spawn((
@@ -116,6 +191,7 @@ pub fn handle_shoot_requests(
             
             // Record shot for audio broadcast
             shots_fired.push((peer_id_to_u64(player.client_id), spawn_pos, weapon.weapon_type));
+            npc_noises.gunshot(spawn_pos, weapon.weapon_type, Some(player_entity));
             
             info!(
                 "Player {:?} fired {:?} (ammo: {}/{})", 
@@ -147,16 +223,20 @@ pub fn simulate_bullets(
         &mut Transform,
         &mut PlayerPosition,
     ), Without<BulletPendingDespawn>>,
+    wind: Query<&Wind>,
 ) {
     let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+    let wind = wind.iter().next().map(|w| w.velocity).unwrap_or(Vec3::ZERO);
     
-    for (_bullet, mut velocity, mut prev_pos, mut transform, mut position) in bullets.iter_mut() {
+    for (bullet, mut velocity, mut prev_pos, mut transform, mut position) in bullets.iter_mut() {
         prev_pos.0 = transform.translation;
         
-        let (new_pos, new_vel) = ballistics::step_bullet_physics(
+        let (new_pos, new_vel) = ballistics::step_bullet_physics_in_wind(
             transform.translation,
             velocity.0,
             dt,
+            bullet.weapon_type.ballistic_coefficient(),
+            wind,
         );
         
         transform.translation = new_pos;
@@ -165,16 +245,93 @@ pub fn simulate_bullets(
     }
 }
 
-/// Detect bullet hits against players
+/// Let suppression wear off
+pub fn decay_suppression(
+    mut commands: Commands,
+    mut suppressed: Query<(Entity, &mut Suppression)>,
+) {
+    let dt = 1.0 / FIXED_TIMESTEP_HZ as f32;
+    for (entity, mut suppression) in suppressed.iter_mut() {
+        if !suppression.decay(dt) {
+            commands.entity(entity).remove::<Suppression>();
+        }
+    }
+}
+
+/// Detect rounds passing close to players: crack/whiz audio for the target and suppression
+pub fn detect_near_misses(
+    mut commands: Commands,
+    bullets: Query<(&Bullet, &BulletVelocity, &BulletPrevPosition, &Transform), Without<BulletPendingDespawn>>,
+    mut players: Query<(Entity, &Player, &PlayerPosition, &Health, Option<&mut Suppression>)>,
+    mut audio_senders: Query<(&RemoteId, &mut MessageSender<AudioEvent>), (With<ClientOf>, With<Connected>)>,
+) {
+    let head_height = PLAYER_HEIGHT * 0.4;
+
+    for (entity, player, position, health, suppression) in players.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+        let player_id = peer_id_to_u64(player.client_id);
+        let head = position.0 + Vec3::new(0.0, head_height, 0.0);
+
+        // Closest round this tick (a shotgun blast is one crack, not nine)
+        let mut closest: Option<(u64, Vec3, f32, f32)> = None;
+        for (bullet, velocity, prev_pos, transform) in bullets.iter() {
+            if bullet.owner_id == player_id {
+                continue;
+            }
+            let Some((point, distance)) = closest_approach(prev_pos.0, transform.translation, head) else {
+                continue;
+            };
+            if distance < NEAR_MISS_RADIUS && closest.is_none_or(|(_, _, best, _)| distance < best) {
+                closest = Some((bullet.owner_id, point, distance, velocity.0.length()));
+            }
+        }
+        let Some((shooter_id, point, distance, speed)) = closest else {
+            continue;
+        };
+
+        match suppression {
+            Some(mut suppression) => suppression.add_near_miss(distance),
+            None => {
+                let mut suppression = Suppression::default();
+                suppression.add_near_miss(distance);
+                commands.entity(entity).insert(suppression);
+            }
+        }
+
+        let event = AudioEvent {
+            player_id: shooter_id,
+            position: point,
+            kind: AudioEventKind::NearMiss {
+                supersonic: is_supersonic(speed),
+                miss_distance: distance,
+            },
+        };
+        if let Some((_, mut sender)) = audio_senders.iter_mut().find(|(remote_id, _)| remote_id.0 == player.client_id) {
+            sender.send::<ReliableChannel>(event);
+        }
+    }
+}
+
+/// Detect bullet hits against players and NPCs (rounds from players or NPC shooters)
 pub fn detect_bullet_hits(
     mut commands: Commands,
     time: Res<Time>,
     bullets: Query<
-        (Entity, &Bullet, &BulletVelocity, &BulletPrevPosition, &Transform),
+        (Entity, &Bullet, &BulletVelocity, &BulletPrevPosition, &Transform, Option<&NpcShooter>),
         Without<BulletPendingDespawn>,
     >,
-    mut players: Query<(Entity, &Player, &PlayerPosition, &mut Health), (With<Player>, Without<Npc>)>,
-    mut npcs: Query<(Entity, &Npc, &NpcPosition, &mut Health), (With<Npc>, Without<Player>)>,
+    mut players: Query<
+        (Entity, &Player, &PlayerPosition, &mut Health, Option<&mut EquippedArmor>, Option<&Team>),
+        (With<Player>, Without<Npc>),
+    >,
+    mut npcs: Query<(Entity, &Npc, &NpcPosition, &mut Health), (With<Npc>, Without<Player>, Without<NpcIndoors>)>,
+    player_poses: Query<
+        (&PlayerRotation, &PlayerVelocity, &PlayerGrounded, &EquippedWeapon),
+        (With<Player>, Without<Npc>),
+    >,
+    npc_poses: Query<(&NpcRotation, Option<&NpcWander>), (With<Npc>, Without<Player>)>,
     mut client_links: Query<
         (
             &RemoteId,
@@ -185,6 +342,9 @@ pub fn detect_bullet_hits(
         ),
         (With<ClientOf>, With<Connected>),
     >,
+    mut combat_log: ResMut<CombatLog>,
+    friendly_fire: Res<FriendlyFire>,
+    mut reflected: ResMut<ReflectedDamage>,
 ) {
     // Collect hits first to avoid borrow issues
     #[derive(Clone, Copy, Debug)]
@@ -197,6 +357,7 @@ pub fn detect_bullet_hits(
     struct HitRecord {
         bullet_entity: Entity,
         shooter_id: u64,
+        npc_shooter: Option<NpcShooter>,
         victim: Victim,
         victim_pos: Vec3,
         hit_point: Vec3,
@@ -212,7 +373,7 @@ pub fn detect_bullet_hits(
     let despawn_delay = 0.05;
     let mut hits: Vec<HitRecord> = Vec::new();
     
-    for (bullet_entity, bullet, _velocity, prev_pos, transform) in bullets.iter() {
+    for (bullet_entity, bullet, _velocity, prev_pos, transform, npc_shooter) in bullets.iter() {
         let ray_start = prev_pos.0;
         let ray_end = transform.translation;
         let ray_dir = ray_end - ray_start;
@@ -224,97 +385,28 @@ pub fn detect_bullet_hits(
         
         let ray_dir_norm = ray_dir / ray_length;
 
-        let mut hit_recorded = false;
-        
-        // --- NPC hits (head sphere first, then capsule) ---
+        // Closest hit across NPCs and players (one hit per bullet)
+        let mut best: Option<(RigHit, Victim, Vec3)> = None;
+
+        // --- NPC hits (per-limb rig) ---
         for (npc_entity, npc, npc_pos, health) in npcs.iter() {
-            if health.is_dead() {
+            if health.is_dead() || npc_shooter.is_some_and(|s| s.entity == npc_entity) {
                 continue;
             }
 
-            let head_center = npc_head_center(npc_pos.0);
-            if let Some(hit_point) = ray_sphere_intersection(
-                ray_start,
-                ray_dir_norm,
-                ray_length,
-                head_center,
-                NPC_HEAD_RADIUS,
-            ) {
-                let distance = (hit_point - bullet.spawn_position).length();
-                let stats = bullet.weapon_type.stats();
-                let damage_amount = damage::calculate_damage(&stats, distance, damage::HitZone::Head);
-
-                let hit_normal = (hit_point - head_center).normalize_or_zero();
-                hits.push(HitRecord {
-                    bullet_entity,
-                    shooter_id: bullet.owner_id,
-                    victim: Victim::Npc(npc_entity, npc.id),
-                    victim_pos: npc_pos.0,
-                    hit_point,
-                    hit_normal,
-                    damage_amount,
-                    hit_zone: damage::HitZone::Head,
-                    weapon_type: bullet.weapon_type,
-                    bullet_spawn_position: bullet.spawn_position,
-                    bullet_initial_velocity: bullet.initial_velocity,
-                });
-                // One hit per bullet.
-                hit_recorded = true;
-                break;
-            }
-
-            let (a, b) = npc_capsule_endpoints(npc_pos.0);
-            if let Some(hit_point) = ray_capsule_intersection(
-                ray_start,
-                ray_dir_norm,
-                ray_length,
-                a,
-                b,
-                NPC_RADIUS,
-            ) {
-                let bottom_y = npc_pos.0.y - NPC_HEIGHT * 0.5;
-                let relative_height = (hit_point.y - bottom_y) / NPC_HEIGHT;
-                let hit_zone = damage::HitZone::from_relative_height(relative_height);
-
-                let distance = (hit_point - bullet.spawn_position).length();
-                let stats = bullet.weapon_type.stats();
-                let damage_amount = damage::calculate_damage(&stats, distance, hit_zone);
-
-                // Approximate normal from capsule axis
-                let ab = b - a;
-                let t = if ab.length_squared() > 1e-6 {
-                    (hit_point - a).dot(ab) / ab.length_squared()
-                } else {
-                    0.0
+            let Ok((rotation, wander)) = npc_poses.get(npc_entity) else {
+                continue;
+            };
+            let rig = HitboxRig::posed(&crate::npc::npc_hitbox_pose(npc_pos.0, rotation.0, wander));
+            if let Some(rig_hit) = rig.raycast(ray_start, ray_dir_norm, ray_length) {
+                if best.as_ref().is_none_or(|(b, _, _)| rig_hit.t < b.t) {
+                    best = Some((rig_hit, Victim::Npc(npc_entity, npc.id), npc_pos.0));
                 }
-                .clamp(0.0, 1.0);
-                let closest = a + ab * t;
-                let hit_normal = (hit_point - closest).normalize_or_zero();
-
-                hits.push(HitRecord {
-                    bullet_entity,
-                    shooter_id: bullet.owner_id,
-                    victim: Victim::Npc(npc_entity, npc.id),
-                    victim_pos: npc_pos.0,
-                    hit_point,
-                    hit_normal,
-                    damage_amount,
-                    hit_zone,
-                    weapon_type: bullet.weapon_type,
-                    bullet_spawn_position: bullet.spawn_position,
-                    bullet_initial_velocity: bullet.initial_velocity,
-                });
-                hit_recorded = true;
-                break;
             }
         }
 
-        if hit_recorded {
-            continue;
-        }
-
-        // --- Player hits (existing capsule approximation) ---
-        for (_player_entity, player, player_pos, health) in players.iter() {
+        // --- Player hits (per-limb rig) ---
+        for (player_entity, player, player_pos, health, _armor, _team) in players.iter() {
             if peer_id_to_u64(player.client_id) == bullet.owner_id {
                 continue;
             }
@@ -323,74 +415,62 @@ pub fn detect_bullet_hits(
                 continue;
             }
 
-            let capsule_bottom = player_pos.0;
-            let capsule_top = player_pos.0 + Vec3::new(0.0, PLAYER_HEIGHT, 0.0);
-
-            if let Some(hit_point) = ray_capsule_intersection(
-                ray_start,
-                ray_dir_norm,
-                ray_length,
-                capsule_bottom,
-                capsule_top,
-                PLAYER_RADIUS,
-            ) {
-                let relative_height = (hit_point.y - capsule_bottom.y) / PLAYER_HEIGHT;
-                let hit_zone = damage::HitZone::from_relative_height(relative_height);
-                let distance = (hit_point - bullet.spawn_position).length();
-                let stats = bullet.weapon_type.stats();
-                let damage_amount = damage::calculate_damage(&stats, distance, hit_zone);
-
-                // Approximate normal from capsule axis
-                let ab = capsule_top - capsule_bottom;
-                let t = if ab.length_squared() > 1e-6 {
-                    (hit_point - capsule_bottom).dot(ab) / ab.length_squared()
-                } else {
-                    0.0
+            let Ok((rotation, velocity, grounded, weapon)) = player_poses.get(player_entity) else {
+                continue;
+            };
+            let rig = HitboxRig::posed(&player_hitbox_pose(player_pos.0, rotation, velocity, grounded, weapon));
+            if let Some(rig_hit) = rig.raycast(ray_start, ray_dir_norm, ray_length) {
+                if best.as_ref().is_none_or(|(b, _, _)| rig_hit.t < b.t) {
+                    best = Some((rig_hit, Victim::Player(player.client_id), player_pos.0));
                 }
-                .clamp(0.0, 1.0);
-                let closest = capsule_bottom + ab * t;
-                let hit_normal = (hit_point - closest).normalize_or_zero();
-
-                hits.push(HitRecord {
-                    bullet_entity,
-                    shooter_id: bullet.owner_id,
-                    victim: Victim::Player(player.client_id),
-                    victim_pos: player_pos.0,
-                    hit_point,
-                    hit_normal,
-                    damage_amount,
-                    hit_zone,
-                    weapon_type: bullet.weapon_type,
-                    bullet_spawn_position: bullet.spawn_position,
-                    bullet_initial_velocity: bullet.initial_velocity,
-                });
-                break;
             }
         }
+
+        if let Some((rig_hit, victim, victim_pos)) = best {
+            let distance = (rig_hit.point - bullet.spawn_position).length();
+            let stats = bullet.weapon_type.stats();
+            let damage_amount = damage::calculate_damage(&stats, distance, rig_hit.hit_zone);
+
+            hits.push(HitRecord {
+                bullet_entity,
+                shooter_id: bullet.owner_id,
+                npc_shooter: npc_shooter.copied(),
+                victim,
+                victim_pos,
+                hit_point: rig_hit.point,
+                hit_normal: rig_hit.normal,
+                damage_amount,
+                hit_zone: rig_hit.hit_zone,
+                weapon_type: bullet.weapon_type,
+                bullet_spawn_position: bullet.spawn_position,
+                bullet_initial_velocity: bullet.initial_velocity,
+            });
+        }
     }
     
-    // Collect shooter peer IDs
-    let shooter_ids: std::collections::HashMap<u64, PeerId> = players
+    // Collect shooter peer IDs, teams and entities
+    let shooter_ids: std::collections::HashMap<u64, (PeerId, Option<Team>, Entity)> = players
         .iter()
-        .map(|(_, p, _, _)| (peer_id_to_u64(p.client_id), p.client_id))
+        .map(|(entity, p, _, _, _, team)| (peer_id_to_u64(p.client_id), (p.client_id, team.copied(), entity)))
         .collect();
     
     // Process hits
     for hit in hits {
-        let shooter_peer_id = shooter_ids.get(&hit.shooter_id).copied();
+        let shooter = shooter_ids.get(&hit.shooter_id).copied();
+        let shooter_peer_id = shooter.map(|(peer_id, ..)| peer_id);
+        let attacker = match (hit.npc_shooter, shooter_peer_id) {
+            (Some(npc), _) => CombatantRef::Npc { id: npc.id, archetype: npc.archetype },
+            (None, Some(peer_id)) => CombatantRef::Player(peer_id),
+            (None, None) => CombatantRef::Environment,
+        };
         
         match hit.victim {
             Victim::Player(victim_id) => {
                 // Find and damage the victim
-                for (_, player, _, mut health) in players.iter_mut() {
+                for (_, player, _, mut health, mut armor, team) in players.iter_mut() {
                     if player.client_id == victim_id {
-                        let is_kill = health.take_damage(hit.damage_amount);
-                        let is_headshot = hit.hit_zone == damage::HitZone::Head;
-
-                        info!(
-                            "Hit! {:?} -> {:?} ({:?}) for {:.1} damage (headshot: {}, kill: {})",
-                            hit.shooter_id, victim_id, hit.hit_zone, hit.damage_amount, is_headshot, is_kill
-                        );
+                        let stats = hit.weapon_type.stats();
+                        let distance = (hit.hit_point - hit.bullet_spawn_position).length();
 
                         let impact = BulletImpact {
                             owner_id: hit.shooter_id,
@@ -402,6 +482,50 @@ pub fn detect_bullet_hits(
                             surface: BulletImpactSurface::Player,
                         };
 
+                        // Friendly fire: scale the teammate's damage and/or turn it back on the shooter
+                        let friendly =
+                            shooter.is_some_and(|(_, shooter_team, _)| are_allies(shooter_team, team.copied()));
+                        let (victim_mult, reflected_mult) = friendly_fire.policy.multipliers(friendly);
+                        if let Some(attacker) = shooter_peer_id.filter(|_| reflected_mult > 0.0) {
+                            reflected.push(ReflectedHit {
+                                attacker,
+                                damage: hit.damage_amount * reflected_mult,
+                                cause: DamageCause::Weapon(hit.weapon_type),
+                                hit_zone: hit.hit_zone,
+                                distance,
+                            });
+                        }
+                        if victim_mult <= 0.0 {
+                            for (_, _, _, _, mut impact_sender) in client_links.iter_mut() {
+                                impact_sender.send::<ReliableChannel>(impact.clone());
+                            }
+                            break;
+                        }
+
+                        // Armor covering the hit zone soaks up part of the damage and wears down
+                        let armored =
+                            apply_armored_hit(armor.as_deref_mut(), &stats, distance, hit.hit_zone, victim_mult);
+                        let damage_amount = armored.damage;
+
+                        let is_kill = health.take_damage(damage_amount);
+                        let is_headshot = hit.hit_zone == damage::HitZone::Head;
+
+                        combat_log.record(CombatHit {
+                            attacker,
+                            victim: CombatantRef::Player(victim_id),
+                            cause: DamageCause::Weapon(hit.weapon_type),
+                            hit_zone: hit.hit_zone,
+                            damage: damage_amount,
+                            armor_absorbed: armored.absorbed,
+                            distance,
+                            kill: is_kill,
+                        });
+
+                        info!(
+                            "Hit! {:?} -> {:?} ({:?}) for {:.1} damage ({:.1} absorbed by armor, headshot: {}, kill: {})",
+                            hit.shooter_id, victim_id, hit.hit_zone, damage_amount, armored.absorbed, is_headshot, is_kill
+                        );
+
                         // Send messages via MessageSender components
                         for (remote_id, mut hit_sender, mut dmg_sender, mut kill_sender, mut impact_sender) in
                             client_links.iter_mut()
@@ -414,10 +538,11 @@ pub fn detect_bullet_hits(
                                 if remote_id.0 == sid {
                                     hit_sender.send::<ReliableChannel>(HitConfirm {
                                         target_id: peer_id_to_u64(victim_id),
-                                        damage: hit.damage_amount,
+                                        damage: damage_amount,
                                         headshot: is_headshot,
                                         kill: is_kill,
                                         hit_zone: hit.hit_zone,
+                                        armor_absorbed: armored.absorbed,
                                     });
                                 }
                             }
@@ -432,7 +557,7 @@ pub fn detect_bullet_hits(
                                 .normalize_or_zero();
                                 dmg_sender.send::<ReliableChannel>(DamageReceived {
                                     direction: damage_direction,
-                                    damage: hit.damage_amount,
+                                    damage: damage_amount,
                                     health_remaining: health.current,
                                 });
 
@@ -451,14 +576,26 @@ pub fn detect_bullet_hits(
                 }
             }
             Victim::Npc(npc_entity, npc_id) => {
-                if let Ok((_e, _npc, _pos, mut health)) = npcs.get_mut(npc_entity) {
+                if let Ok((_e, npc, _pos, mut health)) = npcs.get_mut(npc_entity) {
                     let is_kill = health.take_damage(hit.damage_amount);
                     let is_headshot = hit.hit_zone == damage::HitZone::Head;
 
+                    combat_log.record(CombatHit {
+                        attacker,
+                        victim: CombatantRef::Npc { id: npc_id, archetype: npc.archetype },
+                        cause: DamageCause::Weapon(hit.weapon_type),
+                        hit_zone: hit.hit_zone,
+                        damage: hit.damage_amount,
+                        armor_absorbed: 0.0,
+                        distance: (hit.hit_point - hit.bullet_spawn_position).length(),
+                        kill: is_kill,
+                    });
+
                     // Add damage event component so AI can react
                     commands.entity(npc_entity).insert(NpcDamageEvent {
                         damage_source_position: hit.bullet_spawn_position,
                         damage_amount: hit.damage_amount,
+                        attacker: shooter.map(|(.., entity)| entity),
                     });
 
                     info!(
@@ -491,6 +628,7 @@ pub fn detect_bullet_hits(
                                     headshot: is_headshot,
                                     kill: is_kill,
                                     hit_zone: hit.hit_zone,
+                                    armor_absorbed: 0.0,
                                 });
                             }
                         }
@@ -510,20 +648,21 @@ pub fn detect_bullet_hits(
     }
 }
 
-/// Ray-sphere intersection (approx): returns closest point along ray segment if within radius.
-fn ray_sphere_intersection(
-    ray_origin: Vec3,
-    ray_dir: Vec3,
-    ray_length: f32,
-    sphere_center: Vec3,
-    sphere_radius: f32,
-) -> Option<Vec3> {
-    let to_center = sphere_center - ray_origin;
-    let t = to_center.dot(ray_dir).clamp(0.0, ray_length);
-    let p = ray_origin + ray_dir * t;
-    let d = (p - sphere_center).length();
-    let effective = sphere_radius * 1.25;
-    (d <= effective).then_some(p)
+/// Pose a player's hitbox rig from server-side movement state.
+pub(crate) fn player_hitbox_pose(
+    center: Vec3,
+    rotation: &PlayerRotation,
+    velocity: &PlayerVelocity,
+    grounded: &PlayerGrounded,
+    weapon: &EquippedWeapon,
+) -> HitboxPose {
+    let speed_xz = Vec2::new(velocity.0.x, velocity.0.z).length();
+    HitboxPose {
+        center,
+        yaw: rotation.0,
+        stance: HitboxStance::from_movement(speed_xz, grounded.is_grounded()),
+        armed: weapon.weapon_type != WeaponType::Unarmed,
+    }
 }
 
 /// Detect bullet hits against world geometry (terrain, practice wall, props, structures)
@@ -666,43 +805,8 @@ pub fn cleanup_bullets(
     }
 }
 
-/// Ray-capsule intersection test
-fn ray_capsule_intersection(
-    ray_origin: Vec3,
-    ray_dir: Vec3,
-    ray_length: f32,
-    capsule_a: Vec3,
-    capsule_b: Vec3,
-    capsule_radius: f32,
-) -> Option<Vec3> {
-    let capsule_center = (capsule_a + capsule_b) * 0.5;
-    let capsule_half_height = (capsule_b.y - capsule_a.y) * 0.5;
-    
-    let to_center = capsule_center - ray_origin;
-    let closest_t = to_center.dot(ray_dir).clamp(0.0, ray_length);
-    let closest_point = ray_origin + ray_dir * closest_t;
-    
-    let effective_radius = capsule_radius * 1.5;
-    
-    let horizontal_dist = Vec2::new(
-        closest_point.x - capsule_center.x,
-        closest_point.z - capsule_center.z,
-    ).length();
-    
-    if horizontal_dist > effective_radius {
-        return None;
-    }
-    
-    let height_diff = closest_point.y - capsule_center.y;
-    if height_diff.abs() > capsule_half_height + effective_radius {
-        return None;
-    }
-    
-    Some(closest_point)
-}
-
 /// Segment vs AABB intersection
-fn segment_aabb_intersection(
+pub(crate) fn segment_aabb_intersection(
     start: Vec3,
     end: Vec3,
     aabb_min: Vec3,
@@ -759,7 +863,7 @@ fn segment_aabb_intersection(
 }
 
 /// Segment vs terrain heightfield intersection
-fn segment_terrain_intersection(
+pub(crate) fn segment_terrain_intersection(
     terrain: &WorldTerrain,
     start: Vec3,
     end: Vec3,
@@ -814,7 +918,7 @@ fn segment_terrain_intersection(
 
 /// Test ray segment against static props (trees, rocks, etc.)
 /// Returns (t, hit_point, hit_normal) for the closest hit
-fn segment_props_intersection(
+pub(crate) fn segment_props_intersection(
     start: Vec3,
     end: Vec3,
     colliders: &StaticColliders,
@@ -931,7 +1035,7 @@ fn ray_triangle_intersection(
 }
 
 /// Check ray intersection against structures (domes, walls, towers, etc.)
-fn segment_structures_intersection(
+pub(crate) fn segment_structures_intersection(
     start: Vec3,
     end: Vec3,
     colliders: &StructureColliders,
@@ -1155,10 +1259,9 @@ pub fn handle_weapon_switch(
             for (player, mut weapon) in players.iter_mut() {
                 if player.client_id == peer_id {
                     let stats = request.weapon_type.stats();
-                    weapon.weapon_type = request.weapon_type;
+                    weapon.switch_to(request.weapon_type);
                     weapon.ammo_in_mag = stats.magazine_size;
                     weapon.reserve_ammo = stats.magazine_size * 3;
-                    weapon.last_fire_time = -10.0;
                     
                     info!("Player {:?} switched to {:?}", peer_id, request.weapon_type);
                     break;
@@ -1168,33 +1271,147 @@ pub fn handle_weapon_switch(
     }
 }
 
-/// Handle reload requests from clients
+/// Handle fire mode toggle requests from clients
+pub fn handle_fire_mode_requests(
+    mut client_links: Query<(&RemoteId, &mut MessageReceiver<CycleFireModeRequest>), With<ClientOf>>,
+    mut players: Query<(&Player, &mut EquippedWeapon)>,
+) {
+    for (remote_id, mut receiver) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+        
+        for _msg in receiver.receive() {
+            let Some((_, mut weapon)) = players.iter_mut().find(|(p, _)| p.client_id == peer_id) else {
+                continue;
+            };
+            let next = weapon.weapon_type.next_fire_mode(weapon.fire_mode);
+            if next != weapon.fire_mode {
+                weapon.fire_mode = next;
+                info!("Player {:?} switched {:?} to {:?}", peer_id, weapon.weapon_type, next);
+            }
+        }
+    }
+}
+
+/// Handle scope zero adjustments from clients
+pub fn handle_zero_requests(
+    mut client_links: Query<(&RemoteId, &mut MessageReceiver<AdjustZeroRequest>), With<ClientOf>>,
+    mut players: Query<(&Player, &mut EquippedWeapon)>,
+) {
+    for (remote_id, mut receiver) in client_links.iter_mut() {
+        let peer_id = remote_id.0;
+        
+        for request in receiver.receive() {
+            let Some((_, mut weapon)) = players.iter_mut().find(|(p, _)| p.client_id == peer_id) else {
+                continue;
+            };
+            let zero = weapon.weapon_type.step_zero_range(weapon.zero_range, i32::from(request.steps.signum()));
+            if zero != weapon.zero_range {
+                weapon.zero_range = zero;
+                info!("Player {:?} zeroed {:?} to {}m", peer_id, weapon.weapon_type, zero);
+            }
+        }
+    }
+}
+
+/// Handle reload requests from clients - starts a timed reload (see `tick_reloads`)
 pub fn handle_reload_request(
+    mut commands: Commands,
+    time: Res<Time>,
     mut client_links: Query<(&RemoteId, &mut MessageReceiver<ReloadRequest>), With<ClientOf>>,
-    mut players: Query<(&Player, &mut EquippedWeapon, &mut shared::Inventory)>,
+    players: Query<(Entity, &Player, &EquippedWeapon, &Health, &shared::Inventory, Has<Reloading>, Has<InVehicle>)>,
 ) {
+    let now = time.elapsed_secs();
+    
     for (remote_id, mut receiver) in client_links.iter_mut() {
         let peer_id = remote_id.0;
         
         for _msg in receiver.receive() {
-            for (player, mut weapon, mut inventory) in players.iter_mut() {
-                if player.client_id == peer_id {
-                    let stats = weapon.weapon_type.stats();
-                    let ammo_type = weapon.weapon_type.ammo_type();
-                    let needed = stats.magazine_size - weapon.ammo_in_mag;
-                    let reserve_in_inventory = inventory.count_item(ammo_type);
-                    
-                    if needed > 0 && reserve_in_inventory > 0 {
-                        // Take ammo from inventory
-                        let taken = weapon.reload_from_inventory(&mut inventory);
-                        
-                        info!(
-                            "Player {:?} reloaded {:?} (took {} ammo): {}/{} (reserve in inventory: {})", 
-                            peer_id, weapon.weapon_type, taken, weapon.ammo_in_mag, stats.magazine_size, 
-                            inventory.count_item(ammo_type)
-                        );
-                    }
-                    break;
+            let Some((entity, _, weapon, health, inventory, reloading, in_vehicle)) =
+                players.iter().find(|(_, p, ..)| p.client_id == peer_id)
+            else {
+                continue;
+            };
+            if reloading || in_vehicle || health.is_dead() || weapon.weapon_type.reload_timing().is_none() {
+                continue;
+            }
+            
+            let stats = weapon.weapon_type.stats();
+            let ammo_type = weapon.weapon_type.ammo_type();
+            let needed = stats.magazine_size.saturating_sub(weapon.ammo_in_mag);
+            let reserve_in_inventory = inventory.count_item(ammo_type);
+            
+            if needed > 0 && reserve_in_inventory > 0 {
+                let rounds = needed.min(reserve_in_inventory);
+                let reload = Reloading::start(weapon.weapon_type, weapon.ammo_in_mag, rounds, now);
+                info!(
+                    "Player {:?} started reloading {:?} ({}, {:.2}s)",
+                    peer_id,
+                    weapon.weapon_type,
+                    if reload.empty { "empty" } else { "tactical" },
+                    reload.duration()
+                );
+                commands.entity(entity).insert(reload);
+            }
+        }
+    }
+}
+
+/// Advance reloads: swap magazines / insert shells when due, cancel when interrupted.
+///
+/// Switching weapons, dying or entering a vehicle cancels any reload; sprinting also
+/// cancels a shell-by-shell reload (shells already loaded stay in).
+pub fn tick_reloads(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut client_links: Query<(&RemoteId, &mut MessageReceiver<InterruptReloadRequest>), With<ClientOf>>,
+    mut players: Query<(Entity, &Player, &mut Reloading, &mut EquippedWeapon, &mut shared::Inventory, &Health, Has<InVehicle>)>,
+) {
+    let now = time.elapsed_secs();
+    
+    // Clients report sprinting during a reload
+    let mut sprinting_peers = Vec::new();
+    for (remote_id, mut receiver) in client_links.iter_mut() {
+        if receiver.receive().count() > 0 {
+            sprinting_peers.push(remote_id.0);
+        }
+    }
+    
+    for (entity, player, mut reload, mut weapon, mut inventory, health, in_vehicle) in players.iter_mut() {
+        let sprinting = sprinting_peers.contains(&player.client_id);
+        if weapon.weapon_type != reload.weapon_type
+            || health.is_dead()
+            || in_vehicle
+            || (sprinting && reload.is_per_shell())
+        {
+            info!("Player {:?} reload of {:?} interrupted", player.client_id, reload.weapon_type);
+            commands.entity(entity).remove::<Reloading>();
+            continue;
+        }
+        
+        let stats = weapon.weapon_type.stats();
+        let ammo_type = weapon.weapon_type.ammo_type();
+        match reload.step(now) {
+            ReloadStep::InProgress => {}
+            ReloadStep::SwapMagazine => {
+                let taken = weapon.reload_from_inventory(&mut inventory);
+                info!(
+                    "Player {:?} reloaded {:?} (took {} ammo): {}/{} (reserve in inventory: {})",
+                    player.client_id, weapon.weapon_type, taken, weapon.ammo_in_mag, stats.magazine_size,
+                    inventory.count_item(ammo_type)
+                );
+                commands.entity(entity).remove::<Reloading>();
+            }
+            ReloadStep::InsertShell => {
+                if weapon.ammo_in_mag < stats.magazine_size && inventory.remove_item(ammo_type, 1) == 1 {
+                    weapon.ammo_in_mag += 1;
+                    reload.shells_loaded += 1;
+                }
+                // Done when full, out of shells, or the planned shells are in
+                let done = weapon.ammo_in_mag >= stats.magazine_size
+                    || inventory.count_item(ammo_type) == 0
+                    || reload.shells_loaded >= reload.rounds_needed;
+                if done {
+                    commands.entity(entity).remove::<Reloading>();
                 }
             }
         }
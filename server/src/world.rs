@@ -4,7 +4,7 @@
 
 use bevy::prelude::*;
 use lightyear::prelude::*;
-use shared::WorldTime;
+use shared::{Wind, WorldTime};
 
 /// Set up the game world (server-side, no rendering)
 pub fn setup_world(_commands: Commands) {
@@ -35,10 +35,11 @@ pub fn spawn_world_time_once(mut commands: Commands, spawned: Option<Res<WorldTi
 
     commands.spawn((
         WorldTime::new_default(),
+        Wind::default(),
         Replicate::new(ReplicationMode::SingleServer(NetworkTarget::All)),
     ));
 
-    info!("Spawned WorldTime (day/night cycle) replicated to all clients");
+    info!("Spawned WorldTime (day/night cycle + wind) replicated to all clients");
 }
 
 /// Advance the world clock every fixed tick (server-authoritative).
@@ -48,3 +49,15 @@ pub fn tick_world_time(mut world_time: Query<&mut WorldTime>) {
         wt.advance(dt);
     }
 }
+
+/// Drift the wind along its deterministic cycle (server-authoritative).
+///
+/// Only writes when the change is noticeable, so the component isn't re-sent every tick.
+pub fn tick_wind(mut wind: Query<&mut Wind>, time: Res<Time>) {
+    let target = Wind::sample(time.elapsed_secs());
+    for mut wind in wind.iter_mut() {
+        if wind.velocity.distance(target) >= Wind::REPLICATION_THRESHOLD {
+            wind.velocity = target;
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Armor and helmets
+//!
+//! Defines wearable armor pieces, the equipment slots they occupy, and which
+//! hit zones each piece protects. Durability is stored on the `ItemStack` while
+//! the piece sits in the inventory and on `EquippedArmor` while worn.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::items::ItemStack;
+use crate::weapons::{damage, damage::HitZone, WeaponStats};
+
+/// Wearable armor pieces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum ArmorType {
+    #[default]
+    Vest,
+    Helmet,
+}
+
+/// Equipment slot an armor piece occupies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArmorSlot {
+    Head,
+    Body,
+}
+
+impl ArmorType {
+    /// Which equipment slot this piece goes into
+    pub fn slot(&self) -> ArmorSlot {
+        match self {
+            ArmorType::Vest => ArmorSlot::Body,
+            ArmorType::Helmet => ArmorSlot::Head,
+        }
+    }
+
+    /// Durability of a brand new piece (armor points absorbed before it breaks)
+    pub fn max_durability(&self) -> f32 {
+        match self {
+            ArmorType::Vest => 100.0,
+            ArmorType::Helmet => 60.0,
+        }
+    }
+
+    /// Fraction of incoming damage absorbed while durability remains (0.0 - 1.0)
+    pub fn protection(&self) -> f32 {
+        match self {
+            ArmorType::Vest => 0.4,
+            ArmorType::Helmet => 0.5,
+        }
+    }
+
+    /// Whether this piece protects the given hit zone
+    pub fn covers(&self, zone: HitZone) -> bool {
+        match self {
+            ArmorType::Vest => matches!(zone, HitZone::Chest | HitZone::Stomach),
+            ArmorType::Helmet => zone == HitZone::Head,
+        }
+    }
+
+    /// Display name for UI
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ArmorType::Vest => "Armor Vest",
+            ArmorType::Helmet => "Helmet",
+        }
+    }
+}
+
+/// A worn armor piece with its remaining durability
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArmorPiece {
+    pub armor_type: ArmorType,
+    pub durability: f32,
+}
+
+impl ArmorPiece {
+    /// Create a brand new piece at full durability
+    pub fn new(armor_type: ArmorType) -> Self {
+        Self {
+            armor_type,
+            durability: armor_type.max_durability(),
+        }
+    }
+
+    /// Remaining durability as 0.0 - 1.0
+    pub fn durability_fraction(&self) -> f32 {
+        (self.durability / self.armor_type.max_durability()).clamp(0.0, 1.0)
+    }
+
+    /// Build a worn piece from an inventory stack (None if the stack isn't armor)
+    pub fn from_stack(stack: &ItemStack) -> Option<Self> {
+        let armor_type = stack.item_type.as_armor_type()?;
+        Some(Self {
+            armor_type,
+            durability: stack.get_armor_durability(),
+        })
+    }
+
+    /// Convert back into an inventory stack (preserves durability)
+    pub fn to_stack(&self) -> ItemStack {
+        ItemStack::new_armor(self.armor_type, self.durability)
+    }
+}
+
+/// Armor currently worn by a player (replicated)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct EquippedArmor {
+    pub helmet: Option<ArmorPiece>,
+    pub vest: Option<ArmorPiece>,
+}
+
+impl EquippedArmor {
+    /// Get the piece in an equipment slot
+    pub fn get(&self, slot: ArmorSlot) -> Option<&ArmorPiece> {
+        match slot {
+            ArmorSlot::Head => self.helmet.as_ref(),
+            ArmorSlot::Body => self.vest.as_ref(),
+        }
+    }
+
+    /// Mutable access to an equipment slot
+    pub fn slot_mut(&mut self, slot: ArmorSlot) -> &mut Option<ArmorPiece> {
+        match slot {
+            ArmorSlot::Head => &mut self.helmet,
+            ArmorSlot::Body => &mut self.vest,
+        }
+    }
+
+    /// Put a piece on, returning whatever was previously in that slot
+    pub fn equip(&mut self, piece: ArmorPiece) -> Option<ArmorPiece> {
+        self.slot_mut(piece.armor_type.slot()).replace(piece)
+    }
+
+    /// Take a piece off
+    pub fn unequip(&mut self, slot: ArmorSlot) -> Option<ArmorPiece> {
+        self.slot_mut(slot).take()
+    }
+
+    /// The worn piece (with durability left) protecting a hit zone, if any
+    pub fn piece_for_zone_mut(&mut self, zone: HitZone) -> Option<&mut ArmorPiece> {
+        [&mut self.helmet, &mut self.vest]
+            .into_iter()
+            .filter_map(|slot| slot.as_mut())
+            .find(|piece| piece.armor_type.covers(zone) && piece.durability > 0.0)
+    }
+
+    /// Drain all worn pieces (used when dropping armor on death)
+    pub fn take_all(&mut self) -> Vec<ArmorPiece> {
+        [self.helmet.take(), self.vest.take()].into_iter().flatten().collect()
+    }
+}
+
+/// Outcome of applying a hit to a (possibly armored) target
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArmoredHit {
+    /// Damage that reaches health
+    pub damage: f32,
+    /// Damage soaked up by armor
+    pub absorbed: f32,
+}
+
+/// Calculate damage for a hit and wear down the armor covering that zone.
+///
+/// Uses `damage::calculate_damage_with_armor` with the covering piece's durability
+/// and protection. Zones with no (or broken) armor take full damage. `scale` is the
+/// friendly fire multiplier for the victim: it shrinks the damage and the armor wear
+/// alike, so a reduced teammate hit doesn't strip a full hit's worth of durability.
+pub fn apply_armored_hit(
+    armor: Option<&mut EquippedArmor>,
+    stats: &WeaponStats,
+    distance: f32,
+    hit_zone: HitZone,
+    scale: f32,
+) -> ArmoredHit {
+    let piece = armor.and_then(|a| a.piece_for_zone_mut(hit_zone));
+    let Some(piece) = piece else {
+        return ArmoredHit {
+            damage: damage::calculate_damage(stats, distance, hit_zone) * scale,
+            absorbed: 0.0,
+        };
+    };
+
+    let (final_damage, remaining) = damage::calculate_damage_with_armor(
+        stats,
+        distance,
+        hit_zone,
+        piece.durability,
+        piece.armor_type.protection(),
+    );
+    let absorbed = (piece.durability - remaining) * scale;
+    piece.durability -= absorbed;
+
+    ArmoredHit {
+        damage: final_damage * scale,
+        absorbed,
+    }
+}
+
+// =============================================================================
+// MESSAGES
+// =============================================================================
+
+/// Client -> Server: Wear the armor item in an inventory slot
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EquipArmorRequest {
+    pub slot_index: u8,
+}
+
+/// Client -> Server: Take off armor and put it back in the inventory
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnequipArmorRequest {
+    pub slot: ArmorSlot,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapons::WeaponType;
+
+    #[test]
+    fn test_helmet_only_covers_head() {
+        let mut armor = EquippedArmor {
+            helmet: Some(ArmorPiece::new(ArmorType::Helmet)),
+            vest: None,
+        };
+        let stats = WeaponType::AssaultRifle.stats();
+
+        let body = apply_armored_hit(Some(&mut armor), &stats, 10.0, HitZone::Chest, 1.0);
+        assert_eq!(body.absorbed, 0.0);
+
+        let head = apply_armored_hit(Some(&mut armor), &stats, 10.0, HitZone::Head, 1.0);
+        assert!(head.absorbed > 0.0);
+        assert!(armor.helmet.unwrap().durability < ArmorType::Helmet.max_durability());
+    }
+
+    #[test]
+    fn test_vest_absorbs_until_broken() {
+        let mut armor = EquippedArmor {
+            helmet: None,
+            vest: Some(ArmorPiece { armor_type: ArmorType::Vest, durability: 5.0 }),
+        };
+        let stats = WeaponType::Sniper.stats();
+        let raw = damage::calculate_damage(&stats, 10.0, HitZone::Stomach);
+
+        let hit = apply_armored_hit(Some(&mut armor), &stats, 10.0, HitZone::Stomach, 1.0);
+        assert!((hit.absorbed - 5.0).abs() < 0.01);
+        assert!((hit.damage - (raw - 5.0)).abs() < 0.01);
+
+        // Broken vest no longer protects
+        let hit = apply_armored_hit(Some(&mut armor), &stats, 10.0, HitZone::Stomach, 1.0);
+        assert_eq!(hit.absorbed, 0.0);
+        assert!((hit.damage - raw).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_scaled_hit_wears_armor_less() {
+        let stats = WeaponType::AssaultRifle.stats();
+        let mut full = EquippedArmor { helmet: None, vest: Some(ArmorPiece::new(ArmorType::Vest)) };
+        let mut reduced = full;
+
+        let full_hit = apply_armored_hit(Some(&mut full), &stats, 10.0, HitZone::Chest, 1.0);
+        let reduced_hit = apply_armored_hit(Some(&mut reduced), &stats, 10.0, HitZone::Chest, 0.5);
+        assert!((reduced_hit.damage - full_hit.damage * 0.5).abs() < 0.01);
+        assert!((reduced_hit.absorbed - full_hit.absorbed * 0.5).abs() < 0.01);
+        let max = ArmorType::Vest.max_durability();
+        assert!((max - reduced.vest.unwrap().durability - (max - full.vest.unwrap().durability) * 0.5).abs() < 0.01);
+    }
+}
@@ -0,0 +1,451 @@
+//! Data-authored NPC behaviour trees
+//!
+//! Each NPC archetype runs a `BehaviourTree` loaded from a RON file. Composites
+//! (`Selector`, `Sequence`, `Utility`, `Schedule`) and `Condition`s are evaluated here; the leaves
+//! (`Wander`, `Flee`, `Attack`, ...) are carried out by the server through the
+//! `BehaviourContext` it passes in. The tree is re-evaluated from the root every tick,
+//! so a higher-priority branch takes over as soon as its conditions hold.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::NpcArchetype;
+use crate::npc::NPC_WANDER_RADIUS;
+use crate::perception::NpcAlert;
+
+/// Result of ticking a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BehaviourStatus {
+    Success,
+    Failure,
+    Running,
+}
+
+/// A yes/no question about the NPC's situation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum BehaviourCondition {
+    /// Something hurt the NPC and it hasn't got away yet
+    Damaged,
+    /// The NPC has a combat target
+    HasTarget,
+    /// Health is below this fraction of max
+    HealthBelow(f32),
+    /// A living player is within this distance (m)
+    PlayerWithin(f32),
+    /// It's night
+    Night,
+    /// The NPC's alert level is at least this (see `perception`)
+    Alerted(NpcAlert),
+    Not(Box<BehaviourCondition>),
+}
+
+/// Scores a `Utility` option between 0 and 1
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Consideration {
+    /// Current health fraction
+    Health,
+    /// 1 - health fraction
+    MissingHealth,
+    /// 1 when the condition holds, 0 otherwise
+    If(BehaviourCondition),
+}
+
+/// One choice of a `Utility` node
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UtilityOption {
+    /// Score multiplier
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Multiplied together (an empty list scores 1)
+    #[serde(default)]
+    pub considerations: Vec<Consideration>,
+    pub node: BehaviourNode,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_wander_radius() -> f32 {
+    NPC_WANDER_RADIUS
+}
+
+/// A place assigned to an NPC by the town it lives in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchedulePlace {
+    Home,
+    Work,
+    Tavern,
+}
+
+/// One slot of a `Schedule`: runs `node` while the time of day is in `[from, to)`
+/// (`WorldTime::normalized_time`, wrapping past midnight when `from > to`)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduleEntry {
+    pub from: f32,
+    pub to: f32,
+    pub node: BehaviourNode,
+}
+
+impl ScheduleEntry {
+    pub fn contains(&self, time_of_day: f32) -> bool {
+        time_window_contains(self.from, self.to, time_of_day)
+    }
+}
+
+/// Whether `time_of_day` is in `[from, to)`, wrapping past midnight when `from > to`
+pub(crate) fn time_window_contains(from: f32, to: f32, time_of_day: f32) -> bool {
+    if from <= to {
+        (from..to).contains(&time_of_day)
+    } else {
+        time_of_day >= from || time_of_day < to
+    }
+}
+
+/// Actions the server knows how to carry out
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum BehaviourLeaf {
+    /// Walk to a point (offset from home, XZ meters); succeeds on arrival
+    MoveTo { offset: Vec2 },
+    /// Roam to random spots around home with idle pauses (always running)
+    Wander {
+        #[serde(default = "default_wander_radius")]
+        radius: f32,
+    },
+    /// Run from whatever hurt the NPC for `duration` to 1.5x `duration` seconds;
+    /// succeeds once it stops, fails when nothing hurt it
+    Flee { duration: f32 },
+    /// Fight the current target; fails without one
+    Attack,
+    /// Walk a loop of points (offsets from home), pausing at each
+    Patrol { points: Vec<Vec2>, pause: f32 },
+    /// Stop and face the nearest player within `range`; fails when nobody is there
+    Talk { range: f32 },
+    /// Stand still (always running)
+    Sleep,
+    /// Walk to an assigned place and hang around within `radius` of it; fails without one
+    GoTo { place: SchedulePlace, radius: f32 },
+    /// Walk home and stay inside (hidden) until another branch takes over; fails without a home
+    Indoors,
+    /// Walk the NPC's assigned route, pausing at each point; fails without one
+    PatrolRoute { pause: f32 },
+    /// Go and look at whatever the NPC last noticed; fails once it has calmed down
+    Investigate,
+    /// Keep to its slot in its squad's formation; fails for leaders, loners and when the
+    /// leader is gone
+    Follow,
+    /// Mind its trade stall, facing the nearest player within `range`; fails for NPCs that
+    /// don't trade
+    Tend { range: f32 },
+    /// Stop and face the player talking to it; fails when nobody is
+    Converse,
+}
+
+impl BehaviourLeaf {
+    /// Name for logs and the debug view
+    pub fn label(&self) -> &'static str {
+        match self {
+            BehaviourLeaf::MoveTo { .. } => "MoveTo",
+            BehaviourLeaf::Wander { .. } => "Wander",
+            BehaviourLeaf::Flee { .. } => "Flee",
+            BehaviourLeaf::Attack => "Attack",
+            BehaviourLeaf::Patrol { .. } => "Patrol",
+            BehaviourLeaf::Talk { .. } => "Talk",
+            BehaviourLeaf::Sleep => "Sleep",
+            BehaviourLeaf::GoTo { place: SchedulePlace::Home, .. } => "GoTo(Home)",
+            BehaviourLeaf::GoTo { place: SchedulePlace::Work, .. } => "GoTo(Work)",
+            BehaviourLeaf::GoTo { place: SchedulePlace::Tavern, .. } => "GoTo(Tavern)",
+            BehaviourLeaf::Indoors => "Indoors",
+            BehaviourLeaf::PatrolRoute { .. } => "PatrolRoute",
+            BehaviourLeaf::Investigate => "Investigate",
+            BehaviourLeaf::Follow => "Follow",
+            BehaviourLeaf::Tend { .. } => "Tend",
+            BehaviourLeaf::Converse => "Converse",
+        }
+    }
+}
+
+/// A behaviour tree node
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum BehaviourNode {
+    /// Tick children in order until one doesn't fail
+    Selector(Vec<BehaviourNode>),
+    /// Tick children in order until one doesn't succeed
+    Sequence(Vec<BehaviourNode>),
+    /// Tick the highest-scoring child (options scoring 0 are skipped)
+    Utility(Vec<UtilityOption>),
+    /// Tick the first entry whose time window holds now; fails when none does
+    Schedule(Vec<ScheduleEntry>),
+    /// Succeeds when the condition holds, fails otherwise
+    Condition(BehaviourCondition),
+    /// Inverts success and failure
+    Invert(Box<BehaviourNode>),
+    Leaf(BehaviourLeaf),
+}
+
+/// What a tree needs from the NPC it runs on (implemented by the server AI)
+pub trait BehaviourContext {
+    fn check(&mut self, condition: &BehaviourCondition) -> bool;
+    fn health_fraction(&self) -> f32;
+    /// `WorldTime::normalized_time` (0 = midnight, 0.5 = noon)
+    fn time_of_day(&self) -> f32;
+    fn run(&mut self, leaf: &BehaviourLeaf) -> BehaviourStatus;
+}
+
+impl BehaviourNode {
+    pub fn tick(&self, ctx: &mut impl BehaviourContext) -> BehaviourStatus {
+        match self {
+            BehaviourNode::Selector(children) => {
+                for child in children {
+                    let status = child.tick(ctx);
+                    if status != BehaviourStatus::Failure {
+                        return status;
+                    }
+                }
+                BehaviourStatus::Failure
+            }
+            BehaviourNode::Sequence(children) => {
+                for child in children {
+                    let status = child.tick(ctx);
+                    if status != BehaviourStatus::Success {
+                        return status;
+                    }
+                }
+                BehaviourStatus::Success
+            }
+            BehaviourNode::Utility(options) => {
+                let mut scored: Vec<(f32, &BehaviourNode)> = options
+                    .iter()
+                    .map(|option| (option.score(ctx), &option.node))
+                    .filter(|(score, _)| *score > 0.0)
+                    .collect();
+                // Highest first; ties keep file order
+                scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+                for (_, node) in scored {
+                    let status = node.tick(ctx);
+                    if status != BehaviourStatus::Failure {
+                        return status;
+                    }
+                }
+                BehaviourStatus::Failure
+            }
+            BehaviourNode::Schedule(entries) => {
+                let now = ctx.time_of_day();
+                match entries.iter().find(|entry| entry.contains(now)) {
+                    Some(entry) => entry.node.tick(ctx),
+                    None => BehaviourStatus::Failure,
+                }
+            }
+            BehaviourNode::Condition(condition) => {
+                if ctx.check(condition) {
+                    BehaviourStatus::Success
+                } else {
+                    BehaviourStatus::Failure
+                }
+            }
+            BehaviourNode::Invert(child) => match child.tick(ctx) {
+                BehaviourStatus::Success => BehaviourStatus::Failure,
+                BehaviourStatus::Failure => BehaviourStatus::Success,
+                BehaviourStatus::Running => BehaviourStatus::Running,
+            },
+            BehaviourNode::Leaf(leaf) => ctx.run(leaf),
+        }
+    }
+}
+
+impl UtilityOption {
+    fn score(&self, ctx: &mut impl BehaviourContext) -> f32 {
+        self.considerations.iter().fold(self.weight, |score, consideration| {
+            score
+                * match consideration {
+                    Consideration::Health => ctx.health_fraction(),
+                    Consideration::MissingHealth => 1.0 - ctx.health_fraction(),
+                    Consideration::If(condition) => {
+                        if ctx.check(condition) {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    }
+                }
+        })
+    }
+}
+
+/// A whole tree as stored in a behaviour file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BehaviourTree {
+    pub root: BehaviourNode,
+}
+
+impl BehaviourTree {
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        ron::from_str(source).map_err(|e| e.to_string())
+    }
+
+    pub fn tick(&self, ctx: &mut impl BehaviourContext) -> BehaviourStatus {
+        self.root.tick(ctx)
+    }
+}
+
+/// Behaviour file for an archetype (under `shared/assets/behaviours/`)
+pub fn behaviour_file_name(archetype: NpcArchetype) -> &'static str {
+    match archetype {
+        NpcArchetype::Barbarian => "barbarian.ron",
+        NpcArchetype::Ranger => "ranger.ron",
+        NpcArchetype::Mage => "mage.ron",
+        NpcArchetype::Knight => "knight.ron",
+        NpcArchetype::Rogue => "rogue.ron",
+        NpcArchetype::RogueHooded => "rogue_hooded.ron",
+    }
+}
+
+/// Built-in copy of an archetype's behaviour file (used when the file on disk is missing or broken)
+pub fn default_behaviour_source(archetype: NpcArchetype) -> &'static str {
+    match archetype {
+        NpcArchetype::Barbarian => include_str!("../assets/behaviours/barbarian.ron"),
+        NpcArchetype::Ranger => include_str!("../assets/behaviours/ranger.ron"),
+        NpcArchetype::Mage => include_str!("../assets/behaviours/mage.ron"),
+        NpcArchetype::Knight => include_str!("../assets/behaviours/knight.ron"),
+        NpcArchetype::Rogue => include_str!("../assets/behaviours/rogue.ron"),
+        NpcArchetype::RogueHooded => include_str!("../assets/behaviours/rogue_hooded.ron"),
+    }
+}
+
+/// Active behaviour leaf per NPC (server -> client, for the debug view)
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct NpcActiveBehaviour(pub String);
+
+/// Marker: the NPC is inside its house (hidden on clients, can't be hit)
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct NpcIndoors;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the leaves it runs; `Flee` fails unless damaged
+    struct TestContext {
+        damaged: bool,
+        health: f32,
+        time: f32,
+        ran: Vec<&'static str>,
+    }
+
+    impl BehaviourContext for TestContext {
+        fn check(&mut self, condition: &BehaviourCondition) -> bool {
+            match condition {
+                BehaviourCondition::Damaged => self.damaged,
+                BehaviourCondition::HealthBelow(fraction) => self.health < *fraction,
+                BehaviourCondition::Not(inner) => !self.check(inner),
+                _ => false,
+            }
+        }
+
+        fn health_fraction(&self) -> f32 {
+            self.health
+        }
+
+        fn time_of_day(&self) -> f32 {
+            self.time
+        }
+
+        fn run(&mut self, leaf: &BehaviourLeaf) -> BehaviourStatus {
+            self.ran.push(leaf.label());
+            match leaf {
+                BehaviourLeaf::Flee { .. } if !self.damaged => BehaviourStatus::Failure,
+                _ => BehaviourStatus::Running,
+            }
+        }
+    }
+
+    #[test]
+    fn test_selector_falls_through_failed_branches() {
+        let tree = BehaviourTree::from_ron(
+            "(root: Selector([
+                Sequence([Condition(HealthBelow(0.3)), Leaf(Sleep)]),
+                Leaf(Flee(duration: 5.0)),
+                Leaf(Wander()),
+            ]))",
+        )
+        .unwrap();
+        assert_eq!(
+            tree.root,
+            BehaviourNode::Selector(vec![
+                BehaviourNode::Sequence(vec![
+                    BehaviourNode::Condition(BehaviourCondition::HealthBelow(0.3)),
+                    BehaviourNode::Leaf(BehaviourLeaf::Sleep),
+                ]),
+                BehaviourNode::Leaf(BehaviourLeaf::Flee { duration: 5.0 }),
+                BehaviourNode::Leaf(BehaviourLeaf::Wander { radius: NPC_WANDER_RADIUS }),
+            ])
+        );
+
+        let mut ctx = TestContext { damaged: false, health: 1.0, time: 0.5, ran: Vec::new() };
+        assert_eq!(tree.tick(&mut ctx), BehaviourStatus::Running);
+        assert_eq!(ctx.ran, vec!["Flee", "Wander"]);
+
+        let mut ctx = TestContext { damaged: true, health: 0.2, time: 0.5, ran: Vec::new() };
+        tree.tick(&mut ctx);
+        assert_eq!(ctx.ran, vec!["Sleep"]);
+    }
+
+    #[test]
+    fn test_schedule_picks_slot_by_time_of_day() {
+        let tree = BehaviourTree::from_ron(
+            "(root: Schedule([
+                (from: 0.3, to: 0.75, node: Leaf(GoTo(place: Work, radius: 8.0))),
+                (from: 0.75, to: 0.9, node: Leaf(GoTo(place: Tavern, radius: 6.0))),
+                (from: 0.9, to: 0.3, node: Leaf(Indoors)),
+            ]))",
+        )
+        .unwrap();
+
+        for (time, expected) in [(0.5, "GoTo(Work)"), (0.8, "GoTo(Tavern)"), (0.95, "Indoors"), (0.1, "Indoors")] {
+            let mut ctx = TestContext { damaged: false, health: 1.0, time, ran: Vec::new() };
+            tree.tick(&mut ctx);
+            assert_eq!(ctx.ran, vec![expected], "time {}", time);
+        }
+
+        // A gap in the schedule fails so a selector can fall back
+        let gap = BehaviourTree::from_ron("(root: Schedule([(from: 0.3, to: 0.75, node: Leaf(Sleep))]))").unwrap();
+        let mut ctx = TestContext { damaged: false, health: 1.0, time: 0.8, ran: Vec::new() };
+        assert_eq!(gap.tick(&mut ctx), BehaviourStatus::Failure);
+        assert!(ctx.ran.is_empty());
+    }
+
+    #[test]
+    fn test_builtin_trees_parse() {
+        for archetype in NpcArchetype::ALL {
+            if let Err(e) = BehaviourTree::from_ron(default_behaviour_source(archetype)) {
+                panic!("{}: {}", behaviour_file_name(archetype), e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_utility_picks_highest_score() {
+        let tree = BehaviourTree::from_ron(
+            "(root: Utility([
+                (considerations: [Health], node: Leaf(Attack)),
+                (weight: 1.5, considerations: [MissingHealth, If(Damaged)], node: Leaf(Flee(duration: 5.0))),
+            ]))",
+        )
+        .unwrap();
+
+        // Healthy: attack scores 0.9 vs flee 0.15
+        let mut ctx = TestContext { damaged: true, health: 0.9, time: 0.5, ran: Vec::new() };
+        tree.tick(&mut ctx);
+        assert_eq!(ctx.ran, vec!["Attack"]);
+
+        // Hurt: flee scores 1.2 vs attack 0.2
+        let mut ctx = TestContext { damaged: true, health: 0.2, time: 0.5, ran: Vec::new() };
+        tree.tick(&mut ctx);
+        assert_eq!(ctx.ran, vec!["Flee"]);
+
+        // Not damaged: flee scores 0 and is skipped entirely
+        let mut ctx = TestContext { damaged: false, health: 0.2, time: 0.5, ran: Vec::new() };
+        tree.tick(&mut ctx);
+        assert_eq!(ctx.ran, vec!["Attack"]);
+    }
+}
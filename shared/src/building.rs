@@ -211,6 +211,19 @@ pub struct PlacedBuilding {
     pub rotation: f32,
 }
 
+impl PlacedBuilding {
+    /// Whether `point` lies inside this building's footprint when placed at `position`
+    pub fn contains(&self, position: Vec3, point: Vec3) -> bool {
+        let def = self.building_type.definition();
+        point_in_rotated_rect(
+            Vec2::new(point.x, point.z),
+            Vec2::new(position.x, position.z),
+            def.footprint / 2.0,
+            self.rotation,
+        )
+    }
+}
+
 /// Network position for placed buildings
 #[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BuildingPosition(pub Vec3);
@@ -0,0 +1,187 @@
+//! Kill feed and damage log
+//!
+//! The server records every hit (bullets, melee, explosives) and broadcasts a
+//! `KillFeedEntry` to everyone when something dies. Each player also has a
+//! `DamageLog` of the hits they took and dealt during their current life; it is
+//! sent to them as a `DamageLogReport` when they die.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::NpcArchetype;
+use crate::items::ItemType;
+use crate::weapons::{damage::HitZone, ExplosiveType, WeaponType};
+
+/// Entries kept per player per life (oldest dropped first)
+pub const DAMAGE_LOG_CAPACITY: usize = 64;
+
+/// One side of a fight
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum Combatant {
+    Player { id: u64, name: String },
+    Npc { id: u64, archetype: NpcArchetype },
+    /// The world itself (or an attacker who has since disconnected)
+    Environment,
+}
+
+impl Combatant {
+    /// Name shown in the kill feed / damage log
+    pub fn display_name(&self) -> String {
+        match self {
+            Combatant::Player { name, .. } => name.clone(),
+            Combatant::Npc { archetype, .. } => format!("{:?}", archetype),
+            Combatant::Environment => "The world".to_string(),
+        }
+    }
+
+    /// Network ID (player peer ID or NPC ID; None for the environment)
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            Combatant::Player { id, .. } | Combatant::Npc { id, .. } => Some(*id),
+            Combatant::Environment => None,
+        }
+    }
+}
+
+/// What did the damage
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub enum DamageCause {
+    Weapon(WeaponType),
+    Explosive(ExplosiveType),
+    Environment,
+}
+
+impl DamageCause {
+    /// Short name for the HUD
+    pub fn label(&self) -> &'static str {
+        match self {
+            DamageCause::Weapon(WeaponType::Unarmed) => "Fists",
+            DamageCause::Weapon(weapon) => ItemType::Weapon(*weapon).display_name(),
+            DamageCause::Explosive(explosive) => explosive.display_name(),
+            DamageCause::Environment => "Environment",
+        }
+    }
+}
+
+/// Server -> all clients: something died
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct KillFeedEntry {
+    pub killer: Combatant,
+    pub victim: Combatant,
+    pub cause: DamageCause,
+    pub headshot: bool,
+    /// Meters from the attack's origin (muzzle, swing or blast) to the victim
+    pub distance: f32,
+}
+
+/// One hit in a player's damage log
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DamageLogEntry {
+    /// Server time of the hit (seconds)
+    pub time: f32,
+    /// True if this player dealt the hit, false if they took it
+    pub dealt: bool,
+    /// The other side of the hit
+    pub other: Combatant,
+    pub cause: DamageCause,
+    pub hit_zone: HitZone,
+    /// Damage after armor
+    pub damage: f32,
+    pub armor_absorbed: f32,
+    pub distance: f32,
+    /// The hit killed its target
+    pub fatal: bool,
+}
+
+/// Server -> Client: the dying player's damage log for the life that just ended
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DamageLogReport {
+    pub entries: Vec<DamageLogEntry>,
+}
+
+/// Rolling log of one player's hits taken and dealt
+#[derive(Clone, Debug, Default)]
+pub struct DamageLog {
+    entries: VecDeque<DamageLogEntry>,
+}
+
+impl DamageLog {
+    /// Add an entry, dropping the oldest once full
+    pub fn push(&mut self, entry: DamageLogEntry) {
+        if self.entries.len() >= DAMAGE_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Take every entry (oldest first), leaving the log empty for the next life
+    pub fn take(&mut self) -> Vec<DamageLogEntry> {
+        self.entries.drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Total damage (taken, dealt) across a set of log entries
+pub fn damage_totals(entries: &[DamageLogEntry]) -> (f32, f32) {
+    entries.iter().fold((0.0, 0.0), |(taken, dealt), entry| {
+        if entry.dealt {
+            (taken, dealt + entry.damage)
+        } else {
+            (taken + entry.damage, dealt)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dealt: bool, damage: f32) -> DamageLogEntry {
+        DamageLogEntry {
+            time: 0.0,
+            dealt,
+            other: Combatant::Environment,
+            cause: DamageCause::Weapon(WeaponType::Pistol),
+            hit_zone: HitZone::Chest,
+            damage,
+            armor_absorbed: 0.0,
+            distance: 10.0,
+            fatal: false,
+        }
+    }
+
+    #[test]
+    fn test_damage_log_caps_and_drains() {
+        let mut log = DamageLog::default();
+        for i in 0..DAMAGE_LOG_CAPACITY + 5 {
+            log.push(entry(i % 2 == 0, i as f32));
+        }
+        assert_eq!(log.len(), DAMAGE_LOG_CAPACITY);
+        let entries = log.take();
+        assert!(log.is_empty());
+        // Oldest entries were dropped
+        assert_eq!(entries[0].damage, 5.0);
+
+        let (taken, dealt) = damage_totals(&[entry(false, 30.0), entry(true, 12.0), entry(false, 5.0)]);
+        assert_eq!((taken, dealt), (35.0, 12.0));
+    }
+
+    #[test]
+    fn test_names_and_labels() {
+        let player = Combatant::Player { id: 7, name: "Dune_Rat".to_string() };
+        assert_eq!(player.display_name(), "Dune_Rat");
+        assert_eq!(player.id(), Some(7));
+        assert_eq!(Combatant::Npc { id: 3, archetype: NpcArchetype::Knight }.display_name(), "Knight");
+        assert_eq!(Combatant::Environment.id(), None);
+        assert_eq!(DamageCause::Weapon(WeaponType::Unarmed).label(), "Fists");
+        assert_eq!(DamageCause::Weapon(WeaponType::Sniper).label(), "Sniper Rifle");
+    }
+}
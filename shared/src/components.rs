@@ -4,7 +4,7 @@ use bevy::prelude::*;
 use lightyear::prelude::PeerId;
 use serde::{Deserialize, Serialize};
 
-use crate::weapons::WeaponType;
+use crate::weapons::{FireMode, WeaponType};
 
 // =============================================================================
 // WORLD TIME / DAY-NIGHT CYCLE
@@ -127,6 +127,39 @@ impl WorldTime {
     }
 }
 
+/// Server-authoritative wind replicated to clients (lives on the `WorldTime` entity).
+///
+/// Wind is uniform across the map and changes slowly; it pushes bullets sideways
+/// through `ballistics::step_bullet_physics_in_wind`.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Wind {
+    /// Horizontal air velocity (m/s) - the direction the wind blows toward
+    pub velocity: Vec3,
+}
+
+impl Wind {
+    /// Strongest wind the cycle produces (m/s)
+    pub const MAX_SPEED: f32 = 8.0;
+    /// Server only re-replicates once the wind moved at least this much (m/s)
+    pub const REPLICATION_THRESHOLD: f32 = 0.05;
+
+    /// Deterministic wind at `seconds` of server uptime.
+    ///
+    /// Heading wanders over ~10 minutes, strength over ~4 minutes with short gusts on top.
+    pub fn sample(seconds: f32) -> Vec3 {
+        use std::f32::consts::TAU;
+        let heading = 0.8 + (seconds / 600.0 * TAU).sin() * 1.2 + (seconds / 170.0).sin() * 0.3;
+        let gust = 0.8 * (seconds / 37.0 * TAU).sin() * (seconds / 11.0 * TAU).cos();
+        let strength = (3.0 + 2.0 * (seconds / 240.0 * TAU).sin() + gust).clamp(0.0, Self::MAX_SPEED);
+        Vec3::new(heading.cos(), 0.0, heading.sin()) * strength
+    }
+
+    /// Wind speed (m/s)
+    pub fn speed(&self) -> f32 {
+        self.velocity.length()
+    }
+}
+
 /// Marker component for player entities
 #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Player {
@@ -149,6 +182,17 @@ pub enum NpcArchetype {
     RogueHooded,
 }
 
+impl NpcArchetype {
+    pub const ALL: [NpcArchetype; 6] = [
+        NpcArchetype::Barbarian,
+        NpcArchetype::Ranger,
+        NpcArchetype::Mage,
+        NpcArchetype::Knight,
+        NpcArchetype::Rogue,
+        NpcArchetype::RogueHooded,
+    ];
+}
+
 /// Marker component for NPC entities (server authoritative, replicated to clients)
 #[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Npc {
@@ -268,6 +312,18 @@ pub struct EquippedWeapon {
     pub last_fire_time: f32,
     /// Whether currently aiming down sights
     pub aiming: bool,
+    /// Selected fire mode (one of `weapon_type.fire_modes()`)
+    pub fire_mode: FireMode,
+    /// Rounds fired by the current trigger pull (see `WeaponType::trigger_pull_interval`)
+    pub shots_this_pull: u32,
+    /// Sight zero range in meters (0 = fixed sights, see `WeaponType::zero_ranges`)
+    pub zero_range: f32,
+    /// Rounds fired in the current recoil burst (indexes `WeaponType::recoil_pattern`)
+    pub burst_shots: u32,
+    /// Recoil accumulated over the current burst (x = pitch, y = yaw)
+    pub recoil_offset: Vec2,
+    /// Reported aim of the last round fired (checked against the next round of the burst)
+    pub last_shot_direction: Vec3,
 }
 
 impl Default for EquippedWeapon {
@@ -280,6 +336,12 @@ impl Default for EquippedWeapon {
             reserve_ammo: 0, // Reserve ammo now comes from inventory
             last_fire_time: -10.0, // Allow immediate first shot
             aiming: false,
+            fire_mode: weapon.default_fire_mode(),
+            shots_this_pull: 0,
+            zero_range: weapon.default_zero_range(),
+            burst_shots: 0,
+            recoil_offset: Vec2::ZERO,
+            last_shot_direction: Vec3::NEG_Z,
         }
     }
 }
@@ -293,6 +355,12 @@ impl EquippedWeapon {
             reserve_ammo: 0, // Reserve ammo now comes from inventory
             last_fire_time: -10.0,
             aiming: false,
+            fire_mode: weapon_type.default_fire_mode(),
+            shots_this_pull: 0,
+            zero_range: weapon_type.default_zero_range(),
+            burst_shots: 0,
+            recoil_offset: Vec2::ZERO,
+            last_shot_direction: Vec3::NEG_Z,
         }
     }
     
@@ -313,6 +381,48 @@ impl EquippedWeapon {
         }
     }
     
+    /// Fire a round, enforcing the selected fire mode from shot timing alone.
+    ///
+    /// A round within `trigger_pull_interval` of the previous one belongs to the same
+    /// pull, so semi-auto needs a trigger reset between rounds and a burst stops at its count.
+    pub fn fire_in_mode(&mut self, current_time: f32) -> bool {
+        let same_pull = current_time - self.last_fire_time < self.weapon_type.trigger_pull_interval();
+        let shots = if same_pull { self.shots_this_pull } else { 0 };
+        if !self.fire_mode.allows_shot(shots) || !self.fire(current_time) {
+            return false;
+        }
+        self.shots_this_pull = shots + 1;
+        true
+    }
+    
+    /// Switch to another weapon type, resetting per-weapon state (fire mode, zero, recoil, cooldown, ADS)
+    pub fn switch_to(&mut self, weapon_type: WeaponType) {
+        self.weapon_type = weapon_type;
+        self.aiming = false;
+        self.last_fire_time = -10.0;
+        self.fire_mode = weapon_type.default_fire_mode();
+        self.shots_this_pull = 0;
+        self.zero_range = weapon_type.default_zero_range();
+        self.burst_shots = 0;
+        self.recoil_offset = Vec2::ZERO;
+    }
+    
+    /// Check if a melee swing can start (cooldown only, no ammo)
+    pub fn can_swing(&self, current_time: f32) -> bool {
+        self.weapon_type.is_melee()
+            && (current_time - self.last_fire_time) >= self.weapon_type.fire_cooldown()
+    }
+    
+    /// Start a melee swing (updates cooldown)
+    pub fn swing(&mut self, current_time: f32) -> bool {
+        if self.can_swing(current_time) {
+            self.last_fire_time = current_time;
+            true
+        } else {
+            false
+        }
+    }
+    
     /// Reload from reserve ammo (deprecated - use reload_from_inventory instead)
     pub fn reload(&mut self) {
         let stats = self.weapon_type.stats();
@@ -386,6 +496,8 @@ pub struct BulletPrevPosition(pub Vec3);
 pub struct LocalTracer {
     pub spawn_time: f32,
     pub lifetime: f32,
+    /// Weapon that fired it (selects the ballistic coefficient)
+    pub weapon_type: WeaponType,
 }
 
 /// Event component to signal that an NPC took damage (server-side only, not replicated)
@@ -394,4 +506,6 @@ pub struct LocalTracer {
 pub struct NpcDamageEvent {
     pub damage_source_position: Vec3,
     pub damage_amount: f32,
+    /// Player who dealt the damage (None for NPC crossfire and the environment)
+    pub attacker: Option<Entity>,
 }
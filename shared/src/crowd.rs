@@ -0,0 +1,313 @@
+//! Crowd local avoidance (ORCA)
+//!
+//! Each agent gets a velocity close to the one it wants that stays clear of its
+//! neighbours for `CROWD_TIME_HORIZON` seconds, assuming they all do the same
+//! ("optimal reciprocal collision avoidance", as in RVO2). Neighbours come from
+//! an `AgentGrid` spatial hash, visited in a fixed order so results only depend
+//! on the agents, never on hash map or query order.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Size of an `AgentGrid` cell (m)
+pub const CROWD_CELL_SIZE: f32 = 4.0;
+
+/// Agents further apart than this ignore each other (m)
+pub const CROWD_NEIGHBOR_DIST: f32 = 6.0;
+
+/// At most this many (closest) neighbours are considered per agent
+pub const CROWD_MAX_NEIGHBORS: usize = 8;
+
+/// How far ahead (s) agents keep clear of each other
+pub const CROWD_TIME_HORIZON: f32 = 1.5;
+
+const EPSILON: f32 = 1e-5;
+
+/// One agent as seen by the avoidance solver (XZ plane)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrowdAgent {
+    /// Tie-breaker for agents standing on the same spot
+    pub id: u64,
+    pub position: Vec2,
+    /// Velocity it moved with last tick
+    pub velocity: Vec2,
+    pub radius: f32,
+}
+
+/// Spatial hash of agent indices for neighbour queries
+#[derive(Default, Debug)]
+pub struct AgentGrid {
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl AgentGrid {
+    #[inline]
+    fn cell(pos: Vec2) -> (i32, i32) {
+        ((pos.x / CROWD_CELL_SIZE).floor() as i32, (pos.y / CROWD_CELL_SIZE).floor() as i32)
+    }
+
+    pub fn clear(&mut self) {
+        // Keep the cell vectors around; most are reused next tick
+        for indices in self.cells.values_mut() {
+            indices.clear();
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, pos: Vec2) {
+        self.cells.entry(Self::cell(pos)).or_default().push(index);
+    }
+
+    /// Indices of `agents` within `radius` of agent `index` (excluding it), closest first,
+    /// at most `CROWD_MAX_NEIGHBORS`
+    pub fn neighbors(&self, agents: &[CrowdAgent], index: usize, radius: f32, out: &mut Vec<usize>) {
+        out.clear();
+        let center = agents[index].position;
+        let min = Self::cell(center - Vec2::splat(radius));
+        let max = Self::cell(center + Vec2::splat(radius));
+        for cx in min.0..=max.0 {
+            for cz in min.1..=max.1 {
+                let Some(indices) = self.cells.get(&(cx, cz)) else {
+                    continue;
+                };
+                out.extend(indices.iter().copied().filter(|&other| {
+                    other != index && agents[other].position.distance_squared(center) <= radius * radius
+                }));
+            }
+        }
+        out.sort_by(|&a, &b| {
+            let da = agents[a].position.distance_squared(center);
+            let db = agents[b].position.distance_squared(center);
+            da.total_cmp(&db).then(agents[a].id.cmp(&agents[b].id))
+        });
+        out.truncate(CROWD_MAX_NEIGHBORS);
+    }
+}
+
+/// Half-plane of allowed velocities: left of `direction` through `point`
+#[derive(Clone, Copy, Debug)]
+struct OrcaLine {
+    point: Vec2,
+    direction: Vec2,
+}
+
+/// Velocity closest to `preferred` (capped at `max_speed`) that avoids `neighbors`
+/// for `CROWD_TIME_HORIZON`; agents already overlapping are pushed apart within `dt`.
+pub fn orca_velocity(agent: &CrowdAgent, preferred: Vec2, max_speed: f32, neighbors: &[CrowdAgent], dt: f32) -> Vec2 {
+    let inv_horizon = 1.0 / CROWD_TIME_HORIZON;
+    let lines: Vec<OrcaLine> = neighbors
+        .iter()
+        .map(|other| {
+            let relative_position = other.position - agent.position;
+            let relative_velocity = agent.velocity - other.velocity;
+            let dist_sq = relative_position.length_squared();
+            let combined_radius = agent.radius + other.radius;
+            let combined_radius_sq = combined_radius * combined_radius;
+
+            let (direction, u) = if dist_sq > combined_radius_sq {
+                // Vector from the cutoff circle's center to the relative velocity
+                let w = relative_velocity - relative_position * inv_horizon;
+                let w_length_sq = w.length_squared();
+                let dot = w.dot(relative_position);
+                if dot < 0.0 && dot * dot > combined_radius_sq * w_length_sq {
+                    // Closest to the cutoff circle
+                    let w_length = w_length_sq.sqrt();
+                    let unit_w = w / w_length;
+                    (Vec2::new(unit_w.y, -unit_w.x), unit_w * (combined_radius * inv_horizon - w_length))
+                } else {
+                    // Closest to one of the cone's legs
+                    let leg = (dist_sq - combined_radius_sq).sqrt();
+                    let (x, y) = (relative_position.x, relative_position.y);
+                    let direction = if relative_position.perp_dot(w) > 0.0 {
+                        Vec2::new(x * leg - y * combined_radius, x * combined_radius + y * leg) / dist_sq
+                    } else {
+                        -Vec2::new(x * leg + y * combined_radius, -x * combined_radius + y * leg) / dist_sq
+                    };
+                    (direction, direction * relative_velocity.dot(direction) - relative_velocity)
+                }
+            } else {
+                // Already overlapping: get apart within this step
+                let inv_dt = 1.0 / dt;
+                let w = relative_velocity - relative_position * inv_dt;
+                let w_length = w.length();
+                let unit_w = if w_length > EPSILON {
+                    w / w_length
+                } else if agent.id < other.id {
+                    // Same spot, same velocity: split by ID
+                    Vec2::X
+                } else {
+                    Vec2::NEG_X
+                };
+                (Vec2::new(unit_w.y, -unit_w.x), unit_w * (combined_radius * inv_dt - w_length))
+            };
+            // Each side takes half the responsibility
+            OrcaLine { point: agent.velocity + u * 0.5, direction }
+        })
+        .collect();
+
+    let (failed, mut result) = linear_program2(&lines, max_speed, preferred, false);
+    if failed < lines.len() {
+        linear_program3(&lines, failed, max_speed, &mut result);
+    }
+    result
+}
+
+/// Optimize along line `index` subject to the lines before it and the speed circle
+fn linear_program1(
+    lines: &[OrcaLine],
+    index: usize,
+    radius: f32,
+    optimal: Vec2,
+    optimize_direction: bool,
+    result: &mut Vec2,
+) -> bool {
+    let line = lines[index];
+    let dot = line.point.dot(line.direction);
+    let discriminant = dot * dot + radius * radius - line.point.length_squared();
+    if discriminant < 0.0 {
+        // The speed circle misses this line entirely
+        return false;
+    }
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut t_left = -dot - sqrt_discriminant;
+    let mut t_right = -dot + sqrt_discriminant;
+
+    for other in &lines[..index] {
+        let denominator = line.direction.perp_dot(other.direction);
+        let numerator = other.direction.perp_dot(line.point - other.point);
+        if denominator.abs() <= EPSILON {
+            // Parallel lines
+            if numerator < 0.0 {
+                return false;
+            }
+            continue;
+        }
+        let t = numerator / denominator;
+        if denominator >= 0.0 {
+            t_right = t_right.min(t);
+        } else {
+            t_left = t_left.max(t);
+        }
+        if t_left > t_right {
+            return false;
+        }
+    }
+
+    let t = if optimize_direction {
+        if optimal.dot(line.direction) > 0.0 {
+            t_right
+        } else {
+            t_left
+        }
+    } else {
+        line.direction.dot(optimal - line.point).clamp(t_left, t_right)
+    };
+    *result = line.point + line.direction * t;
+    true
+}
+
+/// Closest velocity to `optimal` satisfying every line; returns the index of the first
+/// line it couldn't satisfy (`lines.len()` on success) with the best velocity so far
+fn linear_program2(lines: &[OrcaLine], radius: f32, optimal: Vec2, optimize_direction: bool) -> (usize, Vec2) {
+    let mut result = if optimize_direction {
+        optimal * radius
+    } else if optimal.length_squared() > radius * radius {
+        optimal.normalize() * radius
+    } else {
+        optimal
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.direction.perp_dot(line.point - result) > 0.0 {
+            let previous = result;
+            if !linear_program1(lines, index, radius, optimal, optimize_direction, &mut result) {
+                return (index, previous);
+            }
+        }
+    }
+    (lines.len(), result)
+}
+
+/// Infeasible: find the velocity that violates the lines from `begin` on the least
+fn linear_program3(lines: &[OrcaLine], begin: usize, radius: f32, result: &mut Vec2) {
+    let mut distance = 0.0;
+    for index in begin..lines.len() {
+        let line = lines[index];
+        if line.direction.perp_dot(line.point - *result) <= distance {
+            continue;
+        }
+
+        let projected: Vec<OrcaLine> = lines[..index]
+            .iter()
+            .filter_map(|other| {
+                let determinant = line.direction.perp_dot(other.direction);
+                let point = if determinant.abs() <= EPSILON {
+                    if line.direction.dot(other.direction) > 0.0 {
+                        // Same direction: already covered
+                        return None;
+                    }
+                    (line.point + other.point) * 0.5
+                } else {
+                    line.point + line.direction * (other.direction.perp_dot(line.point - other.point) / determinant)
+                };
+                Some(OrcaLine { point, direction: (other.direction - line.direction).normalize_or_zero() })
+            })
+            .collect();
+
+        let previous = *result;
+        let optimal = Vec2::new(-line.direction.y, line.direction.x);
+        let (failed, candidate) = linear_program2(&projected, radius, optimal, true);
+        // Only fails through floating point error; keep the previous result then
+        *result = if failed < projected.len() { previous } else { candidate };
+        distance = line.direction.perp_dot(line.point - *result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(id: u64, x: f32, velocity: Vec2) -> CrowdAgent {
+        CrowdAgent { id, position: Vec2::new(x, 0.0), velocity, radius: 0.5 }
+    }
+
+    #[test]
+    fn test_lone_agent_keeps_preferred_velocity() {
+        let me = agent(1, 0.0, Vec2::X);
+        let far = agent(2, 50.0, Vec2::ZERO);
+        assert_eq!(orca_velocity(&me, Vec2::X, 2.0, &[far], 1.0 / 60.0), Vec2::X);
+        // Capped at max speed
+        assert!((orca_velocity(&me, Vec2::X * 5.0, 2.0, &[], 1.0 / 60.0).length() - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_head_on_agents_sidestep_opposite_ways() {
+        let dt = 1.0 / 60.0;
+        let a = agent(1, 0.0, Vec2::X * 1.5);
+        let b = agent(2, 2.0, Vec2::NEG_X * 1.5);
+        let va = orca_velocity(&a, Vec2::X * 1.5, 2.0, &[b], dt);
+        let vb = orca_velocity(&b, Vec2::NEG_X * 1.5, 2.0, &[a], dt);
+        // Both slow down or veer; the relative velocity no longer closes head-on
+        assert!(va.x < 1.5 && vb.x > -1.5);
+        assert!(va.y * vb.y <= 0.0, "should veer to opposite sides: {va:?} {vb:?}");
+
+        // Deterministic: same inputs, same answer
+        assert_eq!(va, orca_velocity(&a, Vec2::X * 1.5, 2.0, &[b], dt));
+    }
+
+    #[test]
+    fn test_grid_neighbors_closest_first() {
+        let agents = [
+            agent(1, 0.0, Vec2::ZERO),
+            agent(2, 5.0, Vec2::ZERO),
+            agent(3, 1.0, Vec2::ZERO),
+            agent(4, 20.0, Vec2::ZERO),
+        ];
+        let mut grid = AgentGrid::default();
+        for (index, a) in agents.iter().enumerate() {
+            grid.insert(index, a.position);
+        }
+        let mut out = Vec::new();
+        grid.neighbors(&agents, 0, CROWD_NEIGHBOR_DIST, &mut out);
+        assert_eq!(out, vec![2, 1]);
+    }
+}
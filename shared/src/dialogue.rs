@@ -0,0 +1,442 @@
+//! Data-authored NPC dialogue
+//!
+//! Talkative NPCs run a `DialogueTree` loaded from a RON file (one per `DialogueKind`).
+//! A tree is a set of named nodes: each has a line the NPC says, an optional voice clip
+//! and the responses a player can pick. Greetings and responses can be gated on
+//! `DialogueCondition`s (items carried, quest progress, time of day), and responses can
+//! carry `DialogueAction`s (hand items over, start or finish quests, open the trade
+//! window). Conversations run on the server: the client asks to talk, the server walks
+//! the tree and answers with a `DialogueUpdate` for the player's UI, and voice lines are
+//! sent to everyone in earshot as `NpcVoiceLine`s.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::behaviour::time_window_contains;
+use crate::components::NpcArchetype;
+use crate::items::{Inventory, ItemType};
+use crate::trade::new_stock_item;
+
+/// How close a player must stand to an NPC to talk to it (m)
+pub const DIALOGUE_RANGE: f32 = 4.0;
+
+/// Voice lines are sent to players within this distance of the speaker (m)
+pub const DIALOGUE_VOICE_RANGE: f32 = 30.0;
+
+/// Most responses a node may offer (picked with number keys 1-9)
+pub const MAX_DIALOGUE_RESPONSES: usize = 9;
+
+/// Which dialogue tree an NPC talks with
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DialogueKind {
+    Knight,
+    Townsfolk,
+    Trader,
+}
+
+impl DialogueKind {
+    pub const ALL: [DialogueKind; 3] = [DialogueKind::Knight, DialogueKind::Townsfolk, DialogueKind::Trader];
+}
+
+/// Dialogue tree for an NPC, if it talks at all
+pub fn dialogue_kind(archetype: NpcArchetype, trader: bool) -> Option<DialogueKind> {
+    match archetype {
+        NpcArchetype::Knight => Some(DialogueKind::Knight),
+        NpcArchetype::RogueHooded if trader => Some(DialogueKind::Trader),
+        NpcArchetype::RogueHooded => Some(DialogueKind::Townsfolk),
+        _ => None,
+    }
+}
+
+/// Dialogue file for a kind (under `shared/assets/dialogue/`)
+pub fn dialogue_file_name(kind: DialogueKind) -> &'static str {
+    match kind {
+        DialogueKind::Knight => "knight.ron",
+        DialogueKind::Townsfolk => "townsfolk.ron",
+        DialogueKind::Trader => "trader.ron",
+    }
+}
+
+/// Built-in copy of a kind's dialogue file (used when the file on disk is missing or broken)
+pub fn default_dialogue_source(kind: DialogueKind) -> &'static str {
+    match kind {
+        DialogueKind::Knight => include_str!("../assets/dialogue/knight.ron"),
+        DialogueKind::Townsfolk => include_str!("../assets/dialogue/townsfolk.ron"),
+        DialogueKind::Trader => include_str!("../assets/dialogue/trader.ron"),
+    }
+}
+
+/// Where a player is with a quest
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum QuestStatus {
+    #[default]
+    NotStarted,
+    Active,
+    Done,
+}
+
+/// A player's quest progress (server-side component on the player, saved with the profile)
+#[derive(bevy::prelude::Component, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct QuestLog {
+    quests: BTreeMap<String, QuestStatus>,
+}
+
+impl QuestLog {
+    pub fn status(&self, quest: &str) -> QuestStatus {
+        self.quests.get(quest).copied().unwrap_or_default()
+    }
+
+    /// Take a quest on; false when it was already started or done
+    pub fn start(&mut self, quest: &str) -> bool {
+        if self.status(quest) != QuestStatus::NotStarted {
+            return false;
+        }
+        self.quests.insert(quest.to_string(), QuestStatus::Active);
+        true
+    }
+
+    pub fn complete(&mut self, quest: &str) {
+        self.quests.insert(quest.to_string(), QuestStatus::Done);
+    }
+}
+
+/// A yes/no question about the player an NPC is talking to
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum DialogueCondition {
+    /// The player carries at least `quantity` of an item
+    HasItem { item: ItemType, quantity: u32 },
+    /// A quest is at this stage
+    Quest { id: String, status: QuestStatus },
+    /// The time of day is in `[from, to)` (`WorldTime::normalized_time`, wrapping past midnight)
+    TimeBetween { from: f32, to: f32 },
+    Not(Box<DialogueCondition>),
+}
+
+/// Something that happens when a player picks a response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum DialogueAction {
+    /// The NPC hands the player items
+    GiveItem { item: ItemType, quantity: u32 },
+    /// The player hands the NPC items
+    TakeItem { item: ItemType, quantity: u32 },
+    StartQuest(String),
+    CompleteQuest(String),
+    /// End the conversation and open the NPC's trade window
+    OpenTrade,
+}
+
+/// Why a response's actions couldn't be carried out
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DialogueError {
+    NotEnoughItems,
+    InventoryFull,
+}
+
+impl DialogueError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            DialogueError::NotEnoughItems => "You don't have that with you",
+            DialogueError::InventoryFull => "Inventory full",
+        }
+    }
+}
+
+/// What a player knows the answers to, for checking conditions
+pub struct DialogueFacts<'a> {
+    pub inventory: &'a Inventory,
+    pub quests: &'a QuestLog,
+    /// `WorldTime::normalized_time`
+    pub time_of_day: f32,
+}
+
+impl DialogueFacts<'_> {
+    pub fn check(&self, condition: &DialogueCondition) -> bool {
+        match condition {
+            DialogueCondition::HasItem { item, quantity } => self.inventory.has_item(*item, *quantity),
+            DialogueCondition::Quest { id, status } => self.quests.status(id) == *status,
+            DialogueCondition::TimeBetween { from, to } => time_window_contains(*from, *to, self.time_of_day),
+            DialogueCondition::Not(inner) => !self.check(inner),
+        }
+    }
+
+    /// All of them hold (an empty list always does)
+    pub fn all(&self, conditions: &[DialogueCondition]) -> bool {
+        conditions.iter().all(|condition| self.check(condition))
+    }
+}
+
+/// What carrying out a response's actions led to
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DialogueOutcome {
+    /// Lines for the player ("Received 60x Coins", "New quest", ...)
+    pub notices: Vec<String>,
+    pub open_trade: bool,
+}
+
+/// Carry out a response's actions. All or nothing: on error neither the inventory nor
+/// the quest log changes.
+pub fn apply_dialogue_actions(
+    actions: &[DialogueAction],
+    inventory: &mut Inventory,
+    quests: &mut QuestLog,
+) -> Result<DialogueOutcome, DialogueError> {
+    // Work on copies and only keep them once every step went through
+    let mut inventory_after = inventory.clone();
+    let mut quests_after = quests.clone();
+    let mut outcome = DialogueOutcome::default();
+
+    for action in actions {
+        match action {
+            DialogueAction::GiveItem { item, quantity } => {
+                if item.max_stack_size() == 1 {
+                    for _ in 0..*quantity {
+                        if inventory_after.add_stack(new_stock_item(*item)).is_some() {
+                            return Err(DialogueError::InventoryFull);
+                        }
+                    }
+                } else if inventory_after.add_item(*item, *quantity) > 0 {
+                    return Err(DialogueError::InventoryFull);
+                }
+                outcome.notices.push(format!("Received {}x {}", quantity, item.display_name()));
+            }
+            DialogueAction::TakeItem { item, quantity } => {
+                if inventory_after.remove_item(*item, *quantity) < *quantity {
+                    return Err(DialogueError::NotEnoughItems);
+                }
+                outcome.notices.push(format!("Handed over {}x {}", quantity, item.display_name()));
+            }
+            DialogueAction::StartQuest(quest) => {
+                if quests_after.start(quest) {
+                    outcome.notices.push("New quest".to_string());
+                }
+            }
+            DialogueAction::CompleteQuest(quest) => {
+                quests_after.complete(quest);
+                outcome.notices.push("Quest complete".to_string());
+            }
+            DialogueAction::OpenTrade => outcome.open_trade = true,
+        }
+    }
+
+    *inventory = inventory_after;
+    *quests = quests_after;
+    Ok(outcome)
+}
+
+/// Something the player can say back
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueResponse {
+    pub text: String,
+    /// Only offered while these all hold
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    #[serde(default)]
+    pub actions: Vec<DialogueAction>,
+    /// Node the NPC answers with (None ends the conversation)
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// One thing an NPC says
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueNode {
+    pub text: String,
+    /// Index into the speaker's voice clips
+    #[serde(default)]
+    pub voice: Option<u8>,
+    pub responses: Vec<DialogueResponse>,
+}
+
+impl DialogueNode {
+    /// Indices of the responses on offer right now
+    pub fn available_responses(&self, facts: &DialogueFacts) -> Vec<usize> {
+        self.responses
+            .iter()
+            .enumerate()
+            .filter(|(_, response)| facts.all(&response.conditions))
+            .map(|(index, _)| index)
+            .take(MAX_DIALOGUE_RESPONSES)
+            .collect()
+    }
+}
+
+/// Where a conversation can start: the first greeting whose conditions hold is used
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueGreeting {
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    pub node: String,
+}
+
+/// A whole tree as stored in a dialogue file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueTree {
+    /// Name shown above the NPC's lines
+    pub speaker: String,
+    pub greetings: Vec<DialogueGreeting>,
+    pub nodes: BTreeMap<String, DialogueNode>,
+}
+
+impl DialogueTree {
+    /// Parse and check that every node a greeting or response points at exists
+    pub fn from_ron(source: &str) -> Result<Self, String> {
+        let tree: DialogueTree = ron::from_str(source).map_err(|e| e.to_string())?;
+        tree.validate()?;
+        Ok(tree)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.greetings.is_empty() {
+            return Err("no greetings".to_string());
+        }
+        for greeting in &self.greetings {
+            if !self.nodes.contains_key(&greeting.node) {
+                return Err(format!("greeting goes to unknown node '{}'", greeting.node));
+            }
+        }
+        for (id, node) in &self.nodes {
+            if node.responses.len() > MAX_DIALOGUE_RESPONSES {
+                return Err(format!("node '{}' has more than {} responses", id, MAX_DIALOGUE_RESPONSES));
+            }
+            for next in node.responses.iter().filter_map(|response| response.next.as_ref()) {
+                if !self.nodes.contains_key(next) {
+                    return Err(format!("node '{}' goes to unknown node '{}'", id, next));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Node a conversation starts at (None when no greeting fits)
+    pub fn opening(&self, facts: &DialogueFacts) -> Option<&str> {
+        self.greetings
+            .iter()
+            .find(|greeting| facts.all(&greeting.conditions))
+            .map(|greeting| greeting.node.as_str())
+    }
+
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.get(id)
+    }
+}
+
+/// What the player's dialogue window shows
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueView {
+    pub speaker: String,
+    pub text: String,
+    /// Responses on offer, in the order `DialogueChoiceRequest::option` indexes them
+    pub responses: Vec<String>,
+}
+
+/// Client -> Server: start talking to an NPC
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StartDialogueRequest {
+    /// `Npc::id` of the NPC
+    pub npc: u64,
+}
+
+/// Client -> Server: pick one of the responses on offer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueChoiceRequest {
+    /// Index into `DialogueView::responses`
+    pub option: u8,
+}
+
+/// Client -> Server: walk out of the conversation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EndDialogueRequest;
+
+/// Server -> Client: the conversation moved on (or ended)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DialogueUpdate {
+    pub npc: u64,
+    /// None: the conversation is over
+    pub view: Option<DialogueView>,
+    /// What the last response did, or why it couldn't be done
+    pub notices: Vec<String>,
+    /// Open the NPC's trade window
+    pub open_trade: bool,
+}
+
+/// Server -> Client: an NPC says a voice line (sent to every player in earshot)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NpcVoiceLine {
+    pub npc: u64,
+    /// Index into the speaker's voice clips
+    pub clip: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE: &str = r#"(
+        speaker: "Guard",
+        greetings: [
+            (conditions: [Quest(id: "wood", status: Done)], node: "thanks"),
+            (node: "hello"),
+        ],
+        nodes: {
+            "hello": (text: "Need wood.", voice: Some(1), responses: [
+                (text: "Here.", conditions: [HasItem(item: Wood, quantity: 5)],
+                    actions: [TakeItem(item: Wood, quantity: 5), CompleteQuest("wood")], next: Some("thanks")),
+                (text: "At night?", conditions: [TimeBetween(from: 0.9, to: 0.2)], next: Some("hello")),
+                (text: "Bye."),
+            ]),
+            "thanks": (text: "Thanks.", responses: [(text: "Bye.")]),
+        },
+    )"#;
+
+    #[test]
+    fn test_conditions_pick_greeting_and_responses() {
+        let tree = DialogueTree::from_ron(TREE).unwrap();
+        let mut inventory = Inventory::new();
+        let mut quests = QuestLog::default();
+
+        let facts = DialogueFacts { inventory: &inventory, quests: &quests, time_of_day: 0.5 };
+        assert_eq!(tree.opening(&facts), Some("hello"));
+        assert_eq!(tree.node("hello").unwrap().available_responses(&facts), vec![2]);
+
+        inventory.add_item(ItemType::Wood, 5);
+        let facts = DialogueFacts { inventory: &inventory, quests: &quests, time_of_day: 0.95 };
+        assert_eq!(tree.node("hello").unwrap().available_responses(&facts), vec![0, 1, 2]);
+
+        let actions = &tree.node("hello").unwrap().responses[0].actions;
+        apply_dialogue_actions(actions, &mut inventory, &mut quests).unwrap();
+        assert_eq!(inventory.count_item(ItemType::Wood), 0);
+        assert_eq!(quests.status("wood"), QuestStatus::Done);
+        let facts = DialogueFacts { inventory: &inventory, quests: &quests, time_of_day: 0.5 };
+        assert_eq!(tree.opening(&facts), Some("thanks"));
+    }
+
+    #[test]
+    fn test_failed_actions_change_nothing() {
+        let mut inventory = Inventory::new();
+        inventory.add_item(ItemType::Wood, 3);
+        let mut quests = QuestLog::default();
+        let actions = [
+            DialogueAction::StartQuest("wood".to_string()),
+            DialogueAction::TakeItem { item: ItemType::Wood, quantity: 5 },
+        ];
+        let before = inventory.clone();
+
+        assert_eq!(apply_dialogue_actions(&actions, &mut inventory, &mut quests), Err(DialogueError::NotEnoughItems));
+        assert_eq!(inventory, before);
+        assert_eq!(quests.status("wood"), QuestStatus::NotStarted);
+
+        let outcome = apply_dialogue_actions(&[DialogueAction::OpenTrade], &mut inventory, &mut quests).unwrap();
+        assert!(outcome.open_trade);
+    }
+
+    #[test]
+    fn test_builtin_trees_parse() {
+        for kind in DialogueKind::ALL {
+            if let Err(e) = DialogueTree::from_ron(default_dialogue_source(kind)) {
+                panic!("{}: {}", dialogue_file_name(kind), e);
+            }
+        }
+        assert!(DialogueTree::from_ron(r#"(speaker: "X", greetings: [(node: "missing")], nodes: {})"#).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Game modes and the match lifecycle
+//!
+//! A match cycles Warmup -> Live -> (Overtime) -> PostMatch -> Warmup. The server
+//! owns a replicated `MatchState` and advances it every fixed tick; kills only score
+//! while the match is live. Team Deathmatch ends at the score limit, or at the time
+//! limit with sudden-death overtime if the teams are level. Battle Royale ends when
+//! one team is left standing (see `zone`).
+
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::SPAWN_POSITION;
+use crate::team::{Team, TEAM_COUNT};
+
+/// Distance of each team's spawn area from the map spawn point (m, along X)
+pub const TDM_BASE_OFFSET: f32 = 60.0;
+
+/// Radius of the ring of spawn points around a team's base (m)
+pub const TDM_SPAWN_RING_RADIUS: f32 = 15.0;
+
+/// Spawn points per team
+pub const TDM_SPAWN_POINTS: usize = 8;
+
+/// Which game mode the server runs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GameModeKind {
+    /// Free roam, no match (the original survival sandbox)
+    #[default]
+    Sandbox,
+    TeamDeathmatch,
+    /// Shrinking safe zone, no respawns, last team standing wins
+    BattleRoyale,
+    /// Persistent settlement capture, no match (see `territory`)
+    Territory,
+}
+
+impl GameModeKind {
+    /// Short name for the HUD
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameModeKind::Sandbox => "Sandbox",
+            GameModeKind::TeamDeathmatch => "Team Deathmatch",
+            GameModeKind::BattleRoyale => "Battle Royale",
+            GameModeKind::Territory => "Territory",
+        }
+    }
+}
+
+impl FromStr for GameModeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sandbox" => Ok(GameModeKind::Sandbox),
+            "tdm" | "team_deathmatch" | "teamdeathmatch" => Ok(GameModeKind::TeamDeathmatch),
+            "br" | "battle_royale" | "battleroyale" => Ok(GameModeKind::BattleRoyale),
+            "territory" | "capture" => Ok(GameModeKind::Territory),
+            other => Err(format!("unknown game mode '{}'", other)),
+        }
+    }
+}
+
+/// Match lifecycle phase
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchPhase {
+    /// Waiting for players; kills don't count
+    Warmup,
+    Live,
+    /// Sudden death after a tied time limit: the next kill wins
+    Overtime,
+    /// Scoreboard up until the next warmup
+    PostMatch,
+}
+
+impl MatchPhase {
+    /// Name for the HUD
+    pub fn label(&self) -> &'static str {
+        match self {
+            MatchPhase::Warmup => "WARMUP",
+            MatchPhase::Live => "LIVE",
+            MatchPhase::Overtime => "OVERTIME",
+            MatchPhase::PostMatch => "MATCH OVER",
+        }
+    }
+}
+
+/// Limits and phase lengths for a mode (seconds)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchRules {
+    pub score_limit: u32,
+    pub time_limit: f32,
+    pub warmup_time: f32,
+    pub overtime_time: f32,
+    pub post_match_time: f32,
+}
+
+impl MatchRules {
+    pub const TEAM_DEATHMATCH: MatchRules = MatchRules {
+        score_limit: 30,
+        time_limit: 600.0,
+        warmup_time: 30.0,
+        overtime_time: 120.0,
+        post_match_time: 15.0,
+    };
+
+    /// No score limit; the time limit outlasts the whole zone schedule
+    pub const BATTLE_ROYALE: MatchRules = MatchRules {
+        score_limit: u32::MAX,
+        time_limit: 600.0,
+        warmup_time: 60.0,
+        overtime_time: 0.0,
+        post_match_time: 20.0,
+    };
+
+    /// Default rules for a mode
+    pub fn for_mode(kind: GameModeKind) -> MatchRules {
+        match kind {
+            GameModeKind::BattleRoyale => MatchRules::BATTLE_ROYALE,
+            GameModeKind::Sandbox | GameModeKind::TeamDeathmatch | GameModeKind::Territory => {
+                MatchRules::TEAM_DEATHMATCH
+            }
+        }
+    }
+
+    /// Length of a phase
+    pub fn phase_time(&self, phase: MatchPhase) -> f32 {
+        match phase {
+            MatchPhase::Warmup => self.warmup_time,
+            MatchPhase::Live => self.time_limit,
+            MatchPhase::Overtime => self.overtime_time,
+            MatchPhase::PostMatch => self.post_match_time,
+        }
+    }
+}
+
+/// Current match (server-authoritative, replicated)
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MatchState {
+    pub mode: GameModeKind,
+    pub phase: MatchPhase,
+    /// Seconds left in the current phase
+    pub time_remaining: f32,
+    /// Team scores, indexed by team ID
+    pub scores: [u32; TEAM_COUNT],
+    /// Matches played since the server started (1-based)
+    pub round: u32,
+}
+
+impl MatchState {
+    /// A fresh match in warmup
+    pub fn new(mode: GameModeKind, rules: &MatchRules) -> Self {
+        Self {
+            mode,
+            phase: MatchPhase::Warmup,
+            time_remaining: rules.warmup_time,
+            scores: [0; TEAM_COUNT],
+            round: 1,
+        }
+    }
+
+    /// Whether kills currently score
+    pub fn is_live(&self) -> bool {
+        matches!(self.phase, MatchPhase::Live | MatchPhase::Overtime)
+    }
+
+    /// Credit a kill to `team`; returns false outside live play
+    pub fn record_kill(&mut self, team: Team) -> bool {
+        if !self.is_live() {
+            return false;
+        }
+        match self.scores.get_mut(team.0 as usize) {
+            Some(score) => {
+                *score += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Team strictly ahead on score, if any
+    pub fn leader(&self) -> Option<Team> {
+        let best = *self.scores.iter().max()?;
+        let mut leaders = self.scores.iter().enumerate().filter(|(_, score)| **score == best);
+        let (index, _) = leaders.next()?;
+        if leaders.next().is_some() {
+            None
+        } else {
+            Some(Team(index as u8))
+        }
+    }
+
+    /// End the match now (e.g. last team standing)
+    pub fn end(&mut self, rules: &MatchRules) {
+        if self.phase != MatchPhase::PostMatch {
+            self.enter(MatchPhase::PostMatch, rules);
+        }
+    }
+
+    fn enter(&mut self, phase: MatchPhase, rules: &MatchRules) {
+        match phase {
+            MatchPhase::Live => self.scores = [0; TEAM_COUNT],
+            MatchPhase::Warmup => self.round += 1,
+            MatchPhase::Overtime | MatchPhase::PostMatch => {}
+        }
+        self.phase = phase;
+        self.time_remaining = rules.phase_time(phase);
+    }
+
+    /// Advance the clock by `dt` and apply transitions; returns the phase entered, if any
+    pub fn tick(&mut self, dt: f32, rules: &MatchRules) -> Option<MatchPhase> {
+        self.time_remaining = (self.time_remaining - dt).max(0.0);
+        let expired = self.time_remaining <= 0.0;
+
+        let next = match self.phase {
+            MatchPhase::Warmup if expired => Some(MatchPhase::Live),
+            MatchPhase::Live if self.scores.iter().any(|s| *s >= rules.score_limit) => Some(MatchPhase::PostMatch),
+            MatchPhase::Live if expired => {
+                Some(if self.leader().is_some() { MatchPhase::PostMatch } else { MatchPhase::Overtime })
+            }
+            MatchPhase::Overtime if expired || self.leader().is_some() => Some(MatchPhase::PostMatch),
+            MatchPhase::PostMatch if expired => Some(MatchPhase::Warmup),
+            _ => None,
+        }?;
+
+        self.enter(next, rules);
+        Some(next)
+    }
+}
+
+/// One player's line on the end-of-match scoreboard
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ScoreboardRow {
+    pub name: String,
+    pub team: Option<Team>,
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+/// Server -> all clients: final results when a match ends
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct MatchScoreboard {
+    pub round: u32,
+    /// None for a draw
+    pub winner: Option<Team>,
+    pub scores: [u32; TEAM_COUNT],
+    /// Most kills first (fewest deaths breaks ties)
+    pub rows: Vec<ScoreboardRow>,
+}
+
+/// Sort scoreboard rows: most kills, then fewest deaths, then name
+pub fn sort_scoreboard(rows: &mut [ScoreboardRow]) {
+    rows.sort_by(|a, b| {
+        b.kills
+            .cmp(&a.kills)
+            .then(a.deaths.cmp(&b.deaths))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Center (XZ) of a team's base: either side of the map spawn along X
+pub fn team_base(team: Team) -> Vec2 {
+    let side = if team.0.is_multiple_of(2) { -1.0 } else { 1.0 };
+    Vec2::new(SPAWN_POSITION[0] + side * TDM_BASE_OFFSET, SPAWN_POSITION[2])
+}
+
+/// Spawn points (XZ) ringed around a team's base
+pub fn team_spawn_points(team: Team) -> Vec<Vec2> {
+    let base = team_base(team);
+    (0..TDM_SPAWN_POINTS)
+        .map(|i| {
+            let angle = i as f32 / TDM_SPAWN_POINTS as f32 * std::f32::consts::TAU;
+            base + Vec2::new(angle.cos(), angle.sin()) * TDM_SPAWN_RING_RADIUS
+        })
+        .collect()
+}
+
+/// The candidate furthest from its nearest enemy (the first one if there are no enemies)
+pub fn pick_spawn_point(candidates: &[Vec2], enemies: &[Vec2]) -> Option<Vec2> {
+    let nearest_enemy = |point: Vec2| {
+        enemies
+            .iter()
+            .map(|enemy| enemy.distance_squared(point))
+            .fold(f32::INFINITY, f32::min)
+    };
+    candidates
+        .iter()
+        .copied()
+        .fold(None, |best: Option<(Vec2, f32)>, point| {
+            let clearance = nearest_enemy(point);
+            match best {
+                Some((_, best_clearance)) if best_clearance >= clearance => best,
+                _ => Some((point, clearance)),
+            }
+        })
+        .map(|(point, _)| point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: MatchRules = MatchRules {
+        score_limit: 3,
+        time_limit: 10.0,
+        warmup_time: 2.0,
+        overtime_time: 5.0,
+        post_match_time: 1.0,
+    };
+
+    #[test]
+    fn test_match_lifecycle_to_score_limit() {
+        let mut state = MatchState::new(GameModeKind::TeamDeathmatch, &RULES);
+        assert!(!state.record_kill(Team::RED));
+        assert_eq!(state.tick(1.0, &RULES), None);
+        assert_eq!(state.tick(1.0, &RULES), Some(MatchPhase::Live));
+        assert_eq!(state.scores, [0, 0]);
+
+        for _ in 0..3 {
+            assert!(state.record_kill(Team::BLUE));
+        }
+        assert_eq!(state.tick(0.1, &RULES), Some(MatchPhase::PostMatch));
+        assert_eq!(state.leader(), Some(Team::BLUE));
+        assert_eq!(state.tick(1.0, &RULES), Some(MatchPhase::Warmup));
+        assert_eq!(state.round, 2);
+        assert_eq!(state.time_remaining, RULES.warmup_time);
+    }
+
+    #[test]
+    fn test_tied_time_limit_goes_to_overtime() {
+        let mut state = MatchState::new(GameModeKind::TeamDeathmatch, &RULES);
+        state.tick(RULES.warmup_time, &RULES);
+        state.record_kill(Team::RED);
+        state.record_kill(Team::BLUE);
+        assert_eq!(state.tick(RULES.time_limit, &RULES), Some(MatchPhase::Overtime));
+        assert_eq!(state.tick(1.0, &RULES), None);
+        // Sudden death
+        state.record_kill(Team::RED);
+        assert_eq!(state.tick(0.1, &RULES), Some(MatchPhase::PostMatch));
+        assert_eq!(state.leader(), Some(Team::RED));
+        assert_eq!("tdm".parse::<GameModeKind>(), Ok(GameModeKind::TeamDeathmatch));
+    }
+
+    #[test]
+    fn test_spawn_point_avoids_enemies() {
+        let candidates = [Vec2::new(0.0, 0.0), Vec2::new(50.0, 0.0), Vec2::new(20.0, 0.0)];
+        assert_eq!(pick_spawn_point(&candidates, &[]), Some(candidates[0]));
+        assert_eq!(pick_spawn_point(&candidates, &[Vec2::new(5.0, 0.0)]), Some(candidates[1]));
+        assert_eq!(pick_spawn_point(&[], &[Vec2::ZERO]), None);
+        assert_eq!(team_spawn_points(Team::BLUE).len(), TDM_SPAWN_POINTS);
+        assert!(team_spawn_points(Team::RED)[0].x < team_spawn_points(Team::BLUE)[0].x);
+    }
+}
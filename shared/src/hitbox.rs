@@ -0,0 +1,433 @@
+//! Per-limb hitbox rig shared by players and NPCs.
+//!
+//! A humanoid is represented by oriented capsules (head, torso, pelvis, upper/lower
+//! arms and legs). The rig is posed purely from replicated state (center position,
+//! yaw, movement stance), so the server's hit detection and the client's debug
+//! gizmos produce identical shapes. NPC stances come from server-side AI state, so
+//! the server replicates them as `NpcHitboxStance`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::PLAYER_HEIGHT;
+use crate::weapons::damage::HitZone;
+
+// =============================================================================
+// RIG DIMENSIONS
+// =============================================================================
+
+/// Extra radius added to every capsule to compensate for network/animation mismatch.
+pub const HITBOX_PADDING: f32 = 0.02;
+
+/// Distance (meters) covered by one full gait cycle (two steps).
+const STRIDE_LENGTH: f32 = 1.6;
+
+const HEAD_RADIUS: f32 = 0.13;
+const TORSO_RADIUS: f32 = 0.19;
+const PELVIS_RADIUS: f32 = 0.17;
+const UPPER_ARM_RADIUS: f32 = 0.06;
+const LOWER_ARM_RADIUS: f32 = 0.055;
+const UPPER_LEG_RADIUS: f32 = 0.085;
+const LOWER_LEG_RADIUS: f32 = 0.07;
+
+const UPPER_ARM_LENGTH: f32 = 0.28;
+const LOWER_ARM_LENGTH: f32 = 0.26;
+const UPPER_LEG_LENGTH: f32 = 0.42;
+const LOWER_LEG_LENGTH: f32 = 0.40;
+
+/// Shoulder joint offset from the capsule center (local space, +X = right).
+const SHOULDER: Vec3 = Vec3::new(0.24, 0.5, 0.0);
+/// Hip joint offset from the capsule center (local space, +X = right).
+const HIP: Vec3 = Vec3::new(0.1, -0.07, 0.0);
+
+// =============================================================================
+// POSE
+// =============================================================================
+
+/// Coarse movement stance used to pose the rig
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HitboxStance {
+    #[default]
+    Idle,
+    Walk,
+    Run,
+    Airborne,
+}
+
+impl HitboxStance {
+    /// Pick a stance from horizontal speed (m/s) and grounded state.
+    ///
+    /// Thresholds match the client's idle/walk/run animation selection.
+    pub fn from_movement(speed_xz: f32, grounded: bool) -> Self {
+        if !grounded {
+            HitboxStance::Airborne
+        } else if speed_xz > 4.5 {
+            HitboxStance::Run
+        } else if speed_xz > 0.15 {
+            HitboxStance::Walk
+        } else {
+            HitboxStance::Idle
+        }
+    }
+
+    /// (arm swing, leg swing, forward lean) in radians
+    fn swing(&self) -> (f32, f32, f32) {
+        match self {
+            HitboxStance::Idle => (0.0, 0.0, 0.0),
+            HitboxStance::Walk => (0.35, 0.4, 0.05),
+            HitboxStance::Run => (0.7, 0.7, 0.18),
+            HitboxStance::Airborne => (0.2, 0.0, 0.0),
+        }
+    }
+}
+
+/// Replicated: how the server poses an NPC's rig (its AI state stays on the server)
+#[derive(Component, Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct NpcHitboxStance {
+    pub stance: HitboxStance,
+    pub armed: bool,
+}
+
+/// Everything needed to pose a rig
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct HitboxPose {
+    /// Capsule center (same as `PlayerPosition` / `NpcPosition`)
+    pub center: Vec3,
+    /// Yaw in radians (same convention as `PlayerRotation` / `NpcRotation`)
+    pub yaw: f32,
+    pub stance: HitboxStance,
+    /// Holding a weapon up (arms forward) instead of swinging them
+    pub armed: bool,
+}
+
+impl HitboxPose {
+    /// Gait phase (radians) derived from position along the facing direction.
+    ///
+    /// Using position instead of time keeps the phase identical on server and client.
+    fn gait_phase(&self) -> f32 {
+        let forward = Vec2::new(-self.yaw.sin(), -self.yaw.cos());
+        let travelled = Vec2::new(self.center.x, self.center.z).dot(forward);
+        travelled / STRIDE_LENGTH * std::f32::consts::TAU
+    }
+}
+
+// =============================================================================
+// BONES
+// =============================================================================
+
+/// Body part a hit capsule belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HitboxBone {
+    Head,
+    Torso,
+    Pelvis,
+    UpperArmLeft,
+    LowerArmLeft,
+    UpperArmRight,
+    LowerArmRight,
+    UpperLegLeft,
+    LowerLegLeft,
+    UpperLegRight,
+    LowerLegRight,
+}
+
+impl HitboxBone {
+    /// Damage zone for hits on this bone
+    pub fn hit_zone(&self) -> HitZone {
+        match self {
+            HitboxBone::Head => HitZone::Head,
+            HitboxBone::Torso => HitZone::Chest,
+            HitboxBone::Pelvis => HitZone::Stomach,
+            HitboxBone::UpperArmLeft
+            | HitboxBone::LowerArmLeft
+            | HitboxBone::UpperArmRight
+            | HitboxBone::LowerArmRight => HitZone::Arms,
+            HitboxBone::UpperLegLeft
+            | HitboxBone::LowerLegLeft
+            | HitboxBone::UpperLegRight
+            | HitboxBone::LowerLegRight => HitZone::Legs,
+        }
+    }
+}
+
+/// A single capsule in world space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HitCapsule {
+    pub bone: HitboxBone,
+    pub a: Vec3,
+    pub b: Vec3,
+    pub radius: f32,
+}
+
+impl HitCapsule {
+    /// Closest point on the capsule axis to `p`
+    pub fn closest_axis_point(&self, p: Vec3) -> Vec3 {
+        let ab = self.b - self.a;
+        let len_sq = ab.length_squared();
+        if len_sq < 1e-8 {
+            return self.a;
+        }
+        let t = ((p - self.a).dot(ab) / len_sq).clamp(0.0, 1.0);
+        self.a + ab * t
+    }
+
+    /// Ray vs capsule. `ray_dir` must be normalized.
+    /// Returns distance along the ray of the entry point (0.0 if the origin is inside).
+    pub fn ray_intersect(&self, ray_origin: Vec3, ray_dir: Vec3, max_dist: f32) -> Option<f32> {
+        let r = self.radius + HITBOX_PADDING;
+
+        // Origin inside the capsule counts as an immediate hit
+        if (ray_origin - self.closest_axis_point(ray_origin)).length_squared() <= r * r {
+            return Some(0.0);
+        }
+
+        let ba = self.b - self.a;
+        let oa = ray_origin - self.a;
+        let baba = ba.dot(ba);
+        let bard = ba.dot(ray_dir);
+        let baoa = ba.dot(oa);
+        let rdoa = ray_dir.dot(oa);
+        let oaoa = oa.dot(oa);
+
+        let mut hit_t: Option<f32> = None;
+
+        // Cylinder body
+        let a = baba - bard * bard;
+        if a > 1e-8 {
+            let b = baba * rdoa - baoa * bard;
+            let c = baba * oaoa - baoa * baoa - r * r * baba;
+            let h = b * b - a * c;
+            if h >= 0.0 {
+                let t = (-b - h.sqrt()) / a;
+                let y = baoa + t * bard;
+                if y > 0.0 && y < baba {
+                    hit_t = Some(t);
+                }
+            }
+        }
+
+        // End caps
+        if hit_t.is_none() {
+            for cap in [self.a, self.b] {
+                let oc = ray_origin - cap;
+                let b = ray_dir.dot(oc);
+                let c = oc.dot(oc) - r * r;
+                let h = b * b - c;
+                if h >= 0.0 {
+                    let t = -b - h.sqrt();
+                    if hit_t.is_none_or(|best| t < best) {
+                        hit_t = Some(t);
+                    }
+                }
+            }
+        }
+
+        hit_t.filter(|t| *t >= 0.0 && *t <= max_dist)
+    }
+}
+
+/// Result of a ray test against a rig
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RigHit {
+    pub bone: HitboxBone,
+    pub hit_zone: HitZone,
+    /// Distance along the ray
+    pub t: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+// =============================================================================
+// RIG
+// =============================================================================
+
+/// Posed set of hit capsules for one humanoid
+#[derive(Clone, Debug, PartialEq)]
+pub struct HitboxRig {
+    pub capsules: [HitCapsule; 11],
+}
+
+impl HitboxRig {
+    /// Build the rig for a pose.
+    pub fn posed(pose: &HitboxPose) -> Self {
+        let rot = Quat::from_rotation_y(pose.yaw);
+        let to_world = |local: Vec3| pose.center + rot * local;
+        // Local space: -Z forward, +X right, +Y up. Pitching a limb by +angle swings it forward.
+        let limb_dir = |pitch: f32| Vec3::new(0.0, -pitch.cos(), -pitch.sin());
+
+        let (arm_swing, leg_swing, lean) = pose.stance.swing();
+        let phase = pose.gait_phase();
+        let s = phase.sin();
+
+        // Upper body leans forward around the hips when moving fast
+        let lean_offset = |local: Vec3| {
+            let above_hips = (local.y - HIP.y).max(0.0);
+            local + Vec3::new(0.0, 0.0, -above_hips * lean.sin())
+        };
+
+        let head = lean_offset(Vec3::new(0.0, PLAYER_HEIGHT * 0.5 - HEAD_RADIUS - 0.02, 0.0));
+        let neck = lean_offset(Vec3::new(0.0, 0.6, 0.0));
+        let chest_top = lean_offset(Vec3::new(0.0, 0.45, 0.0));
+        let chest_bottom = lean_offset(Vec3::new(0.0, 0.22, 0.0));
+        let pelvis_top = Vec3::new(0.0, 0.12, 0.0);
+        let pelvis_bottom = Vec3::new(0.0, -0.03, 0.0);
+
+        let capsule = |bone, a: Vec3, b: Vec3, radius| HitCapsule {
+            bone,
+            a: to_world(a),
+            b: to_world(b),
+            radius,
+        };
+
+        // --- Arms ---
+        let arm = |side: f32, upper: HitboxBone, lower: HitboxBone| {
+            let shoulder = lean_offset(Vec3::new(SHOULDER.x * side, SHOULDER.y, SHOULDER.z));
+            let (upper_dir, lower_dir) = if pose.armed {
+                // Weapon held at the chest: upper arms angled down/forward, forearms
+                // converging toward the centerline.
+                let upper_dir = Vec3::new(-0.15 * side, -0.55, -0.8).normalize();
+                let lower_dir = Vec3::new(-0.55 * side, 0.1, -0.83).normalize();
+                (upper_dir, lower_dir)
+            } else {
+                // Arms swing opposite to the leg on the same side
+                let swing = -side * s * arm_swing;
+                let flare = if pose.stance == HitboxStance::Airborne { 0.35 } else { 0.08 };
+                let upper_dir = (limb_dir(swing) + Vec3::X * side * flare).normalize();
+                let lower_dir = limb_dir(swing + 0.25 + arm_swing * 0.5);
+                (upper_dir, lower_dir)
+            };
+            let elbow = shoulder + upper_dir * UPPER_ARM_LENGTH;
+            let wrist = elbow + lower_dir * LOWER_ARM_LENGTH;
+            [
+                capsule(upper, shoulder, elbow, UPPER_ARM_RADIUS),
+                capsule(lower, elbow, wrist, LOWER_ARM_RADIUS),
+            ]
+        };
+
+        // --- Legs ---
+        let leg = |side: f32, upper: HitboxBone, lower: HitboxBone| {
+            let hip = Vec3::new(HIP.x * side, HIP.y, HIP.z);
+            let (thigh_pitch, knee_bend) = if pose.stance == HitboxStance::Airborne {
+                (0.5, 0.9)
+            } else {
+                let swing = side * s * leg_swing;
+                // Knee bends on the back-swing / passing phase
+                let bend = (-side * (phase + 0.5).sin()).max(0.0) * leg_swing * 1.2;
+                (swing, bend)
+            };
+            let knee = hip + limb_dir(thigh_pitch) * UPPER_LEG_LENGTH;
+            let ankle = knee + limb_dir(thigh_pitch - knee_bend) * LOWER_LEG_LENGTH;
+            [
+                capsule(upper, hip, knee, UPPER_LEG_RADIUS),
+                capsule(lower, knee, ankle, LOWER_LEG_RADIUS),
+            ]
+        };
+
+        let [upper_arm_l, lower_arm_l] = arm(-1.0, HitboxBone::UpperArmLeft, HitboxBone::LowerArmLeft);
+        let [upper_arm_r, lower_arm_r] = arm(1.0, HitboxBone::UpperArmRight, HitboxBone::LowerArmRight);
+        let [upper_leg_l, lower_leg_l] = leg(-1.0, HitboxBone::UpperLegLeft, HitboxBone::LowerLegLeft);
+        let [upper_leg_r, lower_leg_r] = leg(1.0, HitboxBone::UpperLegRight, HitboxBone::LowerLegRight);
+
+        Self {
+            capsules: [
+                capsule(HitboxBone::Head, neck.lerp(head, 0.7), head, HEAD_RADIUS),
+                capsule(HitboxBone::Torso, chest_bottom, chest_top, TORSO_RADIUS),
+                capsule(HitboxBone::Pelvis, pelvis_bottom, pelvis_top, PELVIS_RADIUS),
+                upper_arm_l,
+                lower_arm_l,
+                upper_arm_r,
+                lower_arm_r,
+                upper_leg_l,
+                lower_leg_l,
+                upper_leg_r,
+                lower_leg_r,
+            ],
+        }
+    }
+
+    /// Conservative bounding sphere (center, radius) for cheap rejection
+    pub fn bounds(&self) -> (Vec3, f32) {
+        let center = self.capsules[1].a.lerp(self.capsules[2].a, 0.5);
+        (center, PLAYER_HEIGHT * 0.75)
+    }
+
+    /// Closest capsule hit along a ray. `ray_dir` must be normalized.
+    pub fn raycast(&self, ray_origin: Vec3, ray_dir: Vec3, max_dist: f32) -> Option<RigHit> {
+        let (center, radius) = self.bounds();
+        let to_center = center - ray_origin;
+        let t = to_center.dot(ray_dir).clamp(0.0, max_dist);
+        if (ray_origin + ray_dir * t - center).length_squared() > radius * radius {
+            return None;
+        }
+
+        let mut best: Option<(f32, &HitCapsule)> = None;
+        for capsule in &self.capsules {
+            if let Some(t) = capsule.ray_intersect(ray_origin, ray_dir, max_dist) {
+                if best.is_none_or(|(best_t, _)| t < best_t) {
+                    best = Some((t, capsule));
+                }
+            }
+        }
+
+        best.map(|(t, capsule)| {
+            let point = ray_origin + ray_dir * t;
+            RigHit {
+                bone: capsule.bone,
+                hit_zone: capsule.bone.hit_zone(),
+                t,
+                point,
+                normal: (point - capsule.closest_axis_point(point)).normalize_or_zero(),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idle_rig(armed: bool) -> HitboxRig {
+        HitboxRig::posed(&HitboxPose {
+            center: Vec3::new(0.0, PLAYER_HEIGHT * 0.5, 0.0),
+            yaw: 0.0,
+            stance: HitboxStance::Idle,
+            armed,
+        })
+    }
+
+    #[test]
+    fn test_zones_by_height() {
+        let rig = idle_rig(false);
+        // Shoot along -Z through the centerline at several heights
+        let shoot = |y: f32| rig.raycast(Vec3::new(0.0, y, 5.0), Vec3::NEG_Z, 10.0).map(|h| h.hit_zone);
+
+        assert_eq!(shoot(1.7), Some(HitZone::Head));
+        assert_eq!(shoot(1.25), Some(HitZone::Chest));
+        assert_eq!(shoot(0.95), Some(HitZone::Stomach));
+        assert_eq!(shoot(2.2), None);
+    }
+
+    #[test]
+    fn test_arms_are_reachable() {
+        let rig = idle_rig(false);
+        // From the side at chest height the hanging arm shields the torso
+        let hit = rig.raycast(Vec3::new(5.0, 1.25, 0.0), Vec3::NEG_X, 10.0).unwrap();
+        assert_eq!(hit.hit_zone, HitZone::Arms);
+        assert!(hit.point.x > 0.2);
+
+        // Same for a weapon-holding pose shot from the front, off-center
+        let rig = idle_rig(true);
+        let hit = rig.raycast(Vec3::new(0.26, 1.3, -5.0), Vec3::Z, 10.0).unwrap();
+        assert_eq!(hit.hit_zone, HitZone::Arms);
+    }
+
+    #[test]
+    fn test_gap_between_legs() {
+        let rig = idle_rig(false);
+        // Straight down the middle at shin height passes between the legs
+        assert!(rig.raycast(Vec3::new(0.0, 0.25, 5.0), Vec3::NEG_Z, 10.0).is_none());
+        // Offset onto a leg hits it
+        let hit = rig.raycast(Vec3::new(0.1, 0.25, 5.0), Vec3::NEG_Z, 10.0).unwrap();
+        assert_eq!(hit.hit_zone, HitZone::Legs);
+    }
+}
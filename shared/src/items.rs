@@ -4,7 +4,8 @@
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::weapons::WeaponType;
+use crate::weapons::{ExplosiveType, WeaponType};
+use crate::armor::ArmorType;
 
 // =============================================================================
 // ITEM TYPES
@@ -24,6 +25,12 @@ pub enum ItemType {
     Wood,
     // Weapons (non-stackable)
     Weapon(WeaponType),
+    // Wearable armor (non-stackable)
+    Armor(ArmorType),
+    // Throwables / placeables
+    Explosive(ExplosiveType),
+    // Currency, traded with vendor NPCs (appended last: saved profiles store the variant index)
+    Coins,
 }
 
 impl ItemType {
@@ -38,8 +45,12 @@ impl ItemType {
             // Resources stack to 100
             ItemType::Stone => 100,
             ItemType::Wood => 100,
-            // Weapons are non-stackable
+            // Weapons and armor are non-stackable
             ItemType::Weapon(_) => 1,
+            ItemType::Armor(_) => 1,
+            // Explosives stack to 5
+            ItemType::Explosive(_) => 5,
+            ItemType::Coins => 500,
         }
     }
 
@@ -59,7 +70,13 @@ impl ItemType {
                 WeaponType::Sniper => "Sniper Rifle",
                 WeaponType::Shotgun => "Shotgun",
                 WeaponType::SMG => "SMG",
+                WeaponType::Knife => "Knife",
+                WeaponType::Axe => "Axe",
+                WeaponType::Sledgehammer => "Sledgehammer",
             },
+            ItemType::Armor(a) => a.display_name(),
+            ItemType::Explosive(e) => e.display_name(),
+            ItemType::Coins => "Coins",
         }
     }
 
@@ -79,7 +96,19 @@ impl ItemType {
                 WeaponType::SMG => Color::srgb(0.35, 0.6, 0.75),
                 WeaponType::Shotgun => Color::srgb(0.8, 0.55, 0.25),
                 WeaponType::Sniper => Color::srgb(0.75, 0.25, 0.55),
+                WeaponType::Knife => Color::srgb(0.7, 0.72, 0.75),
+                WeaponType::Axe => Color::srgb(0.55, 0.35, 0.2),
+                WeaponType::Sledgehammer => Color::srgb(0.4, 0.4, 0.42),
             },
+            ItemType::Armor(a) => match a {
+                ArmorType::Vest => Color::srgb(0.35, 0.4, 0.3),     // Olive drab
+                ArmorType::Helmet => Color::srgb(0.3, 0.35, 0.28),
+            },
+            ItemType::Explosive(e) => match e {
+                ExplosiveType::Grenade => Color::srgb(0.3, 0.4, 0.25),
+                ExplosiveType::Charge => Color::srgb(0.85, 0.3, 0.1),
+            },
+            ItemType::Coins => Color::srgb(0.95, 0.78, 0.25),          // Gold
         }
     }
     
@@ -90,6 +119,14 @@ impl ItemType {
             _ => None,
         }
     }
+
+    /// If this item is armor, return its ArmorType
+    pub fn as_armor_type(&self) -> Option<ArmorType> {
+        match self {
+            ItemType::Armor(a) => Some(*a),
+            _ => None,
+        }
+    }
 }
 
 // =============================================================================
@@ -98,6 +135,7 @@ impl ItemType {
 
 /// A stack of items (type + quantity)
 /// For weapon items, also tracks the ammo currently loaded in the magazine.
+/// For armor items, also tracks remaining durability.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ItemStack {
     pub item_type: ItemType,
@@ -105,11 +143,14 @@ pub struct ItemStack {
     /// For weapon items only: ammo currently in the magazine.
     /// None means the weapon has a full magazine (or this isn't a weapon).
     pub ammo_in_mag: Option<u32>,
+    /// For armor items only: remaining durability.
+    /// None means the armor is brand new (or this isn't armor).
+    pub durability: Option<f32>,
 }
 
 impl ItemStack {
     pub fn new(item_type: ItemType, quantity: u32) -> Self {
-        Self { item_type, quantity, ammo_in_mag: None }
+        Self { item_type, quantity, ammo_in_mag: None, durability: None }
     }
     
     /// Create a weapon item with specified magazine ammo
@@ -118,6 +159,26 @@ impl ItemStack {
             item_type: ItemType::Weapon(weapon_type),
             quantity: 1,
             ammo_in_mag: Some(ammo_in_mag),
+            durability: None,
+        }
+    }
+    
+    /// Create an armor item with specified durability
+    pub fn new_armor(armor_type: ArmorType, durability: f32) -> Self {
+        Self {
+            item_type: ItemType::Armor(armor_type),
+            quantity: 1,
+            ammo_in_mag: None,
+            durability: Some(durability),
+        }
+    }
+    
+    /// Get the durability of an armor item (returns max durability if not set)
+    pub fn get_armor_durability(&self) -> f32 {
+        if let ItemType::Armor(a) = self.item_type {
+            self.durability.unwrap_or_else(|| a.max_durability())
+        } else {
+            0.0
         }
     }
     
@@ -315,6 +376,7 @@ impl Inventory {
                     item_type: remaining.item_type,
                     quantity: stack_amount,
                     ammo_in_mag: remaining.ammo_in_mag, // Preserve ammo for first stack
+                    durability: remaining.durability,
                 });
                 remaining.quantity -= stack_amount;
                 remaining.ammo_in_mag = None; // Only first stack gets ammo
@@ -465,19 +527,22 @@ pub struct GroundItem {
     pub quantity: u32,
     /// For weapon items: ammo currently in the magazine (None = empty mag, needs reload)
     pub ammo_in_mag: Option<u32>,
+    /// For armor items: remaining durability (None = brand new)
+    pub durability: Option<f32>,
 }
 
 impl GroundItem {
     pub fn new(item_type: ItemType, quantity: u32) -> Self {
-        Self { item_type, quantity, ammo_in_mag: None }
+        Self { item_type, quantity, ammo_in_mag: None, durability: None }
     }
     
-    /// Create a ground item from an ItemStack (preserves weapon ammo)
+    /// Create a ground item from an ItemStack (preserves weapon ammo and armor durability)
     pub fn from_stack(stack: &ItemStack) -> Self {
         Self {
             item_type: stack.item_type,
             quantity: stack.quantity,
             ammo_in_mag: stack.ammo_in_mag,
+            durability: stack.durability,
         }
     }
     
@@ -487,6 +552,7 @@ impl GroundItem {
             item_type: ItemType::Weapon(weapon_type),
             quantity: 1,
             ammo_in_mag: Some(ammo_in_mag),
+            durability: None,
         }
     }
     
@@ -496,6 +562,7 @@ impl GroundItem {
             item_type: self.item_type,
             quantity: self.quantity,
             ammo_in_mag: self.ammo_in_mag,
+            durability: self.durability,
         }
     }
 }
@@ -575,6 +642,29 @@ impl ChestStorage {
         self.slots.iter().position(|slot| slot.is_none())
     }
     
+    /// Add items (topping up existing stacks first), returns amount that couldn't fit
+    pub fn add_item(&mut self, item_type: ItemType, mut quantity: u32) -> u32 {
+        for stack in self.slots.iter_mut().flatten() {
+            if quantity == 0 {
+                break;
+            }
+            if stack.item_type == item_type && stack.quantity < item_type.max_stack_size() {
+                quantity = stack.add(quantity);
+            }
+        }
+
+        while quantity > 0 {
+            let Some(slot_idx) = self.find_empty_slot() else {
+                break;
+            };
+            let stack_amount = quantity.min(item_type.max_stack_size());
+            self.slots[slot_idx] = Some(ItemStack::new(item_type, stack_amount));
+            quantity -= stack_amount;
+        }
+
+        quantity
+    }
+    
     /// Check if chest is empty
     pub fn is_empty(&self) -> bool {
         self.slots.iter().all(|s| s.is_none())
@@ -686,6 +776,18 @@ mod tests {
         assert_eq!(inv.count_item(ItemType::Stone), 0);
     }
 
+    #[test]
+    fn test_chest_add_item_tops_up_then_fills() {
+        let mut chest = ChestStorage::with_items(vec![ItemStack::new(ItemType::Wood, 80)]);
+        assert_eq!(chest.add_item(ItemType::Wood, 50), 0);
+        assert_eq!(chest.get_slot(0).map(|s| s.quantity), Some(100));
+        assert_eq!(chest.get_slot(1).map(|s| s.quantity), Some(30));
+
+        // Six slots of 100 hold 600; the rest overflows
+        assert_eq!(chest.add_item(ItemType::Wood, 600), 130);
+        assert!(chest.find_empty_slot().is_none());
+    }
+
     #[test]
     fn test_starting_items() {
         let inv = Inventory::with_starting_items();
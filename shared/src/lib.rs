@@ -1,31 +1,59 @@
+pub mod armor;
+pub mod behaviour;
 pub mod building;
+pub mod combat_log;
 pub mod components;
+pub mod crowd;
+pub mod dialogue;
+pub mod game_mode;
 pub mod colliders;
+pub mod hitbox;
 pub mod items;
+pub mod loot;
 pub mod npc;
+pub mod perception;
 pub mod physics;
 pub mod player;
 pub mod player_profile;
+pub mod population;
 pub mod protocol;
 pub mod props;
 pub mod spatial;
 pub mod structures;
+pub mod team;
 pub mod terrain;
+pub mod territory;
+pub mod trade;
 pub mod vehicle;
 pub mod weapons;
+pub mod zone;
 
+pub use armor::*;
+pub use behaviour::*;
 pub use building::*;
+pub use combat_log::*;
 pub use components::*;
+pub use crowd::*;
+pub use dialogue::*;
+pub use game_mode::*;
 pub use colliders::*;
+pub use hitbox::*;
 pub use items::*;
+pub use loot::*;
 pub use npc::*;
+pub use perception::*;
 pub use physics::*;
 pub use player::*;
 pub use player_profile::*;
+pub use population::*;
 pub use protocol::*;
 pub use props::*;
 pub use spatial::*;
 pub use structures::*;
+pub use team::*;
 pub use terrain::*;
+pub use territory::*;
+pub use trade::*;
 pub use vehicle::*;
-pub use weapons::*;
\ No newline at end of file
+pub use weapons::*;
+pub use zone::*;
\ No newline at end of file
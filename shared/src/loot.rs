@@ -0,0 +1,178 @@
+//! NPC loot: per-archetype loot tables rolled when an NPC dies
+//!
+//! The roll is seeded from the NPC's ID, so the same NPC always carries the same
+//! loot. The server puts it in a `ChestStorage` on the body (`LootableCorpse`), looted
+//! through the usual chest UI, and it goes when the body despawns.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::armor::ArmorType;
+use crate::components::NpcArchetype;
+use crate::items::{ItemStack, ItemType};
+use crate::population::splitmix64;
+use crate::weapons::{ExplosiveType, WeaponType};
+
+/// Marker: a dead NPC whose `ChestStorage` holds its loot (drawn as the body, not a chest)
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub struct LootableCorpse;
+
+/// What an archetype may drop
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LootTable {
+    /// Its weapon and the chance it survives the fight
+    pub weapon: Option<(WeaponType, f32)>,
+    /// Ammo carried: (ammo, min, max)
+    pub ammo: &'static [(ItemType, u32, u32)],
+    /// Resources carried: (resource, min, max)
+    pub resources: &'static [(ItemType, u32, u32)],
+    /// Rare finds: (item, chance)
+    pub rare: &'static [(ItemType, f32)],
+}
+
+/// Loot table for an NPC archetype
+pub fn npc_loot_table(archetype: NpcArchetype) -> LootTable {
+    match archetype {
+        NpcArchetype::Barbarian => LootTable {
+            weapon: Some((WeaponType::Axe, 0.35)),
+            ammo: &[],
+            resources: &[(ItemType::Wood, 5, 20), (ItemType::Stone, 0, 10), (ItemType::Coins, 0, 15)],
+            rare: &[(ItemType::Explosive(ExplosiveType::Grenade), 0.05), (ItemType::Armor(ArmorType::Helmet), 0.05)],
+        },
+        NpcArchetype::Knight => LootTable {
+            weapon: Some((WeaponType::Sledgehammer, 0.5)),
+            ammo: &[],
+            resources: &[(ItemType::Stone, 10, 25), (ItemType::Coins, 10, 40)],
+            rare: &[(ItemType::Armor(ArmorType::Vest), 0.25), (ItemType::Armor(ArmorType::Helmet), 0.2)],
+        },
+        NpcArchetype::Ranger => LootTable {
+            weapon: Some((WeaponType::AssaultRifle, 0.3)),
+            ammo: &[(ItemType::RifleAmmo, 15, 45)],
+            resources: &[(ItemType::Coins, 0, 20)],
+            rare: &[
+                (ItemType::Explosive(ExplosiveType::Grenade), 0.15),
+                (ItemType::Armor(ArmorType::Vest), 0.1),
+                (ItemType::Weapon(WeaponType::Sniper), 0.03),
+            ],
+        },
+        NpcArchetype::Mage => LootTable {
+            weapon: None,
+            ammo: &[(ItemType::PistolAmmo, 0, 10)],
+            resources: &[(ItemType::Coins, 5, 30)],
+            rare: &[
+                (ItemType::Explosive(ExplosiveType::Charge), 0.1),
+                (ItemType::Explosive(ExplosiveType::Grenade), 0.1),
+            ],
+        },
+        NpcArchetype::Rogue => LootTable {
+            weapon: Some((WeaponType::Knife, 0.4)),
+            ammo: &[(ItemType::PistolAmmo, 6, 18)],
+            resources: &[(ItemType::Coins, 5, 25)],
+            rare: &[(ItemType::Weapon(WeaponType::Pistol), 0.1)],
+        },
+        NpcArchetype::RogueHooded => LootTable {
+            weapon: Some((WeaponType::Knife, 0.2)),
+            ammo: &[(ItemType::PistolAmmo, 0, 12)],
+            resources: &[(ItemType::Wood, 0, 10), (ItemType::Coins, 0, 8)],
+            rare: &[(ItemType::Weapon(WeaponType::Pistol), 0.05)],
+        },
+    }
+}
+
+/// Counter-based stream of rolls for one NPC
+struct LootRng {
+    seed: u64,
+    counter: u64,
+}
+
+impl LootRng {
+    /// Uniform in [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        self.counter += 1;
+        (splitmix64(self.seed ^ self.counter.wrapping_mul(0xD1B5_4A32_D192_ED03)) >> 40) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Uniform in [min, max]
+    fn range(&mut self, min: u32, max: u32) -> u32 {
+        min + ((self.next_f32() * (max - min + 1) as f32) as u32).min(max - min)
+    }
+}
+
+/// Roll an NPC's loot. Same archetype and seed, same loot; weapons come part-loaded and
+/// armor part-worn.
+pub fn roll_npc_loot(archetype: NpcArchetype, seed: u64) -> Vec<ItemStack> {
+    let table = npc_loot_table(archetype);
+    let mut rng = LootRng { seed: splitmix64(seed), counter: 0 };
+    let mut loot = Vec::new();
+
+    if let Some((weapon, chance)) = table.weapon {
+        if rng.next_f32() < chance {
+            loot.push(weapon_stack(weapon, &mut rng));
+        }
+    }
+    for &(item, min, max) in table.ammo.iter().chain(table.resources) {
+        let quantity = rng.range(min, max);
+        if quantity > 0 {
+            loot.push(ItemStack::new(item, quantity));
+        }
+    }
+    for &(item, chance) in table.rare {
+        if rng.next_f32() >= chance {
+            continue;
+        }
+        loot.push(match item {
+            ItemType::Weapon(weapon) => weapon_stack(weapon, &mut rng),
+            ItemType::Armor(armor) => {
+                ItemStack::new_armor(armor, armor.max_durability() * (0.3 + 0.7 * rng.next_f32()))
+            }
+            item => ItemStack::new(item, 1),
+        });
+    }
+    loot
+}
+
+fn weapon_stack(weapon: WeaponType, rng: &mut LootRng) -> ItemStack {
+    let magazine = weapon.stats().magazine_size;
+    ItemStack::new_weapon(weapon, rng.range(0, magazine))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::CHEST_SLOTS;
+
+    #[test]
+    fn test_loot_is_deterministic_per_seed() {
+        for archetype in NpcArchetype::ALL {
+            for seed in 0..50 {
+                assert_eq!(roll_npc_loot(archetype, seed), roll_npc_loot(archetype, seed));
+            }
+        }
+        // Different NPCs don't all carry the same thing
+        let rolls: Vec<_> = (0..50).map(|seed| roll_npc_loot(NpcArchetype::Ranger, seed)).collect();
+        assert!(rolls.iter().any(|loot| loot != &rolls[0]));
+    }
+
+    #[test]
+    fn test_loot_respects_table() {
+        for archetype in NpcArchetype::ALL {
+            let table = npc_loot_table(archetype);
+            for seed in 0..200 {
+                let loot = roll_npc_loot(archetype, seed);
+                // Always fits a corpse container
+                assert!(loot.len() <= CHEST_SLOTS, "{archetype:?} rolled {} stacks", loot.len());
+                for &(item, min, max) in table.ammo.iter().chain(table.resources) {
+                    if let Some(stack) = loot.iter().find(|stack| stack.item_type == item) {
+                        assert!(stack.quantity >= min.max(1) && stack.quantity <= max);
+                    }
+                }
+                for stack in &loot {
+                    if let Some(ammo) = stack.ammo_in_mag {
+                        let weapon = stack.item_type.as_weapon_type().unwrap();
+                        assert!(ammo <= weapon.stats().magazine_size);
+                    }
+                }
+            }
+        }
+    }
+}
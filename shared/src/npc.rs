@@ -1,9 +1,10 @@
 //! NPC-related constants and helper utilities.
 
-use bevy::prelude::*;
+use std::str::FromStr;
 
 use crate::components::NpcArchetype;
 use crate::player::{PLAYER_HEIGHT, PLAYER_RADIUS};
+use crate::weapons::WeaponType;
 
 // =============================================================================
 // NPC GEOMETRY
@@ -15,29 +16,6 @@ pub const NPC_HEIGHT: f32 = PLAYER_HEIGHT;
 /// NPC capsule radius.
 pub const NPC_RADIUS: f32 = PLAYER_RADIUS;
 
-/// Head hitbox radius.
-pub const NPC_HEAD_RADIUS: f32 = 0.18;
-
-/// Returns the approximate head hitbox center for an upright NPC.
-///
-/// `npc_center` is the NPC's capsule *center* position.
-#[inline]
-pub fn npc_head_center(npc_center: Vec3) -> Vec3 {
-    // Slightly below the capsule top.
-    npc_center + Vec3::new(0.0, NPC_HEIGHT * 0.5 - NPC_HEAD_RADIUS, 0.0)
-}
-
-/// Returns endpoints (sphere centers) for an upright capsule representing the NPC body.
-///
-/// `npc_center` is the NPC's capsule *center* position.
-#[inline]
-pub fn npc_capsule_endpoints(npc_center: Vec3) -> (Vec3, Vec3) {
-    let half = NPC_HEIGHT * 0.5;
-    let a = npc_center + Vec3::new(0.0, -(half - NPC_RADIUS), 0.0);
-    let b = npc_center + Vec3::new(0.0, half - NPC_RADIUS, 0.0);
-    (a, b)
-}
-
 // =============================================================================
 // NPC MOVEMENT CONSTANTS
 // =============================================================================
@@ -79,3 +57,196 @@ pub fn npc_max_health(archetype: NpcArchetype) -> f32 {
     }
 }
 
+
+// =============================================================================
+// NPC COMBAT
+// =============================================================================
+
+/// Aim error never grows past this cone (radians).
+pub const NPC_MAX_AIM_ERROR: f32 = 0.15;
+
+/// Seconds an NPC keeps fighting a target it can't see before giving up.
+pub const NPC_LOST_SIGHT_TIME: f32 = 5.0;
+
+/// Chase speed as a multiple of the walk speed.
+pub const NPC_CHASE_SPEED_MULT: f32 = 1.5;
+
+/// Bullets fired by NPCs carry this bit in `Bullet::owner_id` (player IDs never use it).
+pub const NPC_OWNER_ID_FLAG: u64 = 1 << 63;
+
+/// How readily an NPC picks a fight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NpcAggression {
+    /// Never fights back; flees when hurt.
+    Passive,
+    /// Fights whoever hurt it.
+    Defensive,
+    /// Attacks any player that comes into sight.
+    Hostile,
+}
+
+impl FromStr for NpcAggression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "passive" => Ok(NpcAggression::Passive),
+            "defensive" => Ok(NpcAggression::Defensive),
+            "hostile" => Ok(NpcAggression::Hostile),
+            other => Err(format!("unknown NPC aggression '{}'", other)),
+        }
+    }
+}
+
+impl FromStr for NpcArchetype {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "barbarian" => Ok(NpcArchetype::Barbarian),
+            "ranger" => Ok(NpcArchetype::Ranger),
+            "mage" => Ok(NpcArchetype::Mage),
+            "knight" => Ok(NpcArchetype::Knight),
+            "rogue" => Ok(NpcArchetype::Rogue),
+            "roguehooded" | "rogue_hooded" => Ok(NpcArchetype::RogueHooded),
+            other => Err(format!("unknown NPC archetype '{}'", other)),
+        }
+    }
+}
+
+/// How an NPC deals damage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NpcAttack {
+    None,
+    /// Swings a melee weapon (uses its `MeleeStats`).
+    Melee(WeaponType),
+    /// Fires real bullets from a firearm (uses its `WeaponStats`).
+    Ranged(WeaponType),
+}
+
+/// Per-archetype combat tuning.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NpcCombatProfile {
+    pub aggression: NpcAggression,
+    pub attack: NpcAttack,
+    /// Hostile NPCs notice players this close (m).
+    pub sight_range: f32,
+    /// Ranged NPCs stop and shoot inside this distance (m); melee NPCs close to weapon reach.
+    pub attack_range: f32,
+    /// NPCs break off once the fight drags them this far from home (m).
+    pub leash_range: f32,
+    /// Seconds between shots / swings (on top of a melee weapon's recovery).
+    pub attack_interval: f32,
+    /// Aim error cone at point blank (radians).
+    pub aim_error_base: f32,
+    /// Extra aim error per meter to the target (radians).
+    pub aim_error_per_meter: f32,
+}
+
+/// Returns the default combat profile for an NPC archetype.
+pub fn npc_combat_profile(archetype: NpcArchetype) -> NpcCombatProfile {
+    let passive = NpcCombatProfile {
+        aggression: NpcAggression::Passive,
+        attack: NpcAttack::None,
+        sight_range: 0.0,
+        attack_range: 0.0,
+        leash_range: 0.0,
+        attack_interval: 0.0,
+        aim_error_base: 0.0,
+        aim_error_per_meter: 0.0,
+    };
+    match archetype {
+        NpcArchetype::Barbarian => NpcCombatProfile {
+            aggression: NpcAggression::Defensive,
+            attack: NpcAttack::Melee(WeaponType::Axe),
+            sight_range: 25.0,
+            leash_range: 40.0,
+            attack_interval: 0.6,
+            ..passive
+        },
+        NpcArchetype::Knight => NpcCombatProfile {
+            aggression: NpcAggression::Defensive,
+            attack: NpcAttack::Melee(WeaponType::Sledgehammer),
+            sight_range: 25.0,
+            leash_range: 30.0,
+            attack_interval: 0.8,
+            ..passive
+        },
+        NpcArchetype::Ranger => NpcCombatProfile {
+            aggression: NpcAggression::Hostile,
+            attack: NpcAttack::Ranged(WeaponType::AssaultRifle),
+            sight_range: 60.0,
+            attack_range: 45.0,
+            leash_range: 80.0,
+            attack_interval: 0.7,
+            aim_error_base: 0.01,
+            aim_error_per_meter: 0.0008,
+        },
+        NpcArchetype::Mage | NpcArchetype::Rogue | NpcArchetype::RogueHooded => passive,
+    }
+}
+
+/// Aim error cone (radians) for a shot at `distance` meters.
+pub fn npc_aim_error(profile: &NpcCombatProfile, distance: f32) -> f32 {
+    (profile.aim_error_base + profile.aim_error_per_meter * distance.max(0.0)).min(NPC_MAX_AIM_ERROR)
+}
+
+/// `Bullet::owner_id` for rounds fired by an NPC.
+#[inline]
+pub fn npc_owner_id(npc_id: u64) -> u64 {
+    npc_id | NPC_OWNER_ID_FLAG
+}
+
+/// Parses per-archetype aggression overrides, e.g. `"barbarian=hostile,ranger=passive"`.
+pub fn parse_aggression_overrides(s: &str) -> Result<Vec<(NpcArchetype, NpcAggression)>, String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| -> Result<(NpcArchetype, NpcAggression), String> {
+            let (archetype, aggression) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected archetype=aggression, got '{}'", entry))?;
+            Ok((archetype.parse()?, aggression.parse()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aim_error_grows_with_distance_and_caps() {
+        let profile = npc_combat_profile(NpcArchetype::Ranger);
+        let near = npc_aim_error(&profile, 5.0);
+        let far = npc_aim_error(&profile, 50.0);
+        assert!(far > near);
+        assert!((near - (0.01 + 0.0008 * 5.0)).abs() < 1e-6);
+        assert_eq!(npc_aim_error(&profile, 10_000.0), NPC_MAX_AIM_ERROR);
+    }
+
+    #[test]
+    fn test_profiles_match_archetypes() {
+        assert!(matches!(npc_combat_profile(NpcArchetype::Barbarian).attack, NpcAttack::Melee(_)));
+        assert!(matches!(npc_combat_profile(NpcArchetype::Knight).attack, NpcAttack::Melee(_)));
+        assert!(matches!(npc_combat_profile(NpcArchetype::Ranger).attack, NpcAttack::Ranged(_)));
+        let rogue = npc_combat_profile(NpcArchetype::RogueHooded);
+        assert_eq!(rogue.aggression, NpcAggression::Passive);
+        assert_eq!(rogue.attack, NpcAttack::None);
+        assert_ne!(npc_owner_id(7), 7);
+    }
+
+    #[test]
+    fn test_parse_aggression_overrides() {
+        let overrides = parse_aggression_overrides(" barbarian=Hostile, ranger=passive ,").unwrap();
+        assert_eq!(
+            overrides,
+            vec![
+                (NpcArchetype::Barbarian, NpcAggression::Hostile),
+                (NpcArchetype::Ranger, NpcAggression::Passive),
+            ]
+        );
+        assert!(parse_aggression_overrides("knight").is_err());
+        assert!(parse_aggression_overrides("dragon=hostile").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! NPC perception: sight cones, hearing and alert levels
+//!
+//! NPCs see players inside a view cone (plus a small all-round radius for anyone
+//! right next to them) and hear gunshots out to each weapon's loudness radius.
+//! Whatever they notice raises their `NpcAwareness`, which decays back to calm
+//! over time. The server does the line-of-sight checks and turns alarms into
+//! fleeing or fighting (`server::npc_perception`).
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::weapons::WeaponType;
+
+/// Half-angle of an NPC's view cone (radians, ~60°)
+pub const NPC_VIEW_HALF_ANGLE: f32 = 1.05;
+
+/// How far NPCs without a combat sight range see (m)
+pub const NPC_SIGHT_RANGE: f32 = 35.0;
+
+/// Anyone this close is noticed whichever way the NPC faces (m)
+pub const NPC_PERIPHERAL_RANGE: f32 = 3.0;
+
+/// A player counts as aiming at an NPC when facing within this angle of it (radians)
+pub const NPC_AIMED_AT_TOLERANCE: f32 = 0.12;
+
+/// Gunfire closer than this fraction of its loudness radius alarms; further out it only makes NPCs curious
+pub const NPC_ALARM_LOUDNESS_FRACTION: f32 = 0.5;
+
+/// Seconds an alarm lasts without a new stimulus before dropping to curious
+pub const NPC_ALARM_DURATION: f32 = 12.0;
+
+/// Seconds curiosity lasts before the NPC calms down
+pub const NPC_CURIOUS_DURATION: f32 = 8.0;
+
+/// How worked up an NPC is
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NpcAlert {
+    #[default]
+    Calm,
+    /// Heard something in the distance: goes to have a look
+    Curious,
+    /// Shot at, aimed at or heard gunfire close by: flees or fights
+    Alarmed,
+}
+
+/// What an NPC has noticed lately
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NpcAwareness {
+    pub alert: NpcAlert,
+    /// Where the last stimulus came from
+    pub focus: Option<Vec3>,
+    /// Player behind it, when known
+    pub suspect: Option<Entity>,
+    /// Seconds until the alert drops a level
+    pub timer: f32,
+}
+
+impl NpcAwareness {
+    /// Note a stimulus. Weaker ones than the current alert are ignored; returns true when
+    /// the alert level rose.
+    pub fn notice(&mut self, alert: NpcAlert, focus: Vec3, suspect: Option<Entity>) -> bool {
+        if alert == NpcAlert::Calm || alert < self.alert {
+            return false;
+        }
+        let rose = alert > self.alert;
+        self.alert = alert;
+        self.focus = Some(focus);
+        // A fresh alarm with no culprit forgets the old one
+        if suspect.is_some() || rose {
+            self.suspect = suspect;
+        }
+        self.timer = match alert {
+            NpcAlert::Alarmed => NPC_ALARM_DURATION,
+            _ => NPC_CURIOUS_DURATION,
+        };
+        rose
+    }
+
+    /// Count down, stepping alarmed -> curious -> calm
+    pub fn decay(&mut self, dt: f32) {
+        if self.alert == NpcAlert::Calm {
+            return;
+        }
+        self.timer -= dt;
+        if self.timer > 0.0 {
+            return;
+        }
+        if self.alert == NpcAlert::Alarmed {
+            self.alert = NpcAlert::Curious;
+            self.timer = NPC_CURIOUS_DURATION;
+        } else {
+            *self = Self::default();
+        }
+    }
+}
+
+/// Whether `target` is within `range` of `eye` and inside the view cone of an NPC facing `yaw`
+pub fn in_view_cone(eye: Vec3, yaw: f32, target: Vec3, range: f32) -> bool {
+    let to = target - eye;
+    let distance = to.length();
+    if distance > range {
+        return false;
+    }
+    if distance <= NPC_PERIPHERAL_RANGE {
+        return true;
+    }
+    let forward = Vec2::new(-yaw.sin(), -yaw.cos());
+    let to_xz = Vec2::new(to.x, to.z).normalize_or_zero();
+    forward.angle_to(to_xz).abs() <= NPC_VIEW_HALF_ANGLE
+}
+
+/// Whether someone at `from` facing `yaw` is pointing at `target` (horizontal angle only)
+pub fn is_aimed_at(from: Vec3, yaw: f32, target: Vec3) -> bool {
+    let facing = Vec2::new(-yaw.sin(), -yaw.cos());
+    let to = Vec2::new(target.x - from.x, target.z - from.z).normalize_or_zero();
+    to != Vec2::ZERO && facing.angle_to(to).abs() <= NPC_AIMED_AT_TOLERANCE
+}
+
+/// How a noise `loudness` meters loud at `distance` meters alerts an NPC
+pub fn hearing_alert(distance: f32, loudness: f32) -> NpcAlert {
+    if distance > loudness {
+        NpcAlert::Calm
+    } else if distance > loudness * NPC_ALARM_LOUDNESS_FRACTION {
+        NpcAlert::Curious
+    } else {
+        NpcAlert::Alarmed
+    }
+}
+
+impl WeaponType {
+    /// How far NPCs hear this weapon fire (m); melee weapons make no gunshot
+    pub fn loudness_radius(&self) -> f32 {
+        match self {
+            WeaponType::Pistol => 70.0,
+            WeaponType::SMG => 80.0,
+            WeaponType::Shotgun => 110.0,
+            WeaponType::AssaultRifle => 120.0,
+            WeaponType::Sniper => 180.0,
+            WeaponType::Unarmed | WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_cone_sees_ahead_not_behind() {
+        let eye = Vec3::ZERO;
+        // Yaw 0 faces -Z
+        assert!(in_view_cone(eye, 0.0, Vec3::new(2.0, 0.0, -20.0), 30.0));
+        assert!(!in_view_cone(eye, 0.0, Vec3::new(0.0, 0.0, 20.0), 30.0));
+        assert!(!in_view_cone(eye, 0.0, Vec3::new(0.0, 0.0, -40.0), 30.0));
+        // Right behind but close enough to notice anyway
+        assert!(in_view_cone(eye, 0.0, Vec3::new(0.0, 0.0, 2.0), 30.0));
+    }
+
+    #[test]
+    fn test_gunshot_loudness_bands() {
+        let loudness = WeaponType::AssaultRifle.loudness_radius();
+        assert_eq!(hearing_alert(loudness * 0.25, loudness), NpcAlert::Alarmed);
+        assert_eq!(hearing_alert(loudness * 0.75, loudness), NpcAlert::Curious);
+        assert_eq!(hearing_alert(loudness * 1.5, loudness), NpcAlert::Calm);
+        assert!(WeaponType::Sniper.loudness_radius() > WeaponType::Pistol.loudness_radius());
+        assert_eq!(hearing_alert(1.0, WeaponType::Knife.loudness_radius()), NpcAlert::Calm);
+    }
+
+    #[test]
+    fn test_awareness_decays_and_ignores_weaker_stimuli() {
+        let mut awareness = NpcAwareness::default();
+        assert!(awareness.notice(NpcAlert::Alarmed, Vec3::X, None));
+        // A distant shot doesn't downgrade an alarm
+        assert!(!awareness.notice(NpcAlert::Curious, Vec3::Z, None));
+        assert_eq!(awareness.focus, Some(Vec3::X));
+
+        awareness.decay(NPC_ALARM_DURATION + 0.1);
+        assert_eq!(awareness.alert, NpcAlert::Curious);
+        awareness.decay(NPC_CURIOUS_DURATION + 0.1);
+        assert_eq!(awareness.alert, NpcAlert::Calm);
+        assert_eq!(awareness.focus, None);
+    }
+}
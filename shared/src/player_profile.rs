@@ -2,15 +2,16 @@
 //!
 //! This module defines the PlayerProfile structure used to save/load player state
 //! across disconnects and server restarts. Uses bincode serialization like the
-//! collider baker system.
+//! collider baker system. Older profile layouts are kept here as private structs
+//! so `decode_profile` can upgrade them instead of throwing the player's gear away.
 
 use serde::{Deserialize, Serialize};
 use crate::{
-    ItemStack, WeaponType, VehicleType, INVENTORY_SLOTS, SPAWN_POSITION,
+    EquippedArmor, ItemStack, ItemType, QuestLog, Team, WeaponType, VehicleType, INVENTORY_SLOTS, SPAWN_POSITION,
 };
 
 /// Current profile version for migration support
-pub const PROFILE_VERSION: u32 = 1;
+pub const PROFILE_VERSION: u32 = 4;
 
 /// Serializable player profile containing all persistent state
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +45,8 @@ pub struct PlayerProfile {
     pub inventory_slots: [Option<ItemStack>; INVENTORY_SLOTS],
     /// Active hotbar slot index (0-5)
     pub hotbar_selection: u8,
+    /// Worn helmet/vest (with remaining durability)
+    pub equipped_armor: EquippedArmor,
 
     // === Vehicle State ===
     /// Whether player was in a vehicle when they disconnected
@@ -70,6 +73,14 @@ pub struct PlayerProfile {
     pub last_login: std::time::SystemTime,
     /// Total time played in seconds
     pub total_playtime_secs: u64,
+
+    // === Team ===
+    /// Team the player was on, kept so territory ownership still means something after a restart
+    pub team: Option<Team>,
+
+    // === Quests ===
+    /// Quest progress, so finished quests can't be handed in again after a restart
+    pub quest_log: QuestLog,
 }
 
 impl PlayerProfile {
@@ -84,6 +95,7 @@ impl PlayerProfile {
             item_type: crate::ItemType::Weapon(WeaponType::AssaultRifle),
             quantity: 1,
             ammo_in_mag: Some(30),
+            durability: None,
         });
 
         // Slot 1: 90x Rifle Ammo (3 stacks of 30)
@@ -91,16 +103,19 @@ impl PlayerProfile {
             item_type: crate::ItemType::RifleAmmo,
             quantity: 30,
             ammo_in_mag: None,
+            durability: None,
         });
         inventory_slots[2] = Some(ItemStack {
             item_type: crate::ItemType::RifleAmmo,
             quantity: 30,
             ammo_in_mag: None,
+            durability: None,
         });
         inventory_slots[3] = Some(ItemStack {
             item_type: crate::ItemType::RifleAmmo,
             quantity: 30,
             ammo_in_mag: None,
+            durability: None,
         });
 
         // Slot 4: 20x Shotgun Shells
@@ -108,6 +123,7 @@ impl PlayerProfile {
             item_type: crate::ItemType::ShotgunShells,
             quantity: 20,
             ammo_in_mag: None,
+            durability: None,
         });
 
         // Slot 5: 24x Pistol Ammo
@@ -115,6 +131,7 @@ impl PlayerProfile {
             item_type: crate::ItemType::PistolAmmo,
             quantity: 24,
             ammo_in_mag: None,
+            durability: None,
         });
 
         // Slot 6: 10x Sniper Rounds
@@ -122,6 +139,7 @@ impl PlayerProfile {
             item_type: crate::ItemType::SniperRounds,
             quantity: 10,
             ammo_in_mag: None,
+            durability: None,
         });
 
         Self {
@@ -142,6 +160,7 @@ impl PlayerProfile {
             // Starting inventory
             inventory_slots,
             hotbar_selection: 0,
+            equipped_armor: EquippedArmor::default(),
 
             // Not in vehicle
             in_vehicle: false,
@@ -158,6 +177,284 @@ impl PlayerProfile {
             // Metadata
             last_login: std::time::SystemTime::now(),
             total_playtime_secs: 0,
+
+            // Team is handed out on first spawn
+            team: None,
+
+            quest_log: QuestLog::default(),
         }
     }
 }
+
+/// Read the format version of a saved profile without decoding the rest.
+///
+/// `version` is the first field and bincode stores it as a fixed 4-byte integer,
+/// so this works for every layout, including ones this build can't decode.
+pub fn profile_version(bytes: &[u8]) -> Option<u32> {
+    bincode::deserialize(bytes.get(..4)?).ok()
+}
+
+/// Decode a saved profile, upgrading older layouts to the current one
+pub fn decode_profile(bytes: &[u8]) -> Result<PlayerProfile, String> {
+    match profile_version(bytes) {
+        Some(PROFILE_VERSION) => bincode::deserialize(bytes).map_err(|e| e.to_string()),
+        Some(3) => bincode::deserialize::<PlayerProfileV3>(bytes)
+            .map(PlayerProfile::from)
+            .map_err(|e| e.to_string()),
+        Some(2) => bincode::deserialize::<PlayerProfileV2>(bytes)
+            .map(|v2| PlayerProfile::from(PlayerProfileV3::from(v2)))
+            .map_err(|e| e.to_string()),
+        Some(1) => bincode::deserialize::<PlayerProfileV1>(bytes)
+            .map(|v1| PlayerProfile::from(PlayerProfileV3::from(PlayerProfileV2::from(v1))))
+            .map_err(|e| e.to_string()),
+        Some(version) => Err(format!("unsupported profile version v{}", version)),
+        None => Err("missing version header".to_string()),
+    }
+}
+
+/// v1 item stack: no armor durability yet
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct ItemStackV1 {
+    item_type: ItemType,
+    quantity: u32,
+    ammo_in_mag: Option<u32>,
+}
+
+/// v1 profile: v1 item stacks and no worn armor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerProfileV1 {
+    version: u32,
+    player_name: String,
+    position: [f32; 3],
+    rotation: f32,
+    velocity: [f32; 3],
+    health_current: f32,
+    health_max: f32,
+    equipped_weapon: WeaponType,
+    weapon_ammo_in_mag: u32,
+    inventory_slots: [Option<ItemStackV1>; INVENTORY_SLOTS],
+    hotbar_selection: u8,
+    in_vehicle: bool,
+    vehicle_type: Option<VehicleType>,
+    vehicle_position: Option<[f32; 3]>,
+    vehicle_rotation: Option<[f32; 3]>,
+    vehicle_velocity: Option<[f32; 3]>,
+    vehicle_angular_velocity: Option<[f32; 3]>,
+    is_dead: bool,
+    death_timestamp: Option<f64>,
+    last_login: std::time::SystemTime,
+    total_playtime_secs: u64,
+}
+
+/// v2 profile: no team yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerProfileV2 {
+    version: u32,
+    player_name: String,
+    position: [f32; 3],
+    rotation: f32,
+    velocity: [f32; 3],
+    health_current: f32,
+    health_max: f32,
+    equipped_weapon: WeaponType,
+    weapon_ammo_in_mag: u32,
+    inventory_slots: [Option<ItemStack>; INVENTORY_SLOTS],
+    hotbar_selection: u8,
+    equipped_armor: EquippedArmor,
+    in_vehicle: bool,
+    vehicle_type: Option<VehicleType>,
+    vehicle_position: Option<[f32; 3]>,
+    vehicle_rotation: Option<[f32; 3]>,
+    vehicle_velocity: Option<[f32; 3]>,
+    vehicle_angular_velocity: Option<[f32; 3]>,
+    is_dead: bool,
+    death_timestamp: Option<f64>,
+    last_login: std::time::SystemTime,
+    total_playtime_secs: u64,
+}
+
+/// v3 profile: no quest log yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerProfileV3 {
+    version: u32,
+    player_name: String,
+    position: [f32; 3],
+    rotation: f32,
+    velocity: [f32; 3],
+    health_current: f32,
+    health_max: f32,
+    equipped_weapon: WeaponType,
+    weapon_ammo_in_mag: u32,
+    inventory_slots: [Option<ItemStack>; INVENTORY_SLOTS],
+    hotbar_selection: u8,
+    equipped_armor: EquippedArmor,
+    in_vehicle: bool,
+    vehicle_type: Option<VehicleType>,
+    vehicle_position: Option<[f32; 3]>,
+    vehicle_rotation: Option<[f32; 3]>,
+    vehicle_velocity: Option<[f32; 3]>,
+    vehicle_angular_velocity: Option<[f32; 3]>,
+    is_dead: bool,
+    death_timestamp: Option<f64>,
+    last_login: std::time::SystemTime,
+    total_playtime_secs: u64,
+    team: Option<Team>,
+}
+
+impl From<PlayerProfileV1> for PlayerProfileV2 {
+    fn from(v1: PlayerProfileV1) -> Self {
+        Self {
+            version: 2,
+            player_name: v1.player_name,
+            position: v1.position,
+            rotation: v1.rotation,
+            velocity: v1.velocity,
+            health_current: v1.health_current,
+            health_max: v1.health_max,
+            equipped_weapon: v1.equipped_weapon,
+            weapon_ammo_in_mag: v1.weapon_ammo_in_mag,
+            inventory_slots: v1.inventory_slots.map(|slot| {
+                slot.map(|stack| ItemStack {
+                    item_type: stack.item_type,
+                    quantity: stack.quantity,
+                    ammo_in_mag: stack.ammo_in_mag,
+                    durability: None,
+                })
+            }),
+            hotbar_selection: v1.hotbar_selection,
+            equipped_armor: EquippedArmor::default(),
+            in_vehicle: v1.in_vehicle,
+            vehicle_type: v1.vehicle_type,
+            vehicle_position: v1.vehicle_position,
+            vehicle_rotation: v1.vehicle_rotation,
+            vehicle_velocity: v1.vehicle_velocity,
+            vehicle_angular_velocity: v1.vehicle_angular_velocity,
+            is_dead: v1.is_dead,
+            death_timestamp: v1.death_timestamp,
+            last_login: v1.last_login,
+            total_playtime_secs: v1.total_playtime_secs,
+        }
+    }
+}
+
+impl From<PlayerProfileV2> for PlayerProfileV3 {
+    fn from(v2: PlayerProfileV2) -> Self {
+        Self {
+            version: 3,
+            player_name: v2.player_name,
+            position: v2.position,
+            rotation: v2.rotation,
+            velocity: v2.velocity,
+            health_current: v2.health_current,
+            health_max: v2.health_max,
+            equipped_weapon: v2.equipped_weapon,
+            weapon_ammo_in_mag: v2.weapon_ammo_in_mag,
+            inventory_slots: v2.inventory_slots,
+            hotbar_selection: v2.hotbar_selection,
+            equipped_armor: v2.equipped_armor,
+            in_vehicle: v2.in_vehicle,
+            vehicle_type: v2.vehicle_type,
+            vehicle_position: v2.vehicle_position,
+            vehicle_rotation: v2.vehicle_rotation,
+            vehicle_velocity: v2.vehicle_velocity,
+            vehicle_angular_velocity: v2.vehicle_angular_velocity,
+            is_dead: v2.is_dead,
+            death_timestamp: v2.death_timestamp,
+            last_login: v2.last_login,
+            total_playtime_secs: v2.total_playtime_secs,
+            team: None,
+        }
+    }
+}
+
+impl From<PlayerProfileV3> for PlayerProfile {
+    fn from(v3: PlayerProfileV3) -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            player_name: v3.player_name,
+            position: v3.position,
+            rotation: v3.rotation,
+            velocity: v3.velocity,
+            health_current: v3.health_current,
+            health_max: v3.health_max,
+            equipped_weapon: v3.equipped_weapon,
+            weapon_ammo_in_mag: v3.weapon_ammo_in_mag,
+            inventory_slots: v3.inventory_slots,
+            hotbar_selection: v3.hotbar_selection,
+            equipped_armor: v3.equipped_armor,
+            in_vehicle: v3.in_vehicle,
+            vehicle_type: v3.vehicle_type,
+            vehicle_position: v3.vehicle_position,
+            vehicle_rotation: v3.vehicle_rotation,
+            vehicle_velocity: v3.vehicle_velocity,
+            vehicle_angular_velocity: v3.vehicle_angular_velocity,
+            is_dead: v3.is_dead,
+            death_timestamp: v3.death_timestamp,
+            last_login: v3.last_login,
+            total_playtime_secs: v3.total_playtime_secs,
+            team: v3.team,
+            quest_log: QuestLog::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_profile_round_trips() {
+        let mut profile = PlayerProfile::new_player("alice".to_string());
+        profile.quest_log.complete("palisade");
+        let bytes = bincode::serialize(&profile).unwrap();
+        assert_eq!(profile_version(&bytes), Some(PROFILE_VERSION));
+        let decoded = decode_profile(&bytes).unwrap();
+        assert_eq!(decoded.player_name, "alice");
+        assert_eq!(decoded.inventory_slots, profile.inventory_slots);
+        assert_eq!(decoded.quest_log, profile.quest_log);
+    }
+
+    #[test]
+    fn test_v1_profile_is_upgraded() {
+        let mut inventory_slots = [None; INVENTORY_SLOTS];
+        inventory_slots[0] = Some(ItemStackV1 {
+            item_type: ItemType::Weapon(WeaponType::Shotgun),
+            quantity: 1,
+            ammo_in_mag: Some(3),
+        });
+        let v1 = PlayerProfileV1 {
+            version: 1,
+            player_name: "bob".to_string(),
+            position: [1.0, 2.0, 3.0],
+            rotation: 0.5,
+            velocity: [0.0; 3],
+            health_current: 40.0,
+            health_max: 100.0,
+            equipped_weapon: WeaponType::Shotgun,
+            weapon_ammo_in_mag: 3,
+            inventory_slots,
+            hotbar_selection: 0,
+            in_vehicle: false,
+            vehicle_type: None,
+            vehicle_position: None,
+            vehicle_rotation: None,
+            vehicle_velocity: None,
+            vehicle_angular_velocity: None,
+            is_dead: false,
+            death_timestamp: None,
+            last_login: std::time::SystemTime::UNIX_EPOCH,
+            total_playtime_secs: 60,
+        };
+        let bytes = bincode::serialize(&v1).unwrap();
+        assert_eq!(profile_version(&bytes), Some(1));
+
+        let upgraded = decode_profile(&bytes).unwrap();
+        assert_eq!(upgraded.version, PROFILE_VERSION);
+        assert_eq!(upgraded.health_current, 40.0);
+        assert_eq!(upgraded.inventory_slots[0], Some(ItemStack::new_weapon(WeaponType::Shotgun, 3)));
+        assert_eq!(upgraded.equipped_armor, EquippedArmor::default());
+        assert_eq!(upgraded.team, None);
+        assert_eq!(upgraded.quest_log, QuestLog::default());
+        assert!(decode_profile(&bytes[..2]).is_err());
+    }
+}
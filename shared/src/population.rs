@@ -0,0 +1,218 @@
+//! NPC population: regions the server keeps stocked with NPCs
+//!
+//! The world is split into population regions: every desert settlement, and square
+//! wilderness cells whose biome sets what lives there. Regions near players are filled
+//! up to their targets (`PopulationRegion::targets`) at spawn points picked
+//! deterministically from the region and a running serial, skipping any a player
+//! could see. The server side lives in `server::npc_population`.
+
+use bevy::prelude::*;
+
+use crate::components::NpcArchetype;
+use crate::perception::NPC_VIEW_HALF_ANGLE;
+use crate::player::SPAWN_POSITION;
+use crate::structures::{MEDIEVAL_TOWN_CENTER, MEDIEVAL_TOWN_RADIUS};
+use crate::terrain::{Biome, TerrainGenerator, SETTLEMENT_GRID_SIZE, SETTLEMENT_MIN_SPAWN_DIST, SETTLEMENT_RADIUS};
+
+/// Regions within this distance of a player are populated (m)
+pub const POPULATION_ACTIVE_RADIUS: f32 = 250.0;
+
+/// Idle NPCs with no player this close are despawned (m)
+pub const POPULATION_DESPAWN_RADIUS: f32 = 400.0;
+
+/// Nothing spawns closer than this to a player, seen or not (m)
+pub const POPULATION_MIN_SPAWN_DISTANCE: f32 = 40.0;
+
+/// Players are assumed to see spawns up to this far inside their view (m)
+pub const POPULATION_SIGHT_RANGE: f32 = 150.0;
+
+/// Seconds before a killed NPC is replaced
+pub const NPC_RESPAWN_COOLDOWN: f32 = 90.0;
+
+/// Size of a wilderness region cell (m)
+pub const WILDERNESS_CELL_SIZE: f32 = 200.0;
+
+/// NPCs of a wilderness region spawn within this distance of the cell center (m)
+pub const WILDERNESS_RADIUS: f32 = 60.0;
+
+/// Identifies a population region (grid cell coordinates)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RegionKey {
+    /// Desert settlement in this `SETTLEMENT_GRID_SIZE` cell
+    Settlement(i32, i32),
+    /// Wilderness in this `WILDERNESS_CELL_SIZE` cell
+    Wilderness(i32, i32),
+}
+
+/// An area the population director keeps stocked
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PopulationRegion {
+    pub key: RegionKey,
+    pub center: Vec2,
+    pub radius: f32,
+    pub biome: Biome,
+}
+
+impl PopulationRegion {
+    /// How many of each archetype should live here
+    pub fn targets(&self) -> &'static [(NpcArchetype, u32)] {
+        match (self.key, self.biome) {
+            (RegionKey::Settlement(..), _) => &[
+                (NpcArchetype::RogueHooded, 4),
+                (NpcArchetype::Rogue, 2),
+                (NpcArchetype::Barbarian, 2),
+                (NpcArchetype::Mage, 1),
+            ],
+            // Ranger camps out in the dunes
+            (RegionKey::Wilderness(..), Biome::Desert) => &[(NpcArchetype::Ranger, 2)],
+            (RegionKey::Wilderness(..), Biome::Grasslands) => &[(NpcArchetype::Rogue, 1), (NpcArchetype::Barbarian, 1)],
+            (RegionKey::Wilderness(..), Biome::Natureland) => &[(NpcArchetype::Ranger, 1), (NpcArchetype::Mage, 1)],
+        }
+    }
+
+    /// Spawn point (XZ) for the region's `serial`-th spawn, `attempt`-th try. Same
+    /// inputs, same point.
+    pub fn spawn_candidate(&self, serial: u32, attempt: u32) -> Vec2 {
+        let (kind, x, z) = match self.key {
+            RegionKey::Settlement(x, z) => (1_u64, x, z),
+            RegionKey::Wilderness(x, z) => (2_u64, x, z),
+        };
+        let cell = splitmix64((kind << 62) ^ ((x as u32 as u64) << 32) ^ z as u32 as u64);
+        let hash = splitmix64(cell ^ (((serial as u64) << 32) | attempt as u64));
+        let angle = (hash >> 40) as f32 / (1u32 << 24) as f32 * std::f32::consts::TAU;
+        // sqrt for an even spread over the disc
+        let distance = ((hash & 0xFF_FFFF) as f32 / (1u32 << 24) as f32).sqrt() * self.radius;
+        self.center + Vec2::new(angle.cos(), angle.sin()) * distance
+    }
+}
+
+/// Every population region overlapping the circle around `center`, sorted by key. The
+/// spawn area and the medieval town have hand-placed NPCs and get no wilderness region.
+pub fn population_regions_near(generator: &TerrainGenerator, center: Vec2, radius: f32) -> Vec<PopulationRegion> {
+    let mut regions: Vec<PopulationRegion> = generator
+        .get_settlements_in_radius(center, radius + SETTLEMENT_RADIUS)
+        .into_iter()
+        .map(|settlement| PopulationRegion {
+            key: RegionKey::Settlement(
+                (settlement.center.x / SETTLEMENT_GRID_SIZE).floor() as i32,
+                (settlement.center.y / SETTLEMENT_GRID_SIZE).floor() as i32,
+            ),
+            center: settlement.center,
+            radius: settlement.radius,
+            biome: Biome::Desert,
+        })
+        .collect();
+
+    let spawn = Vec2::new(SPAWN_POSITION[0], SPAWN_POSITION[2]);
+    let reach = radius + WILDERNESS_RADIUS;
+    let min = ((center - Vec2::splat(reach)) / WILDERNESS_CELL_SIZE).floor().as_ivec2();
+    let max = ((center + Vec2::splat(reach)) / WILDERNESS_CELL_SIZE).floor().as_ivec2();
+    for cx in min.x..=max.x {
+        for cz in min.y..=max.y {
+            let cell_center = (Vec2::new(cx as f32, cz as f32) + 0.5) * WILDERNESS_CELL_SIZE;
+            if cell_center.distance(center) > reach
+                || cell_center.distance(spawn) < SETTLEMENT_MIN_SPAWN_DIST
+                || cell_center.distance(MEDIEVAL_TOWN_CENTER) < MEDIEVAL_TOWN_RADIUS + WILDERNESS_RADIUS
+                || !generator
+                    .get_settlements_in_radius(cell_center, SETTLEMENT_RADIUS + WILDERNESS_RADIUS)
+                    .is_empty()
+            {
+                continue;
+            }
+            regions.push(PopulationRegion {
+                key: RegionKey::Wilderness(cx, cz),
+                center: cell_center,
+                radius: WILDERNESS_RADIUS,
+                biome: generator.get_biome(cell_center.x, cell_center.y),
+            });
+        }
+    }
+
+    regions.sort_by_key(|region| region.key);
+    regions
+}
+
+/// How a spawn point looks to one player
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnSight {
+    /// Too close to spawn at all
+    TooClose,
+    /// Inside the player's view: only OK behind cover
+    InView,
+    OutOfView,
+}
+
+/// Check `point` against a player at `eye` looking along `yaw`
+pub fn spawn_sight(eye: Vec3, yaw: f32, point: Vec3) -> SpawnSight {
+    let to = Vec2::new(point.x - eye.x, point.z - eye.z);
+    let distance = to.length();
+    if distance < POPULATION_MIN_SPAWN_DISTANCE {
+        return SpawnSight::TooClose;
+    }
+    // Players see wider than NPCs; leave margin for turning the camera
+    let forward = Vec2::new(-yaw.sin(), -yaw.cos());
+    if distance <= POPULATION_SIGHT_RANGE && forward.angle_to(to / distance).abs() <= NPC_VIEW_HALF_ANGLE * 1.5 {
+        SpawnSight::InView
+    } else {
+        SpawnSight::OutOfView
+    }
+}
+
+/// splitmix64 finalizer: spreads nearby seeds over the whole range
+pub(crate) fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::WORLD_SEED;
+
+    #[test]
+    fn test_spawn_candidates_deterministic_and_inside_region() {
+        let region = PopulationRegion {
+            key: RegionKey::Wilderness(3, -2),
+            center: Vec2::new(700.0, -300.0),
+            radius: WILDERNESS_RADIUS,
+            biome: Biome::Desert,
+        };
+        for serial in 0..20 {
+            let point = region.spawn_candidate(serial, 0);
+            assert!(point.distance(region.center) <= region.radius + 1e-3);
+            assert_eq!(point, region.spawn_candidate(serial, 0));
+            assert_ne!(point, region.spawn_candidate(serial, 1));
+        }
+    }
+
+    #[test]
+    fn test_wilderness_skips_spawn_area_and_town() {
+        let generator = TerrainGenerator::new(WORLD_SEED);
+        let wilderness = |regions: &[PopulationRegion]| {
+            regions
+                .iter()
+                .filter(|region| matches!(region.key, RegionKey::Wilderness(..)))
+                .map(|region| region.center)
+                .collect::<Vec<_>>()
+        };
+        let around_spawn = population_regions_near(&generator, Vec2::ZERO, POPULATION_ACTIVE_RADIUS);
+        assert!(wilderness(&around_spawn).iter().all(|center| center.length() >= SETTLEMENT_MIN_SPAWN_DIST));
+        let around_town = population_regions_near(&generator, MEDIEVAL_TOWN_CENTER, 50.0);
+        assert!(wilderness(&around_town).is_empty());
+
+        // Same query, same regions in the same order
+        assert_eq!(around_spawn, population_regions_near(&generator, Vec2::ZERO, POPULATION_ACTIVE_RADIUS));
+    }
+
+    #[test]
+    fn test_spawn_sight() {
+        let eye = Vec3::ZERO;
+        // Yaw 0 looks down -Z
+        assert_eq!(spawn_sight(eye, 0.0, Vec3::new(0.0, 0.0, -10.0)), SpawnSight::TooClose);
+        assert_eq!(spawn_sight(eye, 0.0, Vec3::new(0.0, 0.0, -80.0)), SpawnSight::InView);
+        assert_eq!(spawn_sight(eye, 0.0, Vec3::new(0.0, 0.0, 80.0)), SpawnSight::OutOfView);
+        assert_eq!(spawn_sight(eye, 0.0, Vec3::new(0.0, 0.0, -300.0)), SpawnSight::OutOfView);
+    }
+}
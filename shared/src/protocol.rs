@@ -9,17 +9,29 @@ use std::time::Duration;
 
 use crate::components::{
     Npc, NpcPosition, NpcRotation, Player, PlayerPosition, PlayerRotation, Health, EquippedWeapon,
-    Bullet, BulletVelocity, WorldTime,
+    Bullet, BulletVelocity, Wind, WorldTime,
 };
 use crate::items::{
     Inventory, GroundItem, GroundItemPosition, PickupRequest, DropRequest,
     HotbarSelection, SelectHotbarSlot, InventoryMoveRequest,
     ChestStorage, ChestPosition, OpenChestRequest, CloseChestRequest, ChestTransferRequest,
 };
+use crate::armor::{EquippedArmor, EquipArmorRequest, UnequipArmorRequest};
+use crate::behaviour::{NpcActiveBehaviour, NpcIndoors};
 use crate::building::{PlacedBuilding, BuildingPosition, PlaceBuildingRequest};
+use crate::combat_log::{DamageLogReport, KillFeedEntry};
+use crate::hitbox::NpcHitboxStance;
+use crate::dialogue::{DialogueChoiceRequest, DialogueUpdate, EndDialogueRequest, NpcVoiceLine, StartDialogueRequest};
+use crate::loot::LootableCorpse;
+use crate::game_mode::{MatchScoreboard, MatchState};
+use crate::territory::{CaptureAnnouncement, ControlPoint};
+use crate::trade::{TradeRequest, TradeResponse, TraderStock};
+use crate::zone::SafeZone;
+use crate::team::{PlayerName, Team, TeamOwner};
 use crate::terrain::TerrainDeltaChunk;
 use crate::vehicle::{Vehicle, VehicleState, VehicleDriver, VehicleInput};
 use crate::weapons::damage::HitZone;
+use crate::weapons::{AdjustZeroRequest, CycleFireModeRequest, Explosion, Explosive, MeleeSwing, Reloading, Suppression, ThrowRequest};
 
 // --- Input (for server-authoritative movement) ---
 
@@ -55,6 +67,10 @@ pub struct ShootRequest {
     pub pitch: f32,
     /// Whether aiming down sights
     pub aiming: bool,
+    /// Client's count of rounds already fired in the current recoil burst
+    pub shot_index: u32,
+    /// Recoil (x = pitch, y = yaw) the client's camera carried when `direction` was taken
+    pub recoil_offset: Vec2,
 }
 
 /// Message sent from server to confirm a hit
@@ -70,6 +86,8 @@ pub struct HitConfirm {
     pub kill: bool,
     /// Hit zone
     pub hit_zone: HitZone,
+    /// Damage soaked up by the target's armor (0 if unarmored)
+    pub armor_absorbed: f32,
 }
 
 /// Message sent from server when player takes damage
@@ -105,6 +123,10 @@ pub struct SwitchWeapon {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ReloadRequest;
 
+/// Message sent from client when sprinting interrupts a shell-by-shell reload
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct InterruptReloadRequest;
+
 /// What the bullet impacted (used for visuals/debug)
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 pub enum BulletImpactSurface {
@@ -112,6 +134,7 @@ pub enum BulletImpactSurface {
     PracticeWall,
     Player,
     Npc,
+    Vehicle,
 }
 
 /// Server -> Client: bullet impact (reliable visual feedback independent of bullet replication)
@@ -131,10 +154,17 @@ pub struct BulletImpact {
 pub enum AudioEventKind {
     /// Gunshot sound (weapon type affects which sound to play)
     Gunshot { weapon_type: crate::weapons::WeaponType },
+    /// A round passed close to the listener (sent only to them, positioned at the closest point)
+    NearMiss {
+        /// Supersonic rounds crack, subsonic ones whiz
+        supersonic: bool,
+        /// Closest approach to the listener's head (m)
+        miss_distance: f32,
+    },
 }
 
 /// Server -> Client: audio event broadcast for spatial audio
-/// Allows clients to hear other players' sounds (gunshots, etc.)
+/// Allows clients to hear other players' sounds (gunshots, near misses, etc.)
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct AudioEvent {
     /// ID of the player who made the sound (skip if it's ourselves)
@@ -212,6 +242,16 @@ impl Plugin for ProtocolPlugin {
         app.register_component::<PlayerRotation>()
             .add_prediction();
 
+        app.register_component::<PlayerName>()
+            .add_prediction();
+
+        // === TEAMS ===
+        app.register_component::<Team>()
+            .add_prediction();
+
+        app.register_component::<TeamOwner>()
+            .add_prediction();
+
         // === NPC COMPONENTS ===
         app.register_component::<Npc>()
             .add_prediction();
@@ -222,6 +262,15 @@ impl Plugin for ProtocolPlugin {
         app.register_component::<NpcRotation>()
             .add_prediction();
 
+        app.register_component::<NpcActiveBehaviour>()
+            .add_prediction();
+
+        app.register_component::<NpcIndoors>()
+            .add_prediction();
+
+        app.register_component::<NpcHitboxStance>()
+            .add_prediction();
+
         // === VEHICLE COMPONENTS ===
         
         app.register_component::<Vehicle>()
@@ -241,6 +290,15 @@ impl Plugin for ProtocolPlugin {
         app.register_component::<EquippedWeapon>()
             .add_prediction();
 
+        app.register_component::<MeleeSwing>()
+            .add_prediction();
+
+        app.register_component::<Reloading>()
+            .add_prediction();
+
+        app.register_component::<Suppression>()
+            .add_prediction();
+
         // === BULLET COMPONENTS ===
         
         app.register_component::<Bullet>()
@@ -249,10 +307,25 @@ impl Plugin for ProtocolPlugin {
         app.register_component::<BulletVelocity>()
             .add_prediction();
 
+        app.register_component::<Explosive>()
+            .add_prediction();
+
         // === WORLD COMPONENTS ===
         app.register_component::<WorldTime>()
             .add_prediction();
 
+        app.register_component::<Wind>()
+            .add_prediction();
+
+        app.register_component::<MatchState>()
+            .add_prediction();
+
+        app.register_component::<SafeZone>()
+            .add_prediction();
+
+        app.register_component::<ControlPoint>()
+            .add_prediction();
+
         // === INVENTORY COMPONENTS ===
         app.register_component::<Inventory>()
             .add_prediction();
@@ -267,6 +340,9 @@ impl Plugin for ProtocolPlugin {
         app.register_component::<HotbarSelection>()
             .add_prediction();
 
+        app.register_component::<EquippedArmor>()
+            .add_prediction();
+
         // === CHEST / STORAGE ===
         app.register_component::<ChestStorage>()
             .add_prediction();
@@ -274,6 +350,13 @@ impl Plugin for ProtocolPlugin {
         app.register_component::<ChestPosition>()
             .add_prediction();
 
+        app.register_component::<LootableCorpse>()
+            .add_prediction();
+
+        // === TRADERS ===
+        app.register_component::<TraderStock>()
+            .add_prediction();
+
         // === BUILDINGS ===
         app.register_component::<PlacedBuilding>()
             .add_prediction();
@@ -297,10 +380,18 @@ impl Plugin for ProtocolPlugin {
             .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<ShootRequest>()
             .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<ThrowRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<SwitchWeapon>()
             .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<ReloadRequest>()
             .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<InterruptReloadRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<CycleFireModeRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<AdjustZeroRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<PickupRequest>()
             .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<DropRequest>()
@@ -315,8 +406,20 @@ impl Plugin for ProtocolPlugin {
             .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<ChestTransferRequest>()
             .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<EquipArmorRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<UnequipArmorRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<PlaceBuildingRequest>()
             .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<TradeRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<StartDialogueRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<DialogueChoiceRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
+        app.register_message::<EndDialogueRequest>()
+            .add_direction(NetworkDirection::ClientToServer);
         app.register_message::<SubmitPlayerName>()
             .add_direction(NetworkDirection::ClientToServer);
 
@@ -327,12 +430,28 @@ impl Plugin for ProtocolPlugin {
             .add_direction(NetworkDirection::ServerToClient);
         app.register_message::<BulletImpact>()
             .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<Explosion>()
+            .add_direction(NetworkDirection::ServerToClient);
         app.register_message::<DamageReceived>()
             .add_direction(NetworkDirection::ServerToClient);
         app.register_message::<PlayerKilled>()
             .add_direction(NetworkDirection::ServerToClient);
         app.register_message::<AudioEvent>()
             .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<KillFeedEntry>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<DamageLogReport>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<MatchScoreboard>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<CaptureAnnouncement>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<TradeResponse>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<DialogueUpdate>()
+            .add_direction(NetworkDirection::ServerToClient);
+        app.register_message::<NpcVoiceLine>()
+            .add_direction(NetworkDirection::ServerToClient);
 
         // === CHANNELS ===
         
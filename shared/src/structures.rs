@@ -381,6 +381,11 @@ pub const MEDIEVAL_BLOCK_SIZE: f32 = 45.0;
 pub const MEDIEVAL_STREET_WIDTH: f32 = 8.0;
 pub const MEDIEVAL_SPACING: f32 = MEDIEVAL_BLOCK_SIZE + MEDIEVAL_STREET_WIDTH; // 53m between block centers
 
+/// Where the server builds the medieval town (XZ), in the grasslands away from desert and player spawn
+pub const MEDIEVAL_TOWN_CENTER: Vec2 = Vec2::new(350.0, 350.0);
+/// Radius of the flattened town area (3x3 blocks)
+pub const MEDIEVAL_TOWN_RADIUS: f32 = MEDIEVAL_SPACING * 1.7;
+
 /// Generate medieval town buildings in a grid pattern around a center point.
 ///
 /// Layout: 3x3 grid of blocks with center block as town square (empty).
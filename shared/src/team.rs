@@ -0,0 +1,171 @@
+//! Teams and friendly fire
+//!
+//! Every player joins a `Team` when they spawn (the smaller team, so sides stay
+//! balanced). The same team IDs mark who owns placed buildings and chests, and the
+//! server's `FriendlyFirePolicy` decides what happens when teammates hit each other.
+
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Number of teams players are split between
+pub const TEAM_COUNT: usize = 2;
+
+/// Fraction of damage a teammate takes under `FriendlyFirePolicy::Reduced`
+pub const FRIENDLY_FIRE_REDUCED_MULT: f32 = 0.35;
+
+/// Which side a player is on (server-assigned, replicated)
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Team(pub u8);
+
+impl Team {
+    pub const RED: Team = Team(0);
+    pub const BLUE: Team = Team(1);
+
+    /// Name shown in the HUD
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            0 => "Red",
+            1 => "Blue",
+            _ => "Unknown",
+        }
+    }
+
+    /// Name tag / HUD colour
+    pub fn color(&self) -> Color {
+        match self.0 {
+            0 => Color::srgb(0.95, 0.3, 0.25),
+            1 => Color::srgb(0.3, 0.55, 1.0),
+            _ => Color::srgb(0.85, 0.85, 0.85),
+        }
+    }
+}
+
+/// Which team owns a building or chest (missing = anyone can use it)
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TeamOwner(pub Team);
+
+/// A player's display name (replicated for name tags)
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PlayerName(pub String);
+
+/// What happens when a player damages a teammate
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FriendlyFirePolicy {
+    /// Teammates can't hurt each other
+    Off,
+    /// Teammates take `FRIENDLY_FIRE_REDUCED_MULT` of the damage
+    #[default]
+    Reduced,
+    /// Teammates take full damage
+    Full,
+    /// The teammate is unharmed and the attacker takes the damage instead
+    Reflect,
+}
+
+impl FriendlyFirePolicy {
+    /// Fraction of the damage a teammate takes
+    pub fn victim_multiplier(&self) -> f32 {
+        match self {
+            FriendlyFirePolicy::Off | FriendlyFirePolicy::Reflect => 0.0,
+            FriendlyFirePolicy::Reduced => FRIENDLY_FIRE_REDUCED_MULT,
+            FriendlyFirePolicy::Full => 1.0,
+        }
+    }
+
+    /// Fraction of the damage turned back on the attacker
+    pub fn reflected_multiplier(&self) -> f32 {
+        match self {
+            FriendlyFirePolicy::Reflect => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// (victim, reflected) damage multipliers for a hit; enemies always take full damage
+    pub fn multipliers(&self, friendly: bool) -> (f32, f32) {
+        if friendly {
+            (self.victim_multiplier(), self.reflected_multiplier())
+        } else {
+            (1.0, 0.0)
+        }
+    }
+}
+
+impl FromStr for FriendlyFirePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(FriendlyFirePolicy::Off),
+            "reduced" => Ok(FriendlyFirePolicy::Reduced),
+            "full" => Ok(FriendlyFirePolicy::Full),
+            "reflect" => Ok(FriendlyFirePolicy::Reflect),
+            other => Err(format!("unknown friendly fire policy '{}'", other)),
+        }
+    }
+}
+
+/// Whether two combatants are on the same team (anyone without a team is hostile to all)
+pub fn are_allies(a: Option<Team>, b: Option<Team>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a == b)
+}
+
+/// Whether a player on `team` may use something owned by `owner`
+pub fn can_access(team: Option<Team>, owner: Option<Team>) -> bool {
+    match owner {
+        None => true,
+        Some(owner) => team == Some(owner),
+    }
+}
+
+/// Team for a new player given each team's current head count (smallest first, ties to the lowest ID)
+pub fn balanced_team(counts: &[usize; TEAM_COUNT]) -> Team {
+    let (index, _) = counts
+        .iter()
+        .enumerate()
+        .min_by_key(|(index, count)| (**count, *index))
+        .unwrap_or((0, &0));
+    Team(index as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_friendly_fire_policies() {
+        let cases = [
+            ("off", 0.0, 0.0),
+            ("Reduced", FRIENDLY_FIRE_REDUCED_MULT, 0.0),
+            ("full", 1.0, 0.0),
+            (" reflect ", 0.0, 1.0),
+        ];
+        for (name, victim, reflected) in cases {
+            let policy: FriendlyFirePolicy = name.parse().unwrap();
+            assert_eq!(policy.victim_multiplier(), victim);
+            assert_eq!(policy.reflected_multiplier(), reflected);
+        }
+        assert!("friendly".parse::<FriendlyFirePolicy>().is_err());
+        assert_eq!(FriendlyFirePolicy::Off.multipliers(false), (1.0, 0.0));
+        assert_eq!(FriendlyFirePolicy::Reflect.multipliers(true), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_allies_and_access() {
+        assert!(are_allies(Some(Team::RED), Some(Team::RED)));
+        assert!(!are_allies(Some(Team::RED), Some(Team::BLUE)));
+        assert!(!are_allies(None, None));
+        assert!(can_access(None, None));
+        assert!(can_access(Some(Team::BLUE), Some(Team::BLUE)));
+        assert!(!can_access(Some(Team::RED), Some(Team::BLUE)));
+        assert!(!can_access(None, Some(Team::RED)));
+    }
+
+    #[test]
+    fn test_balanced_team() {
+        assert_eq!(balanced_team(&[0, 0]), Team::RED);
+        assert_eq!(balanced_team(&[1, 0]), Team::BLUE);
+        assert_eq!(balanced_team(&[3, 5]), Team::RED);
+    }
+}
@@ -251,6 +251,33 @@ impl TerrainGenerator {
         settlements
     }
 
+    /// Get all settlements whose centers lie within `radius` of `center`
+    pub fn get_settlements_in_radius(&self, center: Vec2, radius: f32) -> Vec<SettlementInfo> {
+        let min_cell_x = ((center.x - radius) / SETTLEMENT_GRID_SIZE).floor() as i32;
+        let max_cell_x = ((center.x + radius) / SETTLEMENT_GRID_SIZE).ceil() as i32;
+        let min_cell_z = ((center.y - radius) / SETTLEMENT_GRID_SIZE).floor() as i32;
+        let max_cell_z = ((center.y + radius) / SETTLEMENT_GRID_SIZE).ceil() as i32;
+        let mut settlements = Vec::new();
+        
+        for cx in min_cell_x..=max_cell_x {
+            for cz in min_cell_z..=max_cell_z {
+                if !self.cell_has_settlement(cx, cz) {
+                    continue;
+                }
+                let settlement_center = self.get_cell_settlement_center(cx, cz);
+                if settlement_center.distance(center) <= radius {
+                    settlements.push(SettlementInfo {
+                        center: settlement_center,
+                        radius: SETTLEMENT_RADIUS,
+                        base_height: self.get_desert_height_raw(settlement_center.x, settlement_center.y),
+                    });
+                }
+            }
+        }
+        
+        settlements
+    }
+
     /// Get the biome blend value at a world position
     /// Returns a value from -1 (pure desert) to +1 (pure grasslands)
     /// Values near 0 are in the transition zone
@@ -715,6 +742,29 @@ impl TerrainDeltaData {
     }
 }
 
+/// Rim of a crater extends to this multiple of its radius
+pub const CRATER_RIM_EXTENT: f32 = 1.3;
+
+/// Craters never dig deeper than this below the procedural height (meters)
+pub const MAX_CRATER_DEPTH: f32 = 3.0;
+
+/// Height change at `dist` from a crater center: a smooth bowl down to `-depth`,
+/// then a small raised rim out to `radius * CRATER_RIM_EXTENT`.
+pub fn crater_profile(dist: f32, radius: f32, depth: f32) -> f32 {
+    if radius <= 0.0 {
+        return 0.0;
+    }
+    let t = dist / radius;
+    if t < 1.0 {
+        -depth * (1.0 - t * t)
+    } else if t < CRATER_RIM_EXTENT {
+        let rim_t = (t - 1.0) / (CRATER_RIM_EXTENT - 1.0);
+        depth * 0.15 * (rim_t * std::f32::consts::PI).sin()
+    } else {
+        0.0
+    }
+}
+
 /// Network-replicated terrain delta chunk component
 /// Quantized to centimeters for bandwidth efficiency
 #[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -982,6 +1032,66 @@ impl WorldTerrain {
         all_affected
     }
     
+    /// Dig a bowl-shaped crater with a slightly raised rim (for explosions)
+    /// 
+    /// * `center` - World position of the blast (only X/Z are used)
+    /// * `radius` - Crater radius; the rim extends to `radius * CRATER_RIM_EXTENT`
+    /// * `depth` - Depth at the center
+    /// 
+    /// Craters stack, but the ground is never dug deeper than `MAX_CRATER_DEPTH`
+    /// below its procedural height.
+    /// 
+    /// Returns list of affected chunk coordinates (for mesh regeneration)
+    pub fn apply_crater(&mut self, center: Vec3, radius: f32, depth: f32) -> Vec<ChunkCoord> {
+        let outer = radius * CRATER_RIM_EXTENT;
+        let min_chunk_x = ((center.x - outer) / CHUNK_SIZE).floor() as i32;
+        let max_chunk_x = ((center.x + outer) / CHUNK_SIZE).floor() as i32;
+        let min_chunk_z = ((center.z - outer) / CHUNK_SIZE).floor() as i32;
+        let max_chunk_z = ((center.z + outer) / CHUNK_SIZE).floor() as i32;
+        
+        let mut affected_chunks = Vec::new();
+        
+        for chunk_x in min_chunk_x..=max_chunk_x {
+            for chunk_z in min_chunk_z..=max_chunk_z {
+                let chunk_coord = ChunkCoord::new(chunk_x, chunk_z);
+                let chunk_origin = chunk_coord.world_pos();
+                let mut chunk_modified = false;
+                
+                for zi in 0..CHUNK_RESOLUTION {
+                    for xi in 0..CHUNK_RESOLUTION {
+                        let world_x = chunk_origin.x + xi as f32 * VERTEX_SPACING;
+                        let world_z = chunk_origin.z + zi as f32 * VERTEX_SPACING;
+                        let dist = Vec2::new(world_x - center.x, world_z - center.z).length();
+                        
+                        let change = crater_profile(dist, radius, depth);
+                        if change == 0.0 {
+                            continue;
+                        }
+                        
+                        // Only chunks whose heights actually change get a delta
+                        let current = self.delta_chunks.get(&chunk_coord).map_or(0.0, |d| d.get_vertex(xi, zi));
+                        let dug = (current + change).max(current.min(-MAX_CRATER_DEPTH));
+                        if dug == current {
+                            continue;
+                        }
+                        self.delta_chunks.entry(chunk_coord).or_default().set_vertex(xi, zi, dug);
+                        chunk_modified = true;
+                    }
+                }
+                
+                if let Some(delta_data) = self.delta_chunks.get_mut(&chunk_coord).filter(|_| chunk_modified) {
+                    delta_data.version = delta_data.version.wrapping_add(1);
+                    affected_chunks.push(chunk_coord);
+                }
+            }
+        }
+        
+        if !affected_chunks.is_empty() {
+            self.version = self.version.wrapping_add(1);
+        }
+        affected_chunks
+    }
+    
     /// Get global modification version (for detecting changes)
     pub fn modification_version(&self) -> u32 {
         self.version
@@ -0,0 +1,250 @@
+//! Territory control: settlements as capture points
+//!
+//! In the territory mode every desert settlement within `TERRITORY_RADIUS` of the
+//! map spawn is a replicated `ControlPoint`. A team standing in the capture zone
+//! alone pushes its capture progress up (faster with more players), first wiping out
+//! any other team's partial progress; a point with players from several teams is
+//! contested and frozen. Owned points pay resources into the owning team's chest.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::items::{ChestStorage, ItemType};
+use crate::player::SPAWN_POSITION;
+use crate::team::{Team, TEAM_COUNT};
+use crate::terrain::TerrainGenerator;
+
+/// Settlements this far from the map spawn become control points (m)
+pub const TERRITORY_RADIUS: f32 = 2000.0;
+
+/// Capture zone radius as a fraction of the settlement radius
+pub const CAPTURE_RADIUS_FRACTION: f32 = 0.8;
+
+/// Seconds for one player to capture a point from neutral
+pub const CAPTURE_TIME: f32 = 30.0;
+
+/// Each extra player speeds the capture up by this much, up to `CAPTURE_MAX_SPEEDUP`
+pub const CAPTURE_SPEEDUP_PER_PLAYER: f32 = 0.5;
+pub const CAPTURE_MAX_SPEEDUP: f32 = 3.0;
+
+/// Partial progress on an empty point drains at this fraction of the capture rate
+pub const CAPTURE_DECAY_FRACTION: f32 = 0.25;
+
+/// Seconds between resource payouts
+pub const TERRITORY_INCOME_INTERVAL: f32 = 60.0;
+
+/// What each owned point pays its team per interval
+pub const TERRITORY_INCOME: [(ItemType, u32); 2] = [(ItemType::Stone, 25), (ItemType::Wood, 25)];
+
+/// A capture point on a settlement (server-authoritative, replicated)
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ControlPoint {
+    /// Stable index (settlements sorted by distance from spawn)
+    pub id: u32,
+    /// Capture zone (XZ)
+    pub center: Vec2,
+    pub radius: f32,
+    pub owner: Option<Team>,
+    /// Team whose progress is building (never the owner)
+    pub capturing: Option<Team>,
+    /// 0..1 capture progress of `capturing`
+    pub progress: f32,
+    /// Players from more than one team are inside
+    pub contested: bool,
+}
+
+/// Result of a capture tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureEvent {
+    Captured { team: Team, previous: Option<Team> },
+}
+
+impl ControlPoint {
+    pub fn new(id: u32, center: Vec2, radius: f32) -> Self {
+        Self { id, center, radius, owner: None, capturing: None, progress: 0.0, contested: false }
+    }
+
+    /// "A", "B", ... for the HUD
+    pub fn label(&self) -> String {
+        let letter = (b'A' + (self.id % 26) as u8) as char;
+        if self.id < 26 {
+            letter.to_string()
+        } else {
+            format!("{}{}", letter, self.id / 26)
+        }
+    }
+
+    /// Whether a world position is inside the capture zone
+    pub fn contains(&self, position: Vec3) -> bool {
+        Vec2::new(position.x, position.z).distance(self.center) <= self.radius
+    }
+
+    /// Advance capture progress given how many living players of each team are inside
+    pub fn tick(&mut self, present: [u32; TEAM_COUNT], dt: f32) -> Option<CaptureEvent> {
+        let teams_inside: Vec<usize> = (0..TEAM_COUNT).filter(|&i| present[i] > 0).collect();
+        self.contested = teams_inside.len() > 1;
+        let base_rate = dt / CAPTURE_TIME;
+
+        match teams_inside.as_slice() {
+            [] => {
+                self.progress = (self.progress - base_rate * CAPTURE_DECAY_FRACTION).max(0.0);
+                if self.progress <= 0.0 {
+                    self.capturing = None;
+                }
+                None
+            }
+            [index] => {
+                let team = Team(*index as u8);
+                let speedup = (1.0 + (present[*index] - 1) as f32 * CAPTURE_SPEEDUP_PER_PLAYER).min(CAPTURE_MAX_SPEEDUP);
+                let rate = base_rate * speedup;
+
+                match self.capturing {
+                    // Undo another team's partial capture first
+                    Some(other) if other != team => {
+                        self.progress = (self.progress - rate).max(0.0);
+                        if self.progress <= 0.0 {
+                            self.capturing = None;
+                        }
+                        None
+                    }
+                    // Nothing to take: it's already ours
+                    None if self.owner == Some(team) => None,
+                    _ => {
+                        self.capturing = Some(team);
+                        self.progress = (self.progress + rate).min(1.0);
+                        if self.progress < 1.0 {
+                            return None;
+                        }
+                        let previous = self.owner.replace(team);
+                        self.capturing = None;
+                        self.progress = 0.0;
+                        Some(CaptureEvent::Captured { team, previous })
+                    }
+                }
+            }
+            // Contested: frozen
+            _ => None,
+        }
+    }
+}
+
+/// Control points for every settlement in the territory, ids by distance from spawn
+pub fn territory_control_points(generator: &TerrainGenerator) -> Vec<ControlPoint> {
+    let spawn = Vec2::new(SPAWN_POSITION[0], SPAWN_POSITION[2]);
+    let mut settlements = generator.get_settlements_in_radius(spawn, TERRITORY_RADIUS);
+    settlements.sort_by(|a, b| {
+        a.center
+            .distance_squared(spawn)
+            .total_cmp(&b.center.distance_squared(spawn))
+            .then(a.center.x.total_cmp(&b.center.x))
+    });
+    settlements
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| ControlPoint::new(i as u32, s.center, s.radius * CAPTURE_RADIUS_FRACTION))
+        .collect()
+}
+
+/// Server -> Client: a control point changed hands
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CaptureAnnouncement {
+    pub point_id: u32,
+    pub label: String,
+    pub team: Team,
+    pub previous: Option<Team>,
+}
+
+/// Bump when `TerritorySave` changes shape
+pub const TERRITORY_SAVE_VERSION: u32 = 2;
+
+/// Control point ownership and team chest contents as saved on the server between restarts
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TerritorySave {
+    pub version: u32,
+    /// (point id, owner)
+    pub owners: Vec<(u32, Team)>,
+    /// What each team's income chest holds
+    pub chests: Vec<(Team, ChestStorage)>,
+}
+
+/// v1 save: ownership only
+#[derive(Serialize, Deserialize)]
+struct TerritorySaveV1 {
+    version: u32,
+    owners: Vec<(u32, Team)>,
+}
+
+/// Decode a territory save, upgrading older layouts (see `decode_profile`)
+pub fn decode_territory(bytes: &[u8]) -> Result<TerritorySave, String> {
+    let version: u32 = bytes
+        .get(..4)
+        .and_then(|header| bincode::deserialize(header).ok())
+        .ok_or("missing version header")?;
+    match version {
+        TERRITORY_SAVE_VERSION => bincode::deserialize(bytes).map_err(|e| e.to_string()),
+        1 => bincode::deserialize::<TerritorySaveV1>(bytes)
+            .map(|v1| TerritorySave { version: TERRITORY_SAVE_VERSION, owners: v1.owners, chests: Vec::new() })
+            .map_err(|e| e.to_string()),
+        other => Err(format!("unsupported territory save version v{}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_from_neutral_and_contest() {
+        let mut point = ControlPoint::new(0, Vec2::ZERO, 40.0);
+        assert_eq!(point.tick([1, 0], CAPTURE_TIME * 0.5), None);
+        assert_eq!(point.capturing, Some(Team::RED));
+        assert!((point.progress - 0.5).abs() < 1e-4);
+
+        // Blue walks in: frozen
+        assert_eq!(point.tick([1, 1], 10.0), None);
+        assert!(point.contested);
+        assert!((point.progress - 0.5).abs() < 1e-4);
+
+        // Two red players capture at 1.5x; progress caps at a full capture
+        let event = point.tick([2, 0], CAPTURE_TIME * 0.5);
+        assert_eq!(event, Some(CaptureEvent::Captured { team: Team::RED, previous: None }));
+        assert_eq!(point.owner, Some(Team::RED));
+        assert_eq!(point.capturing, None);
+        assert!(!point.contested);
+    }
+
+    #[test]
+    fn test_enemy_progress_drains_before_recapture() {
+        let mut point = ControlPoint::new(1, Vec2::ZERO, 40.0);
+        point.owner = Some(Team::RED);
+        point.tick([0, 1], CAPTURE_TIME * 0.4);
+        assert_eq!(point.capturing, Some(Team::BLUE));
+
+        // Owner standing on their own point wipes blue's progress, then idles
+        point.tick([1, 0], CAPTURE_TIME * 0.4);
+        assert_eq!(point.capturing, None);
+        assert_eq!(point.progress, 0.0);
+        assert_eq!(point.tick([1, 0], CAPTURE_TIME), None);
+        assert_eq!(point.owner, Some(Team::RED));
+
+        // Blue takes it over
+        let event = point.tick([0, 1], CAPTURE_TIME);
+        assert_eq!(event, Some(CaptureEvent::Captured { team: Team::BLUE, previous: Some(Team::RED) }));
+        assert_eq!(point.label(), "B");
+    }
+
+    #[test]
+    fn test_v1_territory_save_keeps_owners() {
+        let v1 = TerritorySaveV1 { version: 1, owners: vec![(3, Team::BLUE)] };
+        let save = decode_territory(&bincode::serialize(&v1).unwrap()).unwrap();
+        assert_eq!(save.version, TERRITORY_SAVE_VERSION);
+        assert_eq!(save.owners, vec![(3, Team::BLUE)]);
+        assert!(save.chests.is_empty());
+
+        let mut chest = ChestStorage::new();
+        chest.add_item(ItemType::Wood, 40);
+        let current =
+            TerritorySave { version: TERRITORY_SAVE_VERSION, owners: Vec::new(), chests: vec![(Team::RED, chest)] };
+        assert_eq!(decode_territory(&bincode::serialize(&current).unwrap()).unwrap(), current);
+    }
+}
@@ -0,0 +1,385 @@
+//! Trading with vendor NPCs
+//!
+//! Traders keep stalls in town. Each `TraderKind` has a fixed price list: what it sells
+//! and for how many `Coins`, what it buys back, and how much stock it keeps. Stock runs
+//! down as players buy, and the server restocks one unit per line every
+//! `restock_time` seconds up to `max_stock`. Trades are server-authoritative: the
+//! client sends a `TradeRequest`, the server runs `TraderStock::trade` and answers with
+//! a `TradeResponse`. Gear (weapons and armor) is sold one inventory slot at a time, and
+//! worn-down armor fetches less.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::armor::ArmorType;
+use crate::items::{Inventory, ItemStack, ItemType};
+use crate::weapons::{ExplosiveType, WeaponType};
+
+/// How close a player must stand to a trader to trade (m)
+pub const TRADE_RANGE: f32 = 4.0;
+
+/// Most units a single request may buy or sell
+pub const MAX_TRADE_QUANTITY: u32 = 100;
+
+/// What a trader deals in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TraderKind {
+    /// Resources, ammo and grenades
+    General,
+    /// Armor and melee weapons
+    Armorer,
+    /// Guns and rifle ammo
+    Gunsmith,
+}
+
+/// One line of a trader's price list
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceLine {
+    pub item: ItemType,
+    /// Coins a player pays per unit (None: not for sale)
+    pub buy: Option<u32>,
+    /// Coins the trader pays per unit (None: won't take it)
+    pub sell: Option<u32>,
+    /// Stock a trader restocks up to
+    pub max_stock: u32,
+    /// Seconds to restock one unit
+    pub restock_time: f32,
+}
+
+const fn line(item: ItemType, buy: u32, sell: u32, max_stock: u32, restock_time: f32) -> PriceLine {
+    PriceLine { item, buy: Some(buy), sell: Some(sell), max_stock, restock_time }
+}
+
+const GENERAL_PRICES: &[PriceLine] = &[
+    line(ItemType::Wood, 2, 1, 200, 3.0),
+    line(ItemType::Stone, 2, 1, 200, 3.0),
+    line(ItemType::PistolAmmo, 3, 1, 120, 5.0),
+    line(ItemType::ShotgunShells, 4, 2, 60, 8.0),
+    line(ItemType::Explosive(ExplosiveType::Grenade), 60, 25, 3, 120.0),
+];
+
+const ARMORER_PRICES: &[PriceLine] = &[
+    line(ItemType::Armor(ArmorType::Helmet), 100, 40, 2, 240.0),
+    line(ItemType::Armor(ArmorType::Vest), 150, 60, 2, 300.0),
+    line(ItemType::Weapon(WeaponType::Knife), 30, 10, 3, 90.0),
+    line(ItemType::Weapon(WeaponType::Axe), 45, 15, 2, 120.0),
+    line(ItemType::Weapon(WeaponType::Sledgehammer), 70, 25, 1, 180.0),
+];
+
+const GUNSMITH_PRICES: &[PriceLine] = &[
+    line(ItemType::Weapon(WeaponType::Pistol), 120, 45, 2, 240.0),
+    line(ItemType::Weapon(WeaponType::SMG), 220, 80, 1, 360.0),
+    line(ItemType::Weapon(WeaponType::Shotgun), 250, 90, 1, 360.0),
+    line(ItemType::Weapon(WeaponType::AssaultRifle), 350, 120, 1, 480.0),
+    line(ItemType::Weapon(WeaponType::Sniper), 500, 180, 1, 600.0),
+    line(ItemType::RifleAmmo, 4, 2, 180, 4.0),
+    line(ItemType::SniperRounds, 8, 3, 40, 15.0),
+];
+
+impl TraderKind {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TraderKind::General => "General Goods",
+            TraderKind::Armorer => "Armorer",
+            TraderKind::Gunsmith => "Gunsmith",
+        }
+    }
+
+    pub fn price_list(&self) -> &'static [PriceLine] {
+        match self {
+            TraderKind::General => GENERAL_PRICES,
+            TraderKind::Armorer => ARMORER_PRICES,
+            TraderKind::Gunsmith => GUNSMITH_PRICES,
+        }
+    }
+}
+
+/// Why a trade was refused
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeError {
+    /// No trader with that ID within `TRADE_RANGE`
+    TooFar,
+    /// No such line on the price list
+    UnknownItem,
+    /// Asked for zero units
+    NothingToTrade,
+    NotForSale,
+    NotBuying,
+    OutOfStock,
+    NotEnoughCoins,
+    NotEnoughItems,
+    /// What the player would receive doesn't fit in their inventory
+    InventoryFull,
+    /// Tried to sell the item in the selected hotbar slot
+    ItemEquipped,
+}
+
+impl TradeError {
+    /// Message for the trade window
+    pub fn message(&self) -> &'static str {
+        match self {
+            TradeError::TooFar => "Too far from the trader",
+            TradeError::UnknownItem => "The trader doesn't deal in that",
+            TradeError::NothingToTrade => "Nothing to trade",
+            TradeError::NotForSale => "Not for sale",
+            TradeError::NotBuying => "The trader won't buy that",
+            TradeError::OutOfStock => "Out of stock",
+            TradeError::NotEnoughCoins => "Not enough coins",
+            TradeError::NotEnoughItems => "You don't have that many",
+            TradeError::InventoryFull => "Inventory full",
+            TradeError::ItemEquipped => "Unequip it first",
+        }
+    }
+}
+
+/// A completed trade
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TradeReceipt {
+    pub item: ItemType,
+    pub quantity: u32,
+    /// Coins paid (buying) or received (selling)
+    pub coins: u32,
+    /// true = the player bought, false = the player sold
+    pub bought: bool,
+}
+
+/// A trader's stock, one count per price list line (server-authoritative, replicated)
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraderStock {
+    pub kind: TraderKind,
+    pub stock: Vec<u32>,
+}
+
+impl TraderStock {
+    /// Fully stocked
+    pub fn new(kind: TraderKind) -> Self {
+        Self { kind, stock: kind.price_list().iter().map(|line| line.max_stock).collect() }
+    }
+
+    pub fn price_list(&self) -> &'static [PriceLine] {
+        self.kind.price_list()
+    }
+
+    /// Units on hand for a line
+    pub fn available(&self, line: usize) -> u32 {
+        self.stock.get(line).copied().unwrap_or(0)
+    }
+
+    /// Restock one unit of a line; false when it's already full
+    pub fn restock(&mut self, line: usize) -> bool {
+        let Some(max_stock) = self.price_list().get(line).map(|line| line.max_stock) else {
+            return false;
+        };
+        match self.stock.get_mut(line) {
+            Some(stock) if *stock < max_stock => {
+                *stock += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Buy (`buy`) or sell `quantity` units of a line against `inventory`. All or
+    /// nothing: on error neither the inventory nor the stock changes.
+    ///
+    /// Gear is sold one unit from `sell_slot`, which must not be `equipped_slot`.
+    pub fn trade(
+        &mut self,
+        inventory: &mut Inventory,
+        line: usize,
+        quantity: u32,
+        buy: bool,
+        sell_slot: Option<usize>,
+        equipped_slot: usize,
+    ) -> Result<TradeReceipt, TradeError> {
+        let entry = *self.price_list().get(line).ok_or(TradeError::UnknownItem)?;
+        if quantity == 0 {
+            return Err(TradeError::NothingToTrade);
+        }
+        let mut quantity = quantity.min(MAX_TRADE_QUANTITY);
+
+        // Work on a copy and only keep it once every step went through
+        let mut after = inventory.clone();
+        let coins = if buy {
+            let price = entry.buy.ok_or(TradeError::NotForSale)?;
+            if self.available(line) < quantity {
+                return Err(TradeError::OutOfStock);
+            }
+            let cost = price * quantity;
+            if after.remove_item(ItemType::Coins, cost) < cost {
+                return Err(TradeError::NotEnoughCoins);
+            }
+            if entry.item.max_stack_size() == 1 {
+                for _ in 0..quantity {
+                    if after.add_stack(new_stock_item(entry.item)).is_some() {
+                        return Err(TradeError::InventoryFull);
+                    }
+                }
+            } else if after.add_item(entry.item, quantity) > 0 {
+                return Err(TradeError::InventoryFull);
+            }
+            self.stock[line] -= quantity;
+            cost
+        } else {
+            let price = entry.sell.ok_or(TradeError::NotBuying)?;
+            let payment = if entry.item.max_stack_size() == 1 {
+                let slot = sell_slot.ok_or(TradeError::NotEnoughItems)?;
+                let stack = *after.get_slot(slot).ok_or(TradeError::NotEnoughItems)?;
+                if stack.item_type != entry.item {
+                    return Err(TradeError::NotEnoughItems);
+                }
+                if slot == equipped_slot {
+                    return Err(TradeError::ItemEquipped);
+                }
+                after.set_slot(slot, None);
+                quantity = 1;
+                sell_price(price, &stack)
+            } else {
+                if after.remove_item(entry.item, quantity) < quantity {
+                    return Err(TradeError::NotEnoughItems);
+                }
+                price * quantity
+            };
+            if after.add_item(ItemType::Coins, payment) > 0 {
+                return Err(TradeError::InventoryFull);
+            }
+            if let Some(stock) = self.stock.get_mut(line) {
+                *stock = stock.saturating_add(quantity);
+            }
+            payment
+        };
+
+        *inventory = after;
+        Ok(TradeReceipt { item: entry.item, quantity, coins, bought: buy })
+    }
+}
+
+/// What a trader pays for one piece of gear: armor by how much durability is left
+fn sell_price(price: u32, stack: &ItemStack) -> u32 {
+    match stack.item_type {
+        ItemType::Armor(armor) => {
+            let condition = (stack.get_armor_durability() / armor.max_durability()).clamp(0.0, 1.0);
+            (price as f32 * condition).floor() as u32
+        }
+        _ => price,
+    }
+}
+
+/// A single unit as a trader hands it over: guns loaded, armor new
+pub(crate) fn new_stock_item(item: ItemType) -> ItemStack {
+    match item {
+        ItemType::Weapon(weapon) => ItemStack::new_weapon_full_mag(weapon),
+        ItemType::Armor(armor) => ItemStack::new_armor(armor, armor.max_durability()),
+        item => ItemStack::new(item, 1),
+    }
+}
+
+/// Client -> Server: buy or sell at a trader
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TradeRequest {
+    /// `Npc::id` of the trader
+    pub trader: u64,
+    /// Index into the trader's price list
+    pub line: u8,
+    pub quantity: u32,
+    /// true = buy from the trader, false = sell to it
+    pub buy: bool,
+    /// Inventory slot to sell gear from (ignored for stackable items)
+    pub slot: Option<u8>,
+}
+
+/// Server -> Client: how a `TradeRequest` went
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TradeResponse {
+    pub trader: u64,
+    pub result: Result<TradeReceipt, TradeError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_of(stock: &TraderStock, item: ItemType) -> usize {
+        stock.price_list().iter().position(|line| line.item == item).unwrap()
+    }
+
+    #[test]
+    fn test_buy_and_sell() {
+        let mut stock = TraderStock::new(TraderKind::General);
+        let wood = line_of(&stock, ItemType::Wood);
+        let mut inventory = Inventory::new();
+        inventory.add_item(ItemType::Coins, 50);
+
+        let receipt = stock.trade(&mut inventory, wood, 10, true, None, 0).unwrap();
+        assert_eq!(receipt.coins, 20);
+        assert_eq!(inventory.count_item(ItemType::Wood), 10);
+        assert_eq!(inventory.count_item(ItemType::Coins), 30);
+        assert_eq!(stock.available(wood), 190);
+
+        let receipt = stock.trade(&mut inventory, wood, 10, false, None, 0).unwrap();
+        assert_eq!(receipt.coins, 10);
+        assert_eq!(inventory.count_item(ItemType::Wood), 0);
+        assert_eq!(inventory.count_item(ItemType::Coins), 40);
+        assert_eq!(stock.available(wood), 200);
+
+        // Restocking stops at the max
+        assert!(!stock.restock(wood));
+    }
+
+    #[test]
+    fn test_failed_trades_change_nothing() {
+        let mut stock = TraderStock::new(TraderKind::Gunsmith);
+        let sniper = line_of(&stock, ItemType::Weapon(WeaponType::Sniper));
+        let mut inventory = Inventory::new();
+        inventory.add_item(ItemType::Coins, 499);
+        let (before, stock_before) = (inventory.clone(), stock.clone());
+
+        assert_eq!(stock.trade(&mut inventory, sniper, 1, true, None, 0), Err(TradeError::NotEnoughCoins));
+        assert_eq!(stock.trade(&mut inventory, sniper, 1, false, None, 0), Err(TradeError::NotEnoughItems));
+        assert_eq!(stock.trade(&mut inventory, 99, 1, true, None, 0), Err(TradeError::UnknownItem));
+        assert_eq!(inventory, before);
+        assert_eq!(stock, stock_before);
+
+        // Enough coins but no room for the ammo: the coins stay put
+        let ammo = line_of(&stock, ItemType::RifleAmmo);
+        while inventory.find_empty_slot().is_some() {
+            inventory.add_stack(ItemStack::new_weapon_full_mag(WeaponType::Knife));
+        }
+        let before = inventory.clone();
+        assert_eq!(stock.trade(&mut inventory, ammo, 10, true, None, 0), Err(TradeError::InventoryFull));
+        assert_eq!(inventory, before);
+        assert_eq!(stock.available(ammo), 180);
+    }
+
+    #[test]
+    fn test_bought_gear_is_new() {
+        let mut stock = TraderStock::new(TraderKind::Armorer);
+        let vest = line_of(&stock, ItemType::Armor(ArmorType::Vest));
+        let mut inventory = Inventory::new();
+        inventory.add_item(ItemType::Coins, 150);
+
+        stock.trade(&mut inventory, vest, 1, true, None, 0).unwrap();
+        let (_, bought) = inventory.iter_items().next().unwrap();
+        assert_eq!(bought.get_armor_durability(), ArmorType::Vest.max_durability());
+        assert_eq!(stock.trade(&mut inventory, vest, 1, true, None, 0), Err(TradeError::NotEnoughCoins));
+        assert_eq!(stock.available(vest), 1);
+    }
+
+    #[test]
+    fn test_gear_sells_from_its_slot() {
+        let mut stock = TraderStock::new(TraderKind::Armorer);
+        let vest = line_of(&stock, ItemType::Armor(ArmorType::Vest));
+        let mut inventory = Inventory::new();
+        inventory.set_slot(0, Some(ItemStack::new_armor(ArmorType::Vest, 100.0)));
+        inventory.set_slot(7, Some(ItemStack::new_armor(ArmorType::Vest, 50.0)));
+
+        assert_eq!(stock.trade(&mut inventory, vest, 1, false, Some(0), 0), Err(TradeError::ItemEquipped));
+        assert_eq!(stock.trade(&mut inventory, vest, 1, false, Some(3), 0), Err(TradeError::NotEnoughItems));
+
+        // Only the worn vest goes, for half price
+        let receipt = stock.trade(&mut inventory, vest, 10, false, Some(7), 0).unwrap();
+        assert_eq!((receipt.quantity, receipt.coins), (1, 30));
+        assert!(inventory.get_slot(7).is_none());
+        assert_eq!(inventory.get_slot(0).unwrap().get_armor_durability(), 100.0);
+    }
+}
@@ -1,9 +1,17 @@
 //! Bullet physics simulation
 //!
-//! Realistic ballistics with gravity and air drag.
+//! Realistic ballistics with gravity, air drag and wind.
+//!
+//! Drag acts on the bullet's velocity relative to the air, so wind drift falls out
+//! of the same integration step the server, local tracers and reticles all share.
+//! Each ammo type has a ballistic coefficient that scales how hard drag (and
+//! therefore wind) bites.
 
 use bevy::prelude::*;
 
+use super::WeaponType;
+use crate::items::ItemType;
+
 /// Gravity acceleration for bullets (m/s²)
 pub const BULLET_GRAVITY: f32 = -9.81;
 
@@ -20,7 +28,38 @@ pub const BULLET_MIN_SPEED: f32 = 50.0;
 /// Maximum range before bullet despawns (m)
 pub const BULLET_MAX_RANGE: f32 = 1500.0;
 
-/// Simulate one physics step for a bullet
+/// Crosswind exaggeration for bullets.
+///
+/// Drag here is far lower than for real rounds, so physically exact drift would be
+/// a few centimeters at sniper ranges. Scaling the air speed the bullet "sees" keeps
+/// drift readable (about a meter at 800 m in a 5 m/s crosswind for sniper rounds).
+pub const WIND_DRIFT_SCALE: f32 = 12.0;
+
+/// Integration step used for drop/drift tables (s)
+pub const TRAJECTORY_TABLE_DT: f32 = 1.0 / 240.0;
+
+/// Ballistic coefficient for an ammo type, relative to rifle ammo (1.0).
+///
+/// Higher = less drag: the round keeps its speed, drops less and drifts less.
+pub fn ammo_ballistic_coefficient(ammo: ItemType) -> f32 {
+    match ammo {
+        ItemType::SniperRounds => 1.6,
+        ItemType::RifleAmmo => 1.0,
+        ItemType::PistolAmmo => 0.45,
+        // Round pellets shed speed quickly
+        ItemType::ShotgunShells => 0.1,
+        _ => 1.0,
+    }
+}
+
+impl WeaponType {
+    /// Ballistic coefficient of the rounds this weapon fires
+    pub fn ballistic_coefficient(&self) -> f32 {
+        ammo_ballistic_coefficient(self.ammo_type())
+    }
+}
+
+/// Simulate one physics step for a bullet in still air with the baseline coefficient
 /// 
 /// Returns (new_position, new_velocity)
 /// 
@@ -31,19 +70,35 @@ pub fn step_bullet_physics(
     position: Vec3,
     velocity: Vec3,
     dt: f32,
+) -> (Vec3, Vec3) {
+    step_bullet_physics_in_wind(position, velocity, dt, 1.0, Vec3::ZERO)
+}
+
+/// Simulate one physics step for a bullet with a ballistic coefficient and wind
+///
+/// Returns (new_position, new_velocity)
+///
+/// Drag opposes the velocity relative to the (scaled) moving air, which both
+/// slows the bullet and pushes it downwind.
+pub fn step_bullet_physics_in_wind(
+    position: Vec3,
+    velocity: Vec3,
+    dt: f32,
+    ballistic_coefficient: f32,
+    wind: Vec3,
 ) -> (Vec3, Vec3) {
     let mut vel = velocity;
     
     // Apply gravity (bullet drop)
     vel.y += BULLET_GRAVITY * dt;
     
-    // Apply air drag (velocity-squared drag model)
-    // F_drag = -k * v² * v_hat
-    let speed = vel.length();
-    if speed > 0.1 {
-        let drag_magnitude = BULLET_DRAG_COEFFICIENT * speed * speed;
-        let drag_force = -vel.normalize() * drag_magnitude;
-        vel += drag_force * dt;
+    // Apply air drag (velocity-squared drag model, relative to the air)
+    // F_drag = -k * |v_rel| * v_rel
+    let relative = vel - wind * WIND_DRIFT_SCALE;
+    let relative_speed = relative.length();
+    if relative_speed > 0.1 {
+        let k = BULLET_DRAG_COEFFICIENT / ballistic_coefficient.max(0.01);
+        vel -= relative * (k * relative_speed * dt);
     }
     
     // Integrate position
@@ -52,6 +107,74 @@ pub fn step_bullet_physics(
     (new_pos, vel)
 }
 
+/// One row of a drop/drift table
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrajectoryPoint {
+    /// Downrange distance (m)
+    pub distance: f32,
+    /// Drop below the bore line (m, positive = below)
+    pub drop: f32,
+    /// Sideways drift with the crosswind (m, positive = downwind)
+    pub drift: f32,
+    /// Time of flight (s)
+    pub time: f32,
+}
+
+impl TrajectoryPoint {
+    /// Drop as an angle (mils) - what a reticle needs for holdover
+    pub fn drop_mils(&self) -> f32 {
+        (self.drop / self.distance.max(0.001)).atan() * 1000.0
+    }
+
+    /// Drift as an angle (mils)
+    pub fn drift_mils(&self) -> f32 {
+        (self.drift / self.distance.max(0.001)).atan() * 1000.0
+    }
+}
+
+/// Build a drop/drift table for a level shot at the given ranges.
+///
+/// Fires along -Z through a full-value crosswind blowing toward +X, using the exact
+/// integration step bullets use. `ranges` must be ascending; ranges the bullet never
+/// reaches (too slow / lifetime expired) are omitted.
+pub fn trajectory_table(
+    muzzle_speed: f32,
+    ballistic_coefficient: f32,
+    crosswind: f32,
+    ranges: &[f32],
+) -> Vec<TrajectoryPoint> {
+    let wind = Vec3::X * crosswind;
+    let dt = TRAJECTORY_TABLE_DT;
+    let mut table = Vec::with_capacity(ranges.len());
+    let mut pos = Vec3::ZERO;
+    let mut vel = Vec3::NEG_Z * muzzle_speed;
+    let mut time = 0.0;
+    let mut next = 0;
+
+    while next < ranges.len() && time < BULLET_MAX_LIFETIME && vel.length() >= BULLET_MIN_SPEED {
+        let (new_pos, new_vel) = step_bullet_physics_in_wind(pos, vel, dt, ballistic_coefficient, wind);
+
+        // Emit every range crossed during this step (linear interpolation)
+        while next < ranges.len() && -new_pos.z >= ranges[next] {
+            let range = ranges[next];
+            let t = ((range + pos.z) / (pos.z - new_pos.z)).clamp(0.0, 1.0);
+            let p = pos.lerp(new_pos, t);
+            table.push(TrajectoryPoint {
+                distance: range,
+                drop: -p.y,
+                drift: p.x,
+                time: time + dt * t,
+            });
+            next += 1;
+        }
+
+        pos = new_pos;
+        vel = new_vel;
+        time += dt;
+    }
+    table
+}
+
 /// Calculate bullet drop at a given distance for zeroing/aiming
 /// 
 /// Returns the vertical drop in meters
@@ -125,6 +248,36 @@ mod tests {
         assert!(drop > 0.05 && drop < 0.07);
     }
     
+    #[test]
+    fn test_sniper_drop_drift_table() {
+        let table = trajectory_table(1000.0, ammo_ballistic_coefficient(ItemType::SniperRounds), 5.0, &[100.0, 400.0, 800.0]);
+        assert_eq!(table.len(), 3);
+        let [r100, r400, r800] = [table[0], table[1], table[2]];
+
+        // Drop: slightly more than the vacuum estimate, which ignores the bullet slowing down
+        assert!(r100.drop > 0.049 && r100.drop < 0.055, "100m drop {}", r100.drop);
+        assert!(r400.drop > 0.76 && r400.drop < 0.88, "400m drop {}", r400.drop);
+        assert!(r800.drop > 3.1 && r800.drop < 3.6, "800m drop {}", r800.drop);
+        assert!(r800.drop > calculate_bullet_drop(800.0, 1000.0));
+
+        // Drift: downwind, grows faster than linearly with range
+        assert!(r100.drift > 0.0 && r100.drift < 0.03, "100m drift {}", r100.drift);
+        assert!(r800.drift > 0.6 && r800.drift < 1.5, "800m drift {}", r800.drift);
+        assert!(r800.drift > r400.drift * 3.0);
+    }
+
+    #[test]
+    fn test_ballistic_coefficient_ordering() {
+        let ranges = [50.0];
+        let drift = |weapon: WeaponType| {
+            trajectory_table(weapon.stats().bullet_speed, weapon.ballistic_coefficient(), 5.0, &ranges)[0].drift
+        };
+        // Low-BC rounds drift further at the same range; still air means no drift at all
+        assert!(drift(WeaponType::Pistol) > drift(WeaponType::AssaultRifle));
+        assert!(drift(WeaponType::AssaultRifle) > drift(WeaponType::Sniper));
+        assert_eq!(trajectory_table(900.0, 1.0, 0.0, &ranges)[0].drift, 0.0);
+    }
+
     #[test]
     fn test_step_physics() {
         let pos = Vec3::ZERO;
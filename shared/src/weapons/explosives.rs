@@ -0,0 +1,280 @@
+//! Explosives - thrown grenades and placed charges
+//!
+//! Grenades fly as physics projectiles (same integration as bullets, see
+//! `ballistics::step_bullet_physics`) and bounce off the terrain heightfield.
+//! Charges are stuck to whatever surface the player aims at within `PLACE_RANGE`.
+//! On detonation the server applies distance-attenuated damage to everything in
+//! line of sight and digs a crater through the terrain delta system.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{ballistics, WeaponStats};
+use crate::terrain::WorldTerrain;
+
+/// Collision radius of a thrown explosive (meters)
+pub const THROWABLE_RADIUS: f32 = 0.08;
+
+/// Below this speed a grounded explosive comes to rest (m/s)
+pub const THROWABLE_REST_SPEED: f32 = 0.6;
+
+/// How far from the eye a charge can be placed (meters)
+pub const PLACE_RANGE: f32 = 3.0;
+
+/// Fraction of the blast radius that takes full damage
+pub const BLAST_FULL_DAMAGE_FRACTION: f32 = 0.2;
+
+/// Available explosive types
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum ExplosiveType {
+    /// Thrown, bounces, short fuse
+    Grenade,
+    /// Placed where the player aims, long fuse and a bigger blast
+    Charge,
+}
+
+/// Stats for an explosive type
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExplosiveStats {
+    /// Damage at the center of the blast
+    pub damage: f32,
+    /// Blast radius (meters) - no damage beyond this
+    pub radius: f32,
+    /// Seconds from throw until detonation
+    pub fuse: f32,
+    /// Launch speed (m/s, unused for placed explosives)
+    pub throw_speed: f32,
+    /// Fraction of normal velocity kept on bounce (0 = sticks on contact)
+    pub restitution: f32,
+    /// Fraction of tangential velocity kept on bounce
+    pub friction: f32,
+    /// Crater radius (meters)
+    pub crater_radius: f32,
+    /// Crater depth at the center (meters)
+    pub crater_depth: f32,
+    /// Velocity change (m/s) applied to vehicles at the center of the blast
+    pub vehicle_impulse: f32,
+}
+
+impl ExplosiveType {
+    /// Get the stats for this explosive type
+    pub fn stats(&self) -> ExplosiveStats {
+        match self {
+            ExplosiveType::Grenade => ExplosiveStats {
+                damage: 110.0,
+                radius: 7.0,
+                fuse: 3.5,
+                throw_speed: 18.0,
+                restitution: 0.35,
+                friction: 0.7,
+                crater_radius: 2.5,
+                crater_depth: 0.6,
+                vehicle_impulse: 8.0,
+            },
+            ExplosiveType::Charge => ExplosiveStats {
+                damage: 180.0,
+                radius: 9.0,
+                fuse: 6.0,
+                throw_speed: 0.0,
+                restitution: 0.0,
+                friction: 0.0,
+                crater_radius: 4.0,
+                crater_depth: 1.5,
+                vehicle_impulse: 15.0,
+            },
+        }
+    }
+
+    /// Whether it's stuck where the player aims instead of thrown
+    pub fn is_placed(&self) -> bool {
+        matches!(self, ExplosiveType::Charge)
+    }
+
+    /// Get display name for this explosive
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExplosiveType::Grenade => "Grenade",
+            ExplosiveType::Charge => "Explosive Charge",
+        }
+    }
+}
+
+impl ExplosiveStats {
+    /// Weapon stats for a blast hit that already includes distance attenuation.
+    ///
+    /// Lets explosions go through `apply_armored_hit` like bullets and swings do.
+    pub fn blast_weapon_stats(&self, attenuated_damage: f32) -> WeaponStats {
+        WeaponStats {
+            damage: attenuated_damage,
+            min_damage_mult: 1.0,
+            damage_falloff_start: f32::MAX,
+            damage_falloff_end: f32::MAX,
+            headshot_mult: 1.0,
+            pellet_count: 1,
+            ..WeaponStats::default()
+        }
+    }
+}
+
+/// Damage multiplier at `distance` from a blast of `radius`.
+///
+/// Full damage in the inner core, then a smooth falloff to zero at the edge.
+pub fn blast_falloff(distance: f32, radius: f32) -> f32 {
+    if radius <= 0.0 || distance >= radius {
+        return 0.0;
+    }
+    let core = radius * BLAST_FULL_DAMAGE_FRACTION;
+    if distance <= core {
+        return 1.0;
+    }
+    let t = (distance - core) / (radius - core);
+    (1.0 - t) * (1.0 - t)
+}
+
+/// Reflect a velocity off a surface, damping the normal and tangential parts separately.
+pub fn bounce_velocity(velocity: Vec3, normal: Vec3, restitution: f32, friction: f32) -> Vec3 {
+    let into_surface = velocity.dot(normal);
+    if into_surface >= 0.0 {
+        return velocity;
+    }
+    let normal_part = normal * into_surface;
+    let tangent_part = velocity - normal_part;
+    tangent_part * friction - normal_part * restitution
+}
+
+/// Result of one throwable physics step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThrowableStep {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Came to rest on the ground (stop simulating)
+    pub resting: bool,
+}
+
+/// Simulate one physics step for a thrown explosive.
+///
+/// Uses bullet integration (gravity + drag) and bounces off the terrain using
+/// `WorldTerrain::get_normal`.
+pub fn step_throwable_physics(
+    position: Vec3,
+    velocity: Vec3,
+    dt: f32,
+    stats: &ExplosiveStats,
+    terrain: &WorldTerrain,
+) -> ThrowableStep {
+    let (mut new_pos, mut new_vel) = ballistics::step_bullet_physics(position, velocity, dt);
+
+    let ground = terrain.get_height(new_pos.x, new_pos.z);
+    if new_pos.y - THROWABLE_RADIUS > ground {
+        return ThrowableStep { position: new_pos, velocity: new_vel, resting: false };
+    }
+
+    // Hit the ground: push out and bounce
+    new_pos.y = ground + THROWABLE_RADIUS;
+    let normal = terrain.get_normal(new_pos.x, new_pos.z);
+    new_vel = bounce_velocity(new_vel, normal, stats.restitution, stats.friction);
+
+    let resting = stats.restitution <= 0.0 || new_vel.length() < THROWABLE_REST_SPEED;
+    if resting {
+        new_vel = Vec3::ZERO;
+    }
+    ThrowableStep { position: new_pos, velocity: new_vel, resting }
+}
+
+// =============================================================================
+// REPLICATED STATE & MESSAGES
+// =============================================================================
+
+/// A live explosive in the world (server-spawned, replicated for visuals).
+///
+/// Position is replicated through `PlayerPosition`, like bullets.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Explosive {
+    pub explosive_type: ExplosiveType,
+    /// Client ID of the thrower
+    pub owner_id: u64,
+    /// Server time of detonation
+    pub detonate_at: f32,
+}
+
+/// Client -> Server: throw a grenade or place a charge from the inventory
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ThrowRequest {
+    pub explosive_type: ExplosiveType,
+    /// Normalized aim direction in world space
+    pub direction: Vec3,
+}
+
+/// Server -> Client: an explosive detonated (for effects)
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Explosion {
+    pub explosive_type: ExplosiveType,
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blast_falloff() {
+        assert_eq!(blast_falloff(0.0, 7.0), 1.0);
+        assert_eq!(blast_falloff(7.0 * BLAST_FULL_DAMAGE_FRACTION, 7.0), 1.0);
+        assert_eq!(blast_falloff(7.0, 7.0), 0.0);
+        assert_eq!(blast_falloff(20.0, 7.0), 0.0);
+        // Monotonically decreasing through the falloff zone
+        let mut last = 1.0;
+        for i in 0..=10 {
+            let f = blast_falloff(1.4 + i as f32 * 0.5, 7.0);
+            assert!(f <= last);
+            last = f;
+        }
+    }
+
+    #[test]
+    fn test_bounce_velocity() {
+        let v = bounce_velocity(Vec3::new(4.0, -10.0, 0.0), Vec3::Y, 0.5, 0.8);
+        assert!((v - Vec3::new(3.2, 5.0, 0.0)).length() < 1e-5);
+        // Moving away from the surface is untouched
+        let away = Vec3::new(1.0, 2.0, 0.0);
+        assert_eq!(bounce_velocity(away, Vec3::Y, 0.5, 0.8), away);
+    }
+
+    #[test]
+    fn test_grenade_settles_on_terrain() {
+        let terrain = WorldTerrain::default();
+        let stats = ExplosiveType::Grenade.stats();
+        let start = Vec3::new(12.0, terrain.get_height(12.0, 30.0) + 3.0, 30.0);
+        let mut step = ThrowableStep { position: start, velocity: Vec3::new(5.0, 2.0, 0.0), resting: false };
+        for _ in 0..600 {
+            step = step_throwable_physics(step.position, step.velocity, 1.0 / 60.0, &stats, &terrain);
+            if step.resting {
+                break;
+            }
+        }
+        assert!(step.resting);
+        let ground = terrain.get_height(step.position.x, step.position.z);
+        assert!((step.position.y - (ground + THROWABLE_RADIUS)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_crater_deforms_terrain() {
+        let mut terrain = WorldTerrain::default();
+        let stats = ExplosiveType::Charge.stats();
+        let center = Vec3::new(40.0, 0.0, -40.0);
+        let before = terrain.get_height(center.x, center.z);
+        let far_before = terrain.get_height(center.x + 20.0, center.z);
+
+        let affected = terrain.apply_crater(center, stats.crater_radius, stats.crater_depth);
+        assert!(!affected.is_empty());
+        assert!(terrain.get_height(center.x, center.z) < before - stats.crater_depth * 0.5);
+        // Outside the rim nothing changes, and untouched chunks get no delta
+        assert_eq!(terrain.get_height(center.x + 20.0, center.z), far_before);
+        let mut modified = terrain.get_modified_chunk_coords();
+        modified.sort_by_key(|c| (c.x, c.z));
+        let mut affected = affected;
+        affected.sort_by_key(|c| (c.x, c.z));
+        assert_eq!(modified, affected);
+    }
+}
@@ -0,0 +1,135 @@
+//! Fire modes - semi-auto, burst and full-auto
+//!
+//! Each weapon supports a fixed set of modes; the selected one lives in the
+//! replicated `EquippedWeapon`. The server never trusts the client's idea of a
+//! trigger pull: rounds that follow each other faster than the weapon's
+//! `trigger_pull_interval` count as one pull, which caps a pull at 1 round for
+//! semi, N for burst and unlimited for auto.
+
+use serde::{Deserialize, Serialize};
+
+use super::WeaponType;
+
+/// Seconds a trigger needs on top of the fire cooldown to be released and pulled again
+pub const TRIGGER_RESET_TIME: f32 = 0.08;
+
+/// How many rounds one trigger pull fires
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum FireMode {
+    /// One round per pull
+    #[default]
+    Semi,
+    /// Up to N rounds per pull (the burst completes even if the trigger is released)
+    Burst(u8),
+    /// Fires for as long as the trigger is held
+    Auto,
+}
+
+impl FireMode {
+    /// Rounds allowed per trigger pull (None = unlimited)
+    pub fn shots_per_pull(&self) -> Option<u32> {
+        match self {
+            FireMode::Semi => Some(1),
+            FireMode::Burst(n) => Some(u32::from(*n).max(1)),
+            FireMode::Auto => None,
+        }
+    }
+
+    /// Whether another round may be fired after `shots_this_pull` rounds of the current pull
+    pub fn allows_shot(&self, shots_this_pull: u32) -> bool {
+        self.shots_per_pull().is_none_or(|max| shots_this_pull < max)
+    }
+
+    /// Short HUD label
+    pub fn label(&self) -> String {
+        match self {
+            FireMode::Semi => "SEMI".to_string(),
+            FireMode::Burst(n) => format!("BURST {}", n),
+            FireMode::Auto => "AUTO".to_string(),
+        }
+    }
+}
+
+impl WeaponType {
+    /// Fire modes this weapon supports (the first one is the default)
+    pub fn fire_modes(&self) -> &'static [FireMode] {
+        match self {
+            WeaponType::AssaultRifle => &[FireMode::Auto, FireMode::Burst(3), FireMode::Semi],
+            WeaponType::SMG => &[FireMode::Auto, FireMode::Burst(3), FireMode::Semi],
+            WeaponType::Pistol => &[FireMode::Semi],
+            WeaponType::Sniper => &[FireMode::Semi],
+            WeaponType::Shotgun => &[FireMode::Semi],
+            // Melee swings repeat while held, limited by the swing cooldown
+            WeaponType::Unarmed | WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => &[FireMode::Auto],
+        }
+    }
+
+    /// Fire mode selected when this weapon is equipped
+    pub fn default_fire_mode(&self) -> FireMode {
+        self.fire_modes()[0]
+    }
+
+    /// Shortest gap between rounds of two separate trigger pulls
+    pub fn trigger_pull_interval(&self) -> f32 {
+        self.fire_cooldown() + TRIGGER_RESET_TIME
+    }
+
+    /// Mode after `current` in this weapon's cycle (falls back to the default if unsupported)
+    pub fn next_fire_mode(&self, current: FireMode) -> FireMode {
+        let modes = self.fire_modes();
+        match modes.iter().position(|m| *m == current) {
+            Some(i) => modes[(i + 1) % modes.len()],
+            None => self.default_fire_mode(),
+        }
+    }
+}
+
+/// Client -> Server: cycle to the equipped weapon's next fire mode
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CycleFireModeRequest;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shots_per_pull() {
+        assert!(FireMode::Semi.allows_shot(0));
+        assert!(!FireMode::Semi.allows_shot(1));
+        assert!(FireMode::Burst(3).allows_shot(2));
+        assert!(!FireMode::Burst(3).allows_shot(3));
+        assert!(FireMode::Auto.allows_shot(1000));
+    }
+
+    #[test]
+    fn test_fire_mode_cycle() {
+        let ar = WeaponType::AssaultRifle;
+        assert_eq!(ar.default_fire_mode(), FireMode::Auto);
+        assert_eq!(ar.next_fire_mode(FireMode::Auto), FireMode::Burst(3));
+        assert_eq!(ar.next_fire_mode(FireMode::Burst(3)), FireMode::Semi);
+        assert_eq!(ar.next_fire_mode(FireMode::Semi), FireMode::Auto);
+
+        // Single-mode weapons stay put; unsupported modes snap back to the default
+        assert_eq!(WeaponType::Sniper.next_fire_mode(FireMode::Semi), FireMode::Semi);
+        assert_eq!(WeaponType::Sniper.next_fire_mode(FireMode::Auto), FireMode::Semi);
+    }
+
+    #[test]
+    fn test_pulls_counted_from_shot_timing() {
+        let mut pistol = crate::EquippedWeapon::new(WeaponType::Pistol);
+        let interval = WeaponType::Pistol.trigger_pull_interval();
+        assert!(pistol.fire_in_mode(0.0));
+        // Holding the trigger (rounds at the cooldown) is one pull: semi refuses the second round
+        assert!(!pistol.fire_in_mode(WeaponType::Pistol.fire_cooldown()));
+        assert!(pistol.fire_in_mode(interval));
+
+        let mut rifle = crate::EquippedWeapon::new(WeaponType::AssaultRifle);
+        rifle.fire_mode = FireMode::Burst(3);
+        let cooldown = WeaponType::AssaultRifle.fire_cooldown();
+        for i in 0..3 {
+            assert!(rifle.fire_in_mode(i as f32 * cooldown));
+        }
+        assert!(!rifle.fire_in_mode(3.0 * cooldown));
+        assert!(rifle.fire_in_mode(2.0 * cooldown + WeaponType::AssaultRifle.trigger_pull_interval() + 0.01));
+    }
+}
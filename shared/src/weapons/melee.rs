@@ -0,0 +1,171 @@
+//! Melee system - punches and melee weapons
+//!
+//! Melee attacks are defined by a horizontal swing arc, reach, wind-up and damage.
+//! The server resolves a swing after its wind-up by sweeping a fan of rays across
+//! the arc (see `sweep_directions`) and testing them against hitbox rigs and vehicle
+//! boxes. Swings hurt players and NPCs and shove vehicles; walls stop them.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::WeaponType;
+
+/// Number of rays used to sweep a swing arc.
+pub const MELEE_SWEEP_RAYS: usize = 7;
+
+/// Stats for a melee attack
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeleeStats {
+    /// Base damage per hit (hit-zone multipliers still apply)
+    pub damage: f32,
+    /// Maximum reach from the eye (meters)
+    pub reach: f32,
+    /// Full horizontal swing arc (radians)
+    pub arc: f32,
+    /// Time from click until the hit lands (seconds)
+    pub wind_up: f32,
+    /// Time after the hit before the next swing can start (seconds)
+    pub recovery: f32,
+    /// Headshot damage multiplier
+    pub headshot_mult: f32,
+    /// Velocity change (m/s) applied to vehicles that get hit
+    pub vehicle_impulse: f32,
+}
+
+impl MeleeStats {
+    /// Total time one swing occupies (wind-up + recovery)
+    pub fn swing_duration(&self) -> f32 {
+        self.wind_up + self.recovery
+    }
+}
+
+impl WeaponType {
+    /// Whether this weapon attacks with melee swings instead of bullets
+    pub fn is_melee(&self) -> bool {
+        self.melee_stats().is_some()
+    }
+
+    /// Melee stats (None for firearms)
+    pub fn melee_stats(&self) -> Option<MeleeStats> {
+        match self {
+            WeaponType::Unarmed => Some(MeleeStats {
+                damage: 12.0,
+                reach: 1.6,
+                arc: 0.5,
+                wind_up: 0.12,
+                recovery: 0.33,
+                headshot_mult: 1.5,
+                vehicle_impulse: 0.3,
+            }),
+            WeaponType::Knife => Some(MeleeStats {
+                damage: 30.0,
+                reach: 1.8,
+                arc: 0.7,
+                wind_up: 0.15,
+                recovery: 0.4,
+                headshot_mult: 1.8,
+                vehicle_impulse: 0.3,
+            }),
+            WeaponType::Axe => Some(MeleeStats {
+                damage: 50.0,
+                reach: 2.1,
+                arc: 1.2,
+                wind_up: 0.35,
+                recovery: 0.6,
+                headshot_mult: 2.0,
+                vehicle_impulse: 1.0,
+            }),
+            WeaponType::Sledgehammer => Some(MeleeStats {
+                damage: 75.0,
+                reach: 2.3,
+                arc: 1.5,
+                wind_up: 0.6,
+                recovery: 0.9,
+                headshot_mult: 2.0,
+                vehicle_impulse: 3.0,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Directions of the sweep rays for a swing, spread evenly across `arc`
+/// around the world up axis (pitch of `forward` is preserved).
+///
+/// Ordered right-to-left, matching the on-screen swing.
+pub fn sweep_directions(forward: Vec3, arc: f32) -> [Vec3; MELEE_SWEEP_RAYS] {
+    let forward = forward.normalize_or_zero();
+    let mut dirs = [forward; MELEE_SWEEP_RAYS];
+    for (i, dir) in dirs.iter_mut().enumerate() {
+        let t = i as f32 / (MELEE_SWEEP_RAYS - 1) as f32;
+        // Negative yaw turns right (yaw convention: forward = -Z, +yaw turns left)
+        let angle = -arc * 0.5 + arc * t;
+        *dir = Quat::from_rotation_y(angle) * forward;
+    }
+    dirs
+}
+
+/// Whether `target` is inside the swing cone (distance and horizontal angle).
+/// `target_radius` widens the reach for bulky targets.
+pub fn in_swing_arc(origin: Vec3, forward: Vec3, target: Vec3, reach: f32, arc: f32, target_radius: f32) -> bool {
+    let to_target = target - origin;
+    if to_target.length() > reach + target_radius {
+        return false;
+    }
+    let flat_to = Vec2::new(to_target.x, to_target.z);
+    let flat_fwd = Vec2::new(forward.x, forward.z);
+    if flat_to.length_squared() < 1e-6 || flat_fwd.length_squared() < 1e-6 {
+        return true;
+    }
+    flat_fwd.normalize().angle_to(flat_to.normalize()).abs() <= arc * 0.5
+}
+
+// =============================================================================
+// REPLICATED SWING STATE
+// =============================================================================
+
+/// A melee swing in progress (server inserts it on the attacker during wind-up).
+///
+/// Replicated so clients can play the swing animation on remote players.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MeleeSwing {
+    pub weapon_type: WeaponType,
+    /// Aim direction when the swing started
+    pub direction: Vec3,
+    /// Server time when the hit resolves
+    pub resolve_at: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_covers_arc() {
+        let dirs = sweep_directions(Vec3::NEG_Z, 1.0);
+        // Middle ray is straight ahead
+        assert!((dirs[MELEE_SWEEP_RAYS / 2] - Vec3::NEG_Z).length() < 1e-5);
+        // Outer rays are half the arc off-center, starting on the right (+X)
+        assert!(dirs[0].x > 0.0);
+        assert!(dirs[MELEE_SWEEP_RAYS - 1].x < 0.0);
+        assert!((dirs[0].angle_between(Vec3::NEG_Z) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_swing_arc_check() {
+        let origin = Vec3::ZERO;
+        assert!(in_swing_arc(origin, Vec3::NEG_Z, Vec3::new(0.0, 0.0, -1.5), 2.0, 1.0, 0.0));
+        // Behind the attacker
+        assert!(!in_swing_arc(origin, Vec3::NEG_Z, Vec3::new(0.0, 0.0, 1.5), 2.0, 1.0, 0.0));
+        // Out of reach unless the target is bulky
+        assert!(!in_swing_arc(origin, Vec3::NEG_Z, Vec3::new(0.0, 0.0, -3.0), 2.0, 1.0, 0.0));
+        assert!(in_swing_arc(origin, Vec3::NEG_Z, Vec3::new(0.0, 0.0, -3.0), 2.0, 1.0, 1.1));
+    }
+
+    #[test]
+    fn test_only_melee_weapons_have_melee_stats() {
+        assert!(WeaponType::Unarmed.is_melee());
+        assert!(WeaponType::Sledgehammer.is_melee());
+        assert!(!WeaponType::AssaultRifle.is_melee());
+    }
+}
@@ -4,6 +4,21 @@
 
 pub mod ballistics;
 pub mod damage;
+pub mod explosives;
+pub mod fire_mode;
+pub mod melee;
+pub mod recoil;
+pub mod reload;
+pub mod suppression;
+pub mod zeroing;
+
+pub use explosives::*;
+pub use fire_mode::*;
+pub use melee::*;
+pub use recoil::*;
+pub use reload::*;
+pub use suppression::*;
+pub use zeroing::*;
 
 use serde::{Deserialize, Serialize};
 use crate::items::ItemType;
@@ -12,7 +27,7 @@ use crate::items::ItemType;
 // RECOIL CONSTANTS
 // =============================================================================
 
-/// Speed at which recoil recovers back to center (per second).
+/// Speed at which recoil recovers back to center once a burst ends (per second).
 pub const RECOIL_RECOVERY_SPEED: f32 = 4.0;
 
 /// Time in seconds before burst shot counter resets.
@@ -21,9 +36,6 @@ pub const RECOIL_BURST_RESET_TIME: f32 = 0.35;
 /// Recoil multiplier when aiming down sights (lower = less recoil).
 pub const RECOIL_ADS_MULTIPLIER: f32 = 0.5;
 
-/// Multiplier applied to recoil for consecutive shots in a burst.
-pub const RECOIL_ACCUMULATION_MULT: f32 = 1.15;
-
 // =============================================================================
 // WEAPON TYPES
 // =============================================================================
@@ -37,8 +49,11 @@ pub enum WeaponType {
     Sniper,
     Shotgun,
     SMG,
-    /// No weapon equipped
+    /// No weapon equipped (punches)
     Unarmed,
+    Knife,
+    Axe,
+    Sledgehammer,
 }
 
 /// Complete stats for a weapon type
@@ -58,9 +73,9 @@ pub struct WeaponStats {
     pub spread_hip: f32,
     /// Spread cone (radians) when aiming down sights
     pub spread_ads: f32,
-    /// Vertical recoil per shot (radians)
+    /// Vertical recoil per shot (radians, scaled by `recoil_pattern`)
     pub recoil_vertical: f32,
-    /// Horizontal recoil per shot (radians)
+    /// Horizontal recoil per shot (radians, scaled by `recoil_pattern`)
     pub recoil_horizontal: f32,
     /// Distance (m) where damage falloff begins
     pub damage_falloff_start: f32,
@@ -158,22 +173,27 @@ impl WeaponType {
                 headshot_mult: 1.8,
                 pellet_count: 1,
             },
-            WeaponType::Unarmed => WeaponStats {
-                damage: 0.0,
-                fire_rate: 0.0,
-                bullet_speed: 0.0,
-                magazine_size: 0,
-                reload_time: 0.0,
-                spread_hip: 0.0,
-                spread_ads: 0.0,
-                recoil_vertical: 0.0,
-                recoil_horizontal: 0.0,
-                damage_falloff_start: 0.0,
-                damage_falloff_end: 0.0,
-                min_damage_mult: 0.0,
-                headshot_mult: 0.0,
-                pellet_count: 1,
-            },
+            WeaponType::Unarmed | WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => {
+                // Melee: no ballistics. Damage/headshot come from the melee stats and
+                // falloff never kicks in, so `damage::calculate_damage` works for swings too.
+                let melee = self.melee_stats().expect("melee weapon has melee stats");
+                WeaponStats {
+                    damage: melee.damage,
+                    fire_rate: 1.0 / melee.swing_duration(),
+                    bullet_speed: 0.0,
+                    magazine_size: 0,
+                    reload_time: 0.0,
+                    spread_hip: 0.0,
+                    spread_ads: 0.0,
+                    recoil_vertical: 0.0,
+                    recoil_horizontal: 0.0,
+                    damage_falloff_start: melee.reach * 2.0,
+                    damage_falloff_end: melee.reach * 3.0,
+                    min_damage_mult: 1.0,
+                    headshot_mult: melee.headshot_mult,
+                    pellet_count: 1,
+                }
+            }
         }
     }
     
@@ -190,7 +210,8 @@ impl WeaponType {
             WeaponType::Sniper => ItemType::SniperRounds,
             WeaponType::Shotgun => ItemType::ShotgunShells,
             WeaponType::SMG => ItemType::PistolAmmo, // SMG uses pistol ammo
-            WeaponType::Unarmed => ItemType::RifleAmmo, // unused; callers should special-case Unarmed
+            // unused; callers should special-case melee weapons
+            WeaponType::Unarmed | WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => ItemType::RifleAmmo,
         }
     }
     
@@ -0,0 +1,183 @@
+//! Authored recoil patterns
+//!
+//! Every weapon kicks through a fixed sequence of pitch/yaw offsets, indexed by the
+//! round's position in the current burst. Client and server walk the same pattern:
+//! the client kicks the camera, the server checks that the aim the client reports
+//! carries the recoil it should and re-applies the pattern when it doesn't. The
+//! reported offset alone proves nothing, so the server also checks that the shot
+//! direction itself climbs between consecutive rounds the way the pattern says.
+
+use bevy::prelude::*;
+
+use super::{apply_zero, WeaponType, RECOIL_ADS_MULTIPLIER, RECOIL_BURST_RESET_TIME};
+
+/// Once the authored pattern runs out, the last this-many kicks repeat
+pub const RECOIL_PATTERN_LOOP: usize = 4;
+
+/// Absolute slack (radians) when comparing a reported recoil offset with the pattern
+pub const RECOIL_VALIDATION_TOLERANCE: f32 = 0.01;
+
+/// Absolute slack (radians) when checking the aim step between two rounds of a burst
+pub const RECOIL_STEP_TOLERANCE: f32 = 0.002;
+
+/// Share of a round's kick the player can pull back before the next round fires
+pub const RECOIL_MAX_COMPENSATION: f32 = 0.8;
+
+/// Pistol: sharp climb with alternating sideways snap
+const PISTOL_PATTERN: [[f32; 2]; 4] = [[1.0, 0.3], [1.1, -0.5], [1.2, 0.5], [1.2, -0.5]];
+
+/// Assault rifle: strong early climb, then a slow left-right sway
+const RIFLE_PATTERN: [[f32; 2]; 14] = [
+    [1.0, 0.0], [1.0, 0.2], [0.9, -0.1], [0.8, 0.4], [0.7, 0.6], [0.6, 0.5], [0.5, -0.3],
+    [0.4, -0.8], [0.35, -1.0], [0.3, -0.6], [0.25, 0.4], [0.25, 0.9], [0.25, 0.3], [0.25, -0.9],
+];
+
+/// SMG: flatter climb, wide horizontal wobble
+const SMG_PATTERN: [[f32; 2]; 12] = [
+    [0.8, 0.3], [0.8, 0.5], [0.7, 0.2], [0.6, -0.4], [0.5, -0.7], [0.4, -0.5],
+    [0.35, 0.3], [0.3, 0.8], [0.3, 0.6], [0.25, -0.2], [0.25, -0.7], [0.25, 0.6],
+];
+
+/// Sniper: one heavy kick per round
+const SNIPER_PATTERN: [[f32; 2]; 1] = [[1.0, 0.2]];
+
+/// Shotgun: big kick, drifting alternately left and right
+const SHOTGUN_PATTERN: [[f32; 2]; 2] = [[1.0, -0.3], [1.1, 0.3]];
+
+impl WeaponType {
+    /// Recoil pattern as `[pitch, yaw]` multiples of `recoil_vertical` / `recoil_horizontal`.
+    ///
+    /// Positive pitch kicks up, positive yaw kicks left. Empty for melee.
+    pub fn recoil_pattern(&self) -> &'static [[f32; 2]] {
+        match self {
+            WeaponType::Pistol => &PISTOL_PATTERN,
+            WeaponType::AssaultRifle => &RIFLE_PATTERN,
+            WeaponType::SMG => &SMG_PATTERN,
+            WeaponType::Sniper => &SNIPER_PATTERN,
+            WeaponType::Shotgun => &SHOTGUN_PATTERN,
+            WeaponType::Unarmed | WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => &[],
+        }
+    }
+
+    /// Camera kick (x = pitch, y = yaw, radians) for the `shot_index`-th round of a burst
+    pub fn recoil_kick(&self, shot_index: u32, aiming: bool) -> Vec2 {
+        let pattern = self.recoil_pattern();
+        if pattern.is_empty() {
+            return Vec2::ZERO;
+        }
+        let index = shot_index as usize;
+        let entry = if index < pattern.len() {
+            pattern[index]
+        } else {
+            let loop_len = RECOIL_PATTERN_LOOP.min(pattern.len());
+            let loop_start = pattern.len() - loop_len;
+            pattern[loop_start + (index - pattern.len()) % loop_len]
+        };
+        let stats = self.stats();
+        let ads_mult = if aiming { RECOIL_ADS_MULTIPLIER } else { 1.0 };
+        Vec2::new(entry[0] * stats.recoil_vertical, entry[1] * stats.recoil_horizontal) * ads_mult
+    }
+}
+
+/// Position in the burst of the round being fired now.
+///
+/// `burst_shots` rounds have been fired so far and the last one was `since_last_shot`
+/// seconds ago. Close to the reset time the client and server clocks can disagree,
+/// so there the client's `claimed` reset is accepted.
+pub fn burst_shot_index(burst_shots: u32, since_last_shot: f32, claimed: u32) -> u32 {
+    if since_last_shot > RECOIL_BURST_RESET_TIME * 1.5
+        || (since_last_shot > RECOIL_BURST_RESET_TIME * 0.5 && claimed == 0)
+    {
+        0
+    } else {
+        burst_shots
+    }
+}
+
+/// Whether a reported recoil offset is close enough to the expected one
+pub fn recoil_offset_matches(expected: Vec2, reported: Vec2) -> bool {
+    (expected - reported).length() <= RECOIL_VALIDATION_TOLERANCE + expected.length() * 0.1
+}
+
+/// Pitch (up) and yaw (left) of an aim direction, in the same convention as recoil offsets
+fn aim_angles(direction: Vec3) -> Vec2 {
+    let dir = direction.normalize_or(Vec3::NEG_Z);
+    Vec2::new(dir.y.clamp(-1.0, 1.0).asin(), (-dir.x).atan2(-dir.z))
+}
+
+/// Whether the aim moved from `previous` to `current` the way the earlier round's `kick` says.
+///
+/// The player may pull against the kick, but not by more than `RECOIL_MAX_COMPENSATION`
+/// of it: aim that sits still round after round hints the client is stripping recoil.
+pub fn recoil_step_matches(previous: Vec3, current: Vec3, kick: Vec2) -> bool {
+    let kick_len = kick.length();
+    if kick_len <= f32::EPSILON {
+        return true;
+    }
+    let mut step = aim_angles(current) - aim_angles(previous);
+    step.y = (step.y + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+    step.dot(kick / kick_len) >= kick_len * (1.0 - RECOIL_MAX_COMPENSATION) - RECOIL_STEP_TOLERANCE
+}
+
+/// Rotate an aim direction by a recoil offset (x = pitch up, y = yaw left)
+pub fn apply_recoil(direction: Vec3, offset: Vec2) -> Vec3 {
+    (Quat::from_rotation_y(offset.y) * apply_zero(direction, offset.x)).normalize()
+}
+
+/// Undo `apply_recoil`, recovering the aim the player actually held
+pub fn remove_recoil(direction: Vec3, offset: Vec2) -> Vec3 {
+    apply_zero(Quat::from_rotation_y(-offset.y) * direction.normalize_or(Vec3::NEG_Z), -offset.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recoil_pattern_is_deterministic_and_loops() {
+        let rifle = WeaponType::AssaultRifle;
+        let len = rifle.recoil_pattern().len() as u32;
+        assert_eq!(rifle.recoil_kick(3, false), rifle.recoil_kick(3, false));
+        // Past the end the tail of the pattern repeats
+        assert_eq!(rifle.recoil_kick(len, false), rifle.recoil_kick(len - RECOIL_PATTERN_LOOP as u32, false));
+        assert_eq!(rifle.recoil_kick(len + RECOIL_PATTERN_LOOP as u32, false), rifle.recoil_kick(len, false));
+        // ADS scales the kick, melee has none
+        assert_eq!(rifle.recoil_kick(0, true), rifle.recoil_kick(0, false) * RECOIL_ADS_MULTIPLIER);
+        assert_eq!(WeaponType::Knife.recoil_kick(0, false), Vec2::ZERO);
+        assert!(WeaponType::Sniper.recoil_kick(5, false).x > 0.0);
+    }
+
+    #[test]
+    fn test_apply_and_remove_recoil_round_trip() {
+        let aim = Vec3::new(0.3, -0.1, -1.0).normalize();
+        let offset = Vec2::new(0.05, -0.03);
+        let kicked = apply_recoil(aim, offset);
+        assert!(kicked.y > aim.y);
+        assert!((remove_recoil(kicked, offset) - aim).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_burst_shot_index() {
+        // Mid-spray the count continues regardless of what the client claims
+        assert_eq!(burst_shot_index(7, 0.09, 0), 7);
+        // Long pause always resets
+        assert_eq!(burst_shot_index(7, 2.0, 7), 0);
+        // Near the reset time either answer is plausible
+        assert_eq!(burst_shot_index(7, RECOIL_BURST_RESET_TIME, 0), 0);
+        assert_eq!(burst_shot_index(7, RECOIL_BURST_RESET_TIME, 7), 7);
+        assert!(recoil_offset_matches(Vec2::new(0.2, 0.0), Vec2::new(0.21, 0.0)));
+        assert!(!recoil_offset_matches(Vec2::new(0.2, 0.0), Vec2::ZERO));
+    }
+
+    #[test]
+    fn test_recoil_step_needs_the_aim_to_climb() {
+        let aim = Vec3::new(0.2, 0.0, -1.0).normalize();
+        let kick = WeaponType::AssaultRifle.recoil_kick(0, false);
+        // The camera carried the kick, or the player pulled back part of it
+        assert!(recoil_step_matches(aim, apply_recoil(aim, kick), kick));
+        assert!(recoil_step_matches(aim, apply_recoil(aim, kick * 0.5), kick));
+        // Aim that didn't move at all (or moved against the kick) is a stripped pattern
+        assert!(!recoil_step_matches(aim, aim, kick));
+        assert!(!recoil_step_matches(aim, apply_recoil(aim, -kick), kick));
+    }
+}
@@ -0,0 +1,188 @@
+//! Timed reloads
+//!
+//! The server drives a `Reloading` state machine on the player entity. Magazine
+//! weapons swap the whole magazine when the timer runs out; the shotgun inserts one
+//! shell at a time and can be interrupted between shells. Reloading from an empty
+//! magazine takes longer (a round has to be chambered).
+//!
+//! `Reloading` is replicated, so the client's HUD, sounds and animation follow the
+//! server's reload. Only the start is sent: both sides derive progress from their own
+//! clock, so the component changes when a reload starts and when a shell goes in.
+
+use serde::{Deserialize, Serialize};
+
+use super::WeaponType;
+
+/// How a weapon reloads
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReloadTiming {
+    /// Swap the whole magazine
+    Magazine {
+        /// Seconds with a round still chambered
+        tactical: f32,
+        /// Seconds from an empty magazine
+        empty: f32,
+    },
+    /// Insert shells one by one (interruptible between shells)
+    PerShell {
+        /// Seconds before the first shell goes in
+        start: f32,
+        /// Seconds per shell
+        per_shell: f32,
+        /// Extra seconds to chamber a round when starting empty
+        chamber: f32,
+    },
+}
+
+impl WeaponType {
+    /// Reload timing for this weapon (None for melee)
+    pub fn reload_timing(&self) -> Option<ReloadTiming> {
+        let tactical = self.stats().reload_time;
+        match self {
+            WeaponType::Pistol => Some(ReloadTiming::Magazine { tactical, empty: 1.9 }),
+            WeaponType::AssaultRifle => Some(ReloadTiming::Magazine { tactical, empty: 2.9 }),
+            WeaponType::Sniper => Some(ReloadTiming::Magazine { tactical, empty: 4.5 }),
+            WeaponType::SMG => Some(ReloadTiming::Magazine { tactical, empty: 2.5 }),
+            WeaponType::Shotgun => Some(ReloadTiming::PerShell { start: 0.35, per_shell: tactical, chamber: 0.6 }),
+            WeaponType::Unarmed | WeaponType::Knife | WeaponType::Axe | WeaponType::Sledgehammer => None,
+        }
+    }
+}
+
+/// What happened during one reload tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReloadStep {
+    /// Still working
+    InProgress,
+    /// A shell should go in now (per-shell reloads)
+    InsertShell,
+    /// The magazine should be refilled now and the reload ends
+    SwapMagazine,
+}
+
+/// A reload in progress on the player entity (server-driven, replicated).
+#[derive(bevy::prelude::Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Reloading {
+    /// Weapon being reloaded (switching weapons cancels the reload)
+    pub weapon_type: WeaponType,
+    /// Started from an empty magazine (slower)
+    pub empty: bool,
+    /// Server time (seconds) the reload started
+    pub started_at: f32,
+    /// Rounds the reload is expected to load (shells for per-shell reloads)
+    pub rounds_needed: u32,
+    /// Shells inserted so far (per-shell reloads)
+    pub shells_loaded: u32,
+}
+
+impl Reloading {
+    /// Start reloading `weapon_type` at `now` with `ammo_in_mag` rounds left and `rounds_needed` to load
+    pub fn start(weapon_type: WeaponType, ammo_in_mag: u32, rounds_needed: u32, now: f32) -> Self {
+        Self {
+            weapon_type,
+            empty: ammo_in_mag == 0,
+            started_at: now,
+            rounds_needed,
+            shells_loaded: 0,
+        }
+    }
+
+    /// Whether this reload inserts shells one at a time
+    pub fn is_per_shell(&self) -> bool {
+        matches!(self.weapon_type.reload_timing(), Some(ReloadTiming::PerShell { .. }))
+    }
+
+    /// Seconds from the start until shell `n` (1-based) is inserted
+    fn shell_time(start: f32, per_shell: f32, chamber: f32, empty: bool, n: u32) -> f32 {
+        start + if empty { chamber } else { 0.0 } + per_shell * n as f32
+    }
+
+    /// Total expected duration (seconds)
+    pub fn duration(&self) -> f32 {
+        match self.weapon_type.reload_timing() {
+            Some(ReloadTiming::Magazine { tactical, empty }) => if self.empty { empty } else { tactical },
+            Some(ReloadTiming::PerShell { start, per_shell, chamber }) => {
+                Self::shell_time(start, per_shell, chamber, self.empty, self.rounds_needed.max(1))
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Progress 0.0-1.0 after `elapsed` seconds
+    pub fn progress(&self, elapsed: f32) -> f32 {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return 1.0;
+        }
+        (elapsed / duration).clamp(0.0, 1.0)
+    }
+
+    /// Report what the server should do at time `now`.
+    ///
+    /// Per-shell reloads report at most one shell per call; the caller bumps
+    /// `shells_loaded` once the shell is actually taken from the inventory.
+    pub fn step(&self, now: f32) -> ReloadStep {
+        let elapsed = now - self.started_at;
+        match self.weapon_type.reload_timing() {
+            Some(ReloadTiming::Magazine { .. }) | None => {
+                if elapsed >= self.duration() {
+                    ReloadStep::SwapMagazine
+                } else {
+                    ReloadStep::InProgress
+                }
+            }
+            Some(ReloadTiming::PerShell { start, per_shell, chamber }) => {
+                let next = Self::shell_time(start, per_shell, chamber, self.empty, self.shells_loaded + 1);
+                if elapsed >= next {
+                    ReloadStep::InsertShell
+                } else {
+                    ReloadStep::InProgress
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magazine_reload_tactical_vs_empty() {
+        let dt = 1.0 / 60.0;
+        let run = |ammo_in_mag: u32| {
+            let reload = Reloading::start(WeaponType::AssaultRifle, ammo_in_mag, 30 - ammo_in_mag, 0.0);
+            let mut ticks = 0;
+            while reload.step((ticks + 1) as f32 * dt) == ReloadStep::InProgress {
+                ticks += 1;
+            }
+            ticks as f32 * dt
+        };
+        let tactical = run(10);
+        let empty = run(0);
+        assert!((tactical - WeaponType::AssaultRifle.stats().reload_time).abs() < 0.05);
+        assert!(empty > tactical + 0.4);
+    }
+
+    #[test]
+    fn test_shotgun_inserts_shells_one_at_a_time() {
+        let dt = 1.0 / 60.0;
+        let mut reload = Reloading::start(WeaponType::Shotgun, 2, 3, 0.0);
+        let mut shell_ticks = Vec::new();
+        let mut tick = 0;
+        while reload.shells_loaded < 3 {
+            tick += 1;
+            if reload.step(tick as f32 * dt) == ReloadStep::InsertShell {
+                reload.shells_loaded += 1;
+                shell_ticks.push(tick);
+            }
+        }
+        // Shells arrive one per-shell interval apart
+        let per_shell_ticks = (WeaponType::Shotgun.stats().reload_time / dt).round() as i32;
+        assert_eq!(shell_ticks.len(), 3);
+        assert!((shell_ticks[1] - shell_ticks[0] - per_shell_ticks).abs() <= 1);
+        let elapsed = tick as f32 * dt;
+        assert!((elapsed - reload.duration()).abs() < dt * 1.5);
+        assert!((reload.progress(elapsed) - 1.0).abs() < 0.02);
+    }
+}
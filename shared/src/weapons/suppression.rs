@@ -0,0 +1,115 @@
+//! Near misses and suppression
+//!
+//! The server checks each bullet's path every tick for its closest approach to
+//! every player. A round that passes within `NEAR_MISS_RADIUS` sends the target an
+//! `AudioEvent` (supersonic crack or subsonic whiz at the closest point) and adds to
+//! their `Suppression`, which widens their spread and darkens the screen edges.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Rounds passing closer than this to a player's head count as near misses (m)
+pub const NEAR_MISS_RADIUS: f32 = 4.0;
+
+/// Speed of sound (m/s) - faster rounds crack, slower ones whiz
+pub const SPEED_OF_SOUND: f32 = 343.0;
+
+/// Suppression added by a round passing right by the head (falls off to 0 at the radius)
+pub const SUPPRESSION_PER_NEAR_MISS: f32 = 0.35;
+
+/// Suppression lost per second
+pub const SUPPRESSION_DECAY_RATE: f32 = 0.4;
+
+/// Extra spread (radians) at full suppression
+pub const SUPPRESSION_EXTRA_SPREAD: f32 = 0.03;
+
+/// How suppressed a player is (server-driven, replicated for the HUD vignette).
+///
+/// Removed once it has decayed back to zero.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Suppression {
+    /// 0.0 (calm) to 1.0 (pinned down)
+    pub level: f32,
+}
+
+impl Suppression {
+    /// Add the suppression of a round that passed `miss_distance` meters away
+    pub fn add_near_miss(&mut self, miss_distance: f32) {
+        let closeness = (1.0 - miss_distance / NEAR_MISS_RADIUS).clamp(0.0, 1.0);
+        self.level = (self.level + SUPPRESSION_PER_NEAR_MISS * closeness).min(1.0);
+    }
+
+    /// Decay over `dt` seconds; returns false once fully recovered
+    pub fn decay(&mut self, dt: f32) -> bool {
+        self.level = (self.level - SUPPRESSION_DECAY_RATE * dt).max(0.0);
+        self.level > 0.0
+    }
+
+    /// Spread (radians) added on top of the weapon's own
+    pub fn extra_spread(&self) -> f32 {
+        self.level * SUPPRESSION_EXTRA_SPREAD
+    }
+}
+
+/// Closest point of a bullet's step `start -> end` to `point`, with its distance.
+///
+/// Only returns when the closest approach of the (straight) path falls inside this
+/// step, so each passing round is reported once rather than every tick.
+pub fn closest_approach(start: Vec3, end: Vec3, point: Vec3) -> Option<(Vec3, f32)> {
+    let step = end - start;
+    let length_sq = step.length_squared();
+    if length_sq <= f32::EPSILON {
+        return None;
+    }
+    let t = (point - start).dot(step) / length_sq;
+    if !(0.0..1.0).contains(&t) {
+        return None;
+    }
+    let closest = start + step * t;
+    Some((closest, closest.distance(point)))
+}
+
+/// Whether a round moving at `speed` m/s cracks (supersonic) rather than whizzes
+pub fn is_supersonic(speed: f32) -> bool {
+    speed > SPEED_OF_SOUND
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_approach_reported_once() {
+        let head = Vec3::new(1.5, 0.0, -50.0);
+        let dt = 1.0 / 60.0;
+        let velocity = Vec3::new(0.0, 0.0, -880.0);
+        let mut pos = Vec3::ZERO;
+        let mut reports = Vec::new();
+        for _ in 0..20 {
+            let next = pos + velocity * dt;
+            if let Some(hit) = closest_approach(pos, next, head) {
+                reports.push(hit);
+            }
+            pos = next;
+        }
+        assert_eq!(reports.len(), 1);
+        let (closest, distance) = reports[0];
+        assert!((distance - 1.5).abs() < 1e-3);
+        assert!((closest.z + 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_suppression_builds_and_decays() {
+        let mut suppression = Suppression::default();
+        suppression.add_near_miss(NEAR_MISS_RADIUS + 1.0);
+        assert_eq!(suppression.level, 0.0);
+        for _ in 0..10 {
+            suppression.add_near_miss(0.5);
+        }
+        assert_eq!(suppression.level, 1.0);
+        assert!((suppression.extra_spread() - SUPPRESSION_EXTRA_SPREAD).abs() < 1e-6);
+        assert!(suppression.decay(1.0));
+        assert!(!suppression.decay(10.0));
+        assert!(is_supersonic(880.0) && !is_supersonic(320.0));
+    }
+}
@@ -0,0 +1,121 @@
+//! Scope zeroing
+//!
+//! A zeroed scope tilts the bore up so the bullet crosses the line of sight at the
+//! zero range. The zero angle comes from the shared ballistic model, so the server's
+//! corrected shot direction and the client's holdover marks always agree.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{ballistics, WeaponType};
+
+/// Zero ranges for the sniper scope (m)
+pub const SNIPER_ZERO_RANGES: [f32; 4] = [100.0, 200.0, 400.0, 800.0];
+
+/// Zero ranges for the rifle's ADS sight (m)
+pub const RIFLE_ZERO_RANGES: [f32; 3] = [100.0, 200.0, 400.0];
+
+impl WeaponType {
+    /// Zero ranges this weapon's sight supports (empty = fixed sights, no zeroing)
+    pub fn zero_ranges(&self) -> &'static [f32] {
+        match self {
+            WeaponType::Sniper => &SNIPER_ZERO_RANGES,
+            WeaponType::AssaultRifle => &RIFLE_ZERO_RANGES,
+            _ => &[],
+        }
+    }
+
+    /// Zero range set when this weapon is equipped (0 = no zeroing)
+    pub fn default_zero_range(&self) -> f32 {
+        self.zero_ranges().first().copied().unwrap_or(0.0)
+    }
+
+    /// Step the zero `steps` settings up (positive) or down (negative), clamped to the sight's range
+    pub fn step_zero_range(&self, current: f32, steps: i32) -> f32 {
+        let ranges = self.zero_ranges();
+        if ranges.is_empty() {
+            return 0.0;
+        }
+        let index = ranges
+            .iter()
+            .position(|r| *r == current)
+            .unwrap_or(0) as i32;
+        ranges[(index + steps).clamp(0, ranges.len() as i32 - 1) as usize]
+    }
+
+    /// Bore elevation (radians) for the current zero range
+    pub fn zero_angle(&self, zero_range: f32) -> f32 {
+        if zero_range <= 0.0 || self.is_melee() {
+            return 0.0;
+        }
+        zero_angle(self.stats().bullet_speed, self.ballistic_coefficient(), zero_range)
+    }
+}
+
+/// Bore elevation (radians) that puts the bullet back on the line of sight at `range`.
+///
+/// Computed in still air - zeroing doesn't correct for wind.
+pub fn zero_angle(muzzle_speed: f32, ballistic_coefficient: f32, range: f32) -> f32 {
+    ballistics::trajectory_table(muzzle_speed, ballistic_coefficient, 0.0, &[range])
+        .first()
+        .map(|p| (p.drop / p.distance).atan())
+        .unwrap_or(0.0)
+}
+
+/// Tilt an aim direction up by `angle` (radians) around its horizontal right axis.
+pub fn apply_zero(direction: Vec3, angle: f32) -> Vec3 {
+    let direction = direction.normalize_or(Vec3::NEG_Z);
+    let right = direction.cross(Vec3::Y).normalize_or_zero();
+    if angle == 0.0 || right == Vec3::ZERO {
+        return direction;
+    }
+    (Quat::from_axis_angle(right, angle) * direction).normalize()
+}
+
+/// Client -> Server: step the equipped weapon's zero up or down
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AdjustZeroRequest {
+    /// +1 = next longer range, -1 = next shorter
+    pub steps: i8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zeroed_shot_crosses_line_of_sight() {
+        let weapon = WeaponType::Sniper;
+        let stats = weapon.stats();
+        for range in SNIPER_ZERO_RANGES {
+            let direction = apply_zero(Vec3::NEG_Z, weapon.zero_angle(range));
+            let mut pos = Vec3::ZERO;
+            let mut vel = direction * stats.bullet_speed;
+            let dt = ballistics::TRAJECTORY_TABLE_DT;
+            while -pos.z < range {
+                (pos, vel) = ballistics::step_bullet_physics_in_wind(pos, vel, dt, weapon.ballistic_coefficient(), Vec3::ZERO);
+            }
+            // Within a few centimeters of the aim point (one step of overshoot included)
+            assert!(pos.y.abs() < 0.1, "zero {}m: off by {}", range, pos.y);
+        }
+    }
+
+    #[test]
+    fn test_zero_stepping() {
+        let sniper = WeaponType::Sniper;
+        assert_eq!(sniper.default_zero_range(), 100.0);
+        assert_eq!(sniper.step_zero_range(100.0, 1), 200.0);
+        assert_eq!(sniper.step_zero_range(800.0, 1), 800.0);
+        assert_eq!(sniper.step_zero_range(200.0, -5), 100.0);
+        assert_eq!(WeaponType::Pistol.step_zero_range(0.0, 1), 0.0);
+        assert_eq!(WeaponType::Pistol.zero_angle(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_apply_zero_tilts_up() {
+        let tilted = apply_zero(Vec3::NEG_Z, 0.01);
+        assert!(tilted.y > 0.0);
+        assert!((tilted.y - 0.01_f32.sin()).abs() < 1e-5);
+        assert!((tilted.length() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,228 @@
+//! Battle-royale safe zone
+//!
+//! The zone holds for a while, then shrinks towards the next (smaller) circle, then
+//! picks a new one, stage after stage. Every circle is derived from the match seed so
+//! the server can replay it, and must have its center on walkable terrain. Players
+//! outside the current circle take the stage's damage every second.
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::physics::WALKABLE_THRESHOLD;
+use crate::terrain::WorldTerrain;
+
+/// Radius of the first circle (m)
+pub const ZONE_INITIAL_RADIUS: f32 = 600.0;
+
+/// Tries at finding a walkable center before keeping the current one
+pub const ZONE_CENTER_ATTEMPTS: usize = 32;
+
+/// Seconds between zone damage ticks
+pub const ZONE_DAMAGE_INTERVAL: f32 = 1.0;
+
+/// One shrink of the zone
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ZoneStage {
+    /// Seconds the circle holds before shrinking
+    pub hold_time: f32,
+    /// Seconds the shrink takes
+    pub shrink_time: f32,
+    /// Next radius as a fraction of the current one
+    pub radius_fraction: f32,
+    /// Damage per second outside the circle during this stage
+    pub damage_per_second: f32,
+}
+
+/// Shrink schedule (after the last stage the final circle holds until the match ends)
+pub const ZONE_STAGES: [ZoneStage; 5] = [
+    ZoneStage { hold_time: 90.0, shrink_time: 60.0, radius_fraction: 0.6, damage_per_second: 1.0 },
+    ZoneStage { hold_time: 60.0, shrink_time: 45.0, radius_fraction: 0.5, damage_per_second: 2.0 },
+    ZoneStage { hold_time: 45.0, shrink_time: 30.0, radius_fraction: 0.5, damage_per_second: 4.0 },
+    ZoneStage { hold_time: 30.0, shrink_time: 30.0, radius_fraction: 0.4, damage_per_second: 8.0 },
+    ZoneStage { hold_time: 20.0, shrink_time: 20.0, radius_fraction: 0.3, damage_per_second: 15.0 },
+];
+
+/// Total seconds from the first hold to the final circle
+pub fn zone_schedule_length() -> f32 {
+    ZONE_STAGES.iter().map(|s| s.hold_time + s.shrink_time).sum()
+}
+
+/// Whether a zone center at `point` (XZ) is on walkable ground
+pub fn is_walkable_zone_center(terrain: &WorldTerrain, point: Vec2) -> bool {
+    terrain.get_normal(point.x, point.y).y >= WALKABLE_THRESHOLD
+}
+
+/// Deterministic next center: a walkable point keeping the new circle inside the current one
+pub fn next_zone_center(
+    seed: u64,
+    stage: u32,
+    center: Vec2,
+    radius: f32,
+    next_radius: f32,
+    is_walkable: impl Fn(Vec2) -> bool,
+) -> Vec2 {
+    let mut rng = StdRng::seed_from_u64(seed ^ (stage as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let max_offset = (radius - next_radius).max(0.0);
+    for _ in 0..ZONE_CENTER_ATTEMPTS {
+        let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+        let distance = rng.gen::<f32>().sqrt() * max_offset;
+        let candidate = center + Vec2::from_angle(angle) * distance;
+        if is_walkable(candidate) {
+            return candidate;
+        }
+    }
+    center
+}
+
+/// Points (XZ) players are dropped at when a round starts: a ring halfway out
+pub fn zone_drop_points(zone: &SafeZone, count: usize) -> Vec<Vec2> {
+    (0..count)
+        .map(|i| {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            zone.center + Vec2::from_angle(angle) * zone.radius * 0.5
+        })
+        .collect()
+}
+
+/// The safe zone (server-authoritative, replicated)
+#[derive(Component, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SafeZone {
+    /// Current circle (XZ)
+    pub center: Vec2,
+    pub radius: f32,
+    /// Circle the zone is heading for
+    pub next_center: Vec2,
+    pub next_radius: f32,
+    /// Circle at the start of the current shrink
+    pub shrink_from_center: Vec2,
+    pub shrink_from_radius: f32,
+    /// Index into `ZONE_STAGES` (== its length once the final circle is reached)
+    pub stage: u32,
+    pub shrinking: bool,
+    /// Seconds left in the current hold / shrink
+    pub time_remaining: f32,
+    pub seed: u64,
+}
+
+impl SafeZone {
+    /// First circle, holding, with the next one already picked
+    pub fn new(seed: u64, center: Vec2, radius: f32, is_walkable: impl Fn(Vec2) -> bool) -> Self {
+        let stage = ZONE_STAGES[0];
+        let next_radius = radius * stage.radius_fraction;
+        Self {
+            center,
+            radius,
+            next_center: next_zone_center(seed, 0, center, radius, next_radius, is_walkable),
+            next_radius,
+            shrink_from_center: center,
+            shrink_from_radius: radius,
+            stage: 0,
+            shrinking: false,
+            time_remaining: stage.hold_time,
+            seed,
+        }
+    }
+
+    /// Current stage, if the zone hasn't reached its final circle
+    pub fn current_stage(&self) -> Option<&ZoneStage> {
+        ZONE_STAGES.get(self.stage as usize)
+    }
+
+    /// Whether the final circle has been reached
+    pub fn is_final(&self) -> bool {
+        self.current_stage().is_none()
+    }
+
+    /// Damage per second outside the circle
+    pub fn damage_per_second(&self) -> f32 {
+        self.current_stage()
+            .or(ZONE_STAGES.last())
+            .map(|s| s.damage_per_second)
+            .unwrap_or(0.0)
+    }
+
+    /// Whether a world position is inside the current circle
+    pub fn contains(&self, position: Vec3) -> bool {
+        Vec2::new(position.x, position.z).distance(self.center) <= self.radius
+    }
+
+    /// Advance the zone by `dt` seconds
+    pub fn tick(&mut self, dt: f32, is_walkable: impl Fn(Vec2) -> bool) {
+        let Some(stage) = self.current_stage().copied() else {
+            return;
+        };
+        self.time_remaining = (self.time_remaining - dt).max(0.0);
+
+        if !self.shrinking {
+            if self.time_remaining <= 0.0 {
+                self.shrinking = true;
+                self.shrink_from_center = self.center;
+                self.shrink_from_radius = self.radius;
+                self.time_remaining = stage.shrink_time;
+            }
+            return;
+        }
+
+        let t = if stage.shrink_time > 0.0 { 1.0 - self.time_remaining / stage.shrink_time } else { 1.0 };
+        self.center = self.shrink_from_center.lerp(self.next_center, t);
+        self.radius = self.shrink_from_radius + (self.next_radius - self.shrink_from_radius) * t;
+        if self.time_remaining > 0.0 {
+            return;
+        }
+
+        // Shrink finished: hold, then head for a new circle
+        self.center = self.next_center;
+        self.radius = self.next_radius;
+        self.shrinking = false;
+        self.stage += 1;
+        if let Some(next) = self.current_stage().copied() {
+            self.next_radius = self.radius * next.radius_fraction;
+            self.next_center =
+                next_zone_center(self.seed, self.stage, self.center, self.radius, self.next_radius, is_walkable);
+            self.time_remaining = next.hold_time;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_centers_are_deterministic_and_contained() {
+        let walkable = |p: Vec2| p.x > 0.0;
+        let a = next_zone_center(7, 2, Vec2::ZERO, 500.0, 250.0, walkable);
+        let b = next_zone_center(7, 2, Vec2::ZERO, 500.0, 250.0, walkable);
+        assert_eq!(a, b);
+        assert!(a.x > 0.0);
+        assert!(a.length() <= 250.0 + 1e-3);
+        // Nowhere walkable: keep the current center
+        assert_eq!(next_zone_center(7, 2, Vec2::ONE, 500.0, 250.0, |_| false), Vec2::ONE);
+    }
+
+    #[test]
+    fn test_zone_shrinks_through_every_stage() {
+        let mut zone = SafeZone::new(3, Vec2::ZERO, ZONE_INITIAL_RADIUS, |_| true);
+        let first_next = (zone.next_center, zone.next_radius);
+        assert!(zone.contains(Vec3::new(100.0, 5.0, 0.0)));
+        assert!(!zone.contains(Vec3::new(ZONE_INITIAL_RADIUS + 1.0, 0.0, 0.0)));
+
+        // Halfway through the first shrink the radius is halfway too
+        zone.tick(ZONE_STAGES[0].hold_time, |_| true);
+        assert!(zone.shrinking);
+        zone.tick(ZONE_STAGES[0].shrink_time * 0.5, |_| true);
+        let expected = (ZONE_INITIAL_RADIUS + first_next.1) * 0.5;
+        assert!((zone.radius - expected).abs() < 1e-2);
+
+        let step = 0.5;
+        for _ in 0..(zone_schedule_length() / step) as usize + 10 {
+            zone.tick(step, |_| true);
+        }
+        assert!(zone.is_final());
+        let final_radius = ZONE_STAGES.iter().fold(ZONE_INITIAL_RADIUS, |r, s| r * s.radius_fraction);
+        assert!((zone.radius - final_radius).abs() < 1e-2);
+        assert_eq!(zone.damage_per_second(), ZONE_STAGES[4].damage_per_second);
+    }
+}